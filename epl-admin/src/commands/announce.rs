@@ -0,0 +1,52 @@
+use clap::Args;
+use sea_orm::EntityTrait;
+use serde_json::json;
+use epl_common::admin::{AdminActionType, record_admin_action};
+use epl_common::database::entities::prelude::User;
+use epl_common::system::{broadcast_system_message, send_system_message};
+use crate::{connect_database, connect_nats};
+
+#[derive(Debug, Args)]
+pub(crate) struct AnnounceArgs {
+    /// What the System user should say
+    content: String,
+    /// Only send it to this user instead of everyone
+    #[arg(long)]
+    user_id: Option<i64>,
+    /// Highlight the message until it's been read
+    #[arg(long)]
+    urgent: bool,
+}
+
+pub(crate) async fn announce_command(args: AnnounceArgs) {
+    let conn = connect_database().await;
+    let nats_client = connect_nats().await;
+
+    let recipients = match args.user_id {
+        Some(user_id) => {
+            if User::find_by_id(user_id)
+                .one(&conn)
+                .await
+                .expect("Failed to access database!")
+                .is_none() {
+                println!("No user with that ID exists!");
+                return;
+            }
+
+            send_system_message(&conn, &nats_client, user_id, args.content.clone(), args.urgent).await;
+
+            1
+        }
+        None => broadcast_system_message(&conn, &nats_client, args.content.clone(), args.urgent).await,
+    };
+
+    record_admin_action(
+        &conn,
+        None,
+        AdminActionType::SendSystemMessage,
+        args.user_id.map(|x| x.to_string()),
+        Some(json!({ "content": args.content, "urgent": args.urgent, "recipients": recipients })),
+    ).await;
+
+    println!("Sent to {} user(s)", recipients);
+}
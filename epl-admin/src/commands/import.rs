@@ -0,0 +1,52 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use clap::Args;
+use sea_orm::EntityTrait;
+use epl_common::database::entities::prelude::User;
+use epl_common::import::{import_package, ImportTarget};
+use epl_common::options::{EplOptions, Options};
+use crate::{connect_aws, connect_database};
+
+#[derive(Debug, Args)]
+pub(crate) struct ImportArgs {
+    /// Path to the package.zip downloaded from Discord
+    package: PathBuf,
+    /// Import into an existing account instead of creating a new one from the package
+    #[arg(long)]
+    user_id: Option<i64>,
+}
+
+pub(crate) async fn import_command(args: ImportArgs) {
+    let conn = connect_database().await;
+
+    let target = match args.user_id {
+        None => ImportTarget::New,
+        Some(user_id) => match User::find_by_id(user_id)
+            .one(&conn)
+            .await
+            .expect("Failed to access database!") {
+            None => {
+                println!("No user with that ID exists!");
+                return;
+            }
+            Some(user) => ImportTarget::Existing(user),
+        },
+    };
+
+    let package = BufReader::new(File::open(&args.package).expect("Failed to open the package!"));
+
+    let aws = connect_aws().await;
+
+    // Nothing is connected to the gateway through here, so clients pick the new channels up when they reconnect
+    match import_package(&conn, &aws, &EplOptions::get().s3_bucket, None, package, target).await {
+        Ok(report) => {
+            println!("{}", serde_json::to_string_pretty(&report).expect("Failed to serialize the report!"));
+
+            if report.created_account {
+                println!("The new account has no password, use `forgot password` on the login page to set one");
+            }
+        }
+        Err(err) => println!("Failed to import the package ({:?}): {}", err.kind, err.message),
+    }
+}
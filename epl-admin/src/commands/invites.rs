@@ -0,0 +1,86 @@
+use chrono::{Duration, Utc};
+use clap::Subcommand;
+use sea_orm::{EntityTrait, ModelTrait, QueryOrder};
+use serde_json::json;
+use epl_common::admin::{AdminActionType, record_admin_action};
+use epl_common::database::entities::instance_invite;
+use epl_common::database::entities::prelude::InstanceInvite;
+use epl_common::invites::create_instance_invite;
+use crate::connect_database;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum InvitesCommands {
+    /// List all the instance invites
+    List,
+    /// Create an invite that lets someone register, even when registration is invite only
+    Create {
+        /// How many accounts can be made with the invite, unlimited if not set
+        #[arg(long)]
+        max_uses: Option<i32>,
+        /// How many hours until the invite expires, never if not set
+        #[arg(long)]
+        expires_in: Option<i64>,
+    },
+    /// Revoke an invite so it can't be used anymore
+    Revoke {
+        code: String,
+    },
+}
+
+pub(crate) async fn invites_commands(invites: InvitesCommands) {
+    let conn = connect_database().await;
+
+    match invites {
+        InvitesCommands::List => {
+            let invites = InstanceInvite::find()
+                .order_by_desc(instance_invite::Column::CreatedAt)
+                .all(&conn)
+                .await
+                .expect("Failed to access database!");
+
+            for i in invites {
+                println!(
+                    "{}: used {}/{}{}",
+                    i.code,
+                    i.uses,
+                    if i.max_uses == -1 { "∞".to_string() } else { i.max_uses.to_string() },
+                    i.expires_at.map(|x| format!(", expires {}", x.format("%Y-%m-%d %H:%M"))).unwrap_or_default(),
+                );
+            }
+        }
+        InvitesCommands::Create { max_uses, expires_in } => {
+            let expires_at = expires_in.map(|x| Utc::now().naive_utc() + Duration::hours(x));
+
+            let invite = create_instance_invite(&conn, None, max_uses.unwrap_or(-1), expires_at).await;
+
+            record_admin_action(
+                &conn,
+                None,
+                AdminActionType::CreateInstanceInvite,
+                Some(invite.code.clone()),
+                Some(json!({ "max_uses": invite.max_uses, "expires_in": expires_in })),
+            ).await;
+
+            println!("{}", invite.code);
+        }
+        InvitesCommands::Revoke { code } => {
+            match InstanceInvite::find_by_id(code.clone())
+                .one(&conn)
+                .await
+                .expect("Failed to access database!") {
+                None => println!("No invite with that code exists!"),
+                Some(invite) => {
+                    invite.delete(&conn).await.expect("Failed to revoke invite!");
+
+                    record_admin_action(
+                        &conn,
+                        None,
+                        AdminActionType::DeleteInstanceInvite,
+                        Some(code),
+                        None,
+                    ).await;
+                }
+            }
+        }
+    }
+}
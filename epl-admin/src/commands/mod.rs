@@ -1,2 +1,9 @@
+pub(crate) mod announce;
 pub(crate) mod debug;
-pub(crate) mod users;
\ No newline at end of file
+pub(crate) mod import;
+pub(crate) mod invites;
+pub(crate) mod moderation;
+pub(crate) mod registrations;
+pub(crate) mod reports;
+pub(crate) mod users;
+pub(crate) mod voice;
\ No newline at end of file
@@ -0,0 +1,139 @@
+use chrono::{Duration, Utc};
+use clap::Subcommand;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde_json::json;
+use epl_common::admin::{AdminActionType, record_admin_action};
+use epl_common::database::entities::moderation_action;
+use epl_common::database::entities::prelude::{ModerationAction, User};
+use epl_common::moderation::{get_account_standing, get_action_type, get_active_actions, issue_moderation_action, ModerationActionType, revoke_moderation_action};
+use crate::{connect_database, connect_nats};
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum ModerationCommands {
+    /// List the moderation actions taken against a user
+    List {
+        user_id: i64,
+        /// Include expired and revoked actions
+        #[arg(long)]
+        all: bool,
+    },
+    /// Warn a user, this only shows up in their Safety Hub
+    Warn {
+        user_id: i64,
+        reason: String,
+        /// How many hours until the warning stops counting against them, never if not set
+        #[arg(long)]
+        hours: Option<i64>,
+    },
+    /// Stop a user from messaging, reacting, calling or inviting anyone
+    Limit {
+        user_id: i64,
+        reason: String,
+        /// How many hours until the limit is lifted, never if not set
+        #[arg(long)]
+        hours: Option<i64>,
+    },
+    /// Log a user out and stop them from logging in
+    Suspend {
+        user_id: i64,
+        reason: String,
+        /// How many hours until the suspension is lifted, never if not set
+        #[arg(long)]
+        hours: Option<i64>,
+    },
+    /// Permanently suspend a user
+    Ban {
+        user_id: i64,
+        reason: String,
+    },
+    /// Lift an action early
+    Revoke {
+        action_id: i64,
+    },
+}
+
+async fn moderate(user_id: i64, action_type: ModerationActionType, reason: String, hours: Option<i64>) {
+    let conn = connect_database().await;
+
+    if User::find_by_id(user_id)
+        .one(&conn)
+        .await
+        .expect("Failed to access database!")
+        .is_none() {
+        println!("No user with that ID exists!");
+        return;
+    }
+
+    let nats_client = connect_nats().await;
+
+    let expires_at = hours.map(|x| Utc::now().naive_utc() + Duration::hours(x));
+
+    let action = issue_moderation_action(&conn, &nats_client, user_id, None, action_type, reason.clone(), expires_at).await;
+
+    record_admin_action(
+        &conn,
+        None,
+        AdminActionType::ModerateUser,
+        Some(user_id.to_string()),
+        Some(json!({ "action_id": action.id.to_string(), "action_type": action_type, "reason": reason, "hours": hours })),
+    ).await;
+
+    println!("{} ({})", action.id, action_type.description());
+}
+
+pub(crate) async fn moderation_commands(moderation: ModerationCommands) {
+    match moderation {
+        ModerationCommands::List { user_id, all } => {
+            let conn = connect_database().await;
+
+            let active_actions = get_active_actions(&conn, user_id).await;
+
+            let actions = if all {
+                ModerationAction::find()
+                    .filter(moderation_action::Column::UserId.eq(user_id))
+                    .order_by_desc(moderation_action::Column::Id)
+                    .all(&conn)
+                    .await
+                    .expect("Failed to access database!")
+            } else {
+                active_actions.clone()
+            };
+
+            println!("Standing: {:?}", get_account_standing(&active_actions));
+
+            for i in actions {
+                println!(
+                    "{} {:?} on {}{}{}: {}",
+                    i.id,
+                    get_action_type(&i),
+                    i.created_at.format("%Y-%m-%d %H:%M"),
+                    i.expires_at.map(|x| format!(", expires {}", x.format("%Y-%m-%d %H:%M"))).unwrap_or_default(),
+                    if i.revoked_at.is_some() { " [revoked]" } else { "" },
+                    i.reason,
+                );
+            }
+        }
+        ModerationCommands::Warn { user_id, reason, hours } => moderate(user_id, ModerationActionType::Warning, reason, hours).await,
+        ModerationCommands::Limit { user_id, reason, hours } => moderate(user_id, ModerationActionType::Limit, reason, hours).await,
+        ModerationCommands::Suspend { user_id, reason, hours } => moderate(user_id, ModerationActionType::Suspension, reason, hours).await,
+        ModerationCommands::Ban { user_id, reason } => moderate(user_id, ModerationActionType::Ban, reason, None).await,
+        ModerationCommands::Revoke { action_id } => {
+            let conn = connect_database().await;
+
+            if !revoke_moderation_action(&conn, action_id).await {
+                println!("No active moderation action with that ID exists!");
+                return;
+            }
+
+            record_admin_action(
+                &conn,
+                None,
+                AdminActionType::RevokeModerationAction,
+                Some(action_id.to_string()),
+                None,
+            ).await;
+
+            println!("Revoked {}", action_id);
+        }
+    }
+}
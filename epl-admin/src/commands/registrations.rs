@@ -0,0 +1,109 @@
+use clap::Subcommand;
+use sea_orm::{EntityTrait, ModelTrait, QueryOrder};
+use serde_json::json;
+use epl_common::admin::{AdminActionType, record_admin_action};
+use epl_common::database::entities::prelude::{RegistrationRequest, User};
+use epl_common::database::entities::registration_request;
+use epl_common::registration::{approve_registration, is_pending_approval};
+use crate::{connect_database, connect_nats};
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum RegistrationsCommands {
+    /// List the accounts waiting to be approved, oldest first
+    List,
+    /// Approve an account, the user is emailed to let them know they can log in
+    Approve {
+        user_id: i64,
+    },
+    /// Reject an account, deleting it
+    Reject {
+        user_id: i64,
+    },
+}
+
+pub(crate) async fn registrations_commands(registrations: RegistrationsCommands) {
+    let conn = connect_database().await;
+
+    match registrations {
+        RegistrationsCommands::List => {
+            let requests = RegistrationRequest::find()
+                .find_also_related(User)
+                .order_by_asc(registration_request::Column::CreatedAt)
+                .all(&conn)
+                .await
+                .expect("Failed to access database!");
+
+            for (request, user) in requests {
+                let Some(user) = user else {
+                    continue;
+                };
+
+                println!(
+                    "{} {}#{} <{}>{}, registered {}",
+                    user.id,
+                    user.username,
+                    user.discriminator,
+                    user.email,
+                    if user.acct_verified { " [verified]" } else { "" },
+                    request.created_at.format("%Y-%m-%d %H:%M"),
+                );
+            }
+        }
+        RegistrationsCommands::Approve { user_id } => {
+            let Some(user) = User::find_by_id(user_id)
+                .one(&conn)
+                .await
+                .expect("Failed to access database!") else {
+                println!("No user with that ID exists!");
+                return;
+            };
+
+            let nats_client = connect_nats().await;
+
+            if !approve_registration(&conn, &nats_client, &user).await {
+                println!("That account isn't waiting for approval!");
+                return;
+            }
+
+            record_admin_action(
+                &conn,
+                None,
+                AdminActionType::ApproveRegistration,
+                Some(user_id.to_string()),
+                None,
+            ).await;
+
+            println!("Approved {}", user_id);
+        }
+        RegistrationsCommands::Reject { user_id } => {
+            let Some(user) = User::find_by_id(user_id)
+                .one(&conn)
+                .await
+                .expect("Failed to access database!") else {
+                println!("No user with that ID exists!");
+                return;
+            };
+
+            if !is_pending_approval(&conn, user_id).await {
+                println!("That account isn't waiting for approval!");
+                return;
+            }
+
+            let username = user.username.clone();
+            let email = user.email.clone();
+
+            // The account was never usable, so there's nothing to keep around like with a normal deletion
+            user.delete(&conn).await.expect("Failed to access database!");
+
+            record_admin_action(
+                &conn,
+                None,
+                AdminActionType::RejectRegistration,
+                Some(user_id.to_string()),
+                Some(json!({ "username": username, "email": email })),
+            ).await;
+
+            println!("Rejected {}", user_id);
+        }
+    }
+}
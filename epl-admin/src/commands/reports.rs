@@ -0,0 +1,141 @@
+use clap::Subcommand;
+use num_traits::FromPrimitive;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde_json::json;
+use epl_common::admin::{AdminActionType, record_admin_action};
+use epl_common::database::entities::report;
+use epl_common::database::entities::prelude::{ModerationAction, Report};
+use epl_common::reports::{close_report, ReportReason, ReportStatus, ReportType};
+use crate::connect_database;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum ReportsCommands {
+    /// List the open reports, oldest first
+    List {
+        /// Include reports that were already resolved or dismissed
+        #[arg(long)]
+        all: bool,
+    },
+    /// Show a report along with the snapshot of what was reported
+    Show {
+        report_id: i64,
+    },
+    /// Close a report as acted on, optionally linking the moderation action taken
+    Resolve {
+        report_id: i64,
+        /// ID of the moderation action taken because of the report
+        #[arg(long)]
+        action_id: Option<i64>,
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Close a report without doing anything
+    Dismiss {
+        report_id: i64,
+        #[arg(long)]
+        note: Option<String>,
+    },
+}
+
+fn describe(report: &report::Model) -> String {
+    format!(
+        "{} [{:?}] {} report for \"{}\" by {}, target {}{}",
+        report.id,
+        ReportStatus::from_i32(report.status).expect("Unknown report status!"),
+        ReportType::from_i32(report.report_type).expect("Unknown report type!").name(),
+        ReportReason::from_i32(report.reason).expect("Unknown report reason!").label(),
+        report.reporter_id.map(|x| x.to_string()).unwrap_or_else(|| "a deleted user".to_string()),
+        report.target_user_id.map(|x| x.to_string()).unwrap_or_else(|| "unknown".to_string()),
+        report.message_id.map(|x| format!(", message {}", x)).unwrap_or_default(),
+    )
+}
+
+pub(crate) async fn reports_commands(reports: ReportsCommands) {
+    let conn = connect_database().await;
+
+    match reports {
+        ReportsCommands::List { all } => {
+            let mut reports = Report::find();
+
+            if !all {
+                reports = reports.filter(report::Column::Status.eq(ReportStatus::Open as i32));
+            }
+
+            let reports = reports
+                .order_by_asc(report::Column::Id)
+                .all(&conn)
+                .await
+                .expect("Failed to access database!");
+
+            for i in reports {
+                println!("{}", describe(&i));
+            }
+        }
+        ReportsCommands::Show { report_id } => {
+            match Report::find_by_id(report_id)
+                .one(&conn)
+                .await
+                .expect("Failed to access database!") {
+                None => println!("No report with that ID exists!"),
+                Some(report) => {
+                    println!("{}", describe(&report));
+                    println!("Reported on {}", report.created_at.format("%Y-%m-%d %H:%M"));
+
+                    if let Some(resolved_at) = report.resolved_at {
+                        println!(
+                            "Closed on {}{}{}",
+                            resolved_at.format("%Y-%m-%d %H:%M"),
+                            report.moderation_action_id.map(|x| format!(", moderation action {}", x)).unwrap_or_default(),
+                            report.resolution_note.map(|x| format!(": {}", x)).unwrap_or_default(),
+                        );
+                    }
+
+                    println!("{}", serde_json::to_string_pretty(&report.snapshot).expect("Failed to serialize the snapshot!"));
+                }
+            }
+        }
+        ReportsCommands::Resolve { report_id, action_id, note } => {
+            if let Some(action_id) = action_id {
+                if ModerationAction::find_by_id(action_id)
+                    .one(&conn)
+                    .await
+                    .expect("Failed to access database!")
+                    .is_none() {
+                    println!("No moderation action with that ID exists!");
+                    return;
+                }
+            }
+
+            if !close_report(&conn, report_id, ReportStatus::Resolved, note.clone(), action_id).await {
+                println!("No open report with that ID exists!");
+                return;
+            }
+
+            record_admin_action(
+                &conn,
+                None,
+                AdminActionType::ResolveReport,
+                Some(report_id.to_string()),
+                Some(json!({ "action_id": action_id.map(|x| x.to_string()), "note": note })),
+            ).await;
+
+            println!("Resolved {}", report_id);
+        }
+        ReportsCommands::Dismiss { report_id, note } => {
+            if !close_report(&conn, report_id, ReportStatus::Dismissed, note.clone(), None).await {
+                println!("No open report with that ID exists!");
+                return;
+            }
+
+            record_admin_action(
+                &conn,
+                None,
+                AdminActionType::DismissReport,
+                Some(report_id.to_string()),
+                Some(json!({ "note": note })),
+            ).await;
+
+            println!("Dismissed {}", report_id);
+        }
+    }
+}
@@ -1,41 +1,325 @@
-use clap::Subcommand;
-use crate::AdminOptions;
+use clap::{Subcommand, ValueEnum};
+use rand::Rng;
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder};
+use sea_orm::ActiveValue::Set;
+use serde_json::json;
+use epl_common::admin::{AdminActionType, record_admin_action};
+use epl_common::database::auth::{create_user, generate_password_hash};
+use epl_common::database::entities::prelude::{Session, User};
+use epl_common::database::entities::{session, user};
+use epl_common::deletion::{delete_user, delete_user_files};
+use epl_common::flags::{get_user_flags, UserFlags};
+use epl_common::nats::{Messages, send_nats_message};
+use epl_common::rustflake::Snowflake;
+use epl_common::options::{EplOptions, Options};
+use crate::{connect_aws, connect_database, connect_nats};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum OutputFormat {
+    Table,
+    Json,
+}
 
 #[derive(Debug, Subcommand)]
 pub(crate) enum UsersCommands {
+    /// Find users by username, or by ID or email
     Find {
-        username: String,
-        discriminator: Option<i32>
+        /// Username to search for, or the ID/email when using `--by-id`/`--by-email`
+        query: String,
+        /// Only show the user with this discriminator
+        discriminator: Option<String>,
+        /// Look the user up by ID
+        #[arg(long, conflicts_with = "by_email")]
+        by_id: bool,
+        /// Look the user up by email
+        #[arg(long)]
+        by_email: bool,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        output: OutputFormat,
     },
+    /// Give a user a flag, like `staff` or `verified_email`
     SetFlag {
         user_id: i64,
-        flag: i64,
+        flag: UserFlags,
     },
+    /// Take a flag away from a user
     UnsetFlag {
         user_id: i64,
-        flag: i64,
+        flag: UserFlags,
     },
+    /// Create a verified account
     AddUser {
         username: String,
         email: String,
         password: String,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        output: OutputFormat,
     },
+    /// Delete an account right away, skipping the grace period
     DeleteUser {
         user_id: i64,
     },
+    /// Change a user's password, logging them out everywhere
     SetPassword {
         user_id: i64,
         password: String,
     }
 }
 
-pub(crate) async fn users_commands(options: AdminOptions, users: UsersCommands) {
+fn print_users(users: Vec<user::Model>, output: OutputFormat) {
+    match output {
+        OutputFormat::Table => {
+            println!("{:<20} {:<37} {:<40} {:<8} FLAGS", "ID", "USERNAME", "EMAIL", "VERIFIED");
+
+            for user in users {
+                let flags = get_user_flags(user.flags)
+                    .iter()
+                    .map(|x| x.name())
+                    .collect::<Vec<&str>>()
+                    .join(",");
+
+                println!(
+                    "{:<20} {:<37} {:<40} {:<8} {}",
+                    user.id,
+                    format!("{}#{}", user.username, user.discriminator),
+                    user.email,
+                    user.acct_verified,
+                    flags,
+                );
+            }
+        }
+        OutputFormat::Json => {
+            let users = users.iter().map(|x| json!({
+                "id": x.id.to_string(),
+                "username": x.username,
+                "discriminator": x.discriminator,
+                "global_name": x.display_name,
+                "email": x.email,
+                "verified": x.acct_verified,
+                "bot": x.bot,
+                "system": x.system,
+                "flags": x.flags,
+                "flag_names": get_user_flags(x.flags).iter().map(|x| x.name()).collect::<Vec<&str>>(),
+                "deletion_scheduled_at": x.deletion_scheduled_at.map(|x| x.and_utc().format("%Y-%m-%dT%H:%M:%S%z").to_string()),
+            })).collect::<Vec<serde_json::Value>>();
+
+            println!("{}", serde_json::to_string_pretty(&users).expect("Failed to serialize users!"));
+        }
+    }
+}
+
+async fn find_user(conn: &DatabaseConnection, user_id: i64) -> Option<user::Model> {
+    let user = User::find_by_id(user_id)
+        .one(conn)
+        .await
+        .expect("Failed to access database!");
+
+    if user.is_none() {
+        println!("No user with that ID exists!");
+    }
+
+    user
+}
+
+/// Sets or clears a flag, the user's gateway sessions are dropped so clients pick the change up
+async fn update_flag(user_id: i64, flag: UserFlags, set: bool) {
+    let conn = connect_database().await;
+
+    let Some(user) = find_user(&conn, user_id).await else {
+        return;
+    };
+
+    let old_flags = user.flags;
+    let flags = if set {
+        old_flags | flag as i64
+    } else {
+        old_flags & !(flag as i64)
+    };
+
+    if flags == old_flags {
+        println!("Nothing to change");
+        return;
+    }
+
+    let mut active_user = user.into_active_model();
+
+    active_user.flags = Set(flags);
+
+    active_user.update(&conn).await.expect("Failed to access database!");
+
+    record_admin_action(
+        &conn,
+        None,
+        AdminActionType::UpdateUserFlags,
+        Some(user_id.to_string()),
+        Some(json!({ "old": old_flags, "new": flags })),
+    ).await;
+
+    let nats_client = connect_nats().await;
+
+    send_nats_message(&nats_client, user_id.to_string(), Messages::InvalidateGatewaySession { session: "all".to_string() }).await;
+
+    println!("{} {} for {}", if set { "Set" } else { "Unset" }, flag.name(), user_id);
+}
+
+pub(crate) async fn users_commands(users: UsersCommands) {
     match users {
-        UsersCommands::Find { .. } => {}
-        UsersCommands::SetFlag { .. } => {}
-        UsersCommands::UnsetFlag { .. } => {}
-        UsersCommands::AddUser { .. } => {}
-        UsersCommands::DeleteUser { .. } => {}
-        UsersCommands::SetPassword { .. } => {}
+        UsersCommands::Find { query, discriminator, by_id, by_email, output } => {
+            let conn = connect_database().await;
+
+            let condition = if by_id {
+                let Ok(user_id) = query.parse::<i64>() else {
+                    println!("That isn't a valid ID!");
+                    return;
+                };
+
+                user::Column::Id.eq(user_id)
+            } else if by_email {
+                user::Column::Email.eq(query)
+            } else {
+                user::Column::Username.eq(query)
+            };
+
+            let mut users = User::find().filter(condition);
+
+            if let Some(discriminator) = discriminator {
+                users = users.filter(user::Column::Discriminator.eq(discriminator));
+            }
+
+            let users = users
+                .order_by_asc(user::Column::Id)
+                .all(&conn)
+                .await
+                .expect("Failed to access database!");
+
+            print_users(users, output);
+        }
+        UsersCommands::SetFlag { user_id, flag } => update_flag(user_id, flag, true).await,
+        UsersCommands::UnsetFlag { user_id, flag } => update_flag(user_id, flag, false).await,
+        UsersCommands::AddUser { username, email, password, output } => {
+            let conn = connect_database().await;
+
+            if User::find()
+                .filter(user::Column::Email.eq(email.clone()))
+                .one(&conn)
+                .await
+                .expect("Failed to access database!")
+                .is_some() {
+                println!("Email is already registered!");
+                return;
+            }
+
+            let password_hash = match generate_password_hash(&password, vec![&username, &email]) {
+                Ok(password_hash) => password_hash,
+                Err(err) => {
+                    println!("{}", err.message);
+                    return;
+                }
+            };
+
+            let user_id = match create_user(&conn, user::ActiveModel {
+                id: Set(Snowflake::default().generate()),
+                system: Set(false),
+                bot: Set(false),
+                username: Set(username.clone()),
+                password_hash: Set(password_hash),
+                discriminator: Set(rand::thread_rng().gen_range(1..9999).to_string()),
+                email: Set(email.clone()),
+                mfa_enabled: Set(false),
+                acct_verified: Set(true),
+                flags: Set(0),
+                nsfw_allowed: Set(false),
+                ..Default::default()
+            }).await {
+                Ok(user_id) => user_id,
+                Err(err) => {
+                    println!("Failed to create the user ({:?}): {}", err.kind, err.message);
+                    return;
+                }
+            };
+
+            record_admin_action(
+                &conn,
+                None,
+                AdminActionType::CreateUser,
+                Some(user_id.to_string()),
+                Some(json!({ "username": username, "email": email })),
+            ).await;
+
+            let user = User::find_by_id(user_id)
+                .one(&conn)
+                .await
+                .expect("Failed to access database!")
+                .expect("Created user is missing!");
+
+            print_users(vec![user], output);
+        }
+        UsersCommands::DeleteUser { user_id } => {
+            let conn = connect_database().await;
+
+            let Some(user) = find_user(&conn, user_id).await else {
+                return;
+            };
+
+            let username = user.username.clone();
+            let email = user.email.clone();
+
+            let nats_client = connect_nats().await;
+            let aws = connect_aws().await;
+
+            delete_user_files(&conn, &aws, &EplOptions::get().s3_bucket, user_id).await;
+            delete_user(&conn, &nats_client, user).await;
+
+            record_admin_action(
+                &conn,
+                None,
+                AdminActionType::DeleteUser,
+                Some(user_id.to_string()),
+                Some(json!({ "username": username, "email": email })),
+            ).await;
+
+            println!("Deleted {}", user_id);
+        }
+        UsersCommands::SetPassword { user_id, password } => {
+            let conn = connect_database().await;
+
+            let Some(user) = find_user(&conn, user_id).await else {
+                return;
+            };
+
+            let password_hash = match generate_password_hash(&password, vec![&user.username, &user.email]) {
+                Ok(password_hash) => password_hash,
+                Err(err) => {
+                    println!("{}", err.message);
+                    return;
+                }
+            };
+
+            let mut active_user = user.into_active_model();
+
+            active_user.password_hash = Set(password_hash);
+
+            active_user.update(&conn).await.expect("Failed to access database!");
+
+            Session::delete_many()
+                .filter(session::Column::UserId.eq(user_id))
+                .exec(&conn)
+                .await
+                .expect("Failed to access database!");
+
+            record_admin_action(
+                &conn,
+                None,
+                AdminActionType::ResetUserPassword,
+                Some(user_id.to_string()),
+                None,
+            ).await;
+
+            let nats_client = connect_nats().await;
+
+            send_nats_message(&nats_client, user_id.to_string(), Messages::InvalidateGatewaySession { session: "all".to_string() }).await;
+
+            println!("Changed the password for {}, they have been logged out everywhere", user_id);
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,163 @@
+use clap::Subcommand;
+use sea_orm::{ActiveModelTrait, EntityTrait, IntoActiveModel, ModelTrait};
+use sea_orm::ActiveValue::Set;
+use epl_common::database::entities::{voice_region, voice_server};
+use epl_common::database::entities::prelude::{VoiceRegion, VoiceServer};
+use crate::connect_database;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum VoiceCommands {
+    /// List all the voice servers
+    List,
+    /// Add a voice server
+    Add {
+        /// Hostname the LVSP server is reachable at, this is also given to clients
+        hostname: String,
+        /// Region the voice server is in
+        region: String,
+    },
+    /// Remove a voice server, disconnecting everyone on it
+    Remove {
+        /// Hostname of the voice server
+        hostname: String,
+    },
+    /// Manage the voice regions servers are in
+    #[command(subcommand)]
+    Region(RegionCommands),
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum RegionCommands {
+    /// List all the voice regions
+    List,
+    /// Add a voice region
+    Add {
+        /// ID of the region, like `us-west`
+        id: String,
+        /// Name shown to users
+        name: String,
+        /// Rough latitude of the region, used to find the closest region to users
+        #[arg(long, allow_negative_numbers = true)]
+        latitude: Option<f64>,
+        /// Rough longitude of the region, used to find the closest region to users
+        #[arg(long, allow_negative_numbers = true)]
+        longitude: Option<f64>,
+        /// Mark the region as custom
+        #[arg(long)]
+        custom: bool,
+    },
+    /// Mark a voice region as deprecated, it won't be suggested to users anymore
+    Deprecate {
+        /// ID of the region
+        id: String,
+    },
+    /// Remove a voice region along with all of its voice servers
+    Remove {
+        /// ID of the region
+        id: String,
+    },
+}
+
+pub(crate) async fn voice_commands(voice: VoiceCommands) {
+    let conn = connect_database().await;
+
+    match voice {
+        VoiceCommands::List => {
+            let voice_servers = VoiceServer::find()
+                .all(&conn)
+                .await
+                .expect("Failed to access database!");
+
+            for i in voice_servers {
+                println!("{} ({}): health {}", i.hostname, i.region_id, i.last_health);
+            }
+        }
+        VoiceCommands::Add { hostname, region } => {
+            if VoiceRegion::find_by_id(region.clone())
+                .one(&conn)
+                .await
+                .expect("Failed to access database!")
+                .is_none() {
+                println!("No voice region with that ID exists, add it with `voice region add` first!");
+                return;
+            }
+
+            voice_server::ActiveModel {
+                hostname: Set(hostname),
+                region_id: Set(region),
+                last_health: Set(1.0),
+            }
+                .insert(&conn)
+                .await
+                .expect("Failed to add voice server!");
+        }
+        VoiceCommands::Remove { hostname } => {
+            match VoiceServer::find_by_id(hostname)
+                .one(&conn)
+                .await
+                .expect("Failed to access database!") {
+                None => println!("No voice server with that hostname exists!"),
+                Some(voice_server) => {
+                    voice_server.delete(&conn).await.expect("Failed to remove voice server!");
+                }
+            }
+        }
+        VoiceCommands::Region(region) => match region {
+            RegionCommands::List => {
+                let voice_regions = VoiceRegion::find()
+                    .all(&conn)
+                    .await
+                    .expect("Failed to access database!");
+
+                for i in voice_regions {
+                    println!(
+                        "{} ({}){}{}",
+                        i.id,
+                        i.name,
+                        if i.custom { " [custom]" } else { "" },
+                        if i.deprecated { " [deprecated]" } else { "" },
+                    );
+                }
+            }
+            RegionCommands::Add { id, name, latitude, longitude, custom } => {
+                voice_region::ActiveModel {
+                    id: Set(id),
+                    name: Set(name),
+                    latitude: Set(latitude),
+                    longitude: Set(longitude),
+                    deprecated: Set(false),
+                    custom: Set(custom),
+                }
+                    .insert(&conn)
+                    .await
+                    .expect("Failed to add voice region!");
+            }
+            RegionCommands::Deprecate { id } => {
+                match VoiceRegion::find_by_id(id)
+                    .one(&conn)
+                    .await
+                    .expect("Failed to access database!") {
+                    None => println!("No voice region with that ID exists!"),
+                    Some(voice_region) => {
+                        let mut voice_region = voice_region.into_active_model();
+
+                        voice_region.deprecated = Set(true);
+
+                        voice_region.update(&conn).await.expect("Failed to deprecate voice region!");
+                    }
+                }
+            }
+            RegionCommands::Remove { id } => {
+                match VoiceRegion::find_by_id(id)
+                    .one(&conn)
+                    .await
+                    .expect("Failed to access database!") {
+                    None => println!("No voice region with that ID exists!"),
+                    Some(voice_region) => {
+                        voice_region.delete(&conn).await.expect("Failed to remove voice region!");
+                    }
+                }
+            }
+        },
+    }
+}
@@ -1,13 +1,26 @@
 mod commands;
+mod server;
 
 use std::env;
 use clap::{Parser, Subcommand};
+use epl_common::options::{EplOptions, Options as _};
+use sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use tracing::log;
+use crate::commands::announce::{announce_command, AnnounceArgs};
 use crate::commands::debug::{debug_commands, DebugCommands};
+use crate::commands::import::{import_command, ImportArgs};
+use crate::commands::invites::{invites_commands, InvitesCommands};
+use crate::commands::moderation::{moderation_commands, ModerationCommands};
+use crate::commands::registrations::{registrations_commands, RegistrationsCommands};
+use crate::commands::reports::{reports_commands, ReportsCommands};
 use crate::commands::users::{users_commands, UsersCommands};
+use crate::commands::voice::{voice_commands, VoiceCommands};
+use crate::server::run_server;
 
 #[derive(Clone)]
 struct AdminOptions {
-    tenor_key: Option<String>
+    tenor_key: Option<String>,
+    listen_addr: String,
 }
 
 trait Options {
@@ -17,7 +30,8 @@ trait Options {
 impl Options for AdminOptions {
     fn get() -> AdminOptions {
         AdminOptions {
-            tenor_key: env::var("TENOR_KEY").ok()
+            tenor_key: env::var("TENOR_KEY").ok(),
+            listen_addr: env::var("ADMIN_LISTEN_ADDR").unwrap_or_else(|_| "127.0.0.1:3927".to_string()),
         }
     }
 }
@@ -39,6 +53,51 @@ pub(crate) enum Commands {
     Debug(DebugCommands),
     #[command(subcommand)]
     Users(UsersCommands),
+    /// Import a Discord data package, creating an account for it unless one is given
+    Import(ImportArgs),
+    /// Send a message from the System user to everyone, or to one user
+    Announce(AnnounceArgs),
+    /// Manage the invites used to register on the instance
+    #[command(subcommand)]
+    Invites(InvitesCommands),
+    /// Warn, limit, suspend or ban users
+    #[command(subcommand)]
+    Moderation(ModerationCommands),
+    /// Approve or reject accounts when registration requires approval
+    #[command(subcommand)]
+    Registrations(RegistrationsCommands),
+    /// Triage the reports users send in
+    #[command(subcommand)]
+    Reports(ReportsCommands),
+    /// Manage the LVSP voice servers
+    #[command(subcommand)]
+    Voice(VoiceCommands),
+}
+
+async fn connect_database() -> DatabaseConnection {
+    let mut db_opt = ConnectOptions::new(EplOptions::get().database_url);
+    db_opt.sqlx_logging_level(log::LevelFilter::Debug);
+
+    Database::connect(db_opt)
+        .await
+        .expect("Failed to connect to database!")
+}
+
+async fn connect_nats() -> async_nats::Client {
+    async_nats::connect(EplOptions::get().nats_addr)
+        .await
+        .expect("Failed to connect to NATS server")
+}
+
+async fn connect_aws() -> aws_sdk_s3::Client {
+    // Workaround for https://github.com/awslabs/aws-sdk-rust/issues/932
+    let aws_config = if env::var("AWS_ENDPOINT_URL").is_ok() {
+        aws_config::from_env().endpoint_url(env::var("AWS_ENDPOINT_URL").unwrap()).load().await
+    } else {
+        aws_config::load_from_env().await
+    };
+
+    aws_sdk_s3::Client::new(&aws_config)
 }
 
 
@@ -51,10 +110,15 @@ async fn main() {
     // let mut snowflake_factory = rustflake::Snowflake::default();
 
     match args.command {
-        Commands::Server => {
-            unimplemented!()
-        }
+        Commands::Server => run_server(options).await,
         Commands::Debug(debug) => debug_commands(options, debug).await,
-        Commands::Users(users) => users_commands(options, users).await,
+        Commands::Users(users) => users_commands(users).await,
+        Commands::Import(args) => import_command(args).await,
+        Commands::Announce(args) => announce_command(args).await,
+        Commands::Invites(invites) => invites_commands(invites).await,
+        Commands::Moderation(moderation) => moderation_commands(moderation).await,
+        Commands::Registrations(registrations) => registrations_commands(registrations).await,
+        Commands::Reports(reports) => reports_commands(reports).await,
+        Commands::Voice(voice) => voice_commands(voice).await,
     }
 }
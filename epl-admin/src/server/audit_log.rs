@@ -0,0 +1,52 @@
+use axum::extract::Query;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde_derive::{Deserialize, Serialize};
+use epl_common::database::entities::admin_action;
+use epl_common::database::entities::prelude::AdminAction;
+use crate::server::AdminState;
+
+#[derive(Deserialize)]
+pub struct AuditLogQuery {
+    before: Option<i64>,
+    limit: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct AuditLogEntryRes {
+    id: String,
+    actor_id: Option<String>,
+    action: String,
+    target: Option<String>,
+    data: Option<serde_json::Value>,
+    created_at: String,
+}
+
+/// Everything staff have done, newest first
+pub async fn get_audit_log(
+    Extension(state): Extension<AdminState>,
+    Query(query): Query<AuditLogQuery>,
+) -> impl IntoResponse {
+    let mut entries = AdminAction::find();
+
+    if let Some(before) = query.before {
+        entries = entries.filter(admin_action::Column::Id.lt(before));
+    }
+
+    let entries = entries
+        .order_by_desc(admin_action::Column::Id)
+        .limit(query.limit.unwrap_or(50).clamp(1, 100))
+        .all(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    Json(entries.into_iter().map(|x| AuditLogEntryRes {
+        id: x.id.to_string(),
+        actor_id: x.actor_id.map(|x| x.to_string()),
+        action: x.action,
+        target: x.target,
+        data: x.data,
+        created_at: x.created_at.and_utc().format("%Y-%m-%dT%H:%M:%S%z").to_string(),
+    }).collect::<Vec<AuditLogEntryRes>>())
+}
@@ -0,0 +1,8 @@
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use crate::server::admin_error;
+
+/// Epl doesn't have guilds yet, so the guild admin routes never find one
+pub async fn unknown_guild() -> impl IntoResponse {
+    admin_error(StatusCode::NOT_FOUND, 10004, "Unknown Guild")
+}
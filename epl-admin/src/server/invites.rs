@@ -0,0 +1,119 @@
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use chrono::{Duration, Utc};
+use sea_orm::{EntityTrait, ModelTrait, QueryOrder};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::json;
+use epl_common::admin::{AdminActionType, record_admin_action};
+use epl_common::database::entities::instance_invite;
+use epl_common::database::entities::prelude::InstanceInvite;
+use epl_common::invites::create_instance_invite;
+use crate::server::{admin_error, AdminContext, AdminState};
+
+#[derive(Serialize)]
+pub struct InstanceInviteRes {
+    code: String,
+    created_at: String,
+    uses: i32,
+    max_uses: i32,
+    expires_at: Option<String>,
+}
+
+impl From<instance_invite::Model> for InstanceInviteRes {
+    fn from(invite: instance_invite::Model) -> Self {
+        InstanceInviteRes {
+            code: invite.code,
+            created_at: invite.created_at.and_utc().format("%Y-%m-%dT%H:%M:%S%z").to_string(),
+            uses: invite.uses,
+            max_uses: invite.max_uses,
+            expires_at: invite.expires_at.map(|x| x.and_utc().format("%Y-%m-%dT%H:%M:%S%z").to_string()),
+        }
+    }
+}
+
+pub async fn get_invites(
+    Extension(state): Extension<AdminState>,
+) -> impl IntoResponse {
+    let invites = InstanceInvite::find()
+        .order_by_desc(instance_invite::Column::CreatedAt)
+        .all(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    Json(invites.into_iter().map(InstanceInviteRes::from).collect::<Vec<InstanceInviteRes>>())
+}
+
+pub async fn get_invite(
+    Extension(state): Extension<AdminState>,
+    Path(code): Path<String>,
+) -> impl IntoResponse {
+    match InstanceInvite::find_by_id(code)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!") {
+        None => admin_error(StatusCode::NOT_FOUND, 10006, "Unknown Invite"),
+        Some(invite) => Json(InstanceInviteRes::from(invite)).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CreateInviteReq {
+    /// -1 lets the invite be used any number of times
+    #[serde(default = "unlimited_uses")]
+    max_uses: i32,
+    /// Seconds until the invite expires, it never does when missing or 0
+    max_age: Option<i64>,
+}
+
+fn unlimited_uses() -> i32 {
+    -1
+}
+
+pub async fn create_invite(
+    Extension(state): Extension<AdminState>,
+    Extension(admin_context): Extension<AdminContext>,
+    Json(data): Json<CreateInviteReq>,
+) -> impl IntoResponse {
+    let expires_at = data.max_age
+        .filter(|x| *x > 0)
+        .map(|x| Utc::now().naive_utc() + Duration::seconds(x));
+
+    let invite = create_instance_invite(&state.conn, Some(admin_context.user.id), data.max_uses, expires_at).await;
+
+    record_admin_action(
+        &state.conn,
+        Some(admin_context.user.id),
+        AdminActionType::CreateInstanceInvite,
+        Some(invite.code.clone()),
+        Some(json!({ "max_uses": invite.max_uses, "max_age": data.max_age })),
+    ).await;
+
+    Json(InstanceInviteRes::from(invite))
+}
+
+pub async fn delete_invite(
+    Extension(state): Extension<AdminState>,
+    Extension(admin_context): Extension<AdminContext>,
+    Path(code): Path<String>,
+) -> impl IntoResponse {
+    let Some(invite) = InstanceInvite::find_by_id(code.clone())
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!") else {
+        return admin_error(StatusCode::NOT_FOUND, 10006, "Unknown Invite");
+    };
+
+    invite.delete(&state.conn).await.expect("Failed to access database!");
+
+    record_admin_action(
+        &state.conn,
+        Some(admin_context.user.id),
+        AdminActionType::DeleteInstanceInvite,
+        Some(code),
+        None,
+    ).await;
+
+    StatusCode::NO_CONTENT.into_response()
+}
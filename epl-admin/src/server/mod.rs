@@ -0,0 +1,151 @@
+mod audit_log;
+mod guilds;
+mod invites;
+mod users;
+mod voice;
+
+use std::net::SocketAddr;
+use axum::extract::Request;
+use axum::http::{Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, patch, post, put};
+use axum::{Extension, Json, middleware, Router};
+use sea_orm::DatabaseConnection;
+use serde_derive::Serialize;
+use tower_http::cors::{Any, CorsLayer};
+use tracing::info;
+use epl_common::database::auth::{get_session_by_token, get_user_from_session_by_token};
+use epl_common::database::entities::user;
+use epl_common::flags::{get_user_flags, UserFlags};
+use crate::{AdminOptions, connect_aws, connect_database, connect_nats};
+use crate::server::audit_log::get_audit_log;
+use crate::server::guilds::unknown_guild;
+use crate::server::invites::{create_invite, delete_invite, get_invite, get_invites};
+use crate::server::users::{create_user, delete_user, get_user, search_users, update_user};
+use crate::server::voice::{add_voice_server, create_voice_region, deprecate_voice_region, get_voice_servers};
+
+#[derive(Clone)]
+pub(crate) struct AdminState {
+    conn: DatabaseConnection,
+    nats_client: async_nats::Client,
+    aws: aws_sdk_s3::Client,
+}
+
+/// The staff member making the request
+#[derive(Clone)]
+pub(crate) struct AdminContext {
+    user: user::Model,
+}
+
+#[derive(Serialize)]
+struct AdminError {
+    code: u32,
+    message: String,
+}
+
+/// Errors look the same as the ones from the regular API so Litecord admin clients can show them
+pub(crate) fn admin_error(status: StatusCode, code: u32, message: &str) -> Response {
+    (status, Json(AdminError { code, message: message.to_string() })).into_response()
+}
+
+/// Only lets staff through, everyone else is told the routes don't exist
+async fn require_staff(
+    Extension(state): Extension<AdminState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(auth) = request.headers().get("Authorization").and_then(|x| x.to_str().ok()) else {
+        return admin_error(StatusCode::UNAUTHORIZED, 40001, "401: Unauthorized");
+    };
+
+    let auth = auth.to_string();
+
+    let (Ok(_), Ok(user)) = (
+        get_session_by_token(&state.conn, &auth).await,
+        get_user_from_session_by_token(&state.conn, &auth).await,
+    ) else {
+        return admin_error(StatusCode::UNAUTHORIZED, 40001, "401: Unauthorized");
+    };
+
+    if !get_user_flags(user.flags).contains(&UserFlags::Staff) {
+        return admin_error(StatusCode::FORBIDDEN, 50001, "Missing Access");
+    }
+
+    request.extensions_mut().insert(AdminContext { user });
+
+    next.run(request).await
+}
+
+fn admin_api() -> Router {
+    Router::new()
+        .route("/users", get(search_users))
+        .route("/users", post(create_user))
+        .route("/users/:user_id", get(get_user))
+        .route("/users/:user_id", patch(update_user))
+        .route("/users/:user_id", delete(delete_user))
+        .route("/instance/invites", get(get_invites))
+        .route("/instance/invites", post(create_invite))
+        .route("/instance/invites/:code", get(get_invite))
+        .route("/instance/invites/:code", delete(delete_invite))
+        .route("/guilds/:guild_id", get(unknown_guild))
+        .route("/guilds/:guild_id", patch(unknown_guild))
+        .route("/guilds/:guild_id", delete(unknown_guild))
+        .route("/guilds/:guild_id/features", patch(unknown_guild))
+        .route("/guilds/:guild_id/features", put(unknown_guild))
+        .route("/guilds/:guild_id/features", delete(unknown_guild))
+        .route("/voice/regions", put(create_voice_region))
+        .route("/voice/regions/:region", get(get_voice_servers))
+        .route("/voice/regions/:region/server", put(add_voice_server))
+        .route("/voice/regions/:region/deprecate", put(deprecate_voice_region))
+        .route("/audit-log", get(get_audit_log))
+        .route_layer(middleware::from_fn(require_staff))
+}
+
+pub(crate) async fn run_server(options: AdminOptions) {
+    let conn = connect_database().await;
+
+    info!("Connected to database");
+
+    let nats_client = connect_nats().await;
+
+    info!("Connected to NATS server");
+
+    let aws = connect_aws().await;
+
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PATCH,
+            Method::OPTIONS,
+            Method::DELETE,
+            Method::PUT,
+        ])
+        .allow_headers(Any);
+
+    let addr: SocketAddr = options
+        .listen_addr
+        .parse()
+        .expect("Unable to parse listen address!");
+
+    let app_state = AdminState {
+        conn,
+        nats_client,
+        aws,
+    };
+
+    // Litecord's admin API lives under the regular API, clients use either version
+    let app = Router::new()
+        .nest("/api/v6/admin", admin_api())
+        .nest("/api/v9/admin", admin_api())
+        .layer(cors)
+        .layer(Extension(app_state));
+
+    info!("Starting Admin API server on {}", addr);
+
+    let listener = tokio::net::TcpListener::bind(&addr).await.expect("Failed to bind to address!");
+
+    axum::serve(listener, app).await.expect("Failed to start the server!");
+}
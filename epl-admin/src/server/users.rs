@@ -0,0 +1,256 @@
+use axum::extract::{Path, Query};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use rand::Rng;
+use sea_orm::{ActiveModelTrait, ColumnTrait, Condition, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, QuerySelect};
+use sea_orm::ActiveValue::Set;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::json;
+use epl_common::admin::{AdminActionType, record_admin_action};
+use epl_common::database::auth::generate_password_hash;
+use epl_common::database::entities::prelude::User;
+use epl_common::database::entities::user;
+use epl_common::deletion::delete_user_files;
+use epl_common::flags::UserFlags;
+use epl_common::nats::{Messages, send_nats_message};
+use epl_common::options::{EplOptions, Options};
+use epl_common::rustflake::Snowflake;
+use crate::server::{admin_error, AdminContext, AdminState};
+
+#[derive(Serialize)]
+pub struct AdminUserRes {
+    id: String,
+    username: String,
+    discriminator: String,
+    global_name: Option<String>,
+    avatar: Option<String>,
+    email: String,
+    verified: bool,
+    mfa_enabled: bool,
+    bot: bool,
+    system: bool,
+    flags: i64,
+    deletion_scheduled_at: Option<String>,
+}
+
+impl From<user::Model> for AdminUserRes {
+    fn from(user: user::Model) -> Self {
+        AdminUserRes {
+            id: user.id.to_string(),
+            username: user.username,
+            discriminator: user.discriminator,
+            global_name: user.display_name,
+            avatar: user.avatar,
+            email: user.email,
+            verified: user.acct_verified,
+            mfa_enabled: user.mfa_enabled,
+            bot: user.bot,
+            system: user.system,
+            flags: user.flags,
+            deletion_scheduled_at: user.deletion_scheduled_at.map(|x| x.and_utc().format("%Y-%m-%dT%H:%M:%S%z").to_string()),
+        }
+    }
+}
+
+async fn find_user(state: &AdminState, user_id: i64) -> Option<user::Model> {
+    User::find_by_id(user_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+#[derive(Deserialize)]
+pub struct SearchUsersQuery {
+    q: Option<String>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+/// Searches by ID, email, or part of a username
+pub async fn search_users(
+    Extension(state): Extension<AdminState>,
+    Query(query): Query<SearchUsersQuery>,
+) -> impl IntoResponse {
+    let mut condition = Condition::all();
+
+    if let Some(q) = query.q.filter(|x| !x.is_empty()) {
+        let mut search = Condition::any()
+            .add(user::Column::Username.contains(&q))
+            .add(user::Column::Email.eq(q.clone()));
+
+        if let Ok(user_id) = q.parse::<i64>() {
+            search = search.add(user::Column::Id.eq(user_id));
+        }
+
+        condition = condition.add(search);
+    }
+
+    let users = User::find()
+        .filter(condition)
+        .order_by_asc(user::Column::Id)
+        .limit(query.limit.unwrap_or(25).clamp(1, 100))
+        .offset(query.offset.unwrap_or(0))
+        .all(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    Json(users.into_iter().map(AdminUserRes::from).collect::<Vec<AdminUserRes>>())
+}
+
+pub async fn get_user(
+    Extension(state): Extension<AdminState>,
+    Path(user_id): Path<i64>,
+) -> impl IntoResponse {
+    match find_user(&state, user_id).await {
+        None => admin_error(StatusCode::NOT_FOUND, 10013, "Unknown User"),
+        Some(user) => Json(AdminUserRes::from(user)).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CreateUserReq {
+    username: String,
+    email: String,
+    password: String,
+}
+
+pub async fn create_user(
+    Extension(state): Extension<AdminState>,
+    Extension(admin_context): Extension<AdminContext>,
+    Json(data): Json<CreateUserReq>,
+) -> impl IntoResponse {
+    let existing_user = User::find()
+        .filter(user::Column::Email.eq(data.email.clone()))
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    if existing_user.is_some() {
+        return admin_error(StatusCode::BAD_REQUEST, 50035, "Email is already registered.");
+    }
+
+    let password_hash = match generate_password_hash(&data.password, vec![&data.username, &data.email]) {
+        Ok(password_hash) => password_hash,
+        Err(err) => return admin_error(StatusCode::BAD_REQUEST, 50035, &err.message),
+    };
+
+    let user_id = epl_common::database::auth::create_user(&state.conn, user::ActiveModel {
+        id: Set(Snowflake::default().generate()),
+        system: Set(false),
+        bot: Set(false),
+        username: Set(data.username.clone()),
+        password_hash: Set(password_hash),
+        discriminator: Set(rand::thread_rng().gen_range(1..9999).to_string()),
+        email: Set(data.email.clone()),
+        mfa_enabled: Set(false),
+        acct_verified: Set(true),
+        flags: Set(0),
+        nsfw_allowed: Set(false),
+        ..Default::default()
+    })
+        .await
+        .expect("Failed to create user!");
+
+    record_admin_action(
+        &state.conn,
+        Some(admin_context.user.id),
+        AdminActionType::CreateUser,
+        Some(user_id.to_string()),
+        Some(json!({ "username": data.username, "email": data.email })),
+    ).await;
+
+    let user = find_user(&state, user_id).await.expect("Created user is missing!");
+
+    Json(AdminUserRes::from(user)).into_response()
+}
+
+/// Flags the API can't change, staff is only handed out from the CLI and the rest are managed by Epl itself
+const PROTECTED_FLAGS: i64 = UserFlags::Staff as i64
+    | UserFlags::System as i64
+    | UserFlags::Deleted as i64
+    | UserFlags::SelfDeleted as i64
+    | UserFlags::UnderageDeleted as i64;
+
+#[derive(Deserialize)]
+pub struct UpdateUserReq {
+    flags: Option<i64>,
+}
+
+pub async fn update_user(
+    Extension(state): Extension<AdminState>,
+    Extension(admin_context): Extension<AdminContext>,
+    Path(user_id): Path<i64>,
+    Json(data): Json<UpdateUserReq>,
+) -> impl IntoResponse {
+    let Some(user) = find_user(&state, user_id).await else {
+        return admin_error(StatusCode::NOT_FOUND, 10013, "Unknown User");
+    };
+
+    let Some(flags) = data.flags else {
+        return Json(AdminUserRes::from(user)).into_response();
+    };
+
+    let old_flags = user.flags;
+
+    if (flags ^ old_flags) & PROTECTED_FLAGS != 0 {
+        return admin_error(StatusCode::BAD_REQUEST, 50035, "These flags can't be changed over the API.");
+    }
+
+    if flags == old_flags {
+        return Json(AdminUserRes::from(user)).into_response();
+    }
+
+    let mut active_user = user.into_active_model();
+
+    active_user.flags = Set(flags);
+
+    let user = active_user.update(&state.conn).await.expect("Failed to access database!");
+
+    record_admin_action(
+        &state.conn,
+        Some(admin_context.user.id),
+        AdminActionType::UpdateUserFlags,
+        Some(user_id.to_string()),
+        Some(json!({ "old": old_flags, "new": flags })),
+    ).await;
+
+    // Clients only pick up their new flags when they reconnect
+    send_nats_message(&state.nats_client, user_id.to_string(), Messages::InvalidateGatewaySession { session: "all".to_string() }).await;
+
+    Json(AdminUserRes::from(user)).into_response()
+}
+
+#[derive(Serialize)]
+pub struct DeleteUserRes {
+    old: AdminUserRes,
+    new: AdminUserRes,
+}
+
+/// Deletes the account right away, skipping the grace period users get
+pub async fn delete_user(
+    Extension(state): Extension<AdminState>,
+    Extension(admin_context): Extension<AdminContext>,
+    Path(user_id): Path<i64>,
+) -> impl IntoResponse {
+    let Some(user) = find_user(&state, user_id).await else {
+        return admin_error(StatusCode::NOT_FOUND, 10013, "Unknown User");
+    };
+
+    let old = AdminUserRes::from(user.clone());
+
+    delete_user_files(&state.conn, &state.aws, &EplOptions::get().s3_bucket, user_id).await;
+    epl_common::deletion::delete_user(&state.conn, &state.nats_client, user).await;
+
+    record_admin_action(
+        &state.conn,
+        Some(admin_context.user.id),
+        AdminActionType::DeleteUser,
+        Some(user_id.to_string()),
+        Some(json!({ "username": old.username, "email": old.email })),
+    ).await;
+
+    let new = AdminUserRes::from(find_user(&state, user_id).await.expect("Deleted user is missing!"));
+
+    Json(DeleteUserRes { old, new }).into_response()
+}
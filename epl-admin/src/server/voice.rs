@@ -0,0 +1,179 @@
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter};
+use sea_orm::ActiveValue::Set;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::json;
+use epl_common::admin::{AdminActionType, record_admin_action};
+use epl_common::database::entities::{voice_region, voice_server};
+use epl_common::database::entities::prelude::{VoiceRegion, VoiceServer};
+use crate::server::{admin_error, AdminContext, AdminState};
+
+#[derive(Serialize)]
+pub struct VoiceRegionRes {
+    id: String,
+    name: String,
+    vip: bool,
+    deprecated: bool,
+    custom: bool,
+}
+
+#[derive(Serialize)]
+pub struct VoiceServerRes {
+    hostname: String,
+    last_health: f64,
+}
+
+#[derive(Deserialize)]
+pub struct CreateVoiceRegionReq {
+    id: String,
+    name: String,
+    #[serde(default)]
+    deprecated: bool,
+    #[serde(default)]
+    custom: bool,
+}
+
+/// Creates the region, or replaces it if it already exists, and returns every region
+pub async fn create_voice_region(
+    Extension(state): Extension<AdminState>,
+    Extension(admin_context): Extension<AdminContext>,
+    Json(data): Json<CreateVoiceRegionReq>,
+) -> impl IntoResponse {
+    let existing_region = VoiceRegion::find_by_id(data.id.clone())
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    match existing_region {
+        Some(region) => {
+            let mut active_region = region.into_active_model();
+
+            active_region.name = Set(data.name.clone());
+            active_region.deprecated = Set(data.deprecated);
+            active_region.custom = Set(data.custom);
+
+            active_region.update(&state.conn).await.expect("Failed to access database!");
+        }
+        None => {
+            voice_region::ActiveModel {
+                id: Set(data.id.clone()),
+                name: Set(data.name.clone()),
+                latitude: Set(None),
+                longitude: Set(None),
+                deprecated: Set(data.deprecated),
+                custom: Set(data.custom),
+            }
+                .insert(&state.conn)
+                .await
+                .expect("Failed to access database!");
+        }
+    }
+
+    record_admin_action(
+        &state.conn,
+        Some(admin_context.user.id),
+        AdminActionType::CreateVoiceRegion,
+        Some(data.id.clone()),
+        Some(json!({ "name": data.name, "deprecated": data.deprecated, "custom": data.custom })),
+    ).await;
+
+    let regions = VoiceRegion::find()
+        .all(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    Json(regions.into_iter().map(|x| VoiceRegionRes {
+        id: x.id,
+        name: x.name,
+        vip: false,
+        deprecated: x.deprecated,
+        custom: x.custom,
+    }).collect::<Vec<VoiceRegionRes>>())
+}
+
+pub async fn get_voice_servers(
+    Extension(state): Extension<AdminState>,
+    Path(region): Path<String>,
+) -> impl IntoResponse {
+    let servers = VoiceServer::find()
+        .filter(voice_server::Column::RegionId.eq(region))
+        .all(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    Json(servers.into_iter().map(|x| VoiceServerRes {
+        hostname: x.hostname,
+        last_health: x.last_health,
+    }).collect::<Vec<VoiceServerRes>>())
+}
+
+#[derive(Deserialize)]
+pub struct AddVoiceServerReq {
+    hostname: String,
+}
+
+pub async fn add_voice_server(
+    Extension(state): Extension<AdminState>,
+    Extension(admin_context): Extension<AdminContext>,
+    Path(region): Path<String>,
+    Json(data): Json<AddVoiceServerReq>,
+) -> impl IntoResponse {
+    if VoiceRegion::find_by_id(region.clone())
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!")
+        .is_none() {
+        return admin_error(StatusCode::NOT_FOUND, 10036, "Unknown Voice Region");
+    }
+
+    voice_server::ActiveModel {
+        hostname: Set(data.hostname.clone()),
+        region_id: Set(region.clone()),
+        last_health: Set(1.0),
+    }
+        .insert(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    record_admin_action(
+        &state.conn,
+        Some(admin_context.user.id),
+        AdminActionType::AddVoiceServer,
+        Some(data.hostname),
+        Some(json!({ "region": region })),
+    ).await;
+
+    StatusCode::NO_CONTENT.into_response()
+}
+
+pub async fn deprecate_voice_region(
+    Extension(state): Extension<AdminState>,
+    Extension(admin_context): Extension<AdminContext>,
+    Path(region): Path<String>,
+) -> impl IntoResponse {
+    let Some(voice_region) = VoiceRegion::find_by_id(region.clone())
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!") else {
+        return admin_error(StatusCode::NOT_FOUND, 10036, "Unknown Voice Region");
+    };
+
+    let mut active_region = voice_region.into_active_model();
+
+    active_region.deprecated = Set(true);
+
+    active_region.update(&state.conn).await.expect("Failed to access database!");
+
+    record_admin_action(
+        &state.conn,
+        Some(admin_context.user.id),
+        AdminActionType::DeprecateVoiceRegion,
+        Some(region),
+        None,
+    ).await;
+
+    StatusCode::NO_CONTENT.into_response()
+}
@@ -0,0 +1,121 @@
+use aws_sdk_s3::primitives::ByteStream;
+use axum::Extension;
+use axum::extract::{Path, Query};
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use ril::{Image, ImageFormat, ResizeAlgorithm, Rgba};
+use sea_orm::EntityTrait;
+use serde_derive::Deserialize;
+use tracing::error;
+use tracing::log::debug;
+use epl_common::database::entities::prelude::Emoji;
+use epl_common::options::{EplOptions, Options};
+use crate::AppState;
+
+#[derive(Deserialize)]
+pub struct EmojisQuery {
+    pub size: Option<u32>
+}
+
+async fn get_object(state: &AppState, key: String) -> Option<Vec<u8>> {
+    let object = state.aws
+        .get_object()
+        .bucket(EplOptions::get().s3_bucket)
+        .key(key)
+        .send()
+        .await
+        .ok()?;
+
+    Some(object.body.collect().await.ok()?.to_vec())
+}
+
+pub async fn emojis(
+    Path(file): Path<String>,
+    Extension(state): Extension<AppState>,
+    path_query: Query<EmojisQuery>
+) -> impl IntoResponse {
+    debug!("Hello! You wanted the {file} emoji!");
+
+    let (emoji_id, extension) = match file.split_once('.') {
+        Some((emoji_id, extension)) => match emoji_id.parse::<i64>() {
+            Ok(emoji_id) => (emoji_id, extension.to_string()),
+            Err(_) => return StatusCode::NOT_FOUND.into_response(),
+        },
+        None => return StatusCode::NOT_FOUND.into_response(),
+    };
+
+    let (format, content_type) = match extension.as_str() {
+        "webp" => (ImageFormat::WebP, "image/webp"),
+        "png" => (ImageFormat::Png, "image/png"),
+        "gif" => (ImageFormat::Gif, "image/gif"),
+        _ => return StatusCode::BAD_REQUEST.into_response(),
+    };
+
+    let emoji = Emoji::find_by_id(emoji_id)
+        .one(&state.conn)
+        .await
+        .expect("Unable to access database!");
+
+    let emoji = match emoji {
+        None => return StatusCode::NOT_FOUND.into_response(),
+        Some(emoji) => emoji,
+    };
+
+    let headers = [
+        (header::CONTENT_TYPE, content_type),
+        (header::CONTENT_DISPOSITION, &format!("attachment; filename=\"{file}\"")),
+    ];
+
+    // TODO: Resize animated emojis without losing the animation
+    if emoji.animated && extension == "gif" {
+        return match get_object(&state, format!("emojis/{}", emoji.id)).await {
+            None => StatusCode::NOT_FOUND.into_response(),
+            Some(body) => (headers, body).into_response(),
+        };
+    }
+
+    // Cap resizing images
+    if path_query.size.is_some_and(|x| !(16..=4096).contains(&x)) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    let cached_key = format!("emojis/{}.{}.{}", emoji.id, path_query.size.unwrap_or(0), extension);
+
+    if let Some(body) = get_object(&state, cached_key.clone()).await {
+        return (headers, body).into_response();
+    }
+
+    let original = match get_object(&state, format!("emojis/{}", emoji.id)).await {
+        None => return StatusCode::NOT_FOUND.into_response(),
+        Some(original) => original,
+    };
+
+    // Animated emojis only keep their first frame here
+    let mut image: Image<Rgba> = match Image::from_bytes_inferred(original) {
+        Ok(image) => image,
+        Err(error) => {
+            error!("{error}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    if let Some(size) = path_query.size {
+        image.resize(size, size, ResizeAlgorithm::Nearest);
+    }
+
+    let mut image_buffer: Vec<u8> = Vec::new();
+    image.encode(format, &mut image_buffer).expect("Failed to encode image!");
+
+    let upload = state.aws.put_object()
+        .bucket(EplOptions::get().s3_bucket)
+        .key(cached_key)
+        .body(ByteStream::from(image_buffer.clone()))
+        .send()
+        .await;
+
+    if let Err(error) = upload {
+        error!("{error}");
+    }
+
+    (headers, image_buffer).into_response()
+}
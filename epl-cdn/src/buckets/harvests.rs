@@ -0,0 +1,77 @@
+use axum::body::Body;
+use axum::Extension;
+use axum::extract::{Path, Query};
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use chrono::Utc;
+use sea_orm::EntityTrait;
+use serde_derive::Deserialize;
+use tokio_util::io::ReaderStream;
+use epl_common::database::auth::{TicketType, verify_ticket_with_state};
+use epl_common::database::entities::prelude::Harvest;
+use epl_common::harvest::{harvest_expiry, harvest_key, HarvestStatus};
+use epl_common::options::{EplOptions, Options};
+use crate::AppState;
+
+#[derive(Deserialize)]
+pub struct HarvestQuery {
+    ticket: String,
+}
+
+pub async fn get_harvest(
+    Extension(state): Extension<AppState>,
+    Path(query): Path<(i64, String)>,
+    Query(harvest_query): Query<HarvestQuery>,
+) -> impl IntoResponse {
+    let harvest_id = query.0;
+
+    // The ticket is only good for the package it was made for
+    let Some((user_id, Some(ticket_state))) = verify_ticket_with_state(&harvest_query.ticket, TicketType::HarvestDownload) else {
+        return StatusCode::NOT_FOUND.into_response()
+    };
+
+    if ticket_state.as_str() != Some(harvest_id.to_string().as_str()) {
+        return StatusCode::NOT_FOUND.into_response()
+    }
+
+    let harvest = Harvest::find_by_id(harvest_id)
+        .one(&state.conn)
+        .await
+        .expect("Unable to access database!");
+
+    let Some(harvest) = harvest else {
+        return StatusCode::NOT_FOUND.into_response()
+    };
+
+    if harvest.user_id != user_id || harvest.status != HarvestStatus::Completed as i32 {
+        return StatusCode::NOT_FOUND.into_response()
+    }
+
+    if harvest.completed_at.is_some_and(|x| harvest_expiry(x) < Utc::now().naive_utc()) {
+        return StatusCode::NOT_FOUND.into_response()
+    }
+
+    let object = state.aws
+        .get_object()
+        .bucket(EplOptions::get().s3_bucket)
+        .key(harvest_key(harvest.id))
+        .send()
+        .await;
+
+    match object {
+        Ok(object) => {
+            let headers = [
+                (header::CONTENT_TYPE, "application/zip".to_string()),
+                (header::CONTENT_DISPOSITION, format!("attachment; filename=\"package-{}.zip\"", harvest.id)),
+                (header::CONTENT_LENGTH, object.content_length().unwrap_or_default().to_string()),
+            ];
+
+            let stream = ReaderStream::new(object.body.into_async_read());
+
+            (headers, Body::from_stream(stream)).into_response()
+        }
+        Err(_) => {
+            StatusCode::NOT_FOUND.into_response()
+        }
+    }
+}
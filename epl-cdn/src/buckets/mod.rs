@@ -3,6 +3,8 @@ mod channel_icons;
 mod badge_icons;
 mod attachments;
 mod upload;
+mod emojis;
+mod harvests;
 
 use aws_sdk_s3::primitives::ByteStream;
 use axum::body::Body;
@@ -21,6 +23,8 @@ use crate::buckets::attachments::get_attachment;
 use crate::buckets::avatars::avatars;
 use crate::buckets::badge_icons::badge_icons;
 use crate::buckets::channel_icons::channel_icons;
+use crate::buckets::emojis::emojis;
+use crate::buckets::harvests::get_harvest;
 use crate::buckets::upload::upload_attachment;
 
 pub fn buckets() -> Router {
@@ -31,8 +35,12 @@ pub fn buckets() -> Router {
         .route("/channel-icons/:channel_id/:file", get(channel_icons))
         // Badge Icons
         .route("/badge-icons/:file", get(badge_icons))
+        // Custom Emojis
+        .route("/emojis/:file", get(emojis))
         // Attachments
         .route("/attachments/:channel_id/:file_id/:filename", get(get_attachment))
+        // Data packages
+        .route("/harvests/:harvest_id/:filename", get(get_harvest))
         // Uploading
         .route("/upload/:key/:filename", put(upload_attachment))
         .layer(DefaultBodyLimit::max(524288000))
@@ -0,0 +1,70 @@
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, DatabaseConnection};
+use sea_orm::ActiveValue::Set;
+use crate::database::entities::admin_action;
+use crate::rustflake::Snowflake;
+
+/// Something staff did to the instance, every one of these ends up in the audit log
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AdminActionType {
+    CreateUser,
+    DeleteUser,
+    UpdateUserFlags,
+    ResetUserPassword,
+    ModerateUser,
+    RevokeModerationAction,
+    ResolveReport,
+    DismissReport,
+    SendSystemMessage,
+    CreateInstanceInvite,
+    DeleteInstanceInvite,
+    ApproveRegistration,
+    RejectRegistration,
+    CreateVoiceRegion,
+    DeprecateVoiceRegion,
+    AddVoiceServer,
+}
+
+impl AdminActionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdminActionType::CreateUser => "create_user",
+            AdminActionType::DeleteUser => "delete_user",
+            AdminActionType::UpdateUserFlags => "update_user_flags",
+            AdminActionType::ResetUserPassword => "reset_user_password",
+            AdminActionType::ModerateUser => "moderate_user",
+            AdminActionType::RevokeModerationAction => "revoke_moderation_action",
+            AdminActionType::ResolveReport => "resolve_report",
+            AdminActionType::DismissReport => "dismiss_report",
+            AdminActionType::SendSystemMessage => "send_system_message",
+            AdminActionType::CreateInstanceInvite => "create_instance_invite",
+            AdminActionType::DeleteInstanceInvite => "delete_instance_invite",
+            AdminActionType::ApproveRegistration => "approve_registration",
+            AdminActionType::RejectRegistration => "reject_registration",
+            AdminActionType::CreateVoiceRegion => "create_voice_region",
+            AdminActionType::DeprecateVoiceRegion => "deprecate_voice_region",
+            AdminActionType::AddVoiceServer => "add_voice_server",
+        }
+    }
+}
+
+/// Adds an entry to the audit log, the actor is None for actions done from the CLI
+pub async fn record_admin_action(
+    conn: &DatabaseConnection,
+    actor_id: Option<i64>,
+    action: AdminActionType,
+    target: Option<String>,
+    data: Option<serde_json::Value>,
+) {
+    admin_action::ActiveModel {
+        id: Set(Snowflake::default().generate()),
+        actor_id: Set(actor_id),
+        action: Set(action.as_str().to_string()),
+        target: Set(target),
+        data: Set(data),
+        created_at: Set(Utc::now().naive_utc()),
+    }
+        .insert(conn)
+        .await
+        .expect("Failed to access database!");
+}
@@ -0,0 +1,176 @@
+use async_nats::Client;
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, ModelTrait, QueryFilter};
+use sea_orm::ActiveValue::Set;
+use serde_derive::{Deserialize, Serialize};
+use crate::database::entities::{call, channel, channel_member, message};
+use crate::database::entities::prelude::{Call, ChannelMember, Message};
+use crate::messages::MessageTypes;
+use crate::nats::Messages::{CallCreate, CallDelete, CallUpdate, MessageCreate, MessageUpdate};
+use crate::nats::send_nats_message;
+use crate::rustflake::Snowflake;
+
+/// The `call` field of a call message
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MessageCall {
+    /// Everyone who joined the call at some point
+    pub participants: Vec<String>,
+    pub ended_timestamp: Option<String>,
+}
+
+pub async fn get_call(conn: &DatabaseConnection, channel_id: i64) -> Option<call::Model> {
+    Call::find_by_id(channel_id)
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+pub fn get_ringing(call: &call::Model) -> Vec<i64> {
+    serde_json::from_value::<Vec<String>>(call.ringing.clone())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|x| x.parse().ok())
+        .collect()
+}
+
+/// Replaces who is being rung in a call, sends CALL_UPDATE if anything changed
+pub async fn set_ringing(conn: &DatabaseConnection, nats_client: &Client, call: call::Model, ringing: Vec<i64>) -> call::Model {
+    if get_ringing(&call) == ringing {
+        return call;
+    }
+
+    let channel_id = call.channel_id;
+    let mut active_call = call.into_active_model();
+
+    active_call.ringing = Set(serde_json::to_value(ringing.iter().map(|x| x.to_string()).collect::<Vec<String>>())
+        .expect("Failed to encode ringing list!"));
+
+    let updated_call = active_call.update(conn).await.expect("Failed to access database!");
+
+    send_nats_message(
+        nats_client,
+        channel_id.to_string(),
+        CallUpdate { channel_id },
+    ).await;
+
+    updated_call
+}
+
+/// Starts a call in a channel with a call message, ringing everyone else in it
+pub async fn start_call(conn: &DatabaseConnection, nats_client: &Client, channel: &channel::Model, user_id: i64) -> call::Model {
+    let ringing: Vec<String> = ChannelMember::find()
+        .filter(channel_member::Column::Channel.eq(channel.id))
+        .filter(channel_member::Column::User.ne(user_id))
+        .all(conn)
+        .await
+        .expect("Failed to access database!")
+        .iter()
+        .map(|x| x.user.to_string())
+        .collect();
+
+    let message_id = Snowflake::default().generate();
+
+    message::ActiveModel {
+        id: Set(message_id),
+        channel_id: Set(channel.id),
+        author: Set(Some(user_id)),
+        content: Set(String::new()),
+        timestamp: Set(Utc::now().naive_utc()),
+        r#type: Set(MessageTypes::Call as i32),
+        tts: Set(false),
+        mention_everyone: Set(false),
+        pinned: Set(false),
+        call: Set(serde_json::to_value(MessageCall {
+            participants: vec![user_id.to_string()],
+            ended_timestamp: None,
+        }).ok()),
+        ..Default::default()
+    }
+        .insert(conn)
+        .await
+        .expect("Failed to access database!");
+
+    let new_call = call::ActiveModel {
+        channel_id: Set(channel.id),
+        message_id: Set(message_id),
+        region: Set(channel.rtc_region.clone()),
+        ringing: Set(serde_json::to_value(ringing).expect("Failed to encode ringing list!")),
+    }
+        .insert(conn)
+        .await
+        .expect("Failed to access database!");
+
+    send_nats_message(
+        nats_client,
+        channel.id.to_string(),
+        MessageCreate { id: message_id },
+    ).await;
+
+    send_nats_message(
+        nats_client,
+        channel.id.to_string(),
+        CallCreate { channel_id: channel.id },
+    ).await;
+
+    new_call
+}
+
+/// Adds someone who joined a call to its participants, and stops ringing them
+pub async fn join_call(conn: &DatabaseConnection, nats_client: &Client, call: call::Model, user_id: i64) {
+    let call_message = Message::find_by_id(call.message_id)
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+        .expect("Call references non-existent message!");
+
+    let mut message_call: MessageCall = call_message.call.clone()
+        .and_then(|x| serde_json::from_value(x).ok())
+        .unwrap_or(MessageCall { participants: vec![], ended_timestamp: None });
+
+    if !message_call.participants.contains(&user_id.to_string()) {
+        message_call.participants.push(user_id.to_string());
+
+        let mut active_message = call_message.into_active_model();
+        active_message.call = Set(serde_json::to_value(message_call).ok());
+        active_message.update(conn).await.expect("Failed to access database!");
+    }
+
+    let ringing = get_ringing(&call).into_iter().filter(|x| *x != user_id).collect();
+
+    set_ringing(conn, nats_client, call, ringing).await;
+}
+
+/// Ends a call once everyone has left, stamping the call message with when it ended
+pub async fn end_call(conn: &DatabaseConnection, nats_client: &Client, call: call::Model) {
+    let channel_id = call.channel_id;
+
+    if let Some(call_message) = Message::find_by_id(call.message_id)
+        .one(conn)
+        .await
+        .expect("Failed to access database!") {
+        let mut message_call: MessageCall = call_message.call.clone()
+            .and_then(|x| serde_json::from_value(x).ok())
+            .unwrap_or(MessageCall { participants: vec![], ended_timestamp: None });
+
+        message_call.ended_timestamp = Some(Utc::now().format("%Y-%m-%dT%H:%M:%S%z").to_string());
+
+        let message_id = call_message.id;
+        let mut active_message = call_message.into_active_model();
+        active_message.call = Set(serde_json::to_value(message_call).ok());
+        active_message.update(conn).await.expect("Failed to access database!");
+
+        send_nats_message(
+            nats_client,
+            channel_id.to_string(),
+            MessageUpdate { id: message_id },
+        ).await;
+    }
+
+    call.delete(conn).await.expect("Failed to access database!");
+
+    send_nats_message(
+        nats_client,
+        channel_id.to_string(),
+        CallDelete { channel_id },
+    ).await;
+}
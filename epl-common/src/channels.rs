@@ -18,7 +18,7 @@ pub enum ChannelTypes {
     GuildForum = 15,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, FromPrimitive)]
 #[repr(i32)]
 pub enum VideoQualityModes {
     Auto = 1,
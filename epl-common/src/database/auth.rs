@@ -6,10 +6,15 @@ use argon2::{
     password_hash::{rand_core::OsRng, PasswordHasher, SaltString},
     Argon2,
 };
-use chrono::{Days, Utc};
+use base64::prelude::*;
+use chrono::{Days, Duration, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use sea_orm::{ActiveValue, DatabaseConnection};
 
 use crate::{gen_session_id, gen_token};
+use crate::options::{EplOptions, Options};
 use zxcvbn::zxcvbn;
 
 use sea_orm::*;
@@ -18,8 +23,97 @@ use sea_orm::*;
 struct Claims {
     iss: String,
     sub: String,
+    /// What the ticket can be used for
+    aud: String,
     exp: usize,
     iat: usize,
+    /// Anything that has to be remembered until the ticket is used, like a WebAuthn challenge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    state: Option<serde_json::Value>,
+}
+
+/// What a ticket can be exchanged for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TicketType {
+    /// Finishing a login with MFA
+    Login,
+    /// Answering an MFA challenge for a sensitive action
+    MfaChallenge,
+    /// Finishing registering a WebAuthn credential
+    WebauthnRegistration,
+    /// Proving MFA was done recently for sensitive actions
+    MfaVerified,
+    /// Resetting a forgotten password from an emailed link
+    PasswordReset,
+    /// Verifying an email address from an emailed link
+    EmailVerification,
+    /// Approving a login from a new location from an emailed link
+    LoginApproval,
+    /// Downloading a data package from the CDN
+    HarvestDownload,
+}
+
+impl TicketType {
+    fn audience(&self) -> &'static str {
+        match self {
+            TicketType::Login => "login",
+            TicketType::MfaChallenge => "mfa_challenge",
+            TicketType::WebauthnRegistration => "webauthn_registration",
+            TicketType::MfaVerified => "mfa",
+            TicketType::PasswordReset => "password_reset",
+            TicketType::EmailVerification => "email_verification",
+            TicketType::LoginApproval => "login_approval",
+            TicketType::HarvestDownload => "harvest_download",
+        }
+    }
+}
+
+/// Creates a short-lived ticket signed with the instance's secret key
+pub fn create_ticket(user: i64, ticket_type: TicketType, lifetime: Duration) -> String {
+    create_ticket_with_state(user, ticket_type, lifetime, None)
+}
+
+/// Creates a ticket that carries some state, the state is signed but readable by the client
+pub fn create_ticket_with_state(
+    user: i64,
+    ticket_type: TicketType,
+    lifetime: Duration,
+    state: Option<serde_json::Value>,
+) -> String {
+    let options = EplOptions::get();
+    let current_time = Utc::now();
+
+    let claims = Claims {
+        iss: options.url,
+        sub: user.to_string(),
+        aud: ticket_type.audience().to_string(),
+        exp: (current_time + lifetime).timestamp() as usize,
+        iat: current_time.timestamp() as usize,
+        state,
+    };
+
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(options.secret_key.as_bytes()))
+        .expect("Failed to sign ticket!")
+}
+
+/// Checks a ticket, returns the user it was made for
+pub fn verify_ticket(ticket: &str, ticket_type: TicketType) -> Option<i64> {
+    verify_ticket_with_state(ticket, ticket_type).map(|(user, _)| user)
+}
+
+/// Checks a ticket, returns the user it was made for along with its state
+pub fn verify_ticket_with_state(ticket: &str, ticket_type: TicketType) -> Option<(i64, Option<serde_json::Value>)> {
+    let options = EplOptions::get();
+
+    let mut validation = Validation::default();
+    validation.set_audience(&[ticket_type.audience()]);
+    validation.set_issuer(&[options.url]);
+
+    let claims = decode::<Claims>(ticket, &DecodingKey::from_secret(options.secret_key.as_bytes()), &validation)
+        .ok()?
+        .claims;
+
+    Some((claims.sub.parse().ok()?, claims.state))
 }
 
 #[derive(Debug, Clone)]
@@ -34,23 +128,88 @@ pub enum NewSessionEnum {
     BadUser,
 }
 
-/// Creates a new session for the specified user, returns the JWT
+/// How long a session lasts after it was last used
+pub const SESSION_LIFETIME: Days = Days::new(30);
+
+/// How many minutes have to pass between uses of a session before its expiry is pushed back
+const SESSION_TOUCH_INTERVAL: i64 = 5;
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn sign_token_parts(parts: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(EplOptions::get().secret_key.as_bytes())
+        .expect("Failed to create HMAC!");
+
+    mac.update(parts.as_bytes());
+
+    mac
+}
+
+/// Creates a Discord-style token made of the user ID, the creation time and a signature over both
+fn gen_signed_token(user: i64) -> String {
+    let parts = format!(
+        "{}.{}",
+        BASE64_URL_SAFE_NO_PAD.encode(user.to_string()),
+        BASE64_URL_SAFE_NO_PAD.encode(Utc::now().timestamp_millis().to_string())
+    );
+
+    let signature = BASE64_URL_SAFE_NO_PAD.encode(sign_token_parts(&parts).finalize().into_bytes());
+
+    format!("{}.{}", parts, signature)
+}
+
+/// Checks the signature of a self-describing token without touching the database, returns the user ID in it
+pub fn verify_signed_token(token: &str) -> Option<i64> {
+    let (parts, signature) = token.rsplit_once('.')?;
+
+    sign_token_parts(parts)
+        .verify_slice(&BASE64_URL_SAFE_NO_PAD.decode(signature).ok()?)
+        .ok()?;
+
+    let (user, _) = parts.split_once('.')?;
+
+    String::from_utf8(BASE64_URL_SAFE_NO_PAD.decode(user).ok()?).ok()?.parse().ok()
+}
+
+/// Creates a new session for the specified user, returns the token
 pub async fn generate_session(
     conn: &DatabaseConnection,
     user: i64,
+) -> Result<String, NewSessionError> {
+    insert_session(conn, user, SESSION_LIFETIME, None).await
+}
+
+/// Creates a new long-lived session for a bot user, returns the token
+pub async fn generate_bot_session(
+    conn: &DatabaseConnection,
+    user: i64,
+) -> Result<String, NewSessionError> {
+    // Bot tokens don't expire until they are reset
+    insert_session(conn, user, Days::new(365 * 100), Some(String::from("Bot"))).await
+}
+
+async fn insert_session(
+    conn: &DatabaseConnection,
+    user: i64,
+    lifetime: Days,
+    platform: Option<String>,
 ) -> Result<String, NewSessionError> {
     let current_time = Utc::now().naive_utc();
     let expiry_time = current_time
-        .checked_add_days(Days::new(30))
+        .checked_add_days(lifetime)
         .expect("Time has broken!");
-    let token = gen_token();
+    let token = if EplOptions::get().signed_tokens {
+        gen_signed_token(user)
+    } else {
+        gen_token()
+    };
     let session_id = gen_session_id();
 
     let new_session = session::ActiveModel {
         token: ActiveValue::Set(token.clone()),
         status: ActiveValue::Set(String::from("online")),
         os: ActiveValue::Set(None),
-        platform: ActiveValue::Set(None),
+        platform: ActiveValue::Set(platform),
         last_used: ActiveValue::Set(current_time),
         user_id: ActiveValue::Set(user),
         iat: ActiveValue::Set(current_time),
@@ -80,37 +239,26 @@ pub struct GetSessionError {
 pub enum GetSessionEnum {
     SeaORM,
     BadUser,
+    Expired,
 }
 
 pub async fn get_user_from_session_by_token(
     conn: &DatabaseConnection,
     token: &String,
 ) -> Result<user::Model, GetSessionError> {
-    let session: Option<session::Model> = Session::find()
-        .filter(session::Column::Token.eq(token))
+    let session = get_session_by_token(conn, token).await?;
+
+    let user: Option<user::Model> = User::find_by_id(session.user_id)
         .one(conn)
         .await
         .expect("Failed to access db!");
 
-    match session {
+    match user {
         None => Err(GetSessionError {
             kind: GetSessionEnum::BadUser,
-            message: "Session not found!".to_string(),
+            message: "User not found!".to_string(),
         }),
-        Some(session) => {
-            let user: Option<user::Model> = User::find_by_id(session.user_id)
-                .one(conn)
-                .await
-                .expect("Failed to access db!");
-
-            match user {
-                None => Err(GetSessionError {
-                    kind: GetSessionEnum::BadUser,
-                    message: "User not found!".to_string(),
-                }),
-                Some(user) => Ok(user),
-            }
-        }
+        Some(user) => Ok(user),
     }
 }
 
@@ -118,6 +266,14 @@ pub async fn get_session_by_token(
     conn: &DatabaseConnection,
     token: &String,
 ) -> Result<session::Model, GetSessionError> {
+    // Signed tokens can be turned away without looking them up
+    if EplOptions::get().signed_tokens && verify_signed_token(token).is_none() {
+        return Err(GetSessionError {
+            kind: GetSessionEnum::BadUser,
+            message: "Token signature is invalid!".to_string(),
+        });
+    }
+
     let session: Option<session::Model> = Session::find()
         .filter(session::Column::Token.eq(token))
         .one(conn)
@@ -129,10 +285,38 @@ pub async fn get_session_by_token(
             kind: GetSessionEnum::BadUser,
             message: "Session not found!".to_string(),
         }),
+        Some(session) if session.exp < Utc::now().naive_utc() => Err(GetSessionError {
+            kind: GetSessionEnum::Expired,
+            message: "Session has expired!".to_string(),
+        }),
         Some(session) => Ok(session),
     }
 }
 
+/// Pushes back the expiry of a session that's being used, bot sessions already outlive this
+pub async fn touch_session(conn: &DatabaseConnection, session: session::Model) -> session::Model {
+    let current_time = Utc::now().naive_utc();
+
+    // Don't write to the database on every single request
+    if current_time - session.last_used < Duration::minutes(SESSION_TOUCH_INTERVAL) {
+        return session;
+    }
+
+    let expiry_time = current_time
+        .checked_add_days(SESSION_LIFETIME)
+        .expect("Time has broken!");
+
+    let mut active_session = session.clone().into_active_model();
+
+    active_session.last_used = ActiveValue::Set(current_time);
+
+    if session.exp < expiry_time {
+        active_session.exp = ActiveValue::Set(expiry_time);
+    }
+
+    active_session.update(conn).await.expect("Failed to access db!")
+}
+
 pub async fn get_session_by_id(
     conn: &DatabaseConnection,
     id: &String,
@@ -187,7 +371,7 @@ impl From<argon2::password_hash::Error> for NewUserError {
 
 /// Creates a new user in the database, returns the ID of the user
 pub async fn create_user(
-    conn: &DatabaseConnection,
+    conn: &impl ConnectionTrait,
     data: user::ActiveModel,
 ) -> Result<i64, NewUserError> {
     User::insert(data.clone())
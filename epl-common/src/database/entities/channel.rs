@@ -43,8 +43,12 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     SelfRef,
+    #[sea_orm(has_many = "super::call::Entity")]
+    Call,
     #[sea_orm(has_many = "super::channel_member::Entity")]
     ChannelMember,
+    #[sea_orm(has_many = "super::interaction::Entity")]
+    Interaction,
     #[sea_orm(
         belongs_to = "super::message::Entity",
         from = "Column::LastMessageId",
@@ -65,6 +69,14 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     User,
+    #[sea_orm(has_many = "super::voice_state::Entity")]
+    VoiceState,
+}
+
+impl Related<super::call::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Call.def()
+    }
 }
 
 impl Related<super::channel_member::Entity> for Entity {
@@ -73,6 +85,12 @@ impl Related<super::channel_member::Entity> for Entity {
     }
 }
 
+impl Related<super::interaction::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Interaction.def()
+    }
+}
+
 impl Related<super::pin::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Pin.def()
@@ -100,4 +118,10 @@ impl Related<super::message::Entity> for Entity {
     }
 }
 
+impl Related<super::voice_state::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VoiceState.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
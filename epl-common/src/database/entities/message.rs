@@ -22,10 +22,15 @@ pub struct Model {
     pub reference_message_id: Option<i64>,
     pub flags: Option<i32>,
     pub reference_channel_id: Option<i64>,
+    pub interaction: Option<Json>,
+    pub components: Option<Json>,
+    pub call: Option<Json>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::call::Entity")]
+    Call,
     #[sea_orm(
         belongs_to = "super::channel::Entity",
         from = "Column::ChannelId",
@@ -70,6 +75,12 @@ pub enum Relation {
     User,
 }
 
+impl Related<super::call::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Call.def()
+    }
+}
+
 impl Related<super::embed::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Embed.def()
@@ -2,19 +2,39 @@
 
 pub mod prelude;
 
+pub mod admin_action;
+pub mod application;
+pub mod application_command;
 pub mod april_fools2024;
+pub mod backup_code;
+pub mod call;
 pub mod channel;
 pub mod channel_member;
 pub mod embed;
+pub mod emoji;
 pub mod file;
 pub mod frecency;
+pub mod harvest;
+pub mod import_mapping;
+pub mod instance_invite;
+pub mod interaction;
+pub mod login_attempt;
 pub mod mention;
 pub mod message;
 pub mod message_attachment;
+pub mod moderation_action;
 pub mod note;
+pub mod oauth2_code;
+pub mod oauth2_token;
 pub mod pin;
 pub mod reaction;
+pub mod registration_request;
 pub mod relationship;
+pub mod report;
 pub mod session;
 pub mod user;
 pub mod user_setting;
+pub mod voice_region;
+pub mod voice_server;
+pub mod voice_state;
+pub mod webauthn_credential;
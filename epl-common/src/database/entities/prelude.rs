@@ -1,18 +1,38 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.15
 
+pub use super::admin_action::Entity as AdminAction;
+pub use super::application::Entity as Application;
+pub use super::application_command::Entity as ApplicationCommand;
 pub use super::april_fools2024::Entity as AprilFools2024;
+pub use super::backup_code::Entity as BackupCode;
+pub use super::call::Entity as Call;
 pub use super::channel::Entity as Channel;
 pub use super::channel_member::Entity as ChannelMember;
 pub use super::embed::Entity as Embed;
+pub use super::emoji::Entity as Emoji;
 pub use super::file::Entity as File;
 pub use super::frecency::Entity as Frecency;
+pub use super::harvest::Entity as Harvest;
+pub use super::import_mapping::Entity as ImportMapping;
+pub use super::instance_invite::Entity as InstanceInvite;
+pub use super::interaction::Entity as Interaction;
+pub use super::login_attempt::Entity as LoginAttempt;
 pub use super::mention::Entity as Mention;
 pub use super::message::Entity as Message;
 pub use super::message_attachment::Entity as MessageAttachment;
+pub use super::moderation_action::Entity as ModerationAction;
 pub use super::note::Entity as Note;
+pub use super::oauth2_code::Entity as Oauth2Code;
+pub use super::oauth2_token::Entity as Oauth2Token;
 pub use super::pin::Entity as Pin;
 pub use super::reaction::Entity as Reaction;
+pub use super::registration_request::Entity as RegistrationRequest;
 pub use super::relationship::Entity as Relationship;
+pub use super::report::Entity as Report;
 pub use super::session::Entity as Session;
 pub use super::user::Entity as User;
 pub use super::user_setting::Entity as UserSetting;
+pub use super::voice_region::Entity as VoiceRegion;
+pub use super::voice_server::Entity as VoiceServer;
+pub use super::voice_state::Entity as VoiceState;
+pub use super::webauthn_credential::Entity as WebauthnCredential;
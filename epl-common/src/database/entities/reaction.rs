@@ -11,7 +11,8 @@ pub struct Model {
     pub message: i64,
     #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
     pub emoji: String,
-    pub burst: bool,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub r#type: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -44,30 +44,68 @@ pub struct Model {
     pub display_name: Option<String>,
     #[sea_orm(column_type = "Text", nullable)]
     pub legacy_name: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub totp_secret: Option<String>,
+    pub verification_sent_at: Option<DateTime>,
+    pub deletion_scheduled_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::admin_action::Entity")]
+    AdminAction,
     #[sea_orm(has_many = "super::april_fools2024::Entity")]
     AprilFools2024,
+    #[sea_orm(has_many = "super::backup_code::Entity")]
+    BackupCode,
     #[sea_orm(has_many = "super::channel::Entity")]
     Channel,
     #[sea_orm(has_many = "super::channel_member::Entity")]
     ChannelMember,
+    #[sea_orm(has_many = "super::emoji::Entity")]
+    Emoji,
     #[sea_orm(has_many = "super::file::Entity")]
     File,
     #[sea_orm(has_many = "super::frecency::Entity")]
     Frecency,
+    #[sea_orm(has_many = "super::harvest::Entity")]
+    Harvest,
+    #[sea_orm(has_many = "super::instance_invite::Entity")]
+    InstanceInvite,
+    #[sea_orm(has_many = "super::interaction::Entity")]
+    Interaction,
+    #[sea_orm(has_many = "super::login_attempt::Entity")]
+    LoginAttempt,
     #[sea_orm(has_many = "super::mention::Entity")]
     Mention,
     #[sea_orm(has_many = "super::message::Entity")]
     Message,
+    #[sea_orm(has_many = "super::moderation_action::Entity")]
+    ModerationAction,
+    #[sea_orm(has_many = "super::oauth2_code::Entity")]
+    Oauth2Code,
+    #[sea_orm(has_many = "super::oauth2_token::Entity")]
+    Oauth2Token,
     #[sea_orm(has_many = "super::reaction::Entity")]
     Reaction,
+    #[sea_orm(has_many = "super::registration_request::Entity")]
+    RegistrationRequest,
     #[sea_orm(has_many = "super::relationship::Entity")]
     Relationship,
+    #[sea_orm(has_many = "super::report::Entity")]
+    Report,
     #[sea_orm(has_many = "super::user_setting::Entity")]
     UserSetting,
+    #[sea_orm(has_many = "super::voice_state::Entity")]
+    VoiceState,
+    #[sea_orm(has_many = "super::webauthn_credential::Entity")]
+    WebauthnCredential,
+}
+
+impl Related<super::admin_action::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AdminAction.def()
+    }
 }
 
 impl Related<super::april_fools2024::Entity> for Entity {
@@ -76,6 +114,12 @@ impl Related<super::april_fools2024::Entity> for Entity {
     }
 }
 
+impl Related<super::backup_code::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BackupCode.def()
+    }
+}
+
 impl Related<super::channel::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Channel.def()
@@ -88,6 +132,12 @@ impl Related<super::channel_member::Entity> for Entity {
     }
 }
 
+impl Related<super::emoji::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Emoji.def()
+    }
+}
+
 impl Related<super::file::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::File.def()
@@ -100,24 +150,78 @@ impl Related<super::frecency::Entity> for Entity {
     }
 }
 
+impl Related<super::harvest::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Harvest.def()
+    }
+}
+
+impl Related<super::instance_invite::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::InstanceInvite.def()
+    }
+}
+
+impl Related<super::interaction::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Interaction.def()
+    }
+}
+
+impl Related<super::login_attempt::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::LoginAttempt.def()
+    }
+}
+
 impl Related<super::mention::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Mention.def()
     }
 }
 
+impl Related<super::moderation_action::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ModerationAction.def()
+    }
+}
+
+impl Related<super::oauth2_code::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Oauth2Code.def()
+    }
+}
+
+impl Related<super::oauth2_token::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Oauth2Token.def()
+    }
+}
+
 impl Related<super::reaction::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Reaction.def()
     }
 }
 
+impl Related<super::registration_request::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RegistrationRequest.def()
+    }
+}
+
 impl Related<super::relationship::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Relationship.def()
     }
 }
 
+impl Related<super::report::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Report.def()
+    }
+}
+
 impl Related<super::user_setting::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::UserSetting.def()
@@ -133,4 +237,16 @@ impl Related<super::message::Entity> for Entity {
     }
 }
 
+impl Related<super::voice_state::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VoiceState.def()
+    }
+}
+
+impl Related<super::webauthn_credential::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WebauthnCredential.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -61,6 +61,7 @@ pub struct Model {
     pub localization_timezone_offset: i32,
     pub appearance_theme: i32,
     pub appearance_developer_mode: bool,
+    pub privacy_allow_dms_from_everyone: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
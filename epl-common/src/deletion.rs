@@ -0,0 +1,315 @@
+use async_nats::Client;
+use rand::distributions::{Alphanumeric, DistString};
+use sea_orm::{ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter};
+use sea_orm::ActiveValue::Set;
+use crate::channels::ChannelTypes;
+use crate::database::auth::generate_password_hash;
+use crate::database::entities::prelude::{Application, ApplicationCommand, BackupCode, Channel, ChannelMember, Frecency, Harvest, Interaction, LoginAttempt, Note, Oauth2Code, Oauth2Token, Relationship, Session, User, UserSetting, WebauthnCredential};
+use crate::database::entities::{application, application_command, backup_code, channel_member, frecency, harvest, interaction, login_attempt, note, oauth2_code, oauth2_token, relationship, session, user, user_setting, webauthn_credential};
+use crate::flags::UserFlags;
+use crate::harvest::harvest_key;
+use crate::nats::{Messages, send_nats_message};
+use crate::RelationshipType;
+
+/// Removes everything personal about a user and leaves behind a "Deleted User" so their messages still make sense
+pub async fn delete_user(conn: &DatabaseConnection, nats_client: &Client, user: user::Model) {
+    let user_id = user.id;
+
+    // Log them out everywhere first
+    Session::delete_many()
+        .filter(session::Column::UserId.eq(user_id))
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    send_nats_message(
+        nats_client,
+        user_id.to_string(),
+        Messages::InvalidateGatewaySession { session: "all".to_string() },
+    ).await;
+
+    remove_relationships(conn, nats_client, user_id).await;
+    leave_group_dms(conn, nats_client, user_id).await;
+    delete_applications(conn, nats_client, user_id).await;
+
+    Note::delete_many()
+        .filter(
+            Condition::any()
+                .add(note::Column::Creator.eq(user_id))
+                .add(note::Column::Subject.eq(user_id))
+        )
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    UserSetting::delete_many()
+        .filter(user_setting::Column::User.eq(user_id))
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    Frecency::delete_many()
+        .filter(frecency::Column::User.eq(user_id))
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    BackupCode::delete_many()
+        .filter(backup_code::Column::UserId.eq(user_id))
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    WebauthnCredential::delete_many()
+        .filter(webauthn_credential::Column::UserId.eq(user_id))
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    Oauth2Code::delete_many()
+        .filter(oauth2_code::Column::UserId.eq(user_id))
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    Oauth2Token::delete_many()
+        .filter(oauth2_token::Column::UserId.eq(user_id))
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    LoginAttempt::delete_many()
+        .filter(login_attempt::Column::UserId.eq(user_id))
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    Harvest::delete_many()
+        .filter(harvest::Column::UserId.eq(user_id))
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    // Badges and staff flags say something about who the user was, so none of them are kept
+    let flags = UserFlags::Deleted as i64 | UserFlags::SelfDeleted as i64;
+
+    let password_hash = random_password_hash();
+
+    let mut active_user = user.into_active_model();
+
+    active_user.username = Set("Deleted User".to_string());
+    active_user.discriminator = Set("0000".to_string());
+    active_user.display_name = Set(None);
+    active_user.legacy_name = Set(None);
+    active_user.email = Set(format!("{}@deleted.invalid", user_id));
+    active_user.phone = Set(None);
+    active_user.password_hash = Set(password_hash);
+    active_user.bio = Set(None);
+    active_user.pronouns = Set(None);
+    active_user.avatar = Set(None);
+    active_user.avatar_decoration = Set(None);
+    active_user.banner = Set(None);
+    active_user.banner_colour = Set(None);
+    active_user.accent_color = Set(None);
+    active_user.date_of_birth = Set(None);
+    active_user.mfa_enabled = Set(false);
+    active_user.totp_secret = Set(None);
+    active_user.acct_verified = Set(false);
+    active_user.deletion_scheduled_at = Set(None);
+    active_user.flags = Set(flags);
+
+    active_user.update(conn).await.expect("Failed to access database!");
+}
+
+/// Nobody knows this password, so the account can't be logged into anymore
+fn random_password_hash() -> String {
+    generate_password_hash(&Alphanumeric.sample_string(&mut rand::thread_rng(), 64), vec![])
+        .expect("Failed to generate password hash!")
+}
+
+/// Deletes every application the user owns, their bots stay behind as deleted users so their messages still make sense
+async fn delete_applications(conn: &DatabaseConnection, nats_client: &Client, user_id: i64) {
+    let applications = Application::find()
+        .filter(application::Column::OwnerId.eq(user_id))
+        .all(conn)
+        .await
+        .expect("Failed to access database!");
+
+    for application in applications {
+        ApplicationCommand::delete_many()
+            .filter(application_command::Column::ApplicationId.eq(application.id))
+            .exec(conn)
+            .await
+            .expect("Failed to access database!");
+
+        Interaction::delete_many()
+            .filter(interaction::Column::ApplicationId.eq(application.id))
+            .exec(conn)
+            .await
+            .expect("Failed to access database!");
+
+        Oauth2Code::delete_many()
+            .filter(oauth2_code::Column::ApplicationId.eq(application.id))
+            .exec(conn)
+            .await
+            .expect("Failed to access database!");
+
+        Oauth2Token::delete_many()
+            .filter(oauth2_token::Column::ApplicationId.eq(application.id))
+            .exec(conn)
+            .await
+            .expect("Failed to access database!");
+
+        Application::delete_by_id(application.id)
+            .exec(conn)
+            .await
+            .expect("Failed to access database!");
+
+        let Some(bot_user) = application.bot_user_id else {
+            continue;
+        };
+
+        Session::delete_many()
+            .filter(session::Column::UserId.eq(bot_user))
+            .exec(conn)
+            .await
+            .expect("Failed to access database!");
+
+        send_nats_message(
+            nats_client,
+            bot_user.to_string(),
+            Messages::InvalidateGatewaySession { session: "all".to_string() },
+        ).await;
+
+        let Some(bot_user) = User::find_by_id(bot_user)
+            .one(conn)
+            .await
+            .expect("Failed to access database!") else {
+            continue;
+        };
+
+        let mut active_bot_user = bot_user.into_active_model();
+
+        active_bot_user.password_hash = Set(random_password_hash());
+        active_bot_user.flags = Set(UserFlags::Deleted as i64);
+
+        active_bot_user.update(conn).await.expect("Failed to access database!");
+    }
+}
+
+/// Removes the user's avatars and data packages from S3, has to happen before [`delete_user`] while the harvests are still around
+pub async fn delete_user_files(conn: &DatabaseConnection, aws: &aws_sdk_s3::Client, bucket: &str, user_id: i64) {
+    if let Ok(objects) = aws.list_objects_v2()
+        .bucket(bucket)
+        .prefix(format!("avatars/{}/", user_id))
+        .send()
+        .await {
+        for object in objects.contents() {
+            if let Some(key) = object.key() {
+                let _ = aws.delete_object().bucket(bucket).key(key).send().await;
+            }
+        }
+    }
+
+    let harvests = Harvest::find()
+        .filter(harvest::Column::UserId.eq(user_id))
+        .all(conn)
+        .await
+        .expect("Failed to access database!");
+
+    for harvest in harvests {
+        let _ = aws.delete_object().bucket(bucket).key(harvest_key(harvest.id)).send().await;
+    }
+}
+
+async fn remove_relationships(conn: &DatabaseConnection, nats_client: &Client, user_id: i64) {
+    let relationships = Relationship::find()
+        .filter(
+            Condition::any()
+                .add(relationship::Column::Creator.eq(user_id))
+                .add(relationship::Column::Peer.eq(user_id))
+        )
+        .all(conn)
+        .await
+        .expect("Failed to access database!");
+
+    for relationship in relationships {
+        let other_user = if relationship.creator == user_id { relationship.peer } else { relationship.creator };
+
+        // What the relationship looked like from the other side
+        let req_type = if relationship.relationship_type == RelationshipType::Friend as i32 {
+            Some(RelationshipType::Friend)
+        } else if relationship.relationship_type == RelationshipType::Blocked as i32 {
+            // Only whoever blocked knows about it
+            (relationship.creator == other_user).then_some(RelationshipType::Blocked)
+        } else if relationship.creator == other_user {
+            Some(RelationshipType::Outgoing)
+        } else {
+            Some(RelationshipType::Incoming)
+        };
+
+        Relationship::delete_by_id((relationship.creator, relationship.peer))
+            .exec(conn)
+            .await
+            .expect("Failed to access database!");
+
+        if let Some(req_type) = req_type {
+            send_nats_message(
+                nats_client,
+                other_user.to_string(),
+                Messages::RelationshipRemove { user_id, req_type },
+            ).await;
+        }
+    }
+}
+
+/// Leaves every group DM, handing ownership to someone else who's still there
+async fn leave_group_dms(conn: &DatabaseConnection, nats_client: &Client, user_id: i64) {
+    let memberships = ChannelMember::find()
+        .filter(channel_member::Column::User.eq(user_id))
+        .find_also_related(Channel)
+        .all(conn)
+        .await
+        .expect("Failed to access database!");
+
+    for (membership, channel) in memberships {
+        // 1:1 DMs stay so the other person keeps the conversation
+        let channel = match channel {
+            Some(channel) if channel.r#type == ChannelTypes::GroupDM as i32 => channel,
+            _ => continue,
+        };
+
+        ChannelMember::delete(membership.into_active_model())
+            .exec(conn)
+            .await
+            .expect("Failed to access database!");
+
+        send_nats_message(
+            nats_client,
+            channel.id.to_string(),
+            Messages::ChannelRecipientRemove { channel_id: channel.id, user_id },
+        ).await;
+
+        if channel.owner_id != Some(user_id) {
+            continue;
+        }
+
+        let new_owner = ChannelMember::find()
+            .filter(channel_member::Column::Channel.eq(channel.id))
+            .one(conn)
+            .await
+            .expect("Failed to access database!");
+
+        // If nobody is left there's no one to hand it to, the channel just sits there
+        if let Some(new_owner) = new_owner {
+            let channel_id = channel.id;
+            let mut channel = channel.into_active_model();
+
+            channel.owner_id = Set(Some(new_owner.user));
+
+            channel.update(conn).await.expect("Failed to access database!");
+
+            send_nats_message(nats_client, channel_id.to_string(), Messages::ChannelUpdate { channel_id }).await;
+        }
+    }
+}
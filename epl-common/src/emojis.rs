@@ -0,0 +1,144 @@
+use num_derive::FromPrimitive;
+use sea_orm::{DatabaseConnection, EntityTrait};
+use serde_derive::{Deserialize, Serialize};
+use unic::emoji::char::is_emoji;
+use crate::CUSTOM_EMOJI_REGEX;
+use crate::database::entities::prelude::Emoji;
+
+/// Maximum size of an uploaded emoji, in bytes
+pub const MAX_EMOJI_SIZE: usize = 256 * 1024;
+
+/// How many static emojis, and separately how many animated emojis, a guild can have
+pub const MAX_GUILD_EMOJIS: u64 = 50;
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, FromPrimitive)]
+#[repr(i32)]
+pub enum ReactionType {
+    Normal = 0,
+    /// Super reactions
+    Burst = 1,
+}
+
+/// An emoji as used in reactions, either a unicode emoji or a custom one
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReactionEmoji {
+    Unicode(String),
+    Custom {
+        id: i64,
+        name: String,
+        animated: bool,
+    },
+}
+
+impl ReactionEmoji {
+    /// Parses an emoji given by a client, which is either a unicode emoji or `name:id`
+    pub fn parse(emoji: &str) -> Option<ReactionEmoji> {
+        let parts: Vec<&str> = emoji.split(':').collect();
+
+        match parts.as_slice() {
+            [unicode] => {
+                let first = unicode.chars().next()?;
+
+                if !is_emoji(first) {
+                    return None;
+                }
+
+                Some(ReactionEmoji::Unicode(first.to_string()))
+            }
+            [name, id] | ["a", name, id] => Some(ReactionEmoji::Custom {
+                id: id.parse().ok()?,
+                name: name.to_string(),
+                animated: parts.len() == 3,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Gets the emoji back from how it is stored in the database
+    pub fn from_key(key: &str) -> ReactionEmoji {
+        ReactionEmoji::parse(key).unwrap_or(ReactionEmoji::Unicode(key.to_string()))
+    }
+
+    /// The string the emoji is stored as in the database
+    pub fn key(&self) -> String {
+        match self {
+            ReactionEmoji::Unicode(emoji) => emoji.clone(),
+            ReactionEmoji::Custom { id, name, animated: true } => format!("a:{}:{}", name, id),
+            ReactionEmoji::Custom { id, name, animated: false } => format!("{}:{}", name, id),
+        }
+    }
+
+    /// Checks that a custom emoji exists, and fills in its name and animated state from the database
+    pub async fn resolve(self, conn: &DatabaseConnection) -> Option<ReactionEmoji> {
+        match self {
+            ReactionEmoji::Unicode(_) => Some(self),
+            ReactionEmoji::Custom { id, .. } => {
+                let emoji = Emoji::find_by_id(id)
+                    .one(conn)
+                    .await
+                    .expect("Failed to access database!")
+                    .filter(|x| x.available)?;
+
+                Some(ReactionEmoji::Custom {
+                    id: emoji.id,
+                    name: emoji.name,
+                    animated: emoji.animated,
+                })
+            }
+        }
+    }
+}
+
+/// Gets the colours burst reactions of an emoji are animated with
+pub async fn get_burst_colors(conn: &DatabaseConnection, emoji: &ReactionEmoji) -> Vec<String> {
+    match emoji {
+        // TODO: Ship a colour table for unicode emojis
+        ReactionEmoji::Unicode(_) => vec![],
+        ReactionEmoji::Custom { id, .. } => Emoji::find_by_id(*id)
+            .one(conn)
+            .await
+            .expect("Failed to access database!")
+            .and_then(|x| x.burst_colors)
+            .and_then(|x| serde_json::from_value(x).ok())
+            .unwrap_or_default(),
+    }
+}
+
+/// Checks that an emoji name is between 2 and 32 characters, and only uses letters, numbers and underscores
+pub fn is_valid_emoji_name(name: &str) -> bool {
+    (2..=32).contains(&name.chars().count()) && name.chars().all(|x| x.is_ascii_alphanumeric() || x == '_')
+}
+
+/// Replaces custom emojis that don't exist in message content with their plain `:name:` form
+pub async fn downgrade_unknown_emojis(conn: &DatabaseConnection, content: &str) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut last_end = 0;
+
+    for i in CUSTOM_EMOJI_REGEX.captures_iter(content) {
+        let whole = i.get(0).unwrap();
+        let name = i.get(2).unwrap().as_str();
+
+        let exists = match i.get(3).unwrap().as_str().parse::<i64>() {
+            Ok(id) => Emoji::find_by_id(id)
+                .one(conn)
+                .await
+                .expect("Failed to access database!")
+                .is_some_and(|x| x.available),
+            Err(_) => false,
+        };
+
+        output.push_str(&content[last_end..whole.start()]);
+
+        if exists {
+            output.push_str(whole.as_str());
+        } else {
+            output.push_str(&format!(":{}:", name));
+        }
+
+        last_end = whole.end();
+    }
+
+    output.push_str(&content[last_end..]);
+
+    output
+}
@@ -4,6 +4,7 @@ use enum_iterator::{all, Sequence};
 use once_cell::sync::Lazy;
 use serde_derive::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::str::FromStr;
 
 static PUBLIC_FLAGS: Lazy<HashSet<UserFlags>> = Lazy::new(|| {
     let mut set = HashSet::new();
@@ -27,7 +28,7 @@ static PUBLIC_FLAGS: Lazy<HashSet<UserFlags>> = Lazy::new(|| {
 });
 
 #[repr(i64)]
-#[derive(Hash, Eq, PartialEq, Sequence, Clone, Copy)]
+#[derive(Debug, Hash, Eq, PartialEq, Sequence, Clone, Copy)]
 /// Discord User Flags
 pub enum UserFlags {
     /// Discord Employee
@@ -104,6 +105,63 @@ pub enum UserFlags {
     RestrictedCollaborator = 1 << 51,
 }
 
+impl UserFlags {
+    /// Name of the flag as listed on <https://flags.lewisakura.moe/>
+    pub fn name(&self) -> &'static str {
+        match self {
+            UserFlags::Staff => "STAFF",
+            UserFlags::Partner => "PARTNER",
+            UserFlags::Hypesquad => "HYPESQUAD",
+            UserFlags::BugHunterLevel1 => "BUG_HUNTER_LEVEL_1",
+            UserFlags::MFASMS => "MFA_SMS",
+            UserFlags::PremiumPromoDismissed => "PREMIUM_PROMO_DISMISSED",
+            UserFlags::HypesquadOnlineHouse1 => "HYPESQUAD_ONLINE_HOUSE_1",
+            UserFlags::HypesquadOnlineHouse2 => "HYPESQUAD_ONLINE_HOUSE_2",
+            UserFlags::HypesquadOnlineHouse3 => "HYPESQUAD_ONLINE_HOUSE_3",
+            UserFlags::PremiumEarlySupporter => "PREMIUM_EARLY_SUPPORTER",
+            UserFlags::TeamPseudoUser => "TEAM_PSEUDO_USER",
+            UserFlags::InternalApplication => "INTERNAL_APPLICATION",
+            UserFlags::System => "SYSTEM",
+            UserFlags::HasUnreadUrgentMessages => "HAS_UNREAD_URGENT_MESSAGES",
+            UserFlags::BugHunterLevel2 => "BUG_HUNTER_LEVEL_2",
+            UserFlags::UnderageDeleted => "UNDERAGE_DELETED",
+            UserFlags::VerifiedBot => "VERIFIED_BOT",
+            UserFlags::VerifiedDeveloper => "VERIFIED_DEVELOPER",
+            UserFlags::CertifiedModerator => "CERTIFIED_MODERATOR",
+            UserFlags::BotHTTPInteractions => "BOT_HTTP_INTERACTIONS",
+            UserFlags::Spammer => "SPAMMER",
+            UserFlags::DisablePremium => "DISABLE_PREMIUM",
+            UserFlags::ActiveDeveloper => "ACTIVE_DEVELOPER",
+            UserFlags::HighGlobalRateLimit => "HIGH_GLOBAL_RATE_LIMIT",
+            UserFlags::Deleted => "DELETED",
+            UserFlags::DisabledSuspiciousActivity => "DISABLED_SUSPICIOUS_ACTIVITY",
+            UserFlags::SelfDeleted => "SELF_DELETED",
+            UserFlags::PremiumDiscriminator => "PREMIUM_DISCRIMINATOR",
+            UserFlags::UsedDesktopClient => "USED_DESKTOP_CLIENT",
+            UserFlags::UsedWebClient => "USED_WEB_CLIENT",
+            UserFlags::UsedMobileClient => "USED_MOBILE_CLIENT",
+            UserFlags::Disabled => "DISABLED",
+            UserFlags::VerifiedEmail => "VERIFIED_EMAIL",
+            UserFlags::Quarantined => "QUARANTINED",
+            UserFlags::Collaborator => "COLLABORATOR",
+            UserFlags::RestrictedCollaborator => "RESTRICTED_COLLABORATOR",
+        }
+    }
+}
+
+impl FromStr for UserFlags {
+    type Err = String;
+
+    /// Accepts either the flag name in any case (`staff`, `BUG_HUNTER_LEVEL_1`) or its bit value
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.parse::<i64>().ok();
+
+        all::<UserFlags>()
+            .find(|x| x.name().eq_ignore_ascii_case(s) || Some(*x as i64) == value)
+            .ok_or_else(|| format!("Unknown user flag `{}`", s))
+    }
+}
+
 #[repr(u64)]
 pub enum ApplicationFlags {
     /// Embedded Application is Available to Play
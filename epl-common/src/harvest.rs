@@ -0,0 +1,52 @@
+use chrono::{Duration, NaiveDateTime};
+use num_derive::FromPrimitive;
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use crate::database::auth::{create_ticket_with_state, TicketType};
+use crate::options::{EplOptions, Options};
+
+/// How long a finished package can be downloaded for, in days
+pub const HARVEST_LIFETIME_DAYS: i64 = 7;
+
+/// How long a user has to wait between requesting packages, in days
+pub const HARVEST_COOLDOWN_DAYS: i64 = 30;
+
+#[derive(Serialize_repr, Deserialize_repr, Debug, Copy, Clone, PartialEq, FromPrimitive)]
+#[repr(i32)]
+pub enum HarvestStatus {
+    Queued = 0,
+    Running = 1,
+    Completed = 2,
+    Failed = 3,
+    /// The package was built but has since been removed
+    Expired = 4,
+}
+
+/// Where the package is kept in the bucket
+pub fn harvest_key(harvest_id: i64) -> String {
+    format!("harvests/{}.zip", harvest_id)
+}
+
+/// When a package finished at this time stops being downloadable
+pub fn harvest_expiry(completed_at: NaiveDateTime) -> NaiveDateTime {
+    completed_at + Duration::days(HARVEST_LIFETIME_DAYS)
+}
+
+/// Builds a CDN link to download the package, it only works for as long as the package is kept
+pub fn harvest_download_link(user_id: i64, harvest_id: i64) -> String {
+    let options = EplOptions::get();
+
+    let ticket = create_ticket_with_state(
+        user_id,
+        TicketType::HarvestDownload,
+        Duration::days(HARVEST_LIFETIME_DAYS),
+        Some(serde_json::Value::String(harvest_id.to_string())),
+    );
+
+    format!(
+        "{}://{}/harvests/{}/package.zip?ticket={}",
+        if options.require_ssl { "https" } else { "http" },
+        options.cdn_url,
+        harvest_id,
+        ticket
+    )
+}
@@ -0,0 +1,792 @@
+use std::collections::{BTreeSet, HashMap};
+use std::io::{Read, Seek};
+use async_nats::Client;
+use aws_sdk_s3::primitives::ByteStream;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use once_cell::sync::Lazy;
+use rand::distributions::{Alphanumeric, DistString};
+use rand::Rng;
+use regex::{Captures, Regex};
+use ril::{Image, Rgba};
+use ril::ImageFormat::WebP;
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter};
+use sea_orm::ActiveValue::Set;
+use sea_orm::sea_query::OnConflict;
+use serde_derive::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, PickFirst, serde_as};
+use sha2::{Digest, Sha256};
+use zip::ZipArchive;
+use crate::channels::ChannelTypes;
+use crate::database::auth::{create_user, generate_password_hash};
+use crate::database::entities::prelude::{Channel, ChannelMember, ImportMapping, Message, User};
+use crate::database::entities::{channel, channel_member, import_mapping, message, note, user};
+use crate::moderation::is_limited;
+use crate::nats::{Messages, send_nats_message};
+use crate::privacy::can_dm;
+use crate::relationship::get_relationship;
+use crate::rustflake::Snowflake;
+use crate::RelationshipType;
+
+/// Discord's snowflake epoch, used to work out when a message was sent if its timestamp can't be read
+const DISCORD_EPOCH: i64 = 1420070400000;
+
+/// How many messages are inserted at once
+const MESSAGE_BATCH_SIZE: usize = 500;
+
+/// Largest a single file in a package can be once it's decompressed
+const MAX_FILE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Largest all the files read out of a package can be together once they're decompressed
+const MAX_PACKAGE_SIZE: u64 = 1024 * 1024 * 1024;
+
+static MENTION_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"<@!?(\d+)>").unwrap());
+
+/// What kind of Discord object an import mapping points to
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(i32)]
+pub enum ImportKind {
+    User = 0,
+    Channel = 1,
+    Message = 2,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportError {
+    pub kind: ImportErrorEnum,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportErrorEnum {
+    /// The file isn't a Discord data package or is missing something it needs
+    InvalidPackage,
+    /// A new account was asked for but the package's email is already in use
+    EmailTaken,
+    /// The Discord account was already imported into a different account
+    AlreadyMapped,
+    SeaORM,
+}
+
+impl From<sea_orm::DbErr> for ImportError {
+    fn from(error: sea_orm::DbErr) -> Self {
+        ImportError {
+            kind: ImportErrorEnum::SeaORM,
+            message: error.to_string(),
+        }
+    }
+}
+
+fn invalid_package(message: impl Into<String>) -> ImportError {
+    ImportError {
+        kind: ImportErrorEnum::InvalidPackage,
+        message: message.into(),
+    }
+}
+
+/// Which account the package goes into
+pub enum ImportTarget {
+    Existing(user::Model),
+    /// Create a new account from the package, it has no usable password until it's reset
+    New,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct ImportReport {
+    pub user_id: String,
+    pub created_account: bool,
+    pub imported_avatar: bool,
+    pub imported_notes: u64,
+    pub imported_channels: u64,
+    pub imported_messages: u64,
+    /// Messages that were already imported before
+    pub skipped_messages: u64,
+    /// Discord only links to attachments, so they're left in the message content instead
+    pub unimported_attachments: u64,
+    pub unmapped_users: Vec<UnmappedUser>,
+    pub skipped_channels: Vec<SkippedChannel>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct UnmappedUser {
+    pub id: String,
+    pub username: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SkippedChannel {
+    pub id: String,
+    pub name: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Deserialize)]
+struct PackageUser {
+    id: String,
+    username: String,
+    #[serde(default)]
+    global_name: Option<String>,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    relationships: Vec<PackageRelationship>,
+    #[serde(default)]
+    notes: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct PackageRelationship {
+    id: String,
+    #[serde(default)]
+    user: Option<PackageRelationshipUser>,
+}
+
+#[derive(Deserialize)]
+struct PackageRelationshipUser {
+    username: String,
+}
+
+#[derive(Deserialize)]
+struct PackageChannel {
+    id: String,
+    /// Older packages use the numeric type, newer ones the name
+    #[serde(rename = "type")]
+    channel_type: serde_json::Value,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    recipients: Vec<String>,
+}
+
+impl PackageChannel {
+    fn channel_type(&self) -> Option<ChannelTypes> {
+        match &self.channel_type {
+            serde_json::Value::Number(x) if x.as_i64() == Some(ChannelTypes::DM as i64) => Some(ChannelTypes::DM),
+            serde_json::Value::Number(x) if x.as_i64() == Some(ChannelTypes::GroupDM as i64) => Some(ChannelTypes::GroupDM),
+            serde_json::Value::String(x) if x == "DM" => Some(ChannelTypes::DM),
+            serde_json::Value::String(x) if x == "GROUP_DM" => Some(ChannelTypes::GroupDM),
+            _ => None,
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+struct PackageMessage {
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    #[serde(rename = "ID")]
+    id: i64,
+    #[serde(rename = "Timestamp")]
+    timestamp: String,
+    #[serde(rename = "Contents", default)]
+    contents: String,
+    /// Space separated links to Discord's CDN
+    #[serde(rename = "Attachments", default)]
+    attachments: String,
+}
+
+impl PackageMessage {
+    fn timestamp(&self) -> NaiveDateTime {
+        DateTime::parse_from_str(&self.timestamp, "%Y-%m-%d %H:%M:%S%.f%:z")
+            .map(|x| x.naive_utc())
+            .or_else(|_| NaiveDateTime::parse_from_str(&self.timestamp, "%Y-%m-%d %H:%M:%S%.f"))
+            .unwrap_or_else(|_| {
+                DateTime::<Utc>::from_timestamp_millis((self.id >> 22) + DISCORD_EPOCH)
+                    .unwrap_or_default()
+                    .naive_utc()
+            })
+    }
+}
+
+struct Package {
+    user: PackageUser,
+    avatar: Option<Vec<u8>>,
+    channels: Vec<(PackageChannel, Vec<PackageMessage>)>,
+}
+
+/// Reads a whole file out of the package, `remaining` is how much more can be decompressed before it's too large
+fn read_file<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+    remaining: &mut u64,
+) -> Result<Option<Vec<u8>>, ImportError> {
+    let file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(err) => return Err(invalid_package(err.to_string())),
+    };
+
+    let limit = MAX_FILE_SIZE.min(*remaining);
+
+    if file.size() > limit {
+        return Err(invalid_package(format!("{} is too large", name)));
+    }
+
+    // The size in the header can't be trusted, so the read is capped too
+    let mut buffer = vec![];
+    file.take(limit + 1).read_to_end(&mut buffer).map_err(|err| invalid_package(err.to_string()))?;
+
+    if buffer.len() as u64 > limit {
+        return Err(invalid_package(format!("{} is too large", name)));
+    }
+
+    *remaining -= buffer.len() as u64;
+
+    Ok(Some(buffer))
+}
+
+fn read_messages<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    directory: &str,
+    remaining: &mut u64,
+) -> Result<Vec<PackageMessage>, ImportError> {
+    if let Some(messages) = read_file(archive, &format!("{}messages.json", directory), remaining)? {
+        return serde_json::from_slice(&messages).map_err(|err| invalid_package(err.to_string()));
+    }
+
+    // Packages from before 2023 have the messages as CSV
+    if let Some(messages) = read_file(archive, &format!("{}messages.csv", directory), remaining)? {
+        return csv::Reader::from_reader(messages.as_slice())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .map_err(|err| invalid_package(err.to_string()));
+    }
+
+    Ok(vec![])
+}
+
+fn read_package<R: Read + Seek>(reader: R) -> Result<Package, ImportError> {
+    let mut archive = ZipArchive::new(reader).map_err(|err| invalid_package(err.to_string()))?;
+    let file_names: Vec<String> = archive.file_names().map(|x| x.to_string()).collect();
+    let mut remaining = MAX_PACKAGE_SIZE;
+
+    let user = read_file(&mut archive, "account/user.json", &mut remaining)?
+        .ok_or_else(|| invalid_package("account/user.json is missing"))?;
+    let user: PackageUser = serde_json::from_slice(&user).map_err(|err| invalid_package(err.to_string()))?;
+
+    let avatar = match file_names.iter().find(|x| x.starts_with("account/avatar.")) {
+        Some(avatar) => read_file(&mut archive, avatar, &mut remaining)?,
+        None => None,
+    };
+
+    let mut channels = vec![];
+
+    for file_name in file_names.iter().filter(|x| x.starts_with("messages/") && x.ends_with("/channel.json")) {
+        let Some(channel) = read_file(&mut archive, file_name, &mut remaining)? else {
+            continue;
+        };
+        let channel: PackageChannel = serde_json::from_slice(&channel).map_err(|err| invalid_package(err.to_string()))?;
+
+        let messages = read_messages(&mut archive, file_name.trim_end_matches("channel.json"), &mut remaining)?;
+
+        channels.push((channel, messages));
+    }
+
+    Ok(Package { user, avatar, channels })
+}
+
+async fn get_mapping(conn: &DatabaseConnection, kind: ImportKind, discord_id: i64) -> Result<Option<i64>, ImportError> {
+    Ok(ImportMapping::find_by_id((kind as i32, discord_id))
+        .one(conn)
+        .await?
+        .map(|x| x.epl_id))
+}
+
+async fn set_mapping(conn: &DatabaseConnection, kind: ImportKind, discord_id: i64, epl_id: i64) -> Result<(), ImportError> {
+    ImportMapping::insert(import_mapping::ActiveModel {
+        kind: Set(kind as i32),
+        discord_id: Set(discord_id),
+        epl_id: Set(epl_id),
+    })
+        .on_conflict(
+            OnConflict::columns([import_mapping::Column::Kind, import_mapping::Column::DiscordId])
+                .update_column(import_mapping::Column::EplId)
+                .to_owned()
+        )
+        .exec(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Stores the avatar the same way a normal upload would, returns its hash
+async fn upload_avatar(aws: &aws_sdk_s3::Client, bucket: &str, user_id: i64, avatar: Vec<u8>) -> Option<String> {
+    let hash = hex::encode(Sha256::digest(&avatar));
+
+    // Decoding is CPU heavy, so it's kept off the async runtime
+    let image_buffer = tokio::task::spawn_blocking(move || {
+        let mut image_buffer: Vec<u8> = Vec::new();
+        let image: Image<Rgba> = Image::from_bytes_inferred(avatar).ok()?;
+        image.encode(WebP, &mut image_buffer).ok()?;
+
+        Some(image_buffer)
+    })
+        .await
+        .ok()??;
+
+    aws.put_object()
+        .bucket(bucket)
+        .key(format!("avatars/{}/{hash}.webp", user_id))
+        .body(ByteStream::from(image_buffer))
+        .send()
+        .await
+        .ok()?;
+
+    Some(hash)
+}
+
+async fn create_account(conn: &DatabaseConnection, package_user: &PackageUser) -> Result<user::Model, ImportError> {
+    let email = package_user.email.clone().ok_or_else(|| invalid_package("The package has no email address"))?;
+
+    let existing_user = User::find()
+        .filter(user::Column::Email.eq(email.clone()))
+        .one(conn)
+        .await?;
+
+    if existing_user.is_some() {
+        return Err(ImportError {
+            kind: ImportErrorEnum::EmailTaken,
+            message: format!("{} is already in use", email),
+        });
+    }
+
+    // Nobody knows this password, the account has to go through a password reset before it can be logged into
+    let password_hash = generate_password_hash(&Alphanumeric.sample_string(&mut rand::thread_rng(), 64), vec![])
+        .map_err(|err| ImportError { kind: ImportErrorEnum::SeaORM, message: err.message })?;
+
+    let user_id = create_user(conn, user::ActiveModel {
+        id: Set(Snowflake::default().generate()),
+        system: Set(false),
+        bot: Set(false),
+        username: Set(package_user.username.clone()),
+        password_hash: Set(password_hash),
+        discriminator: Set(rand::thread_rng().gen_range(1..9999).to_string()),
+        email: Set(email),
+        mfa_enabled: Set(false),
+        acct_verified: Set(false),
+        flags: Set(0),
+        nsfw_allowed: Set(false),
+        ..Default::default()
+    })
+        .await
+        .map_err(|err| ImportError { kind: ImportErrorEnum::SeaORM, message: err.message })?;
+
+    Ok(User::find_by_id(user_id).one(conn).await?.expect("Imported user is missing!"))
+}
+
+/// Finds a DM the two users already share
+async fn find_dm(conn: &DatabaseConnection, user_a: i64, user_b: i64) -> Result<Option<i64>, ImportError> {
+    let user_a_channels: Vec<i64> = ChannelMember::find()
+        .filter(channel_member::Column::User.eq(user_a))
+        .all(conn)
+        .await?
+        .iter()
+        .map(|x| x.channel)
+        .collect();
+
+    let shared_channels = ChannelMember::find()
+        .filter(channel_member::Column::User.eq(user_b))
+        .filter(channel_member::Column::Channel.is_in(user_a_channels))
+        .find_also_related(Channel)
+        .all(conn)
+        .await?;
+
+    Ok(shared_channels.iter()
+        .filter_map(|(_, channel)| channel.as_ref())
+        .find(|x| x.r#type == ChannelTypes::DM as i32)
+        .map(|x| x.id))
+}
+
+async fn create_channel(
+    conn: &DatabaseConnection,
+    channel_type: ChannelTypes,
+    owner_id: Option<i64>,
+    name: Option<String>,
+    members: &[i64],
+) -> Result<i64, ImportError> {
+    let channel_id = Snowflake::default().generate();
+
+    Channel::insert(channel::ActiveModel {
+        id: Set(channel_id),
+        r#type: Set(channel_type as i32),
+        owner_id: Set(owner_id),
+        name: Set(name),
+        ..Default::default()
+    })
+        .exec(conn)
+        .await?;
+
+    ChannelMember::insert_many(members.iter().map(|x| channel_member::ActiveModel {
+        channel: Set(channel_id),
+        user: Set(*x),
+    }))
+        .exec(conn)
+        .await?;
+
+    Ok(channel_id)
+}
+
+struct Importer<'a> {
+    conn: &'a DatabaseConnection,
+    nats_client: Option<&'a Client>,
+    user: user::Model,
+    discord_user_id: i64,
+    /// Discord users that have an account here, filled in as they're looked up
+    users: HashMap<i64, Option<i64>>,
+    /// Usernames from the package's relationships, only used for the report
+    usernames: HashMap<i64, String>,
+    unmapped_users: BTreeSet<UnmappedUser>,
+    snowflake: Snowflake,
+    report: ImportReport,
+}
+
+impl Importer<'_> {
+    async fn map_user(&mut self, discord_id: i64) -> Result<Option<i64>, ImportError> {
+        if let Some(user_id) = self.users.get(&discord_id) {
+            return Ok(*user_id);
+        }
+
+        let user_id = get_mapping(self.conn, ImportKind::User, discord_id).await?;
+
+        if user_id.is_none() {
+            self.unmapped_users.insert(UnmappedUser {
+                id: discord_id.to_string(),
+                username: self.usernames.get(&discord_id).cloned(),
+            });
+        }
+
+        self.users.insert(discord_id, user_id);
+
+        Ok(user_id)
+    }
+
+    async fn import_notes(&mut self, notes: &HashMap<String, String>) -> Result<(), ImportError> {
+        for (subject, text) in notes {
+            let Ok(subject) = subject.parse::<i64>() else {
+                continue;
+            };
+
+            let Some(subject) = self.map_user(subject).await? else {
+                continue;
+            };
+
+            note::Entity::insert(note::ActiveModel {
+                creator: Set(self.user.id),
+                subject: Set(subject),
+                text: Set(text.clone()),
+            })
+                .on_conflict(
+                    OnConflict::columns([note::Column::Creator, note::Column::Subject])
+                        .update_column(note::Column::Text)
+                        .to_owned()
+                )
+                .exec(self.conn)
+                .await?;
+
+            self.report.imported_notes += 1;
+        }
+
+        Ok(())
+    }
+
+    fn skip_channel(&mut self, channel: &PackageChannel, reason: &str) {
+        self.report.skipped_channels.push(SkippedChannel {
+            id: channel.id.clone(),
+            name: channel.name.clone(),
+            reason: reason.to_string(),
+        });
+    }
+
+    /// Finds or creates the channel the messages go into, None if it can't be imported
+    async fn map_channel(&mut self, channel: &PackageChannel) -> Result<Option<i64>, ImportError> {
+        let Some(channel_type) = channel.channel_type() else {
+            self.skip_channel(channel, "Only DMs and group DMs can be imported");
+            return Ok(None);
+        };
+
+        let Ok(discord_channel_id) = channel.id.parse::<i64>() else {
+            self.skip_channel(channel, "Invalid channel ID");
+            return Ok(None);
+        };
+
+        let is_dm = matches!(channel_type, ChannelTypes::DM);
+
+        // Limited accounts can't reach out to anyone, so their channels only get themselves in them
+        let limited = is_limited(self.conn, self.user.id).await;
+
+        let mut members = vec![self.user.id];
+        let mut refused = false;
+
+        for recipient in channel.recipients.iter().filter_map(|x| x.parse::<i64>().ok()) {
+            if recipient == self.discord_user_id {
+                continue;
+            }
+
+            if let Some(member) = self.map_user(recipient).await? {
+                // Packages can be forged, so only people who would accept the channel from the importer are added
+                if !limited && self.accepts_channel(member, is_dm).await {
+                    members.push(member);
+                } else {
+                    refused = true;
+                }
+            }
+        }
+
+        // Someone else in the group DM may have imported it already, it's only reused by people who are already in it
+        let existing_channel = get_mapping(self.conn, ImportKind::Channel, discord_channel_id).await?;
+
+        if let Some(channel_id) = existing_channel {
+            if ChannelMember::find_by_id((channel_id, self.user.id)).one(self.conn).await?.is_some() {
+                return Ok(Some(channel_id));
+            }
+        }
+
+        let channel_id = match channel_type {
+            ChannelTypes::DM => {
+                let Some(recipient) = members.get(1).copied() else {
+                    if refused {
+                        self.skip_channel(channel, "The other user doesn't accept DMs from you");
+                    } else {
+                        self.skip_channel(channel, "The other user doesn't have an account here");
+                    }
+                    return Ok(None);
+                };
+
+                match find_dm(self.conn, self.user.id, recipient).await? {
+                    Some(channel_id) => channel_id,
+                    None => {
+                        let channel_id = create_channel(self.conn, ChannelTypes::DM, None, None, &members).await?;
+                        self.notify_channel_create(channel_id, &members).await;
+
+                        channel_id
+                    }
+                }
+            }
+            _ => {
+                let channel_id = create_channel(
+                    self.conn,
+                    ChannelTypes::GroupDM,
+                    Some(self.user.id),
+                    channel.name.clone(),
+                    &members,
+                ).await?;
+                self.notify_channel_create(channel_id, &members).await;
+
+                channel_id
+            }
+        };
+
+        // The mapping belongs to whoever imported the channel first, anyone importing it later who isn't in it gets their own
+        if existing_channel.is_none() {
+            set_mapping(self.conn, ImportKind::Channel, discord_channel_id, channel_id).await?;
+        }
+        self.report.imported_channels += 1;
+
+        Ok(Some(channel_id))
+    }
+
+    /// The same rules as opening a channel from the client, friends only, but strangers can get a DM if they allow it
+    async fn accepts_channel(&self, user_id: i64, is_dm: bool) -> bool {
+        match get_relationship(self.user.id, user_id, self.conn).await {
+            None => is_dm && can_dm(self.conn, self.user.id, user_id).await,
+            // Pending and blocked relationships don't count
+            Some(relationship) => relationship.relationship_type == RelationshipType::Friend as i32,
+        }
+    }
+
+    async fn notify_channel_create(&self, channel_id: i64, members: &[i64]) {
+        let Some(nats_client) = self.nats_client else {
+            return;
+        };
+
+        for member in members {
+            send_nats_message(nats_client, member.to_string(), Messages::ChannelCreate { id: channel_id }).await;
+        }
+    }
+
+    /// Points mentions of users that have an account here at their new IDs
+    fn map_mentions(&self, content: &str) -> String {
+        MENTION_REGEX.replace_all(content, |captures: &Captures| {
+            match captures[1].parse::<i64>().ok().and_then(|x| self.users.get(&x).copied().flatten()) {
+                Some(user_id) => format!("<@{}>", user_id),
+                None => captures[0].to_string(),
+            }
+        }).to_string()
+    }
+
+    async fn import_messages(&mut self, channel_id: i64, messages: &[PackageMessage]) -> Result<(), ImportError> {
+        let mut last_message_id = None;
+
+        for batch in messages.chunks(MESSAGE_BATCH_SIZE) {
+            let already_imported: BTreeSet<i64> = ImportMapping::find()
+                .filter(import_mapping::Column::Kind.eq(ImportKind::Message as i32))
+                .filter(import_mapping::Column::DiscordId.is_in(batch.iter().map(|x| x.id)))
+                .all(self.conn)
+                .await?
+                .iter()
+                .map(|x| x.discord_id)
+                .collect();
+
+            let mut new_messages = vec![];
+            let mut new_mappings = vec![];
+
+            for package_message in batch {
+                if already_imported.contains(&package_message.id) {
+                    self.report.skipped_messages += 1;
+                    continue;
+                }
+
+                let timestamp = package_message.timestamp();
+                let message_id = self.snowflake.generate_at(timestamp.and_utc().timestamp_millis());
+
+                let mut content = self.map_mentions(&package_message.contents);
+                let attachments: Vec<&str> = package_message.attachments.split_whitespace().collect();
+
+                if !attachments.is_empty() {
+                    self.report.unimported_attachments += attachments.len() as u64;
+
+                    if !content.is_empty() {
+                        content.push('\n');
+                    }
+                    content.push_str(&attachments.join("\n"));
+                }
+
+                new_messages.push(message::ActiveModel {
+                    id: Set(message_id),
+                    channel_id: Set(channel_id),
+                    author: Set(Some(self.user.id)),
+                    content: Set(content),
+                    timestamp: Set(timestamp),
+                    tts: Set(false),
+                    mention_everyone: Set(false),
+                    pinned: Set(false),
+                    r#type: Set(0),
+                    ..Default::default()
+                });
+
+                new_mappings.push(import_mapping::ActiveModel {
+                    kind: Set(ImportKind::Message as i32),
+                    discord_id: Set(package_message.id),
+                    epl_id: Set(message_id),
+                });
+
+                last_message_id = last_message_id.max(Some(message_id));
+            }
+
+            if new_messages.is_empty() {
+                continue;
+            }
+
+            self.report.imported_messages += new_messages.len() as u64;
+
+            Message::insert_many(new_messages).exec(self.conn).await?;
+            ImportMapping::insert_many(new_mappings).exec(self.conn).await?;
+        }
+
+        // Imported messages are old, so they only move the last message along if the channel was quiet
+        if let Some(channel) = Channel::find_by_id(channel_id).one(self.conn).await? {
+            if last_message_id > channel.last_message_id {
+                let mut channel = channel.into_active_model();
+
+                channel.last_message_id = Set(last_message_id);
+
+                channel.update(self.conn).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Imports a Discord data package, mapping everyone in it to accounts that were imported before.
+///
+/// Packages aren't signed by Discord, so whoever imports a Discord account first is trusted to own it
+pub async fn import_package<R: Read + Seek + Send + 'static>(
+    conn: &DatabaseConnection,
+    aws: &aws_sdk_s3::Client,
+    bucket: &str,
+    nats_client: Option<&Client>,
+    package: R,
+    target: ImportTarget,
+) -> Result<ImportReport, ImportError> {
+    // Unzipping and parsing the package is blocking work
+    let package = tokio::task::spawn_blocking(move || read_package(package))
+        .await
+        .map_err(|err| invalid_package(err.to_string()))??;
+
+    let discord_user_id: i64 = package.user.id.parse().map_err(|_| invalid_package("Invalid user ID"))?;
+
+    let (user, created_account) = match target {
+        ImportTarget::Existing(user) => (user, false),
+        ImportTarget::New => (create_account(conn, &package.user).await?, true),
+    };
+
+    match get_mapping(conn, ImportKind::User, discord_user_id).await? {
+        Some(user_id) if user_id != user.id => {
+            return Err(ImportError {
+                kind: ImportErrorEnum::AlreadyMapped,
+                message: format!("Discord account {} was already imported into {}", discord_user_id, user_id),
+            });
+        }
+        Some(_) => {}
+        None => set_mapping(conn, ImportKind::User, discord_user_id, user.id).await?,
+    }
+
+    let user_id = user.id;
+
+    let mut importer = Importer {
+        conn,
+        nats_client,
+        user,
+        discord_user_id,
+        users: HashMap::from([(discord_user_id, Some(user_id))]),
+        usernames: package.user.relationships.iter()
+            .filter_map(|x| Some((x.id.parse().ok()?, x.user.as_ref()?.username.clone())))
+            .collect(),
+        unmapped_users: BTreeSet::new(),
+        snowflake: Snowflake::default(),
+        report: ImportReport {
+            created_account,
+            ..Default::default()
+        },
+    };
+
+    // Profile fields are only filled in, anything already set here wins
+    let mut active_user = importer.user.clone().into_active_model();
+
+    if importer.user.display_name.is_none() {
+        active_user.display_name = Set(package.user.global_name.clone());
+    }
+
+    if importer.user.avatar.is_none() {
+        if let Some(avatar) = package.avatar {
+            if let Some(hash) = upload_avatar(aws, bucket, importer.user.id, avatar).await {
+                active_user.avatar = Set(Some(hash));
+                importer.report.imported_avatar = true;
+            }
+        }
+    }
+
+    importer.user = active_user.update(conn).await?;
+
+    // Look everyone up first so mentions of them can be mapped too
+    for relationship in &package.user.relationships {
+        if let Ok(discord_id) = relationship.id.parse() {
+            importer.map_user(discord_id).await?;
+        }
+    }
+
+    importer.import_notes(&package.user.notes).await?;
+
+    for (channel, messages) in &package.channels {
+        if let Some(channel_id) = importer.map_channel(channel).await? {
+            importer.import_messages(channel_id, messages).await?;
+        }
+    }
+
+    let mut report = importer.report;
+
+    report.user_id = importer.user.id.to_string();
+    report.unmapped_users = importer.unmapped_users.into_iter().collect();
+
+    Ok(report)
+}
@@ -0,0 +1,714 @@
+use std::collections::HashSet;
+use std::time::Duration;
+use async_nats::Client;
+use chrono::Utc;
+use ed25519_dalek::{Signer, SigningKey};
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sea_orm::*;
+use sea_orm::ActiveValue::Set;
+use sea_orm::sea_query::Expr;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::database::entities::{embed, interaction, mention, message, pin, reaction};
+use crate::database::entities::prelude::{Application, Embed, Interaction, Mention, Message, Reaction, User};
+use crate::emojis::downgrade_unknown_emojis;
+use crate::messages::{MessageFlags, MessageTypes};
+use crate::nats::{Messages, send_nats_message};
+use crate::rustflake::Snowflake;
+use crate::schema::v9::component::{Component, Modal};
+use crate::schema::v9::interaction::generate_message_interaction_struct;
+use crate::{gen_token, USER_MENTION_REGEX};
+
+/// How long an application has to respond to an interaction, in seconds
+pub const INTERACTION_RESPONSE_TIMEOUT: i64 = 3;
+
+/// How long an interaction token can be used for followups, in seconds
+pub const INTERACTION_TOKEN_LIFETIME: i64 = 900;
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, FromPrimitive)]
+#[repr(i32)]
+pub enum InteractionType {
+    Ping = 1,
+    ApplicationCommand = 2,
+    MessageComponent = 3,
+    ApplicationCommandAutocomplete = 4,
+    ModalSubmit = 5,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, FromPrimitive)]
+#[repr(i32)]
+pub enum InteractionCallbackType {
+    /// Acknowledges a ping
+    Pong = 1,
+    /// Responds with a message
+    ChannelMessageWithSource = 4,
+    /// Acknowledges the interaction and shows a loading state, the message is edited later
+    DeferredChannelMessageWithSource = 5,
+    /// Acknowledges a component interaction without a loading state
+    DeferredUpdateMessage = 6,
+    /// Edits the message the component was attached to
+    UpdateMessage = 7,
+    ApplicationCommandAutocompleteResult = 8,
+    /// Responds with a popup modal
+    Modal = 9,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, FromPrimitive)]
+#[repr(i32)]
+pub enum ApplicationCommandType {
+    /// Slash commands
+    ChatInput = 1,
+    /// Shown when right clicking a user
+    User = 2,
+    /// Shown when right clicking a message
+    Message = 3,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, FromPrimitive)]
+#[repr(i32)]
+pub enum ApplicationCommandOptionType {
+    SubCommand = 1,
+    SubCommandGroup = 2,
+    String = 3,
+    Integer = 4,
+    Boolean = 5,
+    User = 6,
+    Channel = 7,
+    Role = 8,
+    Mentionable = 9,
+    Number = 10,
+    Attachment = 11,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, FromPrimitive)]
+#[repr(i32)]
+pub enum ComponentType {
+    /// Holds the other components
+    ActionRow = 1,
+    Button = 2,
+    StringSelect = 3,
+    TextInput = 4,
+    UserSelect = 5,
+    RoleSelect = 6,
+    MentionableSelect = 7,
+    ChannelSelect = 8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, FromPrimitive)]
+#[repr(i32)]
+pub enum ButtonStyle {
+    Primary = 1,
+    Secondary = 2,
+    Success = 3,
+    Danger = 4,
+    /// Opens an URL instead of creating an interaction
+    Link = 5,
+    Premium = 6,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, FromPrimitive)]
+#[repr(i32)]
+pub enum TextInputStyle {
+    Short = 1,
+    Paragraph = 2,
+}
+
+/// Message flags an application is allowed to set when responding
+const ALLOWED_RESPONSE_FLAGS: i32 = MessageFlags::SuppressEmbeds as i32
+    | MessageFlags::Ephemeral as i32
+    | MessageFlags::SuppressNotifications as i32;
+
+#[derive(Deserialize)]
+pub struct InteractionCallback {
+    #[serde(rename = "type")]
+    pub _type: i32,
+    pub data: Option<InteractionCallbackData>,
+}
+
+/// Message contents sent by an application, used for callbacks, followups and edits
+#[derive(Deserialize, Default)]
+pub struct InteractionCallbackData {
+    pub content: Option<String>,
+    pub tts: Option<bool>,
+    pub embeds: Option<Vec<Value>>,
+    pub allowed_mentions: Option<AllowedMentions>,
+    pub flags: Option<i32>,
+    pub components: Option<Vec<Component>>,
+    /// Only used by modals
+    pub custom_id: Option<String>,
+    /// Only used by modals
+    pub title: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AllowedMentions {
+    pub parse: Option<Vec<String>>,
+    pub users: Option<Vec<String>>,
+}
+
+#[derive(Debug)]
+pub enum InteractionCallbackError {
+    /// The interaction already has a response
+    AlreadyAcknowledged,
+    /// The application took too long to respond
+    Expired,
+    /// The response type or contents are not valid for this interaction
+    InvalidCallback,
+}
+
+#[derive(Debug)]
+pub enum InteractionRequestError {
+    /// The endpoint could not be reached or timed out
+    Unreachable,
+    /// The endpoint responded with an error status code
+    Rejected(u16),
+    /// The endpoint responded with something that isn't JSON
+    InvalidResponse,
+}
+
+impl InteractionCallbackData {
+    /// Checks that the data would produce a valid message
+    pub fn is_valid(&self) -> bool {
+        let content_len = self.content.as_ref().map(|x| x.chars().count()).unwrap_or(0);
+        let embeds_len = self.embeds.as_ref().map(|x| x.len()).unwrap_or(0);
+
+        (content_len > 0 || embeds_len > 0) && content_len <= 2000 && embeds_len <= 10
+            && self.components.as_ref().map_or(true, |x| validate_message_components(x))
+    }
+
+    /// Checks that the data is valid for a message edit, where everything is optional
+    pub fn is_valid_edit(&self) -> bool {
+        self.content.as_ref().map_or(true, |x| x.chars().count() <= 2000)
+            && self.embeds.as_ref().map_or(true, |x| x.len() <= 10)
+            && self.components.as_ref().map_or(true, |x| validate_message_components(x))
+    }
+
+    /// Turns the data into a modal, if it describes a valid one
+    pub fn into_modal(self) -> Option<Modal> {
+        let custom_id = self.custom_id?;
+        let title = self.title?;
+        let components = self.components?;
+
+        let custom_id_len = custom_id.chars().count();
+        let title_len = title.chars().count();
+
+        if custom_id_len == 0 || custom_id_len > 100 || title_len == 0 || title_len > 45 || !validate_modal_components(&components) {
+            return None;
+        }
+
+        Some(Modal {
+            custom_id,
+            title,
+            components,
+        })
+    }
+}
+
+/// Checks the layout of components attached to a message
+///
+/// Messages can have up to 5 action rows, each holding either up to 5 buttons or a single select menu
+pub fn validate_message_components(components: &[Component]) -> bool {
+    if components.len() > 5 {
+        return false;
+    }
+
+    let mut custom_ids = HashSet::new();
+
+    for i in components {
+        let row = match i {
+            Component::ActionRow(row) => row,
+            _ => return false,
+        };
+
+        let buttons = row.components.iter().filter(|x| matches!(x, Component::Button(_))).count();
+        let selects = row.components.iter().filter(|x| matches!(x, Component::SelectMenu(_))).count();
+
+        if !(buttons == row.components.len() || (selects == 1 && row.components.len() == 1)) {
+            return false;
+        }
+
+        for i in &row.components {
+            if let Some(custom_id) = i.custom_id() {
+                if !custom_ids.insert(custom_id) {
+                    return false;
+                }
+            }
+        }
+    }
+
+    true
+}
+
+/// Checks the layout of components in a modal, which has up to 5 action rows with a single text input each
+pub fn validate_modal_components(components: &[Component]) -> bool {
+    if components.is_empty() || components.len() > 5 {
+        return false;
+    }
+
+    let mut custom_ids = HashSet::new();
+
+    for i in components {
+        match i {
+            Component::ActionRow(row) => match row.components.as_slice() {
+                [Component::TextInput(text_input)] => {
+                    if !custom_ids.insert(text_input.custom_id.as_str()) {
+                        return false;
+                    }
+                }
+                _ => return false,
+            },
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+/// Gets the subject a message created by an interaction should be announced on
+fn get_message_subject(message: &message::Model, interaction: &interaction::Model) -> String {
+    // Ephemeral messages are only sent to the user who used the interaction
+    if message.flags.is_some_and(|x| x & MessageFlags::Ephemeral as i32 != 0) {
+        interaction.user_id.to_string()
+    } else {
+        message.channel_id.to_string()
+    }
+}
+
+async fn insert_mentions(
+    conn: &DatabaseConnection,
+    message: &message::Model,
+    allowed_mentions: &Option<AllowedMentions>,
+) {
+    for i in USER_MENTION_REGEX.captures_iter(&message.content) {
+        let user_id = match i.get(1).unwrap().as_str().parse::<i64>() {
+            Ok(user_id) => user_id,
+            Err(_) => continue,
+        };
+
+        let allowed = match allowed_mentions {
+            None => true,
+            Some(allowed_mentions) => {
+                allowed_mentions.parse.as_ref().is_some_and(|x| x.iter().any(|x| x == "users"))
+                    || allowed_mentions.users.as_ref().is_some_and(|x| x.contains(&user_id.to_string()))
+            }
+        };
+
+        if !allowed {
+            continue;
+        }
+
+        let user = User::find_by_id(user_id)
+            .one(conn)
+            .await
+            .expect("Failed to access database!");
+
+        if user.is_none() || Mention::find_by_id((message.id, user_id)).one(conn).await.expect("Failed to access database!").is_some() {
+            continue;
+        }
+
+        Mention::insert(
+            mention::Model {
+                message: message.id,
+                user: user_id,
+            }.into_active_model()
+        )
+            .exec(conn)
+            .await
+            .expect("Failed to access database!");
+    }
+}
+
+async fn insert_embeds(conn: &DatabaseConnection, message: &message::Model, embeds: Vec<Value>) {
+    let mut snowflake_factory = Snowflake::default();
+
+    for i in embeds {
+        embed::ActiveModel {
+            id: Set(snowflake_factory.generate()),
+            message: Set(message.id),
+            content: Set(i),
+        }
+            .insert(conn)
+            .await
+            .expect("Failed to access database!");
+    }
+}
+
+/// Creates a message from the application in response to an interaction
+pub async fn create_interaction_message(
+    conn: &DatabaseConnection,
+    nats: &Client,
+    interaction: &interaction::Model,
+    data: InteractionCallbackData,
+    extra_flags: i32,
+) -> message::Model {
+    let application = Application::find_by_id(interaction.application_id)
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+        .expect("Interaction references non-existent application!");
+
+    let user = User::find_by_id(interaction.user_id)
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+        .expect("Interaction references non-existent user!");
+
+    let message_type = if InteractionType::from_i32(interaction.r#type) == Some(InteractionType::ApplicationCommand) {
+        let command_type = interaction
+            .data
+            .as_ref()
+            .and_then(|x| x.get("type"))
+            .and_then(|x| x.as_i64())
+            .and_then(|x| ApplicationCommandType::from_i64(x));
+
+        match command_type {
+            Some(ApplicationCommandType::ChatInput) | None => MessageTypes::ChatInputCommand,
+            _ => MessageTypes::ContextMenuCommand,
+        }
+    } else {
+        MessageTypes::Default
+    };
+
+    let new_message = message::ActiveModel {
+        id: Set(Snowflake::default().generate()),
+        channel_id: Set(interaction.channel_id),
+        author: Set(application.bot_user_id),
+        content: Set(downgrade_unknown_emojis(conn, &data.content.unwrap_or_default()).await),
+        timestamp: Set(Utc::now().naive_utc()),
+        tts: Set(data.tts.unwrap_or(false)),
+        mention_everyone: Set(false),
+        pinned: Set(false),
+        r#type: Set(message_type as i32),
+        application_id: Set(Some(application.id)),
+        flags: Set(Some((data.flags.unwrap_or(0) & ALLOWED_RESPONSE_FLAGS) | extra_flags)),
+        interaction: Set(serde_json::to_value(generate_message_interaction_struct(interaction, user)).ok()),
+        components: Set(data.components.and_then(|x| serde_json::to_value(x).ok())),
+        ..Default::default()
+    }
+        .insert(conn)
+        .await
+        .expect("Failed to access database!");
+
+    insert_mentions(conn, &new_message, &data.allowed_mentions).await;
+
+    if let Some(embeds) = data.embeds {
+        insert_embeds(conn, &new_message, embeds).await;
+    }
+
+    send_nats_message(
+        nats,
+        get_message_subject(&new_message, interaction),
+        Messages::MessageCreate { id: new_message.id },
+    ).await;
+
+    new_message
+}
+
+/// Edits a message created by an interaction, this also finishes deferred responses
+pub async fn edit_interaction_message(
+    conn: &DatabaseConnection,
+    nats: &Client,
+    interaction: &interaction::Model,
+    message: message::Model,
+    data: InteractionCallbackData,
+) -> message::Model {
+    let flags = message.flags.unwrap_or(0);
+    let loading = flags & MessageFlags::Loading as i32 != 0;
+
+    let mut edited_message = message.clone().into_active_model();
+
+    if let Some(content) = data.content {
+        Mention::delete_many()
+            .filter(mention::Column::Message.eq(message.id))
+            .exec(conn)
+            .await
+            .expect("Failed to access database!");
+
+        edited_message.content = Set(downgrade_unknown_emojis(conn, &content).await);
+    }
+
+    if let Some(components) = data.components {
+        edited_message.components = Set(serde_json::to_value(components).ok());
+    }
+
+    // Ephemeral messages can't be made public after the fact, or the other way around
+    edited_message.flags = Set(Some(
+        (flags & MessageFlags::Ephemeral as i32)
+            | (data.flags.unwrap_or(flags) & ALLOWED_RESPONSE_FLAGS & !(MessageFlags::Ephemeral as i32))
+    ));
+
+    // Finishing a deferred response isn't shown as an edit
+    if !loading {
+        edited_message.edited_timestamp = Set(Some(Utc::now().naive_utc()));
+    }
+
+    let edited_message = edited_message.update(conn).await.expect("Failed to access database!");
+
+    insert_mentions(conn, &edited_message, &data.allowed_mentions).await;
+
+    if let Some(embeds) = data.embeds {
+        Embed::delete_many()
+            .filter(embed::Column::Message.eq(message.id))
+            .exec(conn)
+            .await
+            .expect("Failed to access database!");
+
+        insert_embeds(conn, &edited_message, embeds).await;
+    }
+
+    send_nats_message(
+        nats,
+        get_message_subject(&edited_message, interaction),
+        Messages::MessageUpdate { id: edited_message.id },
+    ).await;
+
+    edited_message
+}
+
+/// Deletes a message created by an interaction
+pub async fn delete_interaction_message(
+    conn: &DatabaseConnection,
+    nats: &Client,
+    interaction: &interaction::Model,
+    message: message::Model,
+) {
+    let subject = get_message_subject(&message, interaction);
+
+    pin::Entity::delete_many()
+        .filter(pin::Column::Message.eq(message.id))
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    Embed::delete_many()
+        .filter(embed::Column::Message.eq(message.id))
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    Mention::delete_many()
+        .filter(mention::Column::Message.eq(message.id))
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    Reaction::delete_many()
+        .filter(reaction::Column::Message.eq(message.id))
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    Message::delete_by_id(message.id)
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    send_nats_message(
+        nats,
+        subject,
+        Messages::MessageDelete {
+            id: message.id,
+            channel_id: message.channel_id,
+            guild_id: None,
+        },
+    ).await;
+}
+
+fn set_responded(interaction_id: i64, responded: bool) -> UpdateMany<interaction::Entity> {
+    Interaction::update_many()
+        .col_expr(interaction::Column::Responded, Expr::value(responded))
+        .filter(interaction::Column::Id.eq(interaction_id))
+        .filter(interaction::Column::Responded.eq(!responded))
+}
+
+/// Handles an application's initial response to an interaction
+pub async fn handle_interaction_callback(
+    conn: &DatabaseConnection,
+    nats: &Client,
+    interaction: interaction::Model,
+    callback: InteractionCallback,
+) -> Result<(), InteractionCallbackError> {
+    if (Utc::now().naive_utc() - interaction.created).num_seconds() > INTERACTION_RESPONSE_TIMEOUT {
+        return Err(InteractionCallbackError::Expired);
+    }
+
+    // Claimed before responding, so when two callbacks race only one of them gets to respond
+    let claimed = set_responded(interaction.id, true)
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    if claimed.rows_affected != 1 {
+        return Err(InteractionCallbackError::AlreadyAcknowledged);
+    }
+
+    let interaction_id = interaction.id;
+    let result = respond_to_interaction(conn, nats, interaction, callback).await;
+
+    // An invalid response doesn't count, the application can still send a proper one
+    if result.is_err() {
+        set_responded(interaction_id, false)
+            .exec(conn)
+            .await
+            .expect("Failed to access database!");
+    }
+
+    result
+}
+
+async fn respond_to_interaction(
+    conn: &DatabaseConnection,
+    nats: &Client,
+    interaction: interaction::Model,
+    callback: InteractionCallback,
+) -> Result<(), InteractionCallbackError> {
+    let data = callback.data.unwrap_or_default();
+    let interaction_type = InteractionType::from_i32(interaction.r#type);
+
+    // Component interactions, and modals opened from them, can update the message the component is on
+    let can_update = interaction_type == Some(InteractionType::MessageComponent)
+        || (interaction_type == Some(InteractionType::ModalSubmit) && interaction.message_id.is_some());
+
+    let message_id = match InteractionCallbackType::from_i32(callback._type) {
+        Some(InteractionCallbackType::ChannelMessageWithSource) => {
+            if !data.is_valid() {
+                return Err(InteractionCallbackError::InvalidCallback);
+            }
+
+            Some(create_interaction_message(conn, nats, &interaction, data, 0).await.id)
+        }
+        Some(InteractionCallbackType::DeferredChannelMessageWithSource) => {
+            // Only the flags matter here, the content is sent once the response is edited
+            let data = InteractionCallbackData {
+                flags: data.flags,
+                ..Default::default()
+            };
+
+            Some(create_interaction_message(conn, nats, &interaction, data, MessageFlags::Loading as i32).await.id)
+        }
+        Some(InteractionCallbackType::DeferredUpdateMessage) if can_update => interaction.message_id,
+        Some(InteractionCallbackType::UpdateMessage) if can_update => {
+            if !data.is_valid_edit() {
+                return Err(InteractionCallbackError::InvalidCallback);
+            }
+
+            let message = Message::find_by_id(interaction.message_id.unwrap_or(0))
+                .one(conn)
+                .await
+                .expect("Failed to access database!")
+                .ok_or(InteractionCallbackError::InvalidCallback)?;
+
+            Some(edit_interaction_message(conn, nats, &interaction, message, data).await.id)
+        }
+        // Modals can't be answered with another modal
+        Some(InteractionCallbackType::Modal) if interaction_type != Some(InteractionType::ModalSubmit) => {
+            let modal = data.into_modal().ok_or(InteractionCallbackError::InvalidCallback)?;
+
+            let mut responded_interaction = interaction.clone().into_active_model();
+
+            responded_interaction.responded = Set(true);
+            responded_interaction.modal = Set(serde_json::to_value(modal).ok());
+
+            responded_interaction.update(conn).await.expect("Failed to access database!");
+
+            // The client shows the modal instead of a success state
+            send_nats_message(
+                nats,
+                interaction.user_id.to_string(),
+                Messages::InteractionModalCreate { id: interaction.id },
+            ).await;
+
+            return Ok(());
+        }
+        _ => return Err(InteractionCallbackError::InvalidCallback),
+    };
+
+    let mut responded_interaction = interaction.clone().into_active_model();
+
+    responded_interaction.responded = Set(true);
+    responded_interaction.message_id = Set(message_id);
+
+    responded_interaction.update(conn).await.expect("Failed to access database!");
+
+    send_nats_message(
+        nats,
+        interaction.user_id.to_string(),
+        Messages::InteractionSuccess { id: interaction.id },
+    ).await;
+
+    Ok(())
+}
+
+/// Generates a keypair for signing outgoing interactions, returns the signing key and verify key as hex
+pub fn generate_interaction_keypair() -> (String, String) {
+    let mut secret = [0u8; 32];
+    OsRng.fill_bytes(&mut secret);
+
+    let signing_key = SigningKey::from_bytes(&secret);
+
+    (
+        hex::encode(signing_key.to_bytes()),
+        hex::encode(signing_key.verifying_key().to_bytes()),
+    )
+}
+
+fn sign_interaction_request(signing_key: &str, timestamp: &str, body: &str) -> String {
+    let signing_key: [u8; 32] = hex::decode(signing_key)
+        .expect("Invalid signing key!")
+        .try_into()
+        .expect("Invalid signing key!");
+
+    let signing_key = SigningKey::from_bytes(&signing_key);
+
+    hex::encode(signing_key.sign(format!("{}{}", timestamp, body).as_bytes()).to_bytes())
+}
+
+/// Sends an interaction to an application's interactions endpoint, returns the response body
+///
+/// This blocks, so it should be run outside of async code when possible
+pub fn send_interaction_request(url: &str, signing_key: &str, body: &str) -> Result<Value, InteractionRequestError> {
+    let timestamp = Utc::now().timestamp().to_string();
+    let signature = sign_interaction_request(signing_key, &timestamp, body);
+
+    match ureq::post(url)
+        .timeout(Duration::from_secs(INTERACTION_RESPONSE_TIMEOUT as u64))
+        .set("Content-Type", "application/json")
+        .set("X-Signature-Ed25519", &signature)
+        .set("X-Signature-Timestamp", &timestamp)
+        .send_string(body)
+    {
+        Ok(response) => response.into_json::<Value>().map_err(|_| InteractionRequestError::InvalidResponse),
+        Err(ureq::Error::Status(code, _)) => Err(InteractionRequestError::Rejected(code)),
+        Err(_) => Err(InteractionRequestError::Unreachable),
+    }
+}
+
+/// Checks that an interactions endpoint answers pings and verifies signatures before it is saved
+pub fn validate_interactions_endpoint(url: &str, application_id: i64, signing_key: &str) -> bool {
+    let ping = json!({
+        "id": Snowflake::default().generate().to_string(),
+        "application_id": application_id.to_string(),
+        "type": InteractionType::Ping as i32,
+        "token": gen_token(),
+        "version": 1,
+    })
+        .to_string();
+
+    // A correctly signed ping has to be answered with a pong...
+    let pong = send_interaction_request(url, signing_key, &ping);
+
+    if !pong.is_ok_and(|x| x.get("type").and_then(|x| x.as_i64()) == Some(InteractionCallbackType::Pong as i64)) {
+        return false;
+    }
+
+    // ...and anything signed with the wrong key has to be rejected
+    let (wrong_key, _) = generate_interaction_keypair();
+
+    matches!(
+        send_interaction_request(url, &wrong_key, &ping),
+        Err(InteractionRequestError::Rejected(401))
+    )
+}
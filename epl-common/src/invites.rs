@@ -0,0 +1,70 @@
+use chrono::{NaiveDateTime, Utc};
+use rand::distributions::{Alphanumeric, DistString};
+use sea_orm::{ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use sea_orm::ActiveValue::Set;
+use sea_orm::sea_query::Expr;
+use crate::database::entities::instance_invite;
+use crate::database::entities::prelude::InstanceInvite;
+
+/// How long instance invite codes are
+const INVITE_CODE_LENGTH: usize = 8;
+
+/// Creates an invite to the instance itself, a max_uses of -1 means it can be used any number of times
+pub async fn create_instance_invite(
+    conn: &DatabaseConnection,
+    created_by: Option<i64>,
+    max_uses: i32,
+    expires_at: Option<NaiveDateTime>,
+) -> instance_invite::Model {
+    instance_invite::ActiveModel {
+        code: Set(Alphanumeric.sample_string(&mut rand::thread_rng(), INVITE_CODE_LENGTH)),
+        created_by: Set(created_by),
+        created_at: Set(Utc::now().naive_utc()),
+        uses: Set(0),
+        max_uses: Set(max_uses),
+        expires_at: Set(expires_at),
+    }
+        .insert(conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+/// Invites that haven't expired or run out of uses
+fn usable() -> Condition {
+    Condition::all()
+        .add(
+            Condition::any()
+                .add(instance_invite::Column::MaxUses.eq(-1))
+                .add(Expr::col(instance_invite::Column::Uses).lt(Expr::col(instance_invite::Column::MaxUses)))
+        )
+        .add(
+            Condition::any()
+                .add(instance_invite::Column::ExpiresAt.is_null())
+                .add(instance_invite::Column::ExpiresAt.gt(Utc::now().naive_utc()))
+        )
+}
+
+/// Whether an invite can still be used, without using it up
+pub async fn is_instance_invite_usable(conn: &DatabaseConnection, code: &str) -> bool {
+    InstanceInvite::find()
+        .filter(instance_invite::Column::Code.eq(code))
+        .filter(usable())
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+        .is_some()
+}
+
+/// Uses up an invite for a new account, false if it doesn't exist, expired, or ran out of uses
+pub async fn use_instance_invite(conn: &impl ConnectionTrait, code: &str) -> bool {
+    // Done in one update so two people can't both take the last use
+    let result = InstanceInvite::update_many()
+        .col_expr(instance_invite::Column::Uses, Expr::col(instance_invite::Column::Uses).add(1))
+        .filter(instance_invite::Column::Code.eq(code))
+        .filter(usable())
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    result.rows_affected == 1
+}
@@ -1,32 +1,59 @@
 use crate::options::{EplOptions, Options};
-use maxminddb::geoip2::City;
+use maxminddb::geoip2::{Asn, City};
 use maxminddb::{MaxMindDBError, Reader};
 use once_cell::sync::Lazy;
 use rand::distributions::{Alphanumeric, DistString};
 use serde_derive::{Deserialize, Serialize};
 use std::net::IpAddr;
 
+pub mod admin;
+pub mod calls;
 pub mod channels;
 pub mod database;
+pub mod deletion;
+pub mod emojis;
 pub mod flags;
+pub mod harvest;
+pub mod import;
+pub mod interactions;
+pub mod invites;
+pub mod logins;
+pub mod mail;
 pub mod messages;
+pub mod mfa;
+pub mod moderation;
 pub mod nats;
 pub mod nodeinfo;
+pub mod oauth2;
 pub mod options;
 pub mod permissions;
+pub mod privacy;
 pub mod protobufs;
+pub mod registration;
 pub mod relationship;
+pub mod reports;
 pub mod rustflake;
 pub mod schema;
+pub mod system;
 pub mod tenor;
+pub mod voice;
 
 static GEOIP: Lazy<Reader<Vec<u8>>> = Lazy::new(|| {
     Reader::open_readfile(EplOptions::get().maxminddb).expect("Failed to open maxmind database!")
 });
 
+static GEOIP_ASN: Lazy<Option<Reader<Vec<u8>>>> = Lazy::new(|| {
+    EplOptions::get()
+        .maxmind_asn_db
+        .map(|path| Reader::open_readfile(path).expect("Failed to open maxmind ASN database!"))
+});
+
 pub static USER_MENTION_REGEX: Lazy<regex::Regex> =
     Lazy::new(|| regex::Regex::new(r"<@!?(\d+)>").unwrap());
 
+pub static CUSTOM_EMOJI_REGEX: Lazy<regex::Regex> =
+    Lazy::new(|| regex::Regex::new(r"<(a?):(\w{2,32}):(\d+)>").unwrap());
+
 pub static URL_REGEX: Lazy<regex::Regex> = Lazy::new(|| {
     regex::Regex::new(r"https?:\/\/(www\.)?[-a-zA-Z0-9@:%._\+~#=]{1,256}\.[a-zA-Z0-9()]{1,6}\b([-a-zA-Z0-9()@:%_\+.~#?&//=]*)").unwrap()
 });
@@ -60,17 +87,39 @@ pub fn get_location_from_ip(ip: IpAddr) -> String {
 
     match result {
         Ok(result) => {
-            format!(
-                "{}, {} ({})",
-                result.city.unwrap().names.unwrap().get("en").unwrap(),
-                result.country.unwrap().names.unwrap().get("en").unwrap(),
-                ip
-            )
+            // Not every IP has a known city, so don't fall over when one is missing
+            let city = result.city.and_then(|x| x.names).and_then(|x| x.get("en").copied()).unwrap_or("Unknown");
+            let country = result.country.and_then(|x| x.names).and_then(|x| x.get("en").copied()).unwrap_or("Unknown");
+
+            format!("{}, {} ({})", city, country, ip)
         }
         Err(_) => String::from("Locator service not functioning!"),
     }
 }
 
+/// Gets the ISO code of the country an IP is in
+pub fn get_country_from_ip(ip: IpAddr) -> Option<String> {
+    let result: Result<City, MaxMindDBError> = GEOIP.lookup(ip);
+
+    result.ok()?.country?.iso_code.map(String::from)
+}
+
+/// Gets the autonomous system an IP belongs to, only if an ASN database is configured
+pub fn get_asn_from_ip(ip: IpAddr) -> Option<u32> {
+    let result: Result<Asn, MaxMindDBError> = GEOIP_ASN.as_ref()?.lookup(ip);
+
+    result.ok()?.autonomous_system_number
+}
+
+/// Gets the rough latitude and longitude of an IP, for picking nearby voice regions
+pub fn get_coordinates_from_ip(ip: IpAddr) -> Option<(f64, f64)> {
+    let result: Result<City, MaxMindDBError> = GEOIP.lookup(ip);
+
+    let location = result.ok()?.location?;
+
+    Some((location.latitude?, location.longitude?))
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Stub {}
 
@@ -107,6 +156,9 @@ pub struct User {
     pub avatar_decoration: Option<String>,
     pub avatar: Option<String>,
     pub accent_color: Option<String>,
+    /// Only sent back when changing the password rotates the token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
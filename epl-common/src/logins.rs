@@ -0,0 +1,124 @@
+use std::net::IpAddr;
+use chrono::{Duration, Utc};
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use sea_orm::ActiveValue::Set;
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use crate::database::entities::login_attempt;
+use crate::database::entities::prelude::LoginAttempt;
+use crate::rustflake::Snowflake;
+use crate::{get_asn_from_ip, get_country_from_ip, get_location_from_ip};
+
+/// How many failed attempts in a row lock an account
+pub const MAX_FAILED_LOGINS: u64 = 5;
+
+/// How long an account stays locked after too many failed attempts, in minutes
+pub const LOGIN_LOCKOUT_MINUTES: i64 = 15;
+
+#[derive(Serialize_repr, Deserialize_repr, Debug, Copy, Clone, PartialEq, FromPrimitive)]
+#[repr(i32)]
+pub enum LoginOutcome {
+    Success = 0,
+    InvalidPassword = 1,
+    InvalidTwoFactorCode = 2,
+    /// The login came from somewhere new and has to be approved over email first
+    NewLocation = 3,
+    /// The login came from somewhere new and the user approved it
+    LocationApproved = 4,
+    LockedOut = 5,
+    /// The password was right but the account is suspended or banned
+    Suspended = 6,
+}
+
+impl LoginOutcome {
+    /// Whether this outcome counts towards locking the account
+    fn is_failure(&self) -> bool {
+        matches!(self, LoginOutcome::InvalidPassword | LoginOutcome::InvalidTwoFactorCode)
+    }
+}
+
+/// Where a login came from, as far as new location checks care
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoginLocation {
+    pub ip: String,
+    pub country: Option<String>,
+    pub asn: Option<i64>,
+    /// Human readable city and country
+    pub location: String,
+}
+
+pub fn get_login_location(ip: IpAddr) -> LoginLocation {
+    LoginLocation {
+        ip: ip.to_string(),
+        country: get_country_from_ip(ip),
+        asn: get_asn_from_ip(ip).map(i64::from),
+        location: get_location_from_ip(ip),
+    }
+}
+
+pub async fn record_login_attempt(
+    conn: &DatabaseConnection,
+    user_id: i64,
+    location: &LoginLocation,
+    outcome: LoginOutcome,
+) {
+    login_attempt::ActiveModel {
+        id: Set(Snowflake::default().generate()),
+        user_id: Set(user_id),
+        ip: Set(location.ip.clone()),
+        country: Set(location.country.clone()),
+        asn: Set(location.asn),
+        location: Set(location.location.clone()),
+        outcome: Set(outcome as i32),
+        created_at: Set(Utc::now().naive_utc()),
+    }
+        .insert(conn)
+        .await
+        .expect("Failed to access database!");
+}
+
+/// Whether the user has failed to log in too many times recently
+pub async fn is_locked_out(conn: &DatabaseConnection, user_id: i64) -> bool {
+    let recent_attempts = LoginAttempt::find()
+        .filter(login_attempt::Column::UserId.eq(user_id))
+        .filter(login_attempt::Column::CreatedAt.gt(Utc::now().naive_utc() - Duration::minutes(LOGIN_LOCKOUT_MINUTES)))
+        .filter(login_attempt::Column::Outcome.ne(LoginOutcome::LockedOut as i32))
+        .order_by_desc(login_attempt::Column::CreatedAt)
+        .limit(MAX_FAILED_LOGINS)
+        .all(conn)
+        .await
+        .expect("Failed to access database!");
+
+    // Only failures in a row count, any success in between resets it
+    recent_attempts.len() as u64 >= MAX_FAILED_LOGINS
+        && recent_attempts.iter().all(|x| {
+            LoginOutcome::from_i32(x.outcome).is_some_and(|x| x.is_failure())
+        })
+}
+
+/// Whether the user has logged in from this country and network before, the first login is always known
+pub async fn is_known_location(conn: &DatabaseConnection, user_id: i64, location: &LoginLocation) -> bool {
+    let known_locations = LoginAttempt::find()
+        .filter(login_attempt::Column::UserId.eq(user_id))
+        .filter(
+            login_attempt::Column::Outcome.is_in([LoginOutcome::Success as i32, LoginOutcome::LocationApproved as i32])
+        )
+        .all(conn)
+        .await
+        .expect("Failed to access database!");
+
+    known_locations.is_empty()
+        || known_locations.iter().any(|x| x.country == location.country && x.asn == location.asn)
+}
+
+/// The most recent login attempts on an account
+pub async fn get_login_attempts(conn: &DatabaseConnection, user_id: i64, limit: u64) -> Vec<login_attempt::Model> {
+    LoginAttempt::find()
+        .filter(login_attempt::Column::UserId.eq(user_id))
+        .order_by_desc(login_attempt::Column::CreatedAt)
+        .limit(limit)
+        .all(conn)
+        .await
+        .expect("Failed to access database!")
+}
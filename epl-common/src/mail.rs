@@ -0,0 +1,158 @@
+use askama::Template;
+use async_nats::Client;
+use serde_derive::{Deserialize, Serialize};
+use crate::nats::{Messages, send_nats_message};
+use crate::options::{EplOptions, Options};
+
+/// An email that can be queued, rendered by the worker right before it's sent
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum Mail {
+    /// Confirming the address of a new account or a changed email
+    Verification {
+        username: String,
+        url: String,
+    },
+    /// A link to reset a forgotten password
+    PasswordReset {
+        username: String,
+        url: String,
+    },
+    /// Someone logged in from somewhere new
+    NewLogin {
+        username: String,
+        ip: String,
+        location: String,
+        platform: String,
+        /// Set if the login has to be approved before it goes through
+        approve_url: Option<String>,
+    },
+    /// The account was scheduled to be deleted
+    AccountDeletion {
+        username: String,
+        /// When the account will actually be deleted, logging in before then cancels it
+        deletion_date: String,
+    },
+    /// A requested data package is ready to download
+    HarvestReady {
+        username: String,
+        url: String,
+        /// When the download link stops working
+        expiry_date: String,
+    },
+    /// Staff approved a registration waiting in the approval queue
+    RegistrationApproved {
+        username: String,
+        url: String,
+    },
+}
+
+#[derive(Template)]
+#[template(path = "mail/verification.txt")]
+struct VerificationTemplate<'a> {
+    instance_name: &'a str,
+    username: &'a str,
+    url: &'a str,
+}
+
+#[derive(Template)]
+#[template(path = "mail/password_reset.txt")]
+struct PasswordResetTemplate<'a> {
+    instance_name: &'a str,
+    username: &'a str,
+    url: &'a str,
+}
+
+#[derive(Template)]
+#[template(path = "mail/new_login.txt")]
+struct NewLoginTemplate<'a> {
+    instance_name: &'a str,
+    username: &'a str,
+    ip: &'a str,
+    location: &'a str,
+    platform: &'a str,
+    approve_url: &'a Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "mail/account_deletion.txt")]
+struct AccountDeletionTemplate<'a> {
+    instance_name: &'a str,
+    username: &'a str,
+    deletion_date: &'a str,
+}
+
+#[derive(Template)]
+#[template(path = "mail/harvest_ready.txt")]
+struct HarvestReadyTemplate<'a> {
+    instance_name: &'a str,
+    username: &'a str,
+    url: &'a str,
+    expiry_date: &'a str,
+}
+
+#[derive(Template)]
+#[template(path = "mail/registration_approved.txt")]
+struct RegistrationApprovedTemplate<'a> {
+    instance_name: &'a str,
+    username: &'a str,
+    url: &'a str,
+}
+
+impl Mail {
+    /// Renders the email into its subject and plain text body
+    pub fn render(&self) -> (String, String) {
+        let options = EplOptions::get();
+        let instance_name = options.name.as_str();
+
+        let (subject, body) = match self {
+            Mail::Verification { username, url } => (
+                format!("Verify your email address for {}", instance_name),
+                VerificationTemplate { instance_name, username, url }.render(),
+            ),
+            Mail::PasswordReset { username, url } => (
+                format!("Password reset request for {}", instance_name),
+                PasswordResetTemplate { instance_name, username, url }.render(),
+            ),
+            Mail::NewLogin { username, ip, location, platform, approve_url } => (
+                format!("New login location detected for {}", instance_name),
+                NewLoginTemplate { instance_name, username, ip, location, platform, approve_url }.render(),
+            ),
+            Mail::AccountDeletion { username, deletion_date } => (
+                format!("Your {} account is scheduled for deletion", instance_name),
+                AccountDeletionTemplate { instance_name, username, deletion_date }.render(),
+            ),
+            Mail::HarvestReady { username, url, expiry_date } => (
+                format!("Your {} data package is ready", instance_name),
+                HarvestReadyTemplate { instance_name, username, url, expiry_date }.render(),
+            ),
+            Mail::RegistrationApproved { username, url } => (
+                format!("Your {} account has been approved", instance_name),
+                RegistrationApprovedTemplate { instance_name, username, url }.render(),
+            ),
+        };
+
+        (subject, body.expect("Failed to render mail template!"))
+    }
+}
+
+/// Queues an email to be sent by the worker
+pub async fn queue_mail(nats_client: &Client, to: String, mail: Mail) {
+    send_nats_message(
+        nats_client,
+        "worker_queue".to_string(),
+        Messages::SendMail { to, mail, attempt: 0 },
+    ).await;
+}
+
+/// Builds a link to a page of the client on this instance
+pub fn instance_link(path: &str) -> String {
+    let options = EplOptions::get();
+
+    format!(
+        "{}://{}{}",
+        if options.require_ssl { "https" } else { "http" },
+        options.url,
+        path
+    )
+}
@@ -55,4 +55,21 @@ pub enum MessageTypes {
     StageSpeaker = 29,
     StageTopic = 31,
     GuildApplicationPremiumSubscription = 32,
-}
\ No newline at end of file
+}
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[repr(i32)]
+pub enum MessageFlags {
+    Crossposted = 1 << 0,
+    IsCrosspost = 1 << 1,
+    SuppressEmbeds = 1 << 2,
+    SourceMessageDeleted = 1 << 3,
+    Urgent = 1 << 4,
+    HasThread = 1 << 5,
+    /// Only visible to the user who invoked the interaction
+    Ephemeral = 1 << 6,
+    /// The application is still "thinking" about a deferred interaction
+    Loading = 1 << 7,
+    FailedToMentionSomeRolesInThread = 1 << 8,
+    SuppressNotifications = 1 << 12,
+    IsVoiceMessage = 1 << 13,
+}
@@ -0,0 +1,413 @@
+use once_cell::sync::Lazy;
+use rand::distributions::{Alphanumeric, DistString};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter};
+use sea_orm::ActiveValue::Set;
+use serde_derive::{Deserialize, Serialize};
+use totp_rs::{Algorithm, Secret, TOTP};
+use webauthn_rs::prelude::{
+    CreationChallengeResponse, PublicKeyCredential, RegisterPublicKeyCredential, RequestChallengeResponse, SecurityKey,
+    SecurityKeyAuthentication, SecurityKeyRegistration, Url, Uuid, Webauthn, WebauthnBuilder,
+};
+use crate::database::entities::{backup_code, user, webauthn_credential};
+use crate::database::entities::prelude::{BackupCode, WebauthnCredential};
+use crate::options::{EplOptions, Options};
+
+const BACKUP_CODE_COUNT: usize = 10;
+
+/// The relying party is the instance itself, clients have to be served from the instance URL for keys to work
+static WEBAUTHN: Lazy<Webauthn> = Lazy::new(|| {
+    let options = EplOptions::get();
+
+    let origin = Url::parse(&format!(
+        "{}://{}",
+        if options.require_ssl { "https" } else { "http" },
+        options.url
+    )).expect("URL is invalid!");
+
+    let rp_id = origin.host_str().expect("URL is missing a host!").to_string();
+
+    WebauthnBuilder::new(&rp_id, &origin)
+        .expect("Failed to configure WebAuthn!")
+        .rp_name(&options.name)
+        .build()
+        .expect("Failed to configure WebAuthn!")
+});
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ResBackupCode {
+    pub user_id: String,
+    pub code: String,
+    pub consumed: bool,
+}
+
+impl From<backup_code::Model> for ResBackupCode {
+    fn from(backup_code: backup_code::Model) -> Self {
+        ResBackupCode {
+            user_id: backup_code.user_id.to_string(),
+            code: backup_code.code,
+            consumed: backup_code.consumed,
+        }
+    }
+}
+
+/// Clients show secrets in groups of 4 and backup codes as `xxxx-xxxx`
+fn normalize(input: &str) -> String {
+    input.chars().filter(|x| !x.is_whitespace() && *x != '-').collect()
+}
+
+/// Secrets are generated by the client as base32, checks that we can actually use one
+pub fn parse_totp_secret(secret: &str) -> Option<String> {
+    let secret = normalize(secret).to_uppercase();
+
+    Secret::Encoded(secret.clone()).to_bytes().ok()?;
+
+    Some(secret)
+}
+
+pub fn verify_totp(secret: &str, code: &str) -> bool {
+    let secret = match Secret::Encoded(secret.to_string()).to_bytes() {
+        Ok(secret) => secret,
+        Err(_) => return false,
+    };
+
+    // Clients generate 80-bit secrets, which are shorter than what RFC 4226 recommends
+    let totp = TOTP::new_unchecked(Algorithm::SHA1, 6, 1, 30, secret);
+
+    totp.check_current(&normalize(code)).unwrap_or(false)
+}
+
+pub async fn get_backup_codes(conn: &DatabaseConnection, user_id: i64) -> Vec<backup_code::Model> {
+    BackupCode::find()
+        .filter(backup_code::Column::UserId.eq(user_id))
+        .all(conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+/// Throws out any existing backup codes and makes a new set
+pub async fn generate_backup_codes(conn: &DatabaseConnection, user_id: i64) -> Vec<backup_code::Model> {
+    BackupCode::delete_many()
+        .filter(backup_code::Column::UserId.eq(user_id))
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+
+    let mut backup_codes = vec![];
+
+    for _ in 0..BACKUP_CODE_COUNT {
+        let backup_code = backup_code::ActiveModel {
+            user_id: Set(user_id),
+            code: Set(Alphanumeric.sample_string(&mut rand::thread_rng(), 8).to_lowercase()),
+            consumed: Set(false),
+        }
+            .insert(conn)
+            .await
+            .expect("Failed to access database!");
+
+        backup_codes.push(backup_code);
+    }
+
+    backup_codes
+}
+
+pub async fn get_webauthn_credentials(conn: &DatabaseConnection, user_id: i64) -> Vec<webauthn_credential::Model> {
+    WebauthnCredential::find()
+        .filter(webauthn_credential::Column::UserId.eq(user_id))
+        .all(conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+fn parse_security_key(credential: &webauthn_credential::Model) -> SecurityKey {
+    serde_json::from_value(credential.credential.clone()).expect("Failed to parse stored WebAuthn credential!")
+}
+
+/// Starts registering a new security key, returns the challenge for the client and the state to keep until it answers
+pub async fn start_webauthn_registration(
+    conn: &DatabaseConnection,
+    user: &user::Model,
+) -> Option<(CreationChallengeResponse, SecurityKeyRegistration)> {
+    let existing_keys = get_webauthn_credentials(conn, user.id)
+        .await
+        .iter()
+        .map(|x| parse_security_key(x).cred_id().clone())
+        .collect();
+
+    WEBAUTHN.start_securitykey_registration(
+        Uuid::from_u64_pair(0, user.id as u64),
+        &user.username,
+        user.display_name.as_ref().unwrap_or(&user.username),
+        Some(existing_keys),
+        None,
+        None,
+    ).ok()
+}
+
+pub fn finish_webauthn_registration(
+    credential: &RegisterPublicKeyCredential,
+    state: &SecurityKeyRegistration,
+) -> Option<SecurityKey> {
+    WEBAUTHN.finish_securitykey_registration(credential, state).ok()
+}
+
+/// Starts a WebAuthn assertion with all of a user's security keys, if they have any
+pub async fn start_webauthn_authentication(
+    conn: &DatabaseConnection,
+    user_id: i64,
+) -> Option<(RequestChallengeResponse, SecurityKeyAuthentication)> {
+    let keys: Vec<SecurityKey> = get_webauthn_credentials(conn, user_id)
+        .await
+        .iter()
+        .map(parse_security_key)
+        .collect();
+
+    if keys.is_empty() {
+        return None;
+    }
+
+    WEBAUTHN.start_securitykey_authentication(&keys).ok()
+}
+
+/// Checks a WebAuthn assertion against the state from starting it, the used key's counter gets updated
+pub async fn finish_webauthn_authentication(
+    conn: &DatabaseConnection,
+    user_id: i64,
+    credential: &PublicKeyCredential,
+    state: &SecurityKeyAuthentication,
+) -> bool {
+    let result = match WEBAUTHN.finish_securitykey_authentication(credential, state) {
+        Ok(result) => result,
+        Err(_) => return false,
+    };
+
+    for i in get_webauthn_credentials(conn, user_id).await {
+        let mut key = parse_security_key(&i);
+
+        if key.update_credential(&result) == Some(true) {
+            let mut credential = i.into_active_model();
+
+            credential.credential = Set(serde_json::to_value(key).expect("Failed to encode WebAuthn credential!"));
+
+            credential.update(conn).await.expect("Failed to access database!");
+        }
+    }
+
+    true
+}
+
+/// Checks a TOTP code or an unused backup code, backup codes get used up
+pub async fn verify_mfa_code(conn: &DatabaseConnection, user: &user::Model, code: &str) -> bool {
+    if !user.mfa_enabled {
+        return false;
+    }
+
+    if let Some(secret) = &user.totp_secret {
+        if verify_totp(secret, code) {
+            return true;
+        }
+    }
+
+    let backup_code = BackupCode::find_by_id((user.id, normalize(code).to_lowercase()))
+        .one(conn)
+        .await
+        .expect("Failed to access database!");
+
+    match backup_code {
+        Some(backup_code) if !backup_code.consumed => {
+            let mut backup_code = backup_code.into_active_model();
+
+            backup_code.consumed = Set(true);
+
+            backup_code.update(conn).await.expect("Failed to access database!");
+
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Once;
+
+    use sea_orm::{DatabaseBackend, MockDatabase};
+    use webauthn_authenticator_rs::softtoken::SoftToken;
+    use webauthn_authenticator_rs::WebauthnAuthenticator;
+
+    use super::*;
+
+    const USER_ID: i64 = 1234;
+
+    fn set_options() {
+        static OPTIONS: Once = Once::new();
+
+        OPTIONS.call_once(|| {
+            std::env::set_var("URL", "localhost");
+            std::env::set_var("GATEWAY_URL", "localhost");
+            std::env::set_var("CDN_URL", "localhost");
+            std::env::set_var("SECRET_KEY", "secret");
+            std::env::set_var("LVSP_SECRET", "lvsp-secret");
+            std::env::set_var("REQUIRE_SSL", "false");
+        });
+    }
+
+    fn origin() -> Url {
+        Url::parse("http://localhost").unwrap()
+    }
+
+    fn authenticator() -> WebauthnAuthenticator<SoftToken> {
+        let (token, _) = SoftToken::new(true).expect("Failed to create soft token!");
+
+        WebauthnAuthenticator::new(token)
+    }
+
+    fn test_user() -> user::Model {
+        user::Model {
+            id: USER_ID,
+            system: false,
+            bot: false,
+            username: "test".to_string(),
+            password_hash: String::new(),
+            discriminator: "0".to_string(),
+            bio: None,
+            pronouns: None,
+            avatar: None,
+            avatar_decoration: None,
+            banner: None,
+            banner_colour: None,
+            date_of_birth: None,
+            email: "test@localhost".to_string(),
+            phone: None,
+            mfa_enabled: true,
+            acct_verified: true,
+            flags: 0,
+            nsfw_allowed: false,
+            purchased_flags: None,
+            premium_flags: None,
+            premium_type: None,
+            premium_since: None,
+            accent_color: None,
+            display_name: None,
+            legacy_name: None,
+            totp_secret: None,
+            verification_sent_at: None,
+            deletion_scheduled_at: None,
+        }
+    }
+
+    fn stored_credential(id: i64, key: &SecurityKey) -> webauthn_credential::Model {
+        webauthn_credential::Model {
+            id,
+            user_id: USER_ID,
+            name: "Soft token".to_string(),
+            credential: serde_json::to_value(key).unwrap(),
+        }
+    }
+
+    /// Answers two credential lookups with the given keys, then hands back each key when its counter gets updated
+    fn mock_database(credentials: Vec<webauthn_credential::Model>) -> DatabaseConnection {
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([credentials.clone(), credentials.clone()])
+            .append_query_results(credentials.into_iter().map(|x| vec![x]))
+            .into_connection()
+    }
+
+    /// Registers a new soft token security key for the test user
+    async fn register(authenticator: &mut WebauthnAuthenticator<SoftToken>) -> SecurityKey {
+        let conn = mock_database(vec![]);
+
+        let (challenge, state) = start_webauthn_registration(&conn, &test_user())
+            .await
+            .expect("Failed to start registration!");
+
+        let credential = authenticator
+            .do_registration(origin(), challenge)
+            .expect("The soft token failed to register!");
+
+        finish_webauthn_registration(&credential, &state).expect("The registration was rejected!")
+    }
+
+    #[tokio::test]
+    async fn registers_and_asserts_a_security_key() {
+        set_options();
+
+        let mut authenticator = authenticator();
+        let key = register(&mut authenticator).await;
+
+        let conn = mock_database(vec![stored_credential(1, &key)]);
+
+        let (challenge, state) = start_webauthn_authentication(&conn, USER_ID)
+            .await
+            .expect("Failed to start authentication!");
+
+        let assertion = authenticator
+            .do_authentication(origin(), challenge)
+            .expect("The soft token failed to sign the challenge!");
+
+        assert!(finish_webauthn_authentication(&conn, USER_ID, &assertion, &state).await);
+    }
+
+    #[tokio::test]
+    async fn no_assertion_without_security_keys() {
+        set_options();
+
+        let conn = mock_database(vec![]);
+
+        assert!(start_webauthn_authentication(&conn, USER_ID).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn rejects_an_assertion_from_another_security_key() {
+        set_options();
+
+        let mut registered = authenticator();
+        let registered_key = register(&mut registered).await;
+
+        let mut other = authenticator();
+        let other_key = register(&mut other).await;
+
+        // The assertion is only expected from the registered key
+        let conn = mock_database(vec![stored_credential(1, &registered_key)]);
+
+        let (_, state) = start_webauthn_authentication(&conn, USER_ID)
+            .await
+            .expect("Failed to start authentication!");
+
+        // A valid assertion, but from a key the user never added
+        let other_conn = mock_database(vec![stored_credential(2, &other_key)]);
+
+        let (other_challenge, _) = start_webauthn_authentication(&other_conn, USER_ID)
+            .await
+            .expect("Failed to start authentication!");
+
+        let assertion = other
+            .do_authentication(origin(), other_challenge)
+            .expect("The soft token failed to sign the challenge!");
+
+        assert!(!finish_webauthn_authentication(&conn, USER_ID, &assertion, &state).await);
+    }
+
+    #[tokio::test]
+    async fn rejects_an_assertion_for_another_challenge() {
+        set_options();
+
+        let mut authenticator = authenticator();
+        let key = register(&mut authenticator).await;
+
+        let conn = mock_database(vec![stored_credential(1, &key)]);
+
+        let (challenge, _) = start_webauthn_authentication(&conn, USER_ID)
+            .await
+            .expect("Failed to start authentication!");
+
+        let (_, other_state) = start_webauthn_authentication(&conn, USER_ID)
+            .await
+            .expect("Failed to start authentication!");
+
+        let assertion = authenticator
+            .do_authentication(origin(), challenge)
+            .expect("The soft token failed to sign the challenge!");
+
+        assert!(!finish_webauthn_authentication(&conn, USER_ID, &assertion, &other_state).await);
+    }
+}
@@ -0,0 +1,175 @@
+use async_nats::Client;
+use chrono::{NaiveDateTime, Utc};
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use sea_orm::{ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder};
+use sea_orm::ActiveValue::Set;
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use crate::database::entities::prelude::{ModerationAction, Session};
+use crate::database::entities::{moderation_action, session};
+use crate::nats::{Messages, send_nats_message};
+use crate::rustflake::Snowflake;
+use crate::system::send_system_message;
+
+#[derive(Serialize_repr, Deserialize_repr, Debug, Copy, Clone, PartialEq, FromPrimitive)]
+#[repr(i32)]
+pub enum ModerationActionType {
+    /// Only shows up in the Safety Hub
+    Warning = 1,
+    /// Can still log in and read, but can't message, react, call or invite anyone
+    Limit = 2,
+    /// Can't log in until it expires
+    Suspension = 3,
+    /// A suspension that never expires
+    Ban = 4,
+}
+
+impl ModerationActionType {
+    pub fn description(&self) -> &'static str {
+        match self {
+            ModerationActionType::Warning => "Warning",
+            ModerationActionType::Limit => "Your account was limited",
+            ModerationActionType::Suspension => "Your account was suspended",
+            ModerationActionType::Ban => "Your account was permanently suspended",
+        }
+    }
+}
+
+/// The states shown in the Safety Hub, worst wins
+#[derive(Serialize_repr, Deserialize_repr, Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[repr(i32)]
+pub enum AccountStandingState {
+    AllGood = 100,
+    Limited = 200,
+    VeryLimited = 300,
+    AtRisk = 400,
+    Suspended = 500,
+}
+
+/// How many active warnings put an account at risk
+const AT_RISK_WARNINGS: usize = 3;
+
+/// Actions that haven't expired or been revoked, newest first
+pub async fn get_active_actions(conn: &DatabaseConnection, user_id: i64) -> Vec<moderation_action::Model> {
+    ModerationAction::find()
+        .filter(moderation_action::Column::UserId.eq(user_id))
+        .filter(moderation_action::Column::RevokedAt.is_null())
+        .filter(
+            Condition::any()
+                .add(moderation_action::Column::ExpiresAt.is_null())
+                .add(moderation_action::Column::ExpiresAt.gt(Utc::now().naive_utc()))
+        )
+        .order_by_desc(moderation_action::Column::Id)
+        .all(conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+pub fn get_action_type(action: &moderation_action::Model) -> ModerationActionType {
+    ModerationActionType::from_i32(action.action_type).expect("Unknown moderation action type!")
+}
+
+pub fn get_account_standing(active_actions: &[moderation_action::Model]) -> AccountStandingState {
+    let count = |action_type: ModerationActionType| active_actions
+        .iter()
+        .filter(|x| get_action_type(x) == action_type)
+        .count();
+
+    if count(ModerationActionType::Ban) > 0 || count(ModerationActionType::Suspension) > 0 {
+        AccountStandingState::Suspended
+    } else if count(ModerationActionType::Warning) >= AT_RISK_WARNINGS {
+        AccountStandingState::AtRisk
+    } else if count(ModerationActionType::Limit) > 1 {
+        AccountStandingState::VeryLimited
+    } else if count(ModerationActionType::Limit) == 1 {
+        AccountStandingState::Limited
+    } else {
+        AccountStandingState::AllGood
+    }
+}
+
+/// The suspension or ban keeping the user from logging in, if there is one
+pub async fn get_suspension(conn: &DatabaseConnection, user_id: i64) -> Option<moderation_action::Model> {
+    get_active_actions(conn, user_id)
+        .await
+        .into_iter()
+        .find(|x| matches!(get_action_type(x), ModerationActionType::Suspension | ModerationActionType::Ban))
+}
+
+pub async fn is_limited(conn: &DatabaseConnection, user_id: i64) -> bool {
+    get_active_actions(conn, user_id)
+        .await
+        .iter()
+        .any(|x| get_action_type(x) == ModerationActionType::Limit)
+}
+
+/// Records an action against a user and lets them know, suspensions and bans also log them out everywhere
+pub async fn issue_moderation_action(
+    conn: &DatabaseConnection,
+    nats_client: &Client,
+    user_id: i64,
+    moderator_id: Option<i64>,
+    action_type: ModerationActionType,
+    reason: String,
+    expires_at: Option<NaiveDateTime>,
+) -> moderation_action::Model {
+    let action = moderation_action::ActiveModel {
+        id: Set(Snowflake::default().generate()),
+        user_id: Set(user_id),
+        moderator_id: Set(moderator_id),
+        action_type: Set(action_type as i32),
+        reason: Set(reason),
+        created_at: Set(Utc::now().naive_utc()),
+        // Bans are the only thing that's always permanent
+        expires_at: Set(if action_type == ModerationActionType::Ban { None } else { expires_at }),
+        revoked_at: Set(None),
+    }
+        .insert(conn)
+        .await
+        .expect("Failed to access database!");
+
+    let mut notice = format!("{}: {}", action_type.description(), action.reason);
+
+    if let Some(expires_at) = action.expires_at {
+        notice.push_str(&format!("\nThis ends on {} UTC.", expires_at.format("%Y-%m-%d %H:%M")));
+    }
+
+    notice.push_str("\nYou can check your account standing in the Safety Hub.");
+
+    send_system_message(conn, nats_client, user_id, notice, true).await;
+
+    if matches!(action_type, ModerationActionType::Suspension | ModerationActionType::Ban) {
+        Session::delete_many()
+            .filter(session::Column::UserId.eq(user_id))
+            .exec(conn)
+            .await
+            .expect("Failed to access database!");
+
+        send_nats_message(
+            nats_client,
+            user_id.to_string(),
+            Messages::InvalidateGatewaySession { session: "all".to_string() }
+        ).await;
+    }
+
+    action
+}
+
+/// Lifts an action early, false if it doesn't exist or was already revoked
+pub async fn revoke_moderation_action(conn: &DatabaseConnection, action_id: i64) -> bool {
+    let Some(action) = ModerationAction::find_by_id(action_id)
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+        .filter(|x| x.revoked_at.is_none()) else {
+        return false;
+    };
+
+    let mut active_action = action.into_active_model();
+
+    active_action.revoked_at = Set(Some(Utc::now().naive_utc()));
+
+    active_action.update(conn).await.expect("Failed to access database!");
+
+    true
+}
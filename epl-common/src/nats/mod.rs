@@ -1,5 +1,6 @@
 use chrono::NaiveDateTime;
 use crate::RelationshipType;
+use crate::mail::Mail;
 use serde_derive::{Deserialize, Serialize};
 use async_nats::client::Client;
 
@@ -110,12 +111,91 @@ pub enum Messages {
         message_id: i64,
         user_id: i64,
         emoji: String,
+        reaction_type: i32,
     },
     MessageReactionRemove {
         message_id: i64,
         user_id: i64,
         emoji: String,
-    }
+        reaction_type: i32,
+    },
+    /// Every reaction on a message was removed
+    MessageReactionRemoveAll {
+        message_id: i64,
+    },
+    /// Every reaction of one emoji on a message was removed
+    MessageReactionRemoveEmoji {
+        message_id: i64,
+        emoji: String,
+    },
+    /// A guild's emojis were created, edited or deleted (sent to the guild)
+    GuildEmojisUpdate {
+        guild_id: i64,
+    },
+    /// A user joined, left or changed their state in a voice channel (sent to the channel)
+    VoiceStateUpdate {
+        /// The user whose voice state changed
+        user_id: i64,
+        /// The channel the voice state is or was in
+        channel_id: i64,
+        /// The gateway session the voice state belongs to
+        session_id: String,
+    },
+    /// A call was started in a channel (sent to the channel)
+    CallCreate {
+        channel_id: i64,
+    },
+    /// A call's ringing list or region changed (sent to the channel)
+    CallUpdate {
+        channel_id: i64,
+    },
+    /// A call ended (sent to the channel)
+    CallDelete {
+        channel_id: i64,
+    },
+    /// An interaction was created (sent to the application's bot and the user who used it)
+    InteractionCreate {
+        /// ID of the interaction
+        id: i64,
+    },
+    /// The application responded to an interaction (sent to the user who used it)
+    InteractionSuccess {
+        /// ID of the interaction
+        id: i64,
+    },
+    /// The application failed to respond to an interaction in time (sent to the user who used it)
+    InteractionFailure {
+        /// ID of the interaction
+        id: i64,
+    },
+    /// The application responded to an interaction with a modal (sent to the user who used it)
+    InteractionModalCreate {
+        /// ID of the interaction
+        id: i64,
+    },
+    /// An interaction has to be sent to the application's interactions endpoint
+    DeliverInteraction {
+        /// ID of the interaction
+        id: i64,
+    },
+    /// A user's own account details changed (sent to the user)
+    UserUpdate {
+        user_id: i64,
+    },
+    /// An email has to be sent
+    SendMail {
+        /// Address the email is sent to
+        to: String,
+        /// What is being sent
+        mail: Mail,
+        /// How many times sending this has already failed
+        attempt: u32,
+    },
+    /// A data package has to be built for a user
+    ProcessHarvest {
+        /// ID of the harvest
+        harvest_id: i64,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -0,0 +1,96 @@
+use std::str::FromStr;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum OAuth2Scopes {
+    /// Access to the user's basic information, without the email
+    Identify,
+    /// Access to the user's email, requires `identify`
+    Email,
+    /// Access to the list of guilds the user is in
+    Guilds,
+    /// Allows the application to join the user to a guild
+    GuildsJoin,
+    /// Allows the application to join the user to a group DM
+    GdmJoin,
+    /// Adds the application's bot user to a guild or group DM
+    Bot,
+    /// Access to the user's account connections
+    Connections,
+    /// Allows the application to use commands where it has been authorized
+    ApplicationsCommands,
+    /// Allows the application to update its own commands with a bearer token
+    ApplicationsCommandsUpdate,
+}
+
+impl FromStr for OAuth2Scopes {
+    type Err = ();
+
+    fn from_str(scope: &str) -> Result<Self, Self::Err> {
+        match scope {
+            "identify" => Ok(OAuth2Scopes::Identify),
+            "email" => Ok(OAuth2Scopes::Email),
+            "guilds" => Ok(OAuth2Scopes::Guilds),
+            "guilds.join" => Ok(OAuth2Scopes::GuildsJoin),
+            "gdm.join" => Ok(OAuth2Scopes::GdmJoin),
+            "bot" => Ok(OAuth2Scopes::Bot),
+            "connections" => Ok(OAuth2Scopes::Connections),
+            "applications.commands" => Ok(OAuth2Scopes::ApplicationsCommands),
+            "applications.commands.update" => Ok(OAuth2Scopes::ApplicationsCommandsUpdate),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<OAuth2Scopes> for String {
+    fn from(scope: OAuth2Scopes) -> String {
+        match scope {
+            OAuth2Scopes::Identify => "identify",
+            OAuth2Scopes::Email => "email",
+            OAuth2Scopes::Guilds => "guilds",
+            OAuth2Scopes::GuildsJoin => "guilds.join",
+            OAuth2Scopes::GdmJoin => "gdm.join",
+            OAuth2Scopes::Bot => "bot",
+            OAuth2Scopes::Connections => "connections",
+            OAuth2Scopes::ApplicationsCommands => "applications.commands",
+            OAuth2Scopes::ApplicationsCommandsUpdate => "applications.commands.update",
+        }
+        .to_string()
+    }
+}
+
+/// Parses a space separated scope list (as sent by clients), returns None if any scope is unknown
+pub fn parse_scopes(scopes: &str) -> Option<Vec<OAuth2Scopes>> {
+    let mut output = vec![];
+
+    for i in scopes.split_whitespace() {
+        let scope = OAuth2Scopes::from_str(i).ok()?;
+
+        if !output.contains(&scope) {
+            output.push(scope);
+        }
+    }
+
+    Some(output)
+}
+
+/// Converts scopes stored in the database back into their enum form, ignoring anything unknown
+pub fn get_scopes(scopes: &[String]) -> Vec<OAuth2Scopes> {
+    scopes
+        .iter()
+        .filter_map(|x| OAuth2Scopes::from_str(x).ok())
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[repr(i64)]
+pub enum ApplicationFlags {
+    GatewayPresence = 1 << 12,
+    GatewayPresenceLimited = 1 << 13,
+    GatewayGuildMembers = 1 << 14,
+    GatewayGuildMembersLimited = 1 << 15,
+    Embedded = 1 << 17,
+    GatewayMessageContent = 1 << 18,
+    GatewayMessageContentLimited = 1 << 19,
+    ApplicationCommandBadge = 1 << 23,
+}
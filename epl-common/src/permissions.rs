@@ -5,6 +5,8 @@ use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
 use crate::channels::ChannelTypes;
 use crate::database::entities::{channel, channel_member, message, user};
 use crate::database::entities::prelude::*;
+use crate::moderation::is_limited;
+use crate::privacy::can_dm;
 use crate::relationship::get_relationship;
 use crate::RelationshipType;
 
@@ -24,6 +26,8 @@ pub enum InternalChannelPermissions {
     SendVoiceMessage,
     EditMessage,
     DeleteMessage,
+    /// Removing other people's reactions, granted by guild permissions
+    ManageMessages,
     PinMessage,
     EmbedLinks,
     AttachFiles,
@@ -52,6 +56,8 @@ pub enum InternalChannelPermissions {
     EditNSFW,
     EditRateLimit,
     EditPermissionOverwrites,
+    /// Changing the voice region and video quality
+    EditVoiceSettings,
 
     // Member management permissions
     AddMembers,
@@ -83,6 +89,16 @@ static DM_DEFAULTS: Lazy<HashSet<InternalChannelPermissions>> = Lazy::new(|| {
     permissions.insert(InternalChannelPermissions::UseApplicationCommands);
     permissions.insert(InternalChannelPermissions::UseClyde);
 
+    // DM permissions
+    permissions.insert(InternalChannelPermissions::StartCall);
+
+    // Call permissions
+    permissions.insert(InternalChannelPermissions::JoinCall);
+    permissions.insert(InternalChannelPermissions::UseVoiceActivity);
+
+    // Channel management permissions
+    permissions.insert(InternalChannelPermissions::EditVoiceSettings);
+
     permissions
 });
 
@@ -93,9 +109,6 @@ static GROUP_DM_ADDITIONS: Lazy<HashSet<InternalChannelPermissions>> = Lazy::new
     permissions.insert(InternalChannelPermissions::EditIcon);
     permissions.insert(InternalChannelPermissions::CreateInvite);
 
-    // DM permissions
-    permissions.insert(InternalChannelPermissions::StartCall);
-
     // Channel management permissions
     permissions.insert(InternalChannelPermissions::AddMembers);
     permissions.insert(InternalChannelPermissions::EditName);
@@ -104,6 +117,25 @@ static GROUP_DM_ADDITIONS: Lazy<HashSet<InternalChannelPermissions>> = Lazy::new
     permissions
 });
 
+/// Taken away from accounts with an active moderation limit
+static LIMITED_REMOVALS: Lazy<HashSet<InternalChannelPermissions>> = Lazy::new(|| {
+    let mut permissions = HashSet::new();
+
+    permissions.insert(InternalChannelPermissions::CreateInvite);
+    permissions.insert(InternalChannelPermissions::SendMessage);
+    permissions.insert(InternalChannelPermissions::SendTTSMessage);
+    permissions.insert(InternalChannelPermissions::SendVoiceMessage);
+    permissions.insert(InternalChannelPermissions::EmbedLinks);
+    permissions.insert(InternalChannelPermissions::AttachFiles);
+    permissions.insert(InternalChannelPermissions::AddReactions);
+    permissions.insert(InternalChannelPermissions::MentionEveryone);
+    permissions.insert(InternalChannelPermissions::StartCall);
+    permissions.insert(InternalChannelPermissions::JoinCall);
+    permissions.insert(InternalChannelPermissions::AddMembers);
+
+    permissions
+});
+
 pub async fn internal_permission_calculator(
     channel: &channel::Model,
     user: &user::Model,
@@ -140,21 +172,30 @@ pub async fn internal_permission_calculator(
                 .await
                 .expect("Failed to get channel member");
 
-            let relationship = match channel_member {
-                Some(channel_member) => {
-                    get_relationship(user.id, channel_member.user, conn).await
+            let other_user = channel_member.map(|x| x.user);
+
+            let relationship = match other_user {
+                Some(other_user) => {
+                    get_relationship(user.id, other_user, conn).await
                 }
                 None => None
             };
 
             match relationship {
                 None => {
-                    // TODO: Implement privacy settings
-                    // We'll act like they're blocked for now
-                    permissions.remove(&InternalChannelPermissions::SendMessage);
-                    permissions.remove(&InternalChannelPermissions::AddReactions);
-                    permissions.remove(&InternalChannelPermissions::PinMessage);
-                    permissions.remove(&InternalChannelPermissions::StartCall);
+                    // Strangers only get to talk if the other user's privacy settings allow it
+                    let allowed = match other_user {
+                        Some(other_user) => can_dm(conn, user.id, other_user).await,
+                        None => false,
+                    };
+
+                    if !allowed {
+                        permissions.remove(&InternalChannelPermissions::SendMessage);
+                        permissions.remove(&InternalChannelPermissions::AddReactions);
+                        permissions.remove(&InternalChannelPermissions::PinMessage);
+                        permissions.remove(&InternalChannelPermissions::StartCall);
+                        permissions.remove(&InternalChannelPermissions::JoinCall);
+                    }
                 }
                 Some(relationship) => {
                     // Check if the relationship is blocked
@@ -164,6 +205,7 @@ pub async fn internal_permission_calculator(
                         permissions.remove(&InternalChannelPermissions::AddReactions);
                         permissions.remove(&InternalChannelPermissions::PinMessage);
                         permissions.remove(&InternalChannelPermissions::StartCall);
+                        permissions.remove(&InternalChannelPermissions::JoinCall);
                     }
                 }
             }
@@ -179,6 +221,7 @@ pub async fn internal_permission_calculator(
                     // User is the owner of the group dm
                     permissions.insert(InternalChannelPermissions::KickMembers);
                     permissions.insert(InternalChannelPermissions::DeleteChannel);
+                    permissions.insert(InternalChannelPermissions::ManageMessages);
                 }
             }
         }
@@ -187,6 +230,13 @@ pub async fn internal_permission_calculator(
         }
     }
 
+    // Limited accounts can still read and clean up after themselves, but can't reach out to anyone
+    if is_limited(conn, user.id).await {
+        for permission in LIMITED_REMOVALS.iter() {
+            permissions.remove(permission);
+        }
+    }
+
     // Run permission check for self created messages (overrides all other permissions)
     if let Some(message) = message {
         if message.author.eq(&Some(user.id)) {
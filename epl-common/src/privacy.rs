@@ -0,0 +1,81 @@
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use crate::database::entities::prelude::UserSetting;
+use crate::database::entities::user_setting;
+use crate::relationship::get_mutual_friends;
+
+/// Who can message someone in a DM without being their friend
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DMPrivacy {
+    FriendsOnly,
+    /// The default, "Allow direct messages from server members" in the client
+    MutualServers,
+    Everyone,
+}
+
+/// Who can send someone a friend request, from the settings proto
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(i32)]
+pub enum FriendSourceFlags {
+    MutualFriends = 1 << 1,
+    MutualGuilds = 1 << 2,
+    /// Anyone at all
+    NoRelation = 1 << 3,
+}
+
+async fn get_settings(conn: &DatabaseConnection, user_id: i64) -> Option<user_setting::Model> {
+    UserSetting::find()
+        .filter(user_setting::Column::User.eq(user_id))
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+/// Until guilds exist there's no per server setting, so turning off DMs from server members for new servers means friends only
+pub fn get_dm_privacy(settings: &user_setting::Model) -> DMPrivacy {
+    if settings.privacy_allow_dms_from_everyone {
+        DMPrivacy::Everyone
+    } else if settings.privacy_default_guilds_restricted {
+        DMPrivacy::FriendsOnly
+    } else {
+        DMPrivacy::MutualServers
+    }
+}
+
+/// Epl doesn't have guilds yet, so until it does everyone counts as sharing one rather than nobody being reachable
+pub async fn share_guild(_conn: &DatabaseConnection, _user_a: i64, _user_b: i64) -> bool {
+    true
+}
+
+/// Whether someone who isn't friends with the recipient can message them
+pub async fn can_dm(conn: &DatabaseConnection, sender_id: i64, recipient_id: i64) -> bool {
+    let dm_privacy = get_settings(conn, recipient_id)
+        .await
+        .map(|x| get_dm_privacy(&x))
+        .unwrap_or(DMPrivacy::MutualServers);
+
+    match dm_privacy {
+        DMPrivacy::FriendsOnly => false,
+        DMPrivacy::MutualServers => share_guild(conn, sender_id, recipient_id).await,
+        DMPrivacy::Everyone => true,
+    }
+}
+
+/// Whether the recipient accepts friend requests from the sender
+pub async fn can_send_friend_request(conn: &DatabaseConnection, sender_id: i64, recipient_id: i64) -> bool {
+    let Some(settings) = get_settings(conn, recipient_id).await else {
+        return false;
+    };
+
+    let flags = settings.privacy_friend_source_flags;
+    let allows = |flag: FriendSourceFlags| flags & flag as i32 != 0;
+
+    if allows(FriendSourceFlags::NoRelation) {
+        return true;
+    }
+
+    if allows(FriendSourceFlags::MutualFriends) && !get_mutual_friends(sender_id, recipient_id, conn).await.is_empty() {
+        return true;
+    }
+
+    allows(FriendSourceFlags::MutualGuilds) && share_guild(conn, sender_id, recipient_id).await
+}
@@ -194,6 +194,9 @@ pub fn generate_user_proto(proto: ProtoType) -> String {
                     activity_restricted_guild_ids: settings.privacy_activity_restricted_guild_ids.unwrap_or_default().iter().map(|x| *x as u64).collect(),
                     default_guilds_activity_restricted: settings.privacy_guild_activity_status_restriction_default,
                     activity_joining_restricted_guild_ids: settings.privacy_activity_joining_restricted_guild_ids.unwrap_or_default().iter().map(|x| *x as u64).collect(),
+                    allow_dms_from_everyone: Some(v1::AllowDmsFromEveryone {
+                        value: settings.privacy_allow_dms_from_everyone,
+                    }),
                 }),
                 debug: Some(v1::DebugSettings {
                     rtc_panel_show_voice_states: Some(v1::RtcPanelShowVoiceStates {
@@ -0,0 +1,85 @@
+use std::str::FromStr;
+use async_nats::Client;
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, ModelTrait};
+use sea_orm::ActiveValue::Set;
+use crate::database::entities::prelude::RegistrationRequest;
+use crate::database::entities::{registration_request, user};
+use crate::mail::{instance_link, Mail, queue_mail};
+
+/// Who can make an account on the instance, set with `REGISTRATION`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RegistrationMode {
+    /// Anyone can register
+    Open,
+    /// Registering needs an instance invite code
+    InviteOnly,
+    /// Anyone can register, but staff have to approve the account before it can be used. An invite code skips the queue
+    Approval,
+    /// Nobody can register, not even with an invite
+    Closed,
+}
+
+impl RegistrationMode {
+    /// Whether people can sign up without being invited, for nodeinfo
+    pub fn is_open(&self) -> bool {
+        matches!(self, RegistrationMode::Open | RegistrationMode::Approval)
+    }
+}
+
+impl FromStr for RegistrationMode {
+    type Err = String;
+
+    /// `true` and `false` are still accepted from when registration could only be turned on or off
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "open" | "true" => Ok(RegistrationMode::Open),
+            "invite" | "invite_only" => Ok(RegistrationMode::InviteOnly),
+            "approval" => Ok(RegistrationMode::Approval),
+            "closed" | "false" => Ok(RegistrationMode::Closed),
+            _ => Err(format!("Unknown registration mode `{}`", s)),
+        }
+    }
+}
+
+/// Puts a new account in the approval queue, it can't log in until it's approved
+pub async fn request_approval(conn: &DatabaseConnection, user_id: i64) {
+    registration_request::ActiveModel {
+        user_id: Set(user_id),
+        created_at: Set(Utc::now().naive_utc()),
+    }
+        .insert(conn)
+        .await
+        .expect("Failed to access database!");
+}
+
+pub async fn is_pending_approval(conn: &DatabaseConnection, user_id: i64) -> bool {
+    RegistrationRequest::find_by_id(user_id)
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+        .is_some()
+}
+
+/// Lets the account log in and emails the user to tell them, false if it wasn't waiting for approval
+pub async fn approve_registration(conn: &DatabaseConnection, nats_client: &Client, user: &user::Model) -> bool {
+    let Some(request) = RegistrationRequest::find_by_id(user.id)
+        .one(conn)
+        .await
+        .expect("Failed to access database!") else {
+        return false;
+    };
+
+    request.delete(conn).await.expect("Failed to access database!");
+
+    queue_mail(
+        nats_client,
+        user.email.clone(),
+        Mail::RegistrationApproved {
+            username: user.username.clone(),
+            url: instance_link("/login"),
+        },
+    ).await;
+
+    true
+}
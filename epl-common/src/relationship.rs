@@ -2,6 +2,7 @@ use sea_orm::{Condition, DatabaseConnection, EntityTrait};
 use crate::database::entities::prelude::Relationship;
 use crate::database::entities::relationship;
 use sea_orm::prelude::*;
+use crate::RelationshipType;
 
 
 pub async fn get_relationship(
@@ -23,4 +24,32 @@ pub async fn get_relationship(
         .one(conn)
         .await
         .expect("Failed to access database!")
-}
\ No newline at end of file
+}
+
+/// IDs of everyone the user is friends with
+pub async fn get_friends(user_id: i64, conn: &DatabaseConnection) -> Vec<i64> {
+    Relationship::find()
+        .filter(
+            Condition::any()
+                .add(relationship::Column::Creator.eq(user_id))
+                .add(relationship::Column::Peer.eq(user_id)),
+        )
+        .filter(relationship::Column::RelationshipType.eq(RelationshipType::Friend as i32))
+        .all(conn)
+        .await
+        .expect("Failed to access database!")
+        .iter()
+        .map(|x| if x.creator == user_id { x.peer } else { x.creator })
+        .collect()
+}
+
+/// IDs of the friends two users have in common
+pub async fn get_mutual_friends(user_a: i64, user_b: i64, conn: &DatabaseConnection) -> Vec<i64> {
+    let friends_b = get_friends(user_b, conn).await;
+
+    get_friends(user_a, conn)
+        .await
+        .into_iter()
+        .filter(|x| friends_b.contains(x))
+        .collect()
+}
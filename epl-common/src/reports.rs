@@ -0,0 +1,336 @@
+use chrono::{NaiveDateTime, Utc};
+use num_derive::FromPrimitive;
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, QuerySelect};
+use sea_orm::ActiveValue::Set;
+use serde_json::json;
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use tracing::warn;
+use crate::database::entities::prelude::{ChannelMember, File, Message, MessageAttachment, Report, User};
+use crate::database::entities::{channel, channel_member, message, message_attachment, report, user};
+
+/// How many of the latest messages are kept with a group DM report
+const GROUP_DM_SNAPSHOT_MESSAGES: u64 = 50;
+
+#[derive(Serialize_repr, Deserialize_repr, Debug, Copy, Clone, PartialEq, FromPrimitive)]
+#[repr(i32)]
+pub enum ReportType {
+    Message = 1,
+    User = 2,
+    GroupDM = 3,
+}
+
+impl ReportType {
+    /// Menu names used by the client's reporting API
+    pub fn from_name(name: &str) -> Option<ReportType> {
+        match name {
+            "message" => Some(ReportType::Message),
+            "user" => Some(ReportType::User),
+            "group_dm" => Some(ReportType::GroupDM),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReportType::Message => "message",
+            ReportType::User => "user",
+            ReportType::GroupDM => "group_dm",
+        }
+    }
+}
+
+/// Reasons are the IDs of the nodes in the report menu, the client sends back the ones picked as breadcrumbs
+#[derive(Serialize_repr, Deserialize_repr, Debug, Copy, Clone, PartialEq, FromPrimitive)]
+#[repr(i32)]
+pub enum ReportReason {
+    Spam = 2,
+    Harassment = 3,
+    HateSpeech = 4,
+    ViolentContent = 5,
+    SelfHarm = 6,
+    ChildSafety = 7,
+    Impersonation = 8,
+    Other = 9,
+}
+
+impl ReportReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReportReason::Spam => "Spam",
+            ReportReason::Harassment => "Abuse or harassment",
+            ReportReason::HateSpeech => "Hateful conduct",
+            ReportReason::ViolentContent => "Violent or graphic content",
+            ReportReason::SelfHarm => "Self-harm or suicide",
+            ReportReason::ChildSafety => "Child safety",
+            ReportReason::Impersonation => "Impersonation",
+            ReportReason::Other => "Something else",
+        }
+    }
+}
+
+const ALL_REASONS: [ReportReason; 8] = [
+    ReportReason::Spam,
+    ReportReason::Harassment,
+    ReportReason::HateSpeech,
+    ReportReason::ViolentContent,
+    ReportReason::SelfHarm,
+    ReportReason::ChildSafety,
+    ReportReason::Impersonation,
+    ReportReason::Other,
+];
+
+const ROOT_NODE: i32 = 1;
+const SUCCESS_NODE: i32 = 100;
+
+#[derive(Serialize_repr, Deserialize_repr, Debug, Copy, Clone, PartialEq, FromPrimitive)]
+#[repr(i32)]
+pub enum ReportStatus {
+    Open = 0,
+    /// Looked at and acted on, possibly linked to a moderation action
+    Resolved = 1,
+    /// Looked at and nothing needed doing
+    Dismissed = 2,
+}
+
+/// The menu the client walks through when reporting, every reason leads straight to submitting
+pub fn report_menu(report_type: ReportType) -> serde_json::Value {
+    let mut nodes = serde_json::Map::new();
+
+    nodes.insert(ROOT_NODE.to_string(), json!({
+        "id": ROOT_NODE,
+        "key": "root",
+        "header": "What's going on?",
+        "subheader": "Pick the option that fits best, staff of this instance will take a look.",
+        "info": null,
+        "button": null,
+        "elements": [],
+        "report_type": report_type.name(),
+        "children": ALL_REASONS.iter().map(|x| json!([x.label(), *x as i32])).collect::<Vec<serde_json::Value>>(),
+        "is_multi_select_required": false,
+        "is_auto_submit": false,
+    }));
+
+    for reason in ALL_REASONS {
+        nodes.insert((reason as i32).to_string(), json!({
+            "id": reason as i32,
+            "key": format!("{:?}", reason).to_lowercase(),
+            "header": reason.label(),
+            "subheader": null,
+            "info": null,
+            "button": { "type": "submit", "target": SUCCESS_NODE },
+            "elements": [],
+            "report_type": report_type.name(),
+            "children": [],
+            "is_multi_select_required": false,
+            "is_auto_submit": true,
+        }));
+    }
+
+    nodes.insert(SUCCESS_NODE.to_string(), json!({
+        "id": SUCCESS_NODE,
+        "key": "success",
+        "header": "Thanks for the report",
+        "subheader": "Staff of this instance will look into it.",
+        "info": null,
+        "button": { "type": "done", "target": null },
+        "elements": [],
+        "report_type": report_type.name(),
+        "children": [],
+        "is_multi_select_required": false,
+        "is_auto_submit": false,
+    }));
+
+    json!({
+        "name": report_type.name(),
+        "variant": "1",
+        "version": "1",
+        "language": "en",
+        "root_node_id": ROOT_NODE,
+        "success_node_id": SUCCESS_NODE,
+        "fail_node_id": null,
+        "nodes": nodes,
+    })
+}
+
+/// The reason is the last picked node that's a reason, older clients send a single reason code
+pub fn reason_from_breadcrumbs(breadcrumbs: &[i32]) -> Option<ReportReason> {
+    breadcrumbs
+        .iter()
+        .rev()
+        .find_map(|x| ALL_REASONS.iter().find(|y| **y as i32 == *x).copied())
+}
+
+/// Where a copy of a reported attachment is kept, so it outlives the message
+pub fn evidence_key(report_id: i64, file_id: i64) -> String {
+    format!("reports/{}/{}", report_id, file_id)
+}
+
+fn format_timestamp(timestamp: NaiveDateTime) -> String {
+    timestamp.and_utc().format("%Y-%m-%dT%H:%M:%S%z").to_string()
+}
+
+pub fn snapshot_user(user: &user::Model) -> serde_json::Value {
+    json!({
+        "id": user.id.to_string(),
+        "username": user.username,
+        "discriminator": user.discriminator,
+        "global_name": user.display_name,
+        "avatar": user.avatar,
+        "bio": user.bio,
+        "pronouns": user.pronouns,
+        "bot": user.bot,
+    })
+}
+
+/// Keeps the message, its author and copies of its attachments
+pub async fn snapshot_message(
+    conn: &DatabaseConnection,
+    aws: &aws_sdk_s3::Client,
+    bucket: &str,
+    report_id: i64,
+    message: &message::Model,
+) -> serde_json::Value {
+    let author = match message.author {
+        Some(author) => User::find_by_id(author)
+            .one(conn)
+            .await
+            .expect("Failed to access database!"),
+        None => None,
+    };
+
+    let files = MessageAttachment::find()
+        .filter(message_attachment::Column::Message.eq(message.id))
+        .find_also_related(File)
+        .all(conn)
+        .await
+        .expect("Failed to access database!");
+
+    let mut attachments = vec![];
+
+    for (_, file) in files {
+        let Some(file) = file else {
+            continue;
+        };
+
+        let copied = aws.copy_object()
+            .copy_source(format!("{}/attachments/{}", bucket, file.id))
+            .bucket(bucket)
+            .key(evidence_key(report_id, file.id))
+            .send()
+            .await;
+
+        if let Err(err) = &copied {
+            warn!("Failed to keep attachment {} for report {}: {}", file.id, report_id, err);
+        }
+
+        attachments.push(json!({
+            "id": file.id.to_string(),
+            "filename": file.name,
+            "content_type": file.content_type,
+            "size": file.size,
+            "evidence_key": copied.ok().map(|_| evidence_key(report_id, file.id)),
+        }));
+    }
+
+    json!({
+        "id": message.id.to_string(),
+        "channel_id": message.channel_id.to_string(),
+        "author": author.as_ref().map(snapshot_user),
+        "content": message.content,
+        "timestamp": format_timestamp(message.timestamp),
+        "edited_timestamp": message.edited_timestamp.map(format_timestamp),
+        "attachments": attachments,
+    })
+}
+
+/// Keeps who's in the group DM and the latest messages sent in it
+pub async fn snapshot_group_dm(conn: &DatabaseConnection, channel: &channel::Model) -> serde_json::Value {
+    let recipients = ChannelMember::find()
+        .filter(channel_member::Column::Channel.eq(channel.id))
+        .find_also_related(User)
+        .all(conn)
+        .await
+        .expect("Failed to access database!");
+
+    let messages = Message::find()
+        .filter(message::Column::ChannelId.eq(channel.id))
+        .order_by_desc(message::Column::Id)
+        .limit(GROUP_DM_SNAPSHOT_MESSAGES)
+        .all(conn)
+        .await
+        .expect("Failed to access database!");
+
+    json!({
+        "id": channel.id.to_string(),
+        "name": channel.name,
+        "owner_id": channel.owner_id.map(|x| x.to_string()),
+        "recipients": recipients.iter().filter_map(|(_, x)| x.as_ref().map(snapshot_user)).collect::<Vec<serde_json::Value>>(),
+        "messages": messages.iter().rev().map(|x| json!({
+            "id": x.id.to_string(),
+            "author_id": x.author.map(|x| x.to_string()),
+            "content": x.content,
+            "timestamp": format_timestamp(x.timestamp),
+        })).collect::<Vec<serde_json::Value>>(),
+    })
+}
+
+/// What was reported, by who and why
+pub struct NewReport {
+    pub reporter_id: i64,
+    pub report_type: ReportType,
+    pub reason: ReportReason,
+    pub target_user_id: Option<i64>,
+    pub channel_id: Option<i64>,
+    pub message_id: Option<i64>,
+    pub snapshot: serde_json::Value,
+}
+
+/// The ID is picked by the caller since attachments are copied under it while taking the snapshot
+pub async fn create_report(conn: &DatabaseConnection, report_id: i64, new_report: NewReport) -> report::Model {
+    report::ActiveModel {
+        id: Set(report_id),
+        reporter_id: Set(Some(new_report.reporter_id)),
+        report_type: Set(new_report.report_type as i32),
+        reason: Set(new_report.reason as i32),
+        target_user_id: Set(new_report.target_user_id),
+        channel_id: Set(new_report.channel_id),
+        message_id: Set(new_report.message_id),
+        snapshot: Set(new_report.snapshot),
+        status: Set(ReportStatus::Open as i32),
+        created_at: Set(Utc::now().naive_utc()),
+        resolved_at: Set(None),
+        resolution_note: Set(None),
+        moderation_action_id: Set(None),
+    }
+        .insert(conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+/// Takes a report out of the queue, false if it doesn't exist or was already closed
+pub async fn close_report(
+    conn: &DatabaseConnection,
+    report_id: i64,
+    status: ReportStatus,
+    note: Option<String>,
+    moderation_action_id: Option<i64>,
+) -> bool {
+    let Some(report) = Report::find_by_id(report_id)
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+        .filter(|x| x.status == ReportStatus::Open as i32) else {
+        return false;
+    };
+
+    let mut active_report = report.into_active_model();
+
+    active_report.status = Set(status as i32);
+    active_report.resolved_at = Set(Some(Utc::now().naive_utc()));
+    active_report.resolution_note = Set(note);
+    active_report.moderation_action_id = Set(moderation_action_id);
+
+    active_report.update(conn).await.expect("Failed to access database!");
+
+    true
+}
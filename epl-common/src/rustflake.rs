@@ -41,6 +41,12 @@ impl Snowflake {
         (timestamp << 22) | (self.worker_id << 17) | (self.datacenter_id << 12) | self.sequence
     }
 
+    /// Generate a Snowflake for a point in the past, like when importing old messages
+    pub fn generate_at(&mut self, timestamp_millis: i64) -> i64 {
+        self.sequence = (self.sequence + 1) & (-1 ^ (-1 << 12));
+        ((timestamp_millis - self.epoch) << 22) | (self.worker_id << 17) | (self.datacenter_id << 12) | self.sequence
+    }
+
     fn get_time(&self) -> i64 {
         Utc::now().timestamp_millis() - self.epoch
     }
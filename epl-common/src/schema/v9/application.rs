@@ -0,0 +1,61 @@
+use serde_derive::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use crate::database::entities::{application, user};
+use crate::schema::v9::user::{generate_user_struct, User};
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Application {
+    pub id: String,
+    pub name: String,
+    pub icon: Option<String>,
+    pub description: String,
+    pub summary: String,
+    #[serde(rename = "type")]
+    pub _type: Option<i32>,
+    pub hook: bool,
+    pub bot_public: bool,
+    pub bot_require_code_grant: bool,
+    pub verify_key: String,
+    pub flags: i64,
+    pub bot: Option<User>,
+}
+
+/// Extra information only shown to the owner of the application
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PrivateApplication {
+    #[serde(flatten)]
+    pub application: Application,
+    pub owner: User,
+    pub redirect_uris: Vec<String>,
+    pub interactions_endpoint_url: Option<String>,
+}
+
+pub fn generate_application_struct(application: application::Model, bot: Option<user::Model>) -> Application {
+    Application {
+        id: application.id.to_string(),
+        name: application.name,
+        icon: application.icon,
+        description: application.description,
+        summary: String::new(),
+        _type: None,
+        hook: true,
+        bot_public: application.bot_public,
+        bot_require_code_grant: application.bot_require_code_grant,
+        verify_key: application.verify_key.unwrap_or_default(),
+        flags: application.flags,
+        bot: bot.map(generate_user_struct),
+    }
+}
+
+pub fn generate_private_application_struct(application: application::Model, bot: Option<user::Model>, owner: user::Model) -> PrivateApplication {
+    let redirect_uris = application.redirect_uris.clone();
+    let interactions_endpoint_url = application.interactions_endpoint_url.clone();
+
+    PrivateApplication {
+        application: generate_application_struct(application, bot),
+        owner: generate_user_struct(owner),
+        redirect_uris,
+        interactions_endpoint_url,
+    }
+}
@@ -0,0 +1,62 @@
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+use serde_with::skip_serializing_none;
+use crate::database::entities::application_command;
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ApplicationCommand {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub _type: i32,
+    pub application_id: String,
+    pub guild_id: Option<String>,
+    pub name: String,
+    pub description: String,
+    pub options: Option<Vec<ApplicationCommandOption>>,
+    pub default_member_permissions: Option<String>,
+    pub dm_permission: bool,
+    pub nsfw: bool,
+    pub version: String,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ApplicationCommandOption {
+    #[serde(rename = "type")]
+    pub _type: i32,
+    pub name: String,
+    pub description: String,
+    pub required: Option<bool>,
+    pub choices: Option<Vec<ApplicationCommandOptionChoice>>,
+    pub options: Option<Vec<ApplicationCommandOption>>,
+    pub channel_types: Option<Vec<i32>>,
+    pub min_value: Option<Number>,
+    pub max_value: Option<Number>,
+    pub min_length: Option<i32>,
+    pub max_length: Option<i32>,
+    pub autocomplete: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ApplicationCommandOptionChoice {
+    pub name: String,
+    /// Either a string or a number, depending on the option type
+    pub value: Value,
+}
+
+pub fn generate_application_command_struct(command: application_command::Model) -> ApplicationCommand {
+    ApplicationCommand {
+        id: command.id.to_string(),
+        _type: command.r#type,
+        application_id: command.application_id.to_string(),
+        guild_id: command.guild_id.map(|x| x.to_string()),
+        name: command.name,
+        description: command.description,
+        options: command.options.and_then(|x| serde_json::from_value(x).ok()),
+        default_member_permissions: command.default_member_permissions,
+        dm_permission: command.dm_permission,
+        nsfw: command.nsfw,
+        version: command.version.to_string(),
+    }
+}
@@ -0,0 +1,359 @@
+use num_traits::FromPrimitive;
+use serde_derive::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use crate::interactions::{ButtonStyle, ComponentType, TextInputStyle};
+
+/// A message component, checked for valid fields as it is deserialized
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(try_from = "RawComponent", into = "RawComponent")]
+pub enum Component {
+    ActionRow(ActionRow),
+    Button(Button),
+    /// Any of the select menu types, which share almost all of their fields
+    SelectMenu(SelectMenu),
+    /// Only valid in modals
+    TextInput(TextInput),
+}
+
+#[derive(Clone)]
+pub struct ActionRow {
+    pub components: Vec<Component>,
+}
+
+#[derive(Clone)]
+pub struct Button {
+    pub style: ButtonStyle,
+    pub label: Option<String>,
+    pub emoji: Option<PartialEmoji>,
+    /// Every style except links has a custom ID
+    pub custom_id: Option<String>,
+    /// Only links have an URL
+    pub url: Option<String>,
+    pub disabled: bool,
+}
+
+#[derive(Clone)]
+pub struct SelectMenu {
+    pub _type: ComponentType,
+    pub custom_id: String,
+    /// Only used by string selects
+    pub options: Vec<SelectOption>,
+    /// Only used by channel selects
+    pub channel_types: Option<Vec<i32>>,
+    pub placeholder: Option<String>,
+    pub default_values: Option<Vec<SelectDefaultValue>>,
+    pub min_values: i32,
+    pub max_values: i32,
+    pub disabled: bool,
+}
+
+#[derive(Clone)]
+pub struct TextInput {
+    pub custom_id: String,
+    pub style: TextInputStyle,
+    pub label: String,
+    pub min_length: Option<i32>,
+    pub max_length: Option<i32>,
+    pub required: bool,
+    pub value: Option<String>,
+    pub placeholder: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PartialEmoji {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub animated: Option<bool>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SelectOption {
+    pub label: String,
+    pub value: String,
+    pub description: Option<String>,
+    pub emoji: Option<PartialEmoji>,
+    pub default: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SelectDefaultValue {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub _type: String,
+}
+
+/// A popup form an application responds with, made of text inputs
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Modal {
+    pub custom_id: String,
+    pub title: String,
+    pub components: Vec<Component>,
+}
+
+/// The wire format of a component, every type's fields flattened together
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct RawComponent {
+    #[serde(rename = "type")]
+    pub _type: i32,
+    pub components: Option<Vec<RawComponent>>,
+    pub custom_id: Option<String>,
+    pub style: Option<i32>,
+    pub label: Option<String>,
+    pub emoji: Option<PartialEmoji>,
+    pub url: Option<String>,
+    pub disabled: Option<bool>,
+    pub options: Option<Vec<SelectOption>>,
+    pub channel_types: Option<Vec<i32>>,
+    pub placeholder: Option<String>,
+    pub default_values: Option<Vec<SelectDefaultValue>>,
+    pub min_values: Option<i32>,
+    pub max_values: Option<i32>,
+    pub min_length: Option<i32>,
+    pub max_length: Option<i32>,
+    pub required: Option<bool>,
+    pub value: Option<String>,
+}
+
+fn check_length(value: &str, min: usize, max: usize, field: &str) -> Result<(), String> {
+    let length = value.chars().count();
+
+    if length < min || length > max {
+        return Err(format!("{} must be between {} and {} characters long", field, min, max));
+    }
+
+    Ok(())
+}
+
+fn check_custom_id(custom_id: Option<String>) -> Result<String, String> {
+    let custom_id = custom_id.ok_or("Component is missing a custom_id")?;
+
+    check_length(&custom_id, 1, 100, "custom_id")?;
+
+    Ok(custom_id)
+}
+
+impl TryFrom<RawComponent> for Component {
+    type Error = String;
+
+    fn try_from(value: RawComponent) -> Result<Self, Self::Error> {
+        let component_type = ComponentType::from_i32(value._type).ok_or("Invalid component type")?;
+
+        match component_type {
+            ComponentType::ActionRow => {
+                let components = value.components.unwrap_or_default();
+
+                if components.is_empty() || components.len() > 5 {
+                    return Err("Action rows must have between 1 and 5 components".to_string());
+                }
+
+                Ok(Component::ActionRow(ActionRow {
+                    components: components.into_iter().map(Component::try_from).collect::<Result<_, _>>()?,
+                }))
+            }
+            ComponentType::Button => {
+                let style = value.style.and_then(ButtonStyle::from_i32).ok_or("Invalid button style")?;
+
+                if let Some(label) = &value.label {
+                    check_length(label, 0, 80, "label")?;
+                }
+
+                if value.label.is_none() && value.emoji.is_none() {
+                    return Err("Buttons must have a label or an emoji".to_string());
+                }
+
+                let (custom_id, url) = if style == ButtonStyle::Link {
+                    if value.custom_id.is_some() {
+                        return Err("Link buttons can't have a custom_id".to_string());
+                    }
+
+                    let url = value.url.ok_or("Link buttons must have an url")?;
+
+                    check_length(&url, 1, 512, "url")?;
+
+                    (None, Some(url))
+                } else {
+                    if value.url.is_some() {
+                        return Err("Only link buttons can have an url".to_string());
+                    }
+
+                    (Some(check_custom_id(value.custom_id)?), None)
+                };
+
+                Ok(Component::Button(Button {
+                    style,
+                    label: value.label,
+                    emoji: value.emoji,
+                    custom_id,
+                    url,
+                    disabled: value.disabled.unwrap_or(false),
+                }))
+            }
+            ComponentType::StringSelect
+            | ComponentType::UserSelect
+            | ComponentType::RoleSelect
+            | ComponentType::MentionableSelect
+            | ComponentType::ChannelSelect => {
+                let custom_id = check_custom_id(value.custom_id)?;
+                let options = value.options.unwrap_or_default();
+                let min_values = value.min_values.unwrap_or(1);
+                let max_values = value.max_values.unwrap_or(1);
+
+                if let Some(placeholder) = &value.placeholder {
+                    check_length(placeholder, 0, 150, "placeholder")?;
+                }
+
+                if !(0..=25).contains(&min_values) || !(1..=25).contains(&max_values) || min_values > max_values {
+                    return Err("Invalid min_values or max_values".to_string());
+                }
+
+                if component_type == ComponentType::StringSelect {
+                    if options.is_empty() || options.len() > 25 || max_values as usize > options.len() {
+                        return Err("String selects must have between 1 and 25 options".to_string());
+                    }
+
+                    for i in &options {
+                        check_length(&i.label, 1, 100, "label")?;
+                        check_length(&i.value, 1, 100, "value")?;
+
+                        if let Some(description) = &i.description {
+                            check_length(description, 0, 100, "description")?;
+                        }
+                    }
+                } else if !options.is_empty() {
+                    return Err("Only string selects can have options".to_string());
+                }
+
+                if value.channel_types.is_some() && component_type != ComponentType::ChannelSelect {
+                    return Err("Only channel selects can have channel_types".to_string());
+                }
+
+                Ok(Component::SelectMenu(SelectMenu {
+                    _type: component_type,
+                    custom_id,
+                    options,
+                    channel_types: value.channel_types,
+                    placeholder: value.placeholder,
+                    default_values: value.default_values,
+                    min_values,
+                    max_values,
+                    disabled: value.disabled.unwrap_or(false),
+                }))
+            }
+            ComponentType::TextInput => {
+                let custom_id = check_custom_id(value.custom_id)?;
+                let style = value.style.and_then(TextInputStyle::from_i32).ok_or("Invalid text input style")?;
+                let label = value.label.ok_or("Text inputs must have a label")?;
+
+                check_length(&label, 1, 45, "label")?;
+
+                if value.min_length.is_some_and(|x| !(0..=4000).contains(&x))
+                    || value.max_length.is_some_and(|x| !(1..=4000).contains(&x)) {
+                    return Err("Invalid min_length or max_length".to_string());
+                }
+
+                if let Some(text) = &value.value {
+                    check_length(text, 0, 4000, "value")?;
+                }
+
+                if let Some(placeholder) = &value.placeholder {
+                    check_length(placeholder, 0, 100, "placeholder")?;
+                }
+
+                Ok(Component::TextInput(TextInput {
+                    custom_id,
+                    style,
+                    label,
+                    min_length: value.min_length,
+                    max_length: value.max_length,
+                    required: value.required.unwrap_or(true),
+                    value: value.value,
+                    placeholder: value.placeholder,
+                }))
+            }
+        }
+    }
+}
+
+impl From<Component> for RawComponent {
+    fn from(value: Component) -> Self {
+        match value {
+            Component::ActionRow(row) => RawComponent {
+                _type: ComponentType::ActionRow as i32,
+                components: Some(row.components.into_iter().map(RawComponent::from).collect()),
+                ..Default::default()
+            },
+            Component::Button(button) => RawComponent {
+                _type: ComponentType::Button as i32,
+                custom_id: button.custom_id,
+                style: Some(button.style as i32),
+                label: button.label,
+                emoji: button.emoji,
+                url: button.url,
+                disabled: Some(button.disabled),
+                ..Default::default()
+            },
+            Component::SelectMenu(select) => RawComponent {
+                _type: select._type as i32,
+                custom_id: Some(select.custom_id),
+                options: if select._type == ComponentType::StringSelect { Some(select.options) } else { None },
+                channel_types: select.channel_types,
+                placeholder: select.placeholder,
+                default_values: select.default_values,
+                min_values: Some(select.min_values),
+                max_values: Some(select.max_values),
+                disabled: Some(select.disabled),
+                ..Default::default()
+            },
+            Component::TextInput(text_input) => RawComponent {
+                _type: ComponentType::TextInput as i32,
+                custom_id: Some(text_input.custom_id),
+                style: Some(text_input.style as i32),
+                label: Some(text_input.label),
+                min_length: text_input.min_length,
+                max_length: text_input.max_length,
+                required: Some(text_input.required),
+                value: text_input.value,
+                placeholder: text_input.placeholder,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl Component {
+    pub fn component_type(&self) -> ComponentType {
+        match self {
+            Component::ActionRow(_) => ComponentType::ActionRow,
+            Component::Button(_) => ComponentType::Button,
+            Component::SelectMenu(select) => select._type,
+            Component::TextInput(_) => ComponentType::TextInput,
+        }
+    }
+
+    pub fn custom_id(&self) -> Option<&str> {
+        match self {
+            Component::ActionRow(_) => None,
+            Component::Button(button) => button.custom_id.as_deref(),
+            Component::SelectMenu(select) => Some(&select.custom_id),
+            Component::TextInput(text_input) => Some(&text_input.custom_id),
+        }
+    }
+}
+
+/// Finds a component by its custom ID, looking inside action rows
+pub fn find_component<'a>(components: &'a [Component], custom_id: &str) -> Option<&'a Component> {
+    for i in components {
+        if let Component::ActionRow(row) = i {
+            if let Some(component) = find_component(&row.components, custom_id) {
+                return Some(component);
+            }
+        } else if i.custom_id() == Some(custom_id) {
+            return Some(i);
+        }
+    }
+
+    None
+}
@@ -0,0 +1,32 @@
+use serde_derive::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use crate::database::entities::{emoji, user};
+use crate::schema::v9::user::{generate_user_struct, User};
+
+/// A custom emoji uploaded to a guild
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GuildEmoji {
+    pub id: String,
+    pub name: String,
+    pub roles: Vec<String>,
+    /// Only shown to users who can manage the guild's emojis
+    pub user: Option<User>,
+    pub require_colons: bool,
+    pub managed: bool,
+    pub animated: bool,
+    pub available: bool,
+}
+
+pub fn generate_emoji_struct(emoji: emoji::Model, creator: Option<user::Model>) -> GuildEmoji {
+    GuildEmoji {
+        id: emoji.id.to_string(),
+        name: emoji.name,
+        roles: emoji.roles.and_then(|x| serde_json::from_value(x).ok()).unwrap_or_default(),
+        user: creator.map(generate_user_struct),
+        require_colons: true,
+        managed: false,
+        animated: emoji.animated,
+        available: emoji.available,
+    }
+}
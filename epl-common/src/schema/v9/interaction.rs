@@ -0,0 +1,95 @@
+use sea_orm::{DatabaseConnection, EntityTrait};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::skip_serializing_none;
+use crate::database::entities::{interaction, user};
+use crate::database::entities::prelude::{Message as MessageEntity, User as UserEntity};
+use crate::schema::v9::message::{generate_full_message_struct, Message};
+use crate::schema::v9::user::{generate_user_struct, User};
+
+/// The payload an application receives when an interaction is created
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Interaction {
+    pub id: String,
+    pub application_id: String,
+    #[serde(rename = "type")]
+    pub _type: i32,
+    pub data: Option<Value>,
+    pub channel_id: String,
+    pub user: User,
+    /// The message the component was on, for component interactions
+    pub message: Option<Message>,
+    pub token: String,
+    pub version: i32,
+    pub app_permissions: String,
+    pub locale: String,
+}
+
+/// Attached to messages sent in response to an interaction
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MessageInteraction {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub _type: i32,
+    pub name: String,
+    pub user: User,
+}
+
+pub fn generate_interaction_struct(interaction: interaction::Model, user: user::Model, message: Option<Message>) -> Interaction {
+    Interaction {
+        id: interaction.id.to_string(),
+        application_id: interaction.application_id.to_string(),
+        _type: interaction.r#type,
+        data: interaction.data,
+        channel_id: interaction.channel_id.to_string(),
+        user: generate_user_struct(user),
+        message,
+        token: interaction.token,
+        version: 1,
+        // TODO: Calculate this once guilds exist
+        app_permissions: String::from("0"),
+        // TODO: Use the user's settings
+        locale: String::from("en-US"),
+    }
+}
+
+/// Fetches the user and message an interaction references and generates the payload sent to the application
+pub async fn generate_full_interaction_struct(conn: &DatabaseConnection, interaction: interaction::Model) -> Interaction {
+    let user = UserEntity::find_by_id(interaction.user_id)
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+        .expect("Interaction references non-existent user!");
+
+    // Only component interactions have a message before they are responded to
+    let message = match interaction.message_id {
+        None => None,
+        Some(message_id) => MessageEntity::find_by_id(message_id)
+            .one(conn)
+            .await
+            .expect("Failed to access database!"),
+    };
+
+    let message = match message {
+        None => None,
+        Some(message) => Some(generate_full_message_struct(conn, message, &interaction.user_id).await),
+    };
+
+    generate_interaction_struct(interaction, user, message)
+}
+
+pub fn generate_message_interaction_struct(interaction: &interaction::Model, user: user::Model) -> MessageInteraction {
+    MessageInteraction {
+        id: interaction.id.to_string(),
+        _type: interaction.r#type,
+        name: interaction
+            .data
+            .as_ref()
+            .and_then(|x| x.get("name"))
+            .and_then(|x| x.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        user: generate_user_struct(user),
+    }
+}
@@ -1,25 +1,30 @@
-use std::collections::HashSet;
 use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, ModelTrait, QueryFilter};
 use serde_derive::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use serde_json::Value;
-use crate::database::entities::{embed, file, message, reaction, user};
-use crate::database::entities::prelude::Reaction as ReactionEntity;
+use crate::database::entities::{embed, file, mention, message, pin, reaction, user};
+use crate::database::entities::prelude::{Embed, File, Mention, Reaction as ReactionEntity, User as UserEntity};
+use crate::emojis::{get_burst_colors, ReactionEmoji, ReactionType};
 use crate::options::{EplOptions, Options};
+use crate::schema::v9::component::Component;
 use crate::schema::v9::user::{generate_user_struct, User};
 use crate::Stub;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Message {
+    pub application_id: Option<String>,
     pub attachments: Vec<Attachment>,
     pub author: Option<User>,
+    /// Only on call messages
+    pub call: Option<Value>,
     pub channel_id: String,
-    pub components: Vec<Stub>,
+    pub components: Vec<Component>,
     pub content: String,
     pub edited_timestamp: Option<String>,
     pub embeds: Vec<Value>,
     pub flags: i32,
     pub id: String,
+    pub interaction: Option<Value>,
     pub mention_everyone: bool,
     pub mention_roles: Option<Stub>,
     pub mentions: Option<Vec<User>>,
@@ -74,10 +79,29 @@ pub struct CountDetails {
     normal: i64,
 }
 
+#[skip_serializing_none]
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Emoji {
     pub id: Option<String>,
     pub name: String,
+    pub animated: Option<bool>,
+}
+
+impl From<ReactionEmoji> for Emoji {
+    fn from(value: ReactionEmoji) -> Self {
+        match value {
+            ReactionEmoji::Unicode(name) => Emoji {
+                id: None,
+                name,
+                animated: None,
+            },
+            ReactionEmoji::Custom { id, name, animated } => Emoji {
+                id: Some(id.to_string()),
+                name,
+                animated: Some(animated),
+            },
+        }
+    }
 }
 
 pub fn generate_message_struct(
@@ -93,6 +117,7 @@ pub fn generate_message_struct(
     let options = EplOptions::get();
 
     Message {
+        application_id: message.application_id.map(|x| x.to_string()),
         attachments: attachments.iter().map(|x| {
             let attachment_url = format!("{}://{}/attachments/{}/{}/{}",
                                          if options.require_ssl { "https" } else { "http" },
@@ -117,13 +142,15 @@ pub fn generate_message_struct(
             }
         }).collect(),
         author: author.map(generate_user_struct),
+        call: message.call,
         channel_id: message.channel_id.to_string(),
-        components: vec![],
+        components: message.components.and_then(|x| serde_json::from_value(x).ok()).unwrap_or_default(),
         content: message.content,
         edited_timestamp: message.edited_timestamp.map(|e| e.and_utc().format("%Y-%m-%dT%H:%M:%S%z").to_string()),
         embeds: embeds.iter().map(|x| x.content.clone()).collect(),
         flags: message.flags.unwrap_or(0),
         id: message.id.to_string(),
+        interaction: message.interaction,
         mention_everyone: message.mention_everyone,
         mention_roles: None,
         mentions: Some(mentions.into_iter().map(generate_user_struct).collect()),
@@ -183,49 +210,37 @@ pub async fn generate_reactions(
     message: &message::Model,
     current_user: &i64,
 ) -> Vec<Reaction> {
-    // TODO: guild emojis when that exists
     let mut final_reactions = vec![];
 
-    let mut visited_emojis: HashSet<String> = HashSet::new();
-
     let reactions = message.find_related(ReactionEntity)
-        .filter(reaction::Column::Burst.eq(false))
         .all(conn)
         .await
         .expect("Failed to access database!");
 
-    let burst_reactions = message.find_related(ReactionEntity)
-        .filter(reaction::Column::Burst.eq(true))
-        .all(conn)
-        .await
-        .expect("Failed to access database!");
+    // Keep the emojis in the order they were first used
+    let mut visited_emojis: Vec<String> = vec![];
 
-    for i in reactions.clone() {
-        visited_emojis.insert(i.emoji);
-    }
-
-    for i in burst_reactions.clone() {
-        visited_emojis.insert(i.emoji);
+    for i in reactions.iter() {
+        if !visited_emojis.contains(&i.emoji) {
+            visited_emojis.push(i.emoji.clone());
+        }
     }
 
     for i in visited_emojis {
-        let burst_reactions_iter: Vec<i64> = burst_reactions.iter().filter(|x| x.burst && x.emoji == i).map(|x| x.user).collect();
-        let reactions_iter: Vec<i64> = reactions.iter().filter(|x| !x.burst && x.emoji == i).map(|x| x.user).collect();
+        let burst_reactions_iter: Vec<i64> = reactions.iter().filter(|x| x.r#type == ReactionType::Burst as i32 && x.emoji == i).map(|x| x.user).collect();
+        let reactions_iter: Vec<i64> = reactions.iter().filter(|x| x.r#type == ReactionType::Normal as i32 && x.emoji == i).map(|x| x.user).collect();
+
+        let emoji = ReactionEmoji::from_key(&i);
 
         final_reactions.push(
             Reaction {
-                // TODO: figure out how to calculate these
-                burst_colors: vec![],
+                burst_colors: get_burst_colors(conn, &emoji).await,
                 burst_count: burst_reactions_iter.len() as i64,
                 burst_me: burst_reactions_iter.contains(current_user),
-                count: reactions_iter.len() as i64,
-                me:reactions_iter.contains(current_user),
+                count: (reactions_iter.len() + burst_reactions_iter.len()) as i64,
+                me: reactions_iter.contains(current_user),
                 me_burst: burst_reactions_iter.contains(current_user),
-                // TODO: guild emojis when that exists
-                emoji: Emoji {
-                    id: None,
-                    name: i
-                },
+                emoji: Emoji::from(emoji),
                 count_details: CountDetails {
                     burst: burst_reactions_iter.len() as i64,
                     normal: reactions_iter.len() as i64
@@ -236,3 +251,44 @@ pub async fn generate_reactions(
 
     final_reactions
 }
+
+/// Fetches everything a message needs from the database and generates it, as seen by `current_user`
+pub async fn generate_full_message_struct(
+    conn: &DatabaseConnection,
+    message: message::Model,
+    current_user: &i64,
+) -> Message {
+    let author = UserEntity::find_by_id(message.author.unwrap_or(0))
+        .one(conn)
+        .await
+        .expect("Failed to access database!");
+
+    let refed_message = match message.reference_message_id {
+        None => None,
+        Some(reference_message_id) => generate_refed_message(conn, reference_message_id).await,
+    };
+
+    let mentions: Vec<(mention::Model, Vec<user::Model>)> = Mention::find()
+        .filter(mention::Column::Message.eq(message.id))
+        .find_with_related(UserEntity)
+        .all(conn)
+        .await
+        .expect("Failed to access database!");
+
+    let mentioned_users = mentions.into_iter().flat_map(|x| x.1).collect();
+
+    let pinned = pin::Entity::find()
+        .filter(pin::Column::Message.eq(message.id))
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+        .is_some();
+
+    let embeds = message.find_related(Embed).all(conn).await.expect("Failed to access database!");
+
+    let attachments = message.find_related(File).all(conn).await.expect("Failed to access database!");
+
+    let reactions = generate_reactions(conn, &message, current_user).await;
+
+    generate_message_struct(message, author, refed_message, mentioned_users, pinned, embeds, attachments, reactions)
+}
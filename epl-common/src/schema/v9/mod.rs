@@ -1,2 +1,9 @@
+pub mod application;
+pub mod application_command;
+pub mod component;
+pub mod emoji;
+pub mod interaction;
 pub mod message;
-pub mod user;
\ No newline at end of file
+pub mod user;
+pub mod voice_region;
+pub mod voice_state;
\ No newline at end of file
@@ -11,6 +11,9 @@ pub struct User {
     pub id: String,
     pub public_flags: i64,
     pub username: String,
+    /// Only sent for the System user
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub system: bool,
 }
 
 pub fn generate_user_struct(user: user::Model) -> User {
@@ -22,5 +25,36 @@ pub fn generate_user_struct(user: user::Model) -> User {
         id: user.id.to_string(),
         public_flags: generate_public_flags(get_user_flags(user.flags)),
         username: user.username,
+        system: user.system,
     }
-}
\ No newline at end of file
+}
+/// The user as they see themselves, the platform of their session decides the mobile and desktop flags
+pub fn generate_private_user_struct(user: user::Model, platform: Option<String>) -> crate::User {
+    let platform = platform.unwrap_or_default();
+
+    crate::User {
+        verified: user.acct_verified,
+        username: user.username,
+        purchased_flags: user.purchased_flags.unwrap_or(0),
+        premium_type: user.premium_type.unwrap_or(0),
+        premium: (user.premium_type.unwrap_or(0) != 0),
+        phone: user.phone,
+        nsfw_allowed: user.nsfw_allowed,
+        mobile: matches!(platform.as_str(), "Discord Android" | "Discord iOS"),
+        mfa_enabled: user.mfa_enabled,
+        id: user.id.to_string(),
+        global_name: user.display_name.clone(),
+        flags: user.flags,
+        email: user.email,
+        display_name: user.display_name,
+        discriminator: user.discriminator,
+        desktop: matches!(platform.as_str(), "Discord Client"),
+        bio: user.bio.unwrap_or_default(),
+        banner_color: user.banner_colour,
+        banner: user.banner,
+        avatar_decoration: user.avatar_decoration,
+        avatar: user.avatar,
+        accent_color: user.accent_color,
+        token: None,
+    }
+}
@@ -0,0 +1,22 @@
+use serde_derive::{Deserialize, Serialize};
+use crate::database::entities::voice_region;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct VoiceRegion {
+    pub id: String,
+    pub name: String,
+    /// Closest region to the user
+    pub optimal: bool,
+    pub deprecated: bool,
+    pub custom: bool,
+}
+
+pub fn generate_voice_region_struct(voice_region: voice_region::Model, optimal: bool) -> VoiceRegion {
+    VoiceRegion {
+        id: voice_region.id,
+        name: voice_region.name,
+        optimal,
+        deprecated: voice_region.deprecated,
+        custom: voice_region.custom,
+    }
+}
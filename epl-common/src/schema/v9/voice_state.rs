@@ -0,0 +1,61 @@
+use chrono::NaiveDateTime;
+use serde_derive::{Deserialize, Serialize};
+use crate::database::entities::voice_state;
+use crate::Stub;
+
+/// A user's connection to a voice channel or call
+#[derive(Serialize, Deserialize, Clone)]
+pub struct VoiceState {
+    pub guild_id: Option<String>,
+    /// Null when the user has left
+    pub channel_id: Option<String>,
+    pub user_id: String,
+    // TODO: Implement when guilds are a thing
+    pub member: Option<Stub>,
+    pub session_id: String,
+    pub deaf: bool,
+    pub mute: bool,
+    pub self_deaf: bool,
+    pub self_mute: bool,
+    pub self_stream: bool,
+    pub self_video: bool,
+    pub suppress: bool,
+    pub request_to_speak_timestamp: Option<NaiveDateTime>,
+}
+
+pub fn generate_voice_state_struct(voice_state: voice_state::Model) -> VoiceState {
+    VoiceState {
+        guild_id: voice_state.guild_id.map(|x| x.to_string()),
+        channel_id: Some(voice_state.channel_id.to_string()),
+        user_id: voice_state.user_id.to_string(),
+        member: None,
+        session_id: voice_state.session_id,
+        deaf: voice_state.deaf,
+        mute: voice_state.mute,
+        self_deaf: voice_state.self_deaf,
+        self_mute: voice_state.self_mute,
+        self_stream: voice_state.self_stream,
+        self_video: voice_state.self_video,
+        suppress: voice_state.suppress,
+        request_to_speak_timestamp: None,
+    }
+}
+
+/// The voice state sent when a user leaves a channel
+pub fn generate_left_voice_state_struct(user_id: i64, session_id: String) -> VoiceState {
+    VoiceState {
+        guild_id: None,
+        channel_id: None,
+        user_id: user_id.to_string(),
+        member: None,
+        session_id,
+        deaf: false,
+        mute: false,
+        self_deaf: false,
+        self_mute: false,
+        self_stream: false,
+        self_video: false,
+        suppress: false,
+        request_to_speak_timestamp: None,
+    }
+}
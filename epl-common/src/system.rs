@@ -0,0 +1,223 @@
+use async_nats::Client;
+use chrono::Utc;
+use rand::distributions::{Alphanumeric, DistString};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter, TransactionTrait};
+use sea_orm::ActiveValue::Set;
+use tracing::info;
+use crate::channels::ChannelTypes;
+use crate::database::auth::{create_user, generate_password_hash};
+use crate::database::entities::prelude::{Channel, ChannelMember, User};
+use crate::database::entities::{channel, channel_member, message, user};
+use crate::flags::{get_user_flags, UserFlags};
+use crate::messages::{MessageFlags, MessageTypes};
+use crate::nats::{Messages, send_nats_message};
+use crate::rustflake::Snowflake;
+
+const SYSTEM_USERNAME: &str = "System";
+
+async fn find_system_user(conn: &DatabaseConnection) -> Option<user::Model> {
+    User::find()
+        .filter(user::Column::System.eq(true))
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+/// The account instance-wide notices come from, it's created the first time it's needed
+pub async fn get_system_user(conn: &DatabaseConnection) -> user::Model {
+    if let Some(system_user) = find_system_user(conn).await {
+        return system_user;
+    }
+
+    // Nobody knows this password, the System user is never logged into
+    let password_hash = generate_password_hash(&Alphanumeric.sample_string(&mut rand::thread_rng(), 64), vec![])
+        .expect("Failed to generate password hash!");
+
+    let txn = conn.begin().await.expect("Failed to access database!");
+
+    let created = create_user(&txn, user::ActiveModel {
+        id: Set(Snowflake::default().generate()),
+        system: Set(true),
+        bot: Set(false),
+        username: Set(SYSTEM_USERNAME.to_string()),
+        password_hash: Set(password_hash),
+        discriminator: Set("0000".to_string()),
+        email: Set("system@system.invalid".to_string()),
+        mfa_enabled: Set(false),
+        acct_verified: Set(true),
+        flags: Set(UserFlags::System as i64),
+        nsfw_allowed: Set(false),
+        ..Default::default()
+    }).await;
+
+    // Only one System user can exist, if someone else created it first that's the one to use
+    match created {
+        Ok(user_id) => {
+            txn.commit().await.expect("Failed to access database!");
+
+            info!("Created the System user ({})", user_id);
+        }
+        Err(_) => txn.rollback().await.expect("Failed to access database!"),
+    }
+
+    find_system_user(conn).await.expect("Failed to create the System user!")
+}
+
+/// Finds the DM between the System user and someone else
+async fn find_system_dm(conn: &DatabaseConnection, system_user_id: i64, user_id: i64) -> Option<channel::Model> {
+    let memberships = ChannelMember::find()
+        .filter(channel_member::Column::User.eq(user_id))
+        .find_also_related(Channel)
+        .all(conn)
+        .await
+        .expect("Failed to access database!");
+
+    for (_, channel) in memberships {
+        let Some(channel) = channel.filter(|x| x.r#type == ChannelTypes::DM as i32) else {
+            continue;
+        };
+
+        if ChannelMember::find_by_id((channel.id, system_user_id))
+            .one(conn)
+            .await
+            .expect("Failed to access database!")
+            .is_some() {
+            return Some(channel);
+        }
+    }
+
+    None
+}
+
+/// Whether the channel is someone's DM with the System user
+pub async fn is_system_dm(conn: &DatabaseConnection, channel: &channel::Model) -> bool {
+    if channel.r#type != ChannelTypes::DM as i32 {
+        return false;
+    }
+
+    let system_user = get_system_user(conn).await;
+
+    ChannelMember::find_by_id((channel.id, system_user.id))
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+        .is_some()
+}
+
+/// Sends a message from the System user, urgent ones are highlighted by the client until they're read
+pub async fn send_system_message(
+    conn: &DatabaseConnection,
+    nats_client: &Client,
+    user_id: i64,
+    content: String,
+    urgent: bool,
+) -> message::Model {
+    let system_user = get_system_user(conn).await;
+
+    let channel_id = match find_system_dm(conn, system_user.id, user_id).await {
+        Some(channel) => channel.id,
+        None => {
+            let channel_id = Snowflake::default().generate();
+
+            Channel::insert(channel::ActiveModel {
+                id: Set(channel_id),
+                r#type: Set(ChannelTypes::DM as i32),
+                owner_id: Set(None),
+                ..Default::default()
+            })
+                .exec(conn)
+                .await
+                .expect("Failed to access database!");
+
+            ChannelMember::insert_many([
+                channel_member::Model { channel: channel_id, user: system_user.id }.into_active_model(),
+                channel_member::Model { channel: channel_id, user: user_id }.into_active_model(),
+            ])
+                .exec(conn)
+                .await
+                .expect("Failed to access database!");
+
+            send_nats_message(nats_client, user_id.to_string(), Messages::ChannelCreate { id: channel_id }).await;
+
+            channel_id
+        }
+    };
+
+    let message = message::ActiveModel {
+        id: Set(Snowflake::default().generate()),
+        channel_id: Set(channel_id),
+        author: Set(Some(system_user.id)),
+        content: Set(content),
+        timestamp: Set(Utc::now().naive_utc()),
+        r#type: Set(MessageTypes::Default as i32),
+        flags: Set(Some(if urgent { MessageFlags::Urgent as i32 } else { 0 })),
+        tts: Set(false),
+        mention_everyone: Set(false),
+        pinned: Set(false),
+        ..Default::default()
+    }
+        .insert(conn)
+        .await
+        .expect("Failed to access database!");
+
+    send_nats_message(nats_client, channel_id.to_string(), Messages::MessageCreate { id: message.id }).await;
+
+    if urgent {
+        set_unread_urgent_messages(conn, nats_client, user_id, true).await;
+    }
+
+    message
+}
+
+/// Sends a message from the System user to everyone, returns how many people it was sent to
+pub async fn broadcast_system_message(
+    conn: &DatabaseConnection,
+    nats_client: &Client,
+    content: String,
+    urgent: bool,
+) -> usize {
+    let recipients: Vec<i64> = User::find()
+        .filter(user::Column::System.eq(false))
+        .filter(user::Column::Bot.eq(false))
+        .all(conn)
+        .await
+        .expect("Failed to access database!")
+        .into_iter()
+        .filter(|x| !get_user_flags(x.flags).contains(&UserFlags::Deleted))
+        .map(|x| x.id)
+        .collect();
+
+    for user_id in &recipients {
+        send_system_message(conn, nats_client, *user_id, content.clone(), urgent).await;
+    }
+
+    recipients.len()
+}
+
+/// Sets or clears `HasUnreadUrgentMessages`, letting the user's clients know if it changed
+pub async fn set_unread_urgent_messages(conn: &DatabaseConnection, nats_client: &Client, user_id: i64, unread: bool) {
+    let Some(user) = User::find_by_id(user_id)
+        .one(conn)
+        .await
+        .expect("Failed to access database!") else {
+        return;
+    };
+
+    let flags = if unread {
+        user.flags | UserFlags::HasUnreadUrgentMessages as i64
+    } else {
+        user.flags & !(UserFlags::HasUnreadUrgentMessages as i64)
+    };
+
+    if flags == user.flags {
+        return;
+    }
+
+    let mut active_user = user.into_active_model();
+
+    active_user.flags = Set(flags);
+
+    active_user.update(conn).await.expect("Failed to access database!");
+
+    send_nats_message(nats_client, user_id.to_string(), Messages::UserUpdate { user_id }).await;
+}
@@ -0,0 +1,48 @@
+use std::net::IpAddr;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder};
+use crate::database::entities::voice_region;
+use crate::database::entities::prelude::VoiceRegion;
+use crate::get_coordinates_from_ip;
+
+/// Great-circle distance between two points in kilometers
+fn distance(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat_from, long_from) = (from.0.to_radians(), from.1.to_radians());
+    let (lat_to, long_to) = (to.0.to_radians(), to.1.to_radians());
+
+    let a = ((lat_to - lat_from) / 2.0).sin().powi(2)
+        + lat_from.cos() * lat_to.cos() * ((long_to - long_from) / 2.0).sin().powi(2);
+
+    6371.0 * 2.0 * a.sqrt().asin()
+}
+
+pub async fn get_voice_region(conn: &DatabaseConnection, id: &str) -> Option<voice_region::Model> {
+    VoiceRegion::find_by_id(id)
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+/// Gets all voice regions closest first, regions without a location and deprecated regions go last
+pub async fn get_geo_ordered_regions(conn: &DatabaseConnection, ip: IpAddr) -> Vec<voice_region::Model> {
+    let mut regions = VoiceRegion::find()
+        .order_by_asc(voice_region::Column::Id)
+        .all(conn)
+        .await
+        .expect("Failed to access database!");
+
+    let location = get_coordinates_from_ip(ip);
+
+    regions.sort_by(|a, b| {
+        let distance_to = |region: &voice_region::Model| {
+            match (location, region.latitude, region.longitude) {
+                (Some(location), Some(latitude), Some(longitude)) => distance(location, (latitude, longitude)),
+                _ => f64::MAX,
+            }
+        };
+
+        a.deprecated.cmp(&b.deprecated)
+            .then(distance_to(a).total_cmp(&distance_to(b)))
+    });
+
+    regions
+}
@@ -0,0 +1,62 @@
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use epl_common::calls::{get_call, get_ringing};
+use epl_common::database::entities::voice_state;
+use epl_common::database::entities::prelude::VoiceState;
+use epl_common::schema::v9::voice_state::generate_voice_state_struct;
+use crate::AppState;
+use crate::gateway::dispatch::{assemble_dispatch, DispatchTypes, send_message};
+use crate::gateway::schema::calls::{CallCreate, CallDelete, CallUpdate};
+use crate::state::ThreadData;
+
+pub async fn dispatch_call_create(thread_data: &mut ThreadData, state: &AppState, channel_id: i64) {
+    // The call might have ended already
+    let call = match get_call(&state.conn, channel_id).await {
+        None => return,
+        Some(call) => call,
+    };
+
+    let voice_states = VoiceState::find()
+        .filter(voice_state::Column::ChannelId.eq(channel_id))
+        .all(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    send_message(
+        thread_data,
+        assemble_dispatch(DispatchTypes::CallCreate(CallCreate {
+            channel_id: call.channel_id.to_string(),
+            message_id: call.message_id.to_string(),
+            region: call.region.clone(),
+            ringing: get_ringing(&call).iter().map(|x| x.to_string()).collect(),
+            voice_states: voice_states.into_iter().map(generate_voice_state_struct).collect(),
+        })),
+    ).await;
+}
+
+pub async fn dispatch_call_update(thread_data: &mut ThreadData, state: &AppState, channel_id: i64) {
+    let call = match get_call(&state.conn, channel_id).await {
+        None => return,
+        Some(call) => call,
+    };
+
+    send_message(
+        thread_data,
+        assemble_dispatch(DispatchTypes::CallUpdate(CallUpdate {
+            channel_id: call.channel_id.to_string(),
+            message_id: call.message_id.to_string(),
+            region: call.region.clone(),
+            ringing: get_ringing(&call).iter().map(|x| x.to_string()).collect(),
+            // TODO: Implement this when guilds are a thing
+            guild_id: None,
+        })),
+    ).await;
+}
+
+pub async fn dispatch_call_delete(thread_data: &mut ThreadData, channel_id: i64) {
+    send_message(
+        thread_data,
+        assemble_dispatch(DispatchTypes::CallDelete(CallDelete {
+            channel_id: channel_id.to_string(),
+        })),
+    ).await;
+}
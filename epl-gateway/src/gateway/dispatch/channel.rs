@@ -64,6 +64,7 @@ pub async fn dispatch_channel_update(thread_data: &mut ThreadData, state: &AppSt
                 id: user.id.to_string(),
                 public_flags: generate_public_flags(get_user_flags(user.flags)),
                 username: user.username,
+                system: user.system,
             })
         }
 
@@ -95,6 +96,8 @@ pub async fn dispatch_channel_update(thread_data: &mut ThreadData, state: &AppSt
         topic: channel.topic,
         owner_id: channel.owner_id.map(|e| e.to_string()),
         recipients,
+        rtc_region: channel.rtc_region,
+        video_quality_mode: channel.video_quality_mode,
         _type: channel.r#type,
         version: None,
         is_spam: Some(false),
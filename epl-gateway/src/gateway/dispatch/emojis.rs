@@ -0,0 +1,43 @@
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde_derive::{Deserialize, Serialize};
+use epl_common::database::entities::emoji;
+use epl_common::database::entities::prelude::Emoji;
+use epl_common::schema::v9::emoji::{generate_emoji_struct, GuildEmoji};
+use crate::AppState;
+use crate::gateway::dispatch::{assemble_dispatch, DispatchTypes, send_message};
+use crate::state::ThreadData;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GuildEmojisUpdate {
+    pub guild_id: String,
+    pub emojis: Vec<GuildEmoji>,
+}
+
+pub async fn dispatch_guild_emojis_update(
+    thread_data: &mut ThreadData,
+    state: &AppState,
+    guild_id: i64,
+) {
+    // The whole list is sent every time, clients replace what they had
+    let emojis = Emoji::find()
+        .filter(emoji::Column::GuildId.eq(guild_id))
+        .order_by_asc(emoji::Column::Id)
+        .all(&state.conn)
+        .await
+        .expect("Failed to access database!")
+        .into_iter()
+        .map(|x| generate_emoji_struct(x, None))
+        .collect();
+
+    send_message(
+        thread_data,
+        assemble_dispatch(
+            DispatchTypes::GuildEmojisUpdate(
+                GuildEmojisUpdate {
+                    guild_id: guild_id.to_string(),
+                    emojis,
+                }
+            )
+        ),
+    ).await;
+}
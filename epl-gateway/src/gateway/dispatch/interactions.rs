@@ -0,0 +1,151 @@
+use sea_orm::EntityTrait;
+use serde_derive::{Deserialize, Serialize};
+use epl_common::database::entities::prelude::{Application, Interaction, User};
+use epl_common::schema::v9::component::{Component, Modal};
+use epl_common::schema::v9::interaction::generate_full_interaction_struct;
+use crate::AppState;
+use crate::gateway::dispatch::{assemble_dispatch, DispatchTypes, send_message};
+use crate::state::ThreadData;
+
+/// What the user who used an interaction is told about it, used to match it up with the nonce the client sent
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InteractionNotice {
+    pub id: String,
+    pub nonce: Option<String>,
+}
+
+/// Tells the client to show a modal the application responded with
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InteractionModal {
+    pub id: String,
+    pub nonce: Option<String>,
+    pub channel_id: String,
+    pub custom_id: String,
+    pub title: String,
+    pub components: Vec<Component>,
+    pub application: ModalApplication,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ModalApplication {
+    pub id: String,
+    pub name: String,
+    pub icon: Option<String>,
+    pub bot_id: Option<String>,
+}
+
+pub enum InteractionStatus {
+    Success,
+    Failure,
+}
+
+pub async fn dispatch_interaction_create(
+    thread_data: &mut ThreadData,
+    state: &AppState,
+    id: i64,
+) {
+    let interaction = Interaction::find_by_id(id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!")
+        .expect("Failed to get interaction requested by NATS!");
+
+    // The application gets the full interaction, the user only gets a notice
+    if thread_data.gateway_state.user_id == Some(interaction.user_id) {
+        send_message(
+            thread_data,
+            assemble_dispatch(
+                DispatchTypes::InteractionCreateNotice(
+                    InteractionNotice {
+                        id: interaction.id.to_string(),
+                        nonce: interaction.nonce,
+                    }
+                )
+            ),
+        ).await;
+    } else {
+        send_message(
+            thread_data,
+            assemble_dispatch(DispatchTypes::InteractionCreate(generate_full_interaction_struct(&state.conn, interaction).await)),
+        ).await;
+    }
+}
+
+pub async fn dispatch_interaction_status(
+    thread_data: &mut ThreadData,
+    state: &AppState,
+    id: i64,
+    status: InteractionStatus,
+) {
+    let interaction = Interaction::find_by_id(id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!")
+        .expect("Failed to get interaction requested by NATS!");
+
+    let notice = InteractionNotice {
+        id: interaction.id.to_string(),
+        nonce: interaction.nonce,
+    };
+
+    send_message(
+        thread_data,
+        assemble_dispatch(
+            match status {
+                InteractionStatus::Success => DispatchTypes::InteractionSuccess(notice),
+                InteractionStatus::Failure => DispatchTypes::InteractionFailure(notice),
+            }
+        ),
+    ).await;
+}
+
+pub async fn dispatch_interaction_modal_create(
+    thread_data: &mut ThreadData,
+    state: &AppState,
+    id: i64,
+) {
+    let interaction = Interaction::find_by_id(id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!")
+        .expect("Failed to get interaction requested by NATS!");
+
+    let modal: Modal = match interaction.modal.clone().and_then(|x| serde_json::from_value(x).ok()) {
+        None => return,
+        Some(modal) => modal,
+    };
+
+    let application = Application::find_by_id(interaction.application_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!")
+        .expect("Interaction references non-existent application!");
+
+    let bot = User::find_by_id(application.bot_user_id.unwrap_or(0))
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    send_message(
+        thread_data,
+        assemble_dispatch(
+            DispatchTypes::InteractionModalCreate(
+                InteractionModal {
+                    // Modal submits reference the interaction that opened the modal
+                    id: interaction.id.to_string(),
+                    nonce: interaction.nonce,
+                    channel_id: interaction.channel_id.to_string(),
+                    custom_id: modal.custom_id,
+                    title: modal.title,
+                    components: modal.components,
+                    application: ModalApplication {
+                        id: application.id.to_string(),
+                        name: application.name,
+                        icon: application.icon,
+                        bot_id: bot.map(|x| x.id.to_string()),
+                    },
+                }
+            )
+        ),
+    ).await;
+}
@@ -5,8 +5,11 @@ use std::mem;
 use axum_tungstenite::Message;
 
 use crate::fragmented_write::two_frame_fragmentaion;
+use crate::gateway::dispatch::emojis::GuildEmojisUpdate;
+use crate::gateway::dispatch::interactions::{InteractionModal, InteractionNotice};
 use crate::gateway::dispatch::typing::TypingStart;
 use crate::gateway::dispatch::user_note_update::UserNoteUpdate;
+use crate::gateway::schema::calls::{CallCreate, CallDelete, CallUpdate};
 use crate::gateway::schema::channels::{
     ChannelCreate, ChannelDelete, ChannelPinsAck, ChannelPinsUpdate, ChannelRecipientAdd,
     ChannelRecipientRemove,
@@ -14,9 +17,10 @@ use crate::gateway::schema::channels::{
 use crate::gateway::schema::error_codes::ErrorCode;
 use crate::gateway::schema::message::MessageDelete;
 use crate::gateway::schema::opcodes::{GatewayData, OpCodes};
-use crate::gateway::schema::reactions::{MessageReactionAdd, MessageReactionRemove};
+use crate::gateway::schema::reactions::{MessageReactionAdd, MessageReactionRemove, MessageReactionRemoveAll, MessageReactionRemoveEmoji};
 use crate::gateway::schema::ready::{Ready, ReadySupplemental};
 use crate::gateway::schema::relationships::{RelationshipAdd, RelationshipRemove};
+use crate::gateway::schema::voice_state::VoiceServerUpdate;
 use crate::gateway::schema::GatewayMessage;
 use crate::state::{CompressionType, EncodingType, ThreadData};
 use epl_common::schema::v9;
@@ -27,7 +31,10 @@ use tracing::debug;
 use tungstenite::protocol::frame::coding::{CloseCode, Data, OpCode};
 use tungstenite::protocol::frame::{CloseFrame, Frame};
 
+pub(crate) mod calls;
 pub(crate) mod channel;
+pub(crate) mod emojis;
+pub(crate) mod interactions;
 pub(crate) mod message;
 pub(crate) mod reactions;
 pub(crate) mod ready;
@@ -35,6 +42,8 @@ pub(crate) mod ready_supplemental;
 pub(crate) mod relationships;
 pub(crate) mod typing;
 pub(crate) mod user_note_update;
+pub(crate) mod user_update;
+pub(crate) mod voice;
 
 #[derive(Deserialize, Serialize, Clone)]
 #[serde(untagged)]
@@ -57,6 +66,20 @@ pub enum DispatchTypes {
     ChannelPinsAck(ChannelPinsAck),
     MessageReactionAdd(MessageReactionAdd),
     MessageReactionRemove(MessageReactionRemove),
+    MessageReactionRemoveAll(MessageReactionRemoveAll),
+    MessageReactionRemoveEmoji(MessageReactionRemoveEmoji),
+    InteractionCreate(v9::interaction::Interaction),
+    InteractionCreateNotice(InteractionNotice),
+    InteractionSuccess(InteractionNotice),
+    InteractionFailure(InteractionNotice),
+    InteractionModalCreate(InteractionModal),
+    GuildEmojisUpdate(GuildEmojisUpdate),
+    VoiceStateUpdate(v9::voice_state::VoiceState),
+    VoiceServerUpdate(VoiceServerUpdate),
+    CallCreate(CallCreate),
+    CallUpdate(CallUpdate),
+    CallDelete(CallDelete),
+    UserUpdate(Box<epl_common::User>),
 }
 
 impl From<DispatchTypes> for String {
@@ -80,6 +103,20 @@ impl From<DispatchTypes> for String {
             DispatchTypes::ChannelPinsAck(_) => String::from("CHANNEL_PINS_ACK"),
             DispatchTypes::MessageReactionAdd(_) => String::from("MESSAGE_REACTION_ADD"),
             DispatchTypes::MessageReactionRemove(_) => String::from("MESSAGE_REACTION_REMOVE"),
+            DispatchTypes::MessageReactionRemoveAll(_) => String::from("MESSAGE_REACTION_REMOVE_ALL"),
+            DispatchTypes::MessageReactionRemoveEmoji(_) => String::from("MESSAGE_REACTION_REMOVE_EMOJI"),
+            DispatchTypes::InteractionCreate(_) => String::from("INTERACTION_CREATE"),
+            DispatchTypes::InteractionCreateNotice(_) => String::from("INTERACTION_CREATE"),
+            DispatchTypes::InteractionSuccess(_) => String::from("INTERACTION_SUCCESS"),
+            DispatchTypes::InteractionFailure(_) => String::from("INTERACTION_FAILURE"),
+            DispatchTypes::InteractionModalCreate(_) => String::from("INTERACTION_MODAL_CREATE"),
+            DispatchTypes::GuildEmojisUpdate(_) => String::from("GUILD_EMOJIS_UPDATE"),
+            DispatchTypes::VoiceStateUpdate(_) => String::from("VOICE_STATE_UPDATE"),
+            DispatchTypes::VoiceServerUpdate(_) => String::from("VOICE_SERVER_UPDATE"),
+            DispatchTypes::CallCreate(_) => String::from("CALL_CREATE"),
+            DispatchTypes::CallUpdate(_) => String::from("CALL_UPDATE"),
+            DispatchTypes::CallDelete(_) => String::from("CALL_DELETE"),
+            DispatchTypes::UserUpdate(_) => String::from("USER_UPDATE"),
         }
     }
 }
@@ -1,30 +1,38 @@
-use sea_orm::{EntityTrait, ModelTrait};
-use epl_common::database::entities::{message, reaction};
-use epl_common::database::entities::prelude::{Message, Reaction};
+use sea_orm::EntityTrait;
+use epl_common::database::entities::message;
+use epl_common::database::entities::prelude::Message;
+use epl_common::emojis::{get_burst_colors, ReactionEmoji, ReactionType};
 use epl_common::schema::v9::message::Emoji;
 use crate::AppState;
 use crate::gateway::dispatch::{assemble_dispatch, DispatchTypes, send_message};
-use crate::gateway::schema::reactions::{MessageReactionAdd, MessageReactionRemove};
+use crate::gateway::schema::reactions::{MessageReactionAdd, MessageReactionRemove, MessageReactionRemoveAll, MessageReactionRemoveEmoji};
 use crate::state::ThreadData;
 
+async fn get_message(state: &AppState, message_id: i64) -> message::Model {
+    Message::find_by_id(message_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!")
+        .expect("Can't find message wanted by internal NATS!")
+}
+
 pub async fn dispatch_message_reaction_add(
     thread_data: &mut ThreadData,
     state: &AppState,
     message_id: i64,
     user_id: i64,
     emoji: String,
+    reaction_type: i32,
 ) {
-    let reaction: reaction::Model = Reaction::find_by_id((user_id, message_id, emoji))
-        .one(&state.conn)
-        .await
-        .expect("Failed to access database!")
-        .expect("Can't find reaction wanted by internal NATS!");
+    let message = get_message(state, message_id).await;
+    let emoji = ReactionEmoji::from_key(&emoji);
+    let burst = reaction_type == ReactionType::Burst as i32;
 
-    let message: message::Model = reaction.find_related(Message)
-        .one(&state.conn)
-        .await
-        .expect("Failed to access database!")
-        .expect("Can't find message wanted by internal NATS!");
+    let burst_colors = if burst {
+        Some(get_burst_colors(&state.conn, &emoji).await)
+    } else {
+        None
+    };
 
     send_message(
         thread_data,
@@ -32,22 +40,15 @@ pub async fn dispatch_message_reaction_add(
             DispatchTypes::MessageReactionAdd(
                 MessageReactionAdd {
                     user_id: user_id.to_string(),
-                    _type: if reaction.burst {
-                        1
-                    } else {
-                        0
-                    },
+                    _type: reaction_type,
                     message_id: message.id.to_string(),
                     message_author_id: message.author.map(|x| x.to_string()),
                     // TODO: Implement this when guilds are a thing
                     member: None,
-                    emoji: Emoji {
-                        // TODO: Implement this when guilds are a thing
-                        id: None,
-                        name: reaction.emoji,
-                    },
+                    emoji: Emoji::from(emoji),
                     channel_id: message.channel_id.to_string(),
-                    burst: reaction.burst,
+                    burst,
+                    burst_colors,
                     // TODO: Implement this when guilds are a thing
                     guild_id: None,
                 }
@@ -62,42 +63,72 @@ pub async fn dispatch_message_reaction_remove(
     message_id: i64,
     user_id: i64,
     emoji: String,
+    reaction_type: i32,
 ) {
-    let reaction: reaction::Model = Reaction::find_by_id((user_id, message_id, emoji))
-        .one(&state.conn)
-        .await
-        .expect("Failed to access database!")
-        .expect("Can't find reaction wanted by internal NATS!");
+    // The reaction is already gone by now, so everything comes from the NATS message
+    let message = get_message(state, message_id).await;
 
-    let message: message::Model = reaction.find_related(Message)
-        .one(&state.conn)
-        .await
-        .expect("Failed to access database!")
-        .expect("Can't find message wanted by internal NATS!");
-    
     send_message(
         thread_data,
         assemble_dispatch(
             DispatchTypes::MessageReactionRemove(
                 MessageReactionRemove {
                     user_id: user_id.to_string(),
-                    _type: if reaction.burst {
-                        1
-                    } else {
-                        0
-                    },
+                    _type: reaction_type,
+                    message_id: message.id.to_string(),
+                    emoji: Emoji::from(ReactionEmoji::from_key(&emoji)),
+                    channel_id: message.channel_id.to_string(),
+                    burst: reaction_type == ReactionType::Burst as i32,
+                    // TODO: Implement this when guilds are a thing
+                    guild_id: None,
+                }
+            )
+        ),
+    ).await;
+}
+
+pub async fn dispatch_message_reaction_remove_all(
+    thread_data: &mut ThreadData,
+    state: &AppState,
+    message_id: i64,
+) {
+    let message = get_message(state, message_id).await;
+
+    send_message(
+        thread_data,
+        assemble_dispatch(
+            DispatchTypes::MessageReactionRemoveAll(
+                MessageReactionRemoveAll {
                     message_id: message.id.to_string(),
+                    channel_id: message.channel_id.to_string(),
                     // TODO: Implement this when guilds are a thing
-                    emoji: Emoji {
-                        id: None,
-                        name: reaction.emoji,
-                    },
+                    guild_id: None,
+                }
+            )
+        ),
+    ).await;
+}
+
+pub async fn dispatch_message_reaction_remove_emoji(
+    thread_data: &mut ThreadData,
+    state: &AppState,
+    message_id: i64,
+    emoji: String,
+) {
+    let message = get_message(state, message_id).await;
+
+    send_message(
+        thread_data,
+        assemble_dispatch(
+            DispatchTypes::MessageReactionRemoveEmoji(
+                MessageReactionRemoveEmoji {
+                    message_id: message.id.to_string(),
+                    emoji: Emoji::from(ReactionEmoji::from_key(&emoji)),
                     channel_id: message.channel_id.to_string(),
-                    burst: reaction.burst,
                     // TODO: Implement this when guilds are a thing
                     guild_id: None,
                 }
             )
         ),
     ).await;
-}
\ No newline at end of file
+}
@@ -11,6 +11,7 @@ use epl_common::database::auth::{get_all_sessions, get_session_by_token};
 use epl_common::database::entities::prelude::{Channel, ChannelMember, Relationship, User, Message, UserSetting};
 use epl_common::database::entities::{channel, channel_member, message, relationship, user, user_setting};
 use epl_common::options::{EplOptions, Options};
+use epl_common::voice::get_geo_ordered_regions;
 use sea_orm::{Condition, EntityTrait, QueryOrder};
 use std::collections::HashSet;
 
@@ -19,6 +20,7 @@ use epl_common::flags::{generate_public_flags, get_user_flags};
 use epl_common::{RelationshipType, Stub};
 use sea_orm::prelude::*;
 use epl_common::protobufs::{generate_user_proto, ProtoType};
+use epl_common::schema::v9::user::generate_private_user_struct;
 
 pub async fn dispatch_ready(
     thread_data: &mut ThreadData,
@@ -46,30 +48,7 @@ pub async fn dispatch_ready(
         }
     };
 
-    let user_struct = epl_common::User {
-        verified: user.acct_verified,
-        username: user.username,
-        purchased_flags: user.purchased_flags.unwrap_or(0),
-        premium_type: user.premium_type.unwrap_or(0),
-        premium: (user.premium_type.unwrap_or(0) != 0),
-        phone: user.phone,
-        nsfw_allowed: user.nsfw_allowed,
-        mobile: matches!(current_session.platform.clone().unwrap_or_default().as_str(), "Discord Android" | "Discord iOS"),
-        mfa_enabled: user.mfa_enabled,
-        id: user.id.to_string(),
-        global_name: user.display_name.clone(),
-        flags: user.flags,
-        email: user.email,
-        display_name: user.display_name,
-        discriminator: user.discriminator,
-        desktop: matches!(current_session.platform.unwrap_or_default().as_str(), "Discord Client"),
-        bio: user.bio.unwrap_or_default(),
-        banner_color: user.banner_colour,
-        banner: user.banner,
-        avatar_decoration: user.avatar_decoration,
-        avatar: user.avatar,
-        accent_color: user.accent_color,
-    };
+    let user_struct = generate_private_user_struct(user.clone(), current_session.platform.clone());
 
     // TODO: not super important but we stub this and suppress tutorial indicators to not be annoying
     let tutorial = Tutorial {
@@ -162,7 +141,15 @@ pub async fn dispatch_ready(
         .await
         .expect("Failed to access database!");
 
+    let geo_ordered_rtc_regions: Vec<String> = get_geo_ordered_regions(&state.conn, thread_data.session_ip)
+        .await
+        .into_iter()
+        .filter(|x| !x.deprecated)
+        .map(|x| x.id)
+        .collect();
+
     let mut private_channels: Vec<PrivateChannel> = vec![];
+    let mut private_channel_ids: Vec<i64> = vec![];
 
     for i in channels_in {
         let channel: channel::Model = Channel::find_by_id(i.channel)
@@ -207,6 +194,8 @@ pub async fn dispatch_ready(
             .expect("Failed to access database!")
             .map(|e| e.id.to_string());
 
+        private_channel_ids.push(channel.id);
+
         private_channels.push(PrivateChannel {
             _type: channel.r#type,
             recipient_ids,
@@ -234,6 +223,7 @@ pub async fn dispatch_ready(
             global_name: user.display_name.clone(),
             discriminator: Some(user.discriminator),
             bot: user.bot,
+            system: user.system,
             avatar_decoration: user.avatar_decoration,
             avatar: user.avatar,
         })
@@ -276,7 +266,7 @@ pub async fn dispatch_ready(
             guilds: vec![],
             guild_join_requests: vec![],
             guild_experiments: vec![],
-            geo_ordered_rtc_regions: vec![],
+            geo_ordered_rtc_regions,
             friend_suggestion_count: 0,
             experiments,
             country_code: String::from("US"),
@@ -293,4 +283,9 @@ pub async fn dispatch_ready(
     .await;
 
     dispatch::ready_supplemental::dispatch_ready_supplemental(thread_data).await;
+
+    // Let the client know about calls that are already going on
+    for channel_id in private_channel_ids {
+        dispatch::calls::dispatch_call_create(thread_data, state, channel_id).await;
+    }
 }
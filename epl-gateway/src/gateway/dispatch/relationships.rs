@@ -38,6 +38,7 @@ pub async fn dispatch_relationship_add(
                 id: originating_user.id.clone().to_string(),
                 public_flags: generate_public_flags(get_user_flags(originating_user.flags)),
                 username: originating_user.username,
+                system: originating_user.system,
             },
         })),
     )
@@ -0,0 +1,39 @@
+use sea_orm::EntityTrait;
+use epl_common::database::entities::prelude::{Session, User};
+use epl_common::schema::v9::user::generate_private_user_struct;
+use crate::AppState;
+use crate::gateway::dispatch::{assemble_dispatch, DispatchTypes, send_message};
+use crate::state::ThreadData;
+
+pub async fn dispatch_user_update(
+    thread_data: &mut ThreadData,
+    state: &AppState,
+    user_id: i64,
+) {
+    let user = User::find_by_id(user_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    let user = match user {
+        None => return,
+        Some(user) => user,
+    };
+
+    // The mobile and desktop flags depend on what this session is running on
+    let platform = match &thread_data.gateway_state.session_id {
+        None => None,
+        Some(session_id) => Session::find_by_id(session_id.clone())
+            .one(&state.conn)
+            .await
+            .expect("Failed to access database!")
+            .and_then(|session| session.platform),
+    };
+
+    send_message(
+        thread_data,
+        assemble_dispatch(
+            DispatchTypes::UserUpdate(Box::new(generate_private_user_struct(user, platform)))
+        ),
+    ).await;
+}
@@ -0,0 +1,31 @@
+use sea_orm::EntityTrait;
+use epl_common::database::entities::prelude::VoiceState;
+use epl_common::schema::v9::voice_state::{generate_left_voice_state_struct, generate_voice_state_struct};
+use crate::AppState;
+use crate::gateway::dispatch::{assemble_dispatch, DispatchTypes, send_message};
+use crate::state::ThreadData;
+
+pub async fn dispatch_voice_state_update(
+    thread_data: &mut ThreadData,
+    state: &AppState,
+    user_id: i64,
+    channel_id: i64,
+    session_id: String,
+) {
+    // If the user isn't in the channel anymore, they left
+    let voice_state = VoiceState::find_by_id(user_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!")
+        .filter(|x| x.channel_id == channel_id);
+
+    let voice_state = match voice_state {
+        Some(voice_state) => generate_voice_state_struct(voice_state),
+        None => generate_left_voice_state_struct(user_id, session_id),
+    };
+
+    send_message(
+        thread_data,
+        assemble_dispatch(DispatchTypes::VoiceStateUpdate(voice_state)),
+    ).await;
+}
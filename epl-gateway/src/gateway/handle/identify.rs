@@ -10,7 +10,7 @@ use crate::gateway::dispatch::send_close;
 use crate::gateway::schema::error_codes::ErrorCode::AuthenticationFailed;
 use crate::state::{CompressionType, EncodingType, GatewayState, ThreadData};
 use crate::AppState;
-use epl_common::database::auth::{get_session_by_token, get_user_from_session_by_token};
+use epl_common::database::auth::{get_session_by_token, get_user_from_session_by_token, touch_session};
 use epl_common::get_location_from_ip;
 
 pub async fn handle_identify(thread_data: &mut ThreadData, data: Identify, state: &AppState) {
@@ -44,7 +44,7 @@ pub async fn handle_identify(thread_data: &mut ThreadData, data: Identify, state
     };
 
     let mut session = match get_session_by_token(&state.conn, &data.token).await {
-        Ok(session) => session,
+        Ok(session) => touch_session(&state.conn, session).await,
         Err(_) => {
             send_close(thread_data, AuthenticationFailed).await;
             return;
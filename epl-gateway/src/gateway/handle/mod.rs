@@ -1,13 +1,16 @@
 mod identify;
+pub(crate) mod voice_state;
 
 use tracing::debug;
 
 use crate::gateway::handle::identify::handle_identify;
+use crate::gateway::handle::voice_state::handle_voice_state_update;
 use crate::gateway::schema::opcodes::{get_opcode, GatewayData, OpCodes};
 
 use crate::gateway::dispatch::{send_close, send_message};
 use crate::gateway::schema::error_codes::ErrorCode::DecodeError;
 use crate::gateway::schema::GatewayMessage;
+use crate::gateway::schema::voice_state::VoiceStateUpdateMessage;
 use crate::state::ThreadData;
 use crate::AppState;
 
@@ -36,6 +39,12 @@ pub async fn handle_op(thread_data: &mut ThreadData, msg: String, state: &AppSta
                     send_close(thread_data, DecodeError).await;
                 }
             }
+            OpCodes::VoiceStateUpdate => {
+                match serde_json::from_str::<VoiceStateUpdateMessage>(&msg) {
+                    Ok(data) => handle_voice_state_update(thread_data, data.d, state).await,
+                    Err(_) => send_close(thread_data, DecodeError).await,
+                }
+            }
             _ => {
                 debug!("Got an OP code that I don't have implemented but I do understand!");
             }
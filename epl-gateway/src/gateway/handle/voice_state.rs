@@ -0,0 +1,246 @@
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel, ModelTrait, PaginatorTrait, QueryFilter};
+use sea_orm::ActiveValue::Set;
+use tracing::{debug, error};
+use epl_common::calls::{end_call, get_call, join_call, start_call};
+use epl_common::channels::ChannelTypes;
+use epl_common::database::entities::{user, voice_state};
+use epl_common::database::entities::prelude::{Channel, User, VoiceState};
+use epl_common::nats::Messages;
+use epl_common::nats::send_nats_message;
+use epl_common::permissions::{internal_permission_calculator, InternalChannelPermissions};
+
+use crate::gateway::dispatch::{assemble_dispatch, DispatchTypes, send_close, send_message};
+use crate::gateway::schema::error_codes::ErrorCode::NotAuthenticated;
+use crate::gateway::schema::voice_state::{VoiceServerUpdate, VoiceStateUpdate};
+use crate::lvsp::pick_voice_server;
+use crate::state::ThreadData;
+use crate::AppState;
+
+async fn publish_voice_state(thread_data: &ThreadData, user_id: i64, channel_id: i64, session_id: String) {
+    send_nats_message(
+        &thread_data.nats,
+        channel_id.to_string(),
+        Messages::VoiceStateUpdate {
+            user_id,
+            channel_id,
+            session_id,
+        },
+    ).await;
+}
+
+/// Removes a voice state, and ends the call and frees the channel on its voice server if nobody is left in it
+async fn leave_voice_channel(thread_data: &ThreadData, state: &AppState, voice_state: voice_state::Model) {
+    let remaining = VoiceState::find()
+        .filter(voice_state::Column::ChannelId.eq(voice_state.channel_id))
+        .filter(voice_state::Column::UserId.ne(voice_state.user_id))
+        .count(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    match state.lvsp.get_connection(&state.conn, &voice_state.hostname).await {
+        Ok(connection) => {
+            let _ = connection.destroy_voice_state(voice_state.voice_session_id.clone());
+
+            if remaining == 0 {
+                let _ = connection.destroy_channel(voice_state.channel_id, voice_state.guild_id).await;
+            }
+        }
+        Err(error) => {
+            error!("Failed to reach voice server {}: {:?}", voice_state.hostname, error);
+        }
+    }
+
+    if remaining == 0 {
+        if let Some(call) = get_call(&state.conn, voice_state.channel_id).await {
+            end_call(&state.conn, &thread_data.nats, call).await;
+        }
+    }
+
+    let user_id = voice_state.user_id;
+    let channel_id = voice_state.channel_id;
+    let session_id = voice_state.session_id.clone();
+
+    voice_state.delete(&state.conn).await.expect("Failed to access database!");
+
+    publish_voice_state(thread_data, user_id, channel_id, session_id).await;
+}
+
+async fn join_voice_channel(
+    thread_data: &mut ThreadData,
+    state: &AppState,
+    user: &user::Model,
+    session_id: String,
+    data: VoiceStateUpdate,
+    channel_id: i64,
+) {
+    let channel = match Channel::find_by_id(channel_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!") {
+        None => return,
+        Some(channel) => channel,
+    };
+
+    // TODO: Allow guild voice channels when guilds are a thing
+    if channel.r#type != ChannelTypes::DM as i32 && channel.r#type != ChannelTypes::GroupDM as i32 {
+        debug!("Tried to join voice in a channel that isn't a DM or group DM!");
+        return;
+    }
+
+    let calculated_permissions = internal_permission_calculator(&channel, user, None, &state.conn).await;
+
+    if !calculated_permissions.contains(&InternalChannelPermissions::JoinCall) {
+        return;
+    }
+
+    // Joining when there's no call starts one
+    if get_call(&state.conn, channel.id).await.is_none()
+        && !calculated_permissions.contains(&InternalChannelPermissions::StartCall) {
+        return;
+    }
+
+    // Everyone in a channel has to be on the same voice server
+    let existing_voice_state = VoiceState::find()
+        .filter(voice_state::Column::ChannelId.eq(channel.id))
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    let hostname = match existing_voice_state {
+        Some(existing_voice_state) => existing_voice_state.hostname,
+        None => match pick_voice_server(&state.conn, channel.rtc_region.clone().or(data.preferred_region)).await {
+            Ok(voice_server) => voice_server.hostname,
+            Err(error) => {
+                error!("Failed to pick a voice server: {:?}", error);
+                return;
+            }
+        },
+    };
+
+    let connection = match state.lvsp.get_connection(&state.conn, &hostname).await {
+        Ok(connection) => connection,
+        Err(error) => {
+            error!("Failed to reach voice server {}: {:?}", hostname, error);
+            return;
+        }
+    };
+
+    let (token, voice_session_id) = match connection.create_voice_state(channel.id, channel.guild_id, user.id).await {
+        Ok(created) => created,
+        Err(error) => {
+            error!("Voice server {} failed to create a voice state: {:?}", hostname, error);
+            return;
+        }
+    };
+
+    let new_voice_state = voice_state::ActiveModel {
+        user_id: Set(user.id),
+        channel_id: Set(channel.id),
+        guild_id: Set(channel.guild_id),
+        session_id: Set(session_id.clone()),
+        hostname: Set(hostname.clone()),
+        voice_session_id: Set(voice_session_id),
+        deaf: Set(false),
+        mute: Set(false),
+        self_deaf: Set(data.self_deaf),
+        self_mute: Set(data.self_mute),
+        self_stream: Set(false),
+        self_video: Set(data.self_video),
+        suppress: Set(false),
+    }
+        .insert(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    // VST_CREATE doesn't carry the self mute and deaf state
+    let _ = connection.update_voice_state(&new_voice_state);
+
+    send_message(
+        thread_data,
+        assemble_dispatch(
+            DispatchTypes::VoiceServerUpdate(
+                VoiceServerUpdate {
+                    token,
+                    guild_id: channel.guild_id.map(|x| x.to_string()),
+                    channel_id: Some(channel.id.to_string()),
+                    endpoint: Some(hostname),
+                }
+            )
+        ),
+    ).await;
+
+    match get_call(&state.conn, channel.id).await {
+        None => {
+            start_call(&state.conn, &thread_data.nats, &channel, user.id).await;
+        }
+        Some(call) => join_call(&state.conn, &thread_data.nats, call, user.id).await,
+    }
+
+    publish_voice_state(thread_data, user.id, channel.id, session_id).await;
+}
+
+pub async fn handle_voice_state_update(thread_data: &mut ThreadData, data: VoiceStateUpdate, state: &AppState) {
+    let (user_id, session_id) = match (thread_data.gateway_state.user_id, thread_data.gateway_state.session_id.clone()) {
+        (Some(user_id), Some(session_id)) => (user_id, session_id),
+        _ => {
+            send_close(thread_data, NotAuthenticated).await;
+            return;
+        }
+    };
+
+    let current_voice_state = VoiceState::find_by_id(user_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    let channel_id = data.channel_id.as_ref().and_then(|x| x.parse::<i64>().ok());
+
+    if let Some(current_voice_state) = current_voice_state {
+        if Some(current_voice_state.channel_id) == channel_id {
+            // Staying in the same channel, so only the state itself changed
+            let mut active_voice_state = current_voice_state.into_active_model();
+
+            active_voice_state.session_id = Set(session_id.clone());
+            active_voice_state.self_mute = Set(data.self_mute);
+            active_voice_state.self_deaf = Set(data.self_deaf);
+            active_voice_state.self_video = Set(data.self_video);
+
+            let updated_voice_state = active_voice_state.update(&state.conn).await.expect("Failed to access database!");
+
+            if let Ok(connection) = state.lvsp.get_connection(&state.conn, &updated_voice_state.hostname).await {
+                let _ = connection.update_voice_state(&updated_voice_state);
+            }
+
+            publish_voice_state(thread_data, user_id, updated_voice_state.channel_id, session_id).await;
+
+            return;
+        }
+
+        leave_voice_channel(thread_data, state, current_voice_state).await;
+    }
+
+    if let Some(channel_id) = channel_id {
+        let user = User::find_by_id(user_id)
+            .one(&state.conn)
+            .await
+            .expect("Failed to access database!")
+            .expect("Gateway session references non-existent user!");
+
+        join_voice_channel(thread_data, state, &user, session_id, data, channel_id).await;
+    }
+}
+
+/// Leaves voice when the session that joined disconnects from the gateway
+pub async fn disconnect_voice_state(thread_data: &ThreadData, state: &AppState) {
+    if let (Some(user_id), Some(session_id)) = (thread_data.gateway_state.user_id, thread_data.gateway_state.session_id.clone()) {
+        let current_voice_state = VoiceState::find_by_id(user_id)
+            .filter(voice_state::Column::SessionId.eq(session_id))
+            .one(&state.conn)
+            .await
+            .expect("Failed to access database!");
+
+        if let Some(current_voice_state) = current_voice_state {
+            leave_voice_channel(thread_data, state, current_voice_state).await;
+        }
+    }
+}
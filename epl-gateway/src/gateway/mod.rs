@@ -18,6 +18,7 @@ use epl_common::options::{EplOptions, Options};
 use tracing::{debug, info};
 
 use crate::gateway::handle::handle_op;
+use crate::gateway::handle::voice_state::disconnect_voice_state;
 use crate::gateway::schema::hello::Hello;
 use crate::gateway::schema::opcodes::{GatewayData, OpCodes};
 use crate::gateway::schema::GatewayMessage;
@@ -192,4 +193,9 @@ async fn handle_socket(mut rawsocket: WebSocket, addr: IpAddr, state: AppState,
         // Capture next websocket message
         msg_try = thread_data.socket.recv().now_or_never()
     }
+
+    disconnect_voice_state(&thread_data, &state).await;
+
+    // Make sure anything sent while cleaning up leaves before the connection is dropped
+    let _ = thread_data.nats.flush().await;
 }
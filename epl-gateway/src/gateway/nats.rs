@@ -10,9 +10,14 @@ use crate::gateway::schema::GatewayMessage;
 use crate::state::ThreadData;
 use crate::AppState;
 use epl_common::nats::Messages;
-use crate::gateway::dispatch::reactions::{dispatch_message_reaction_add, dispatch_message_reaction_remove};
+use crate::gateway::dispatch::calls::{dispatch_call_create, dispatch_call_delete, dispatch_call_update};
+use crate::gateway::dispatch::emojis::dispatch_guild_emojis_update;
+use crate::gateway::dispatch::interactions::{dispatch_interaction_create, dispatch_interaction_modal_create, dispatch_interaction_status, InteractionStatus};
+use crate::gateway::dispatch::reactions::{dispatch_message_reaction_add, dispatch_message_reaction_remove, dispatch_message_reaction_remove_all, dispatch_message_reaction_remove_emoji};
 use crate::gateway::dispatch::typing::dispatch_typing_start;
 use crate::gateway::dispatch::user_note_update::dispatch_user_note_update;
+use crate::gateway::dispatch::user_update::dispatch_user_update;
+use crate::gateway::dispatch::voice::dispatch_voice_state_update;
 use crate::gateway::schema::error_codes::ErrorCode;
 
 pub async fn handle_nats_message(thread_data: &mut ThreadData, msg: Messages, state: &AppState) {
@@ -65,6 +70,9 @@ pub async fn handle_nats_message(thread_data: &mut ThreadData, msg: Messages, st
         Messages::UserNoteUpdate { creator_id, subject_id } => {
             dispatch_user_note_update(thread_data, state, creator_id, subject_id).await;
         }
+        Messages::UserUpdate { user_id } => {
+            dispatch_user_update(thread_data, state, user_id).await;
+        }
         Messages::ChannelUpdate { channel_id } => {
             dispatch_channel_update(thread_data, state, channel_id, ChannelTypeUpdate::UPDATE).await;
         }
@@ -77,11 +85,44 @@ pub async fn handle_nats_message(thread_data: &mut ThreadData, msg: Messages, st
         Messages::MessageAck { message_id } => {
             // TODO
         }
-        Messages::MessageReactionAdd { message_id, user_id, emoji } => {
-            dispatch_message_reaction_add(thread_data, state, message_id, user_id, emoji).await;
+        Messages::MessageReactionAdd { message_id, user_id, emoji, reaction_type } => {
+            dispatch_message_reaction_add(thread_data, state, message_id, user_id, emoji, reaction_type).await;
+        }
+        Messages::MessageReactionRemove { message_id, user_id, emoji, reaction_type } => {
+            dispatch_message_reaction_remove(thread_data, state, message_id, user_id, emoji, reaction_type).await;
+        }
+        Messages::MessageReactionRemoveAll { message_id } => {
+            dispatch_message_reaction_remove_all(thread_data, state, message_id).await;
+        }
+        Messages::MessageReactionRemoveEmoji { message_id, emoji } => {
+            dispatch_message_reaction_remove_emoji(thread_data, state, message_id, emoji).await;
+        }
+        Messages::VoiceStateUpdate { user_id, channel_id, session_id } => {
+            dispatch_voice_state_update(thread_data, state, user_id, channel_id, session_id).await;
+        }
+        Messages::CallCreate { channel_id } => {
+            dispatch_call_create(thread_data, state, channel_id).await;
+        }
+        Messages::CallUpdate { channel_id } => {
+            dispatch_call_update(thread_data, state, channel_id).await;
+        }
+        Messages::CallDelete { channel_id } => {
+            dispatch_call_delete(thread_data, channel_id).await;
+        }
+        Messages::InteractionCreate { id } => {
+            dispatch_interaction_create(thread_data, state, id).await;
+        }
+        Messages::InteractionSuccess { id } => {
+            dispatch_interaction_status(thread_data, state, id, InteractionStatus::Success).await;
+        }
+        Messages::InteractionFailure { id } => {
+            dispatch_interaction_status(thread_data, state, id, InteractionStatus::Failure).await;
+        }
+        Messages::InteractionModalCreate { id } => {
+            dispatch_interaction_modal_create(thread_data, state, id).await;
         }
-        Messages::MessageReactionRemove { message_id, user_id, emoji } => {
-            dispatch_message_reaction_remove(thread_data, state, message_id, user_id, emoji).await;
+        Messages::GuildEmojisUpdate { guild_id } => {
+            dispatch_guild_emojis_update(thread_data, state, guild_id).await;
         }
         _ => {
             error!("Unsupported message received!");
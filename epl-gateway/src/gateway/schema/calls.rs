@@ -0,0 +1,25 @@
+use serde_derive::{Deserialize, Serialize};
+use epl_common::schema::v9::voice_state::VoiceState;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CallCreate {
+    pub channel_id: String,
+    pub message_id: String,
+    pub region: Option<String>,
+    pub ringing: Vec<String>,
+    pub voice_states: Vec<VoiceState>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CallUpdate {
+    pub channel_id: String,
+    pub message_id: String,
+    pub region: Option<String>,
+    pub ringing: Vec<String>,
+    pub guild_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CallDelete {
+    pub channel_id: String,
+}
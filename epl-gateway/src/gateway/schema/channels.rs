@@ -23,6 +23,8 @@ pub struct ChannelCreate {
     pub owner_id: Option<String>,
     /// Recipients of DM/Group DM
     pub recipients: Option<Vec<v9::user::User>>,
+    pub rtc_region: Option<String>,
+    pub video_quality_mode: Option<i32>,
     #[serde(rename = "type")]
     pub _type: i32,
     pub version: Option<i32>,
@@ -3,6 +3,7 @@ use serde_with::skip_serializing_none;
 
 use crate::gateway::schema::opcodes::{GatewayData, OpCodes};
 
+pub(crate) mod calls;
 pub(crate) mod channels;
 pub(crate) mod error_codes;
 pub(crate) mod hello;
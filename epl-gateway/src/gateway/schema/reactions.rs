@@ -16,6 +16,7 @@ pub struct MessageReactionAdd {
     pub emoji: Emoji,
     pub channel_id: String,
     pub burst: bool,
+    pub burst_colors: Option<Vec<String>>,
     pub guild_id: Option<String>
 }
 
@@ -30,4 +31,21 @@ pub struct MessageReactionRemove {
     pub channel_id: String,
     pub burst: bool,
     pub guild_id: Option<String>
-}
\ No newline at end of file
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MessageReactionRemoveAll {
+    pub message_id: String,
+    pub channel_id: String,
+    pub guild_id: Option<String>
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MessageReactionRemoveEmoji {
+    pub message_id: String,
+    pub emoji: Emoji,
+    pub channel_id: String,
+    pub guild_id: Option<String>
+}
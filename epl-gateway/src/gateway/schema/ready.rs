@@ -120,6 +120,8 @@ pub struct OtherUser {
     pub global_name: Option<String>,
     pub discriminator: Option<String>,
     pub bot: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub system: bool,
     pub avatar_decoration: Option<String>,
     pub avatar: Option<String>,
 }
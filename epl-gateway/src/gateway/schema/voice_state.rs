@@ -1,4 +1,28 @@
 use serde_derive::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
-pub struct VoiceState {}
+/// Sent by clients to join, leave or change their state in a voice channel
+#[derive(Serialize, Deserialize, Clone)]
+pub struct VoiceStateUpdate {
+    pub guild_id: Option<String>,
+    /// Null to leave
+    pub channel_id: Option<String>,
+    pub self_mute: bool,
+    pub self_deaf: bool,
+    #[serde(default)]
+    pub self_video: bool,
+    pub preferred_region: Option<String>,
+}
+
+/// The untagged gateway data can't tell voice state updates apart, so they are parsed again on their own
+#[derive(Deserialize)]
+pub struct VoiceStateUpdateMessage {
+    pub d: VoiceStateUpdate,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct VoiceServerUpdate {
+    pub token: String,
+    pub guild_id: Option<String>,
+    pub channel_id: Option<String>,
+    pub endpoint: Option<String>,
+}
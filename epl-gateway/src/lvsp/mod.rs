@@ -0,0 +1,720 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{SinkExt, Stream, StreamExt};
+use hmac::{Hmac, Mac};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use sea_orm::sea_query::Expr;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::Sha256;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::time::timeout;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::{Error, Message};
+use tracing::{debug, error, info};
+
+use epl_common::database::entities::{voice_server, voice_state};
+use epl_common::database::entities::prelude::VoiceServer;
+use epl_common::options::{EplOptions, Options};
+
+use crate::lvsp::schema::{
+    ChannelAssign, ChannelDestroy, ChannelReq, Health, Heartbeat, Hello, Identify, Info, InfoTypes,
+    LvspMessage, LvspOpCodes, VstCreate, VstDestroy, VstDone, VstUpdate,
+};
+
+pub(crate) mod schema;
+
+/// How long to wait for a voice server to answer before giving up
+const LVSP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+pub enum LvspError {
+    /// No voice servers have been added
+    NoVoiceServers,
+    /// The voice server couldn't be reached or didn't accept us
+    Connection,
+    /// The voice server didn't answer in time
+    Timeout,
+}
+
+/// Requests waiting on an answer from the voice server
+#[derive(Hash, Eq, PartialEq, Clone)]
+enum PendingRequest {
+    /// Waiting on CHANNEL_ASSIGN for a channel ID
+    ChannelAssign(String),
+    /// Waiting on VST_DONE for a user ID
+    VstDone(String),
+}
+
+type PendingRequests = Arc<Mutex<HashMap<PendingRequest, oneshot::Sender<Value>>>>;
+
+/// A connection to a single voice server
+#[derive(Clone)]
+pub struct LvspConnection {
+    sender: mpsc::UnboundedSender<LvspMessage>,
+    pending: PendingRequests,
+    /// Tokens of the channels this voice server has assigned
+    channels: Arc<Mutex<HashMap<i64, String>>>,
+}
+
+/// Keeps a connection open to every voice server that is in use, shared by all gateway sessions
+#[derive(Clone, Default)]
+pub struct LvspManager {
+    connections: Arc<Mutex<HashMap<String, LvspConnection>>>,
+}
+
+fn encode(op: LvspOpCodes, d: impl Serialize) -> LvspMessage {
+    LvspMessage {
+        op,
+        d: serde_json::to_value(d).expect("Failed to encode LVSP message!"),
+    }
+}
+
+fn to_text(message: &LvspMessage) -> Message {
+    Message::Text(serde_json::to_string(message).expect("Failed to encode LVSP message!"))
+}
+
+/// Signs the nonce from HELLO with the LVSP secret
+fn sign_nonce(nonce: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(EplOptions::get().lvsp_secret.as_bytes())
+        .expect("HMAC can take a key of any size!");
+
+    mac.update(nonce.as_bytes());
+
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|x| format!("{:02x}", x))
+        .collect()
+}
+
+async fn set_health(conn: &DatabaseConnection, hostname: &str, health: f64) {
+    VoiceServer::update_many()
+        .col_expr(voice_server::Column::LastHealth, Expr::value(health))
+        .filter(voice_server::Column::Hostname.eq(hostname))
+        .exec(conn)
+        .await
+        .expect("Failed to access database!");
+}
+
+/// Waits for the next message from the voice server, which has to have the given op code
+async fn expect_message<T: DeserializeOwned>(
+    read: &mut (impl Stream<Item = Result<Message, Error>> + Unpin),
+    op: LvspOpCodes,
+) -> Result<T, LvspError> {
+    let message = timeout(LVSP_TIMEOUT, read.next())
+        .await
+        .map_err(|_| LvspError::Timeout)?;
+
+    match message {
+        Some(Ok(Message::Text(text))) => {
+            let message: LvspMessage = serde_json::from_str(&text).map_err(|_| LvspError::Connection)?;
+
+            if message.op != op {
+                return Err(LvspError::Connection);
+            }
+
+            serde_json::from_value(message.d).map_err(|_| LvspError::Connection)
+        }
+        _ => Err(LvspError::Connection),
+    }
+}
+
+async fn handle_lvsp_message(
+    conn: &DatabaseConnection,
+    hostname: &str,
+    pending: &PendingRequests,
+    channels: &Arc<Mutex<HashMap<i64, String>>>,
+    text: String,
+) {
+    let message: LvspMessage = match serde_json::from_str(&text) {
+        Ok(message) => message,
+        Err(_) => {
+            debug!("Got an LVSP message that I don't understand!");
+            return;
+        }
+    };
+
+    match message.op {
+        LvspOpCodes::HeartbeatAck => {
+            if let Ok(health) = serde_json::from_value::<Health>(message.d) {
+                set_health(conn, hostname, health.health).await;
+            }
+        }
+        LvspOpCodes::Info => {
+            let info = match serde_json::from_value::<Info>(message.d) {
+                Ok(info) => info,
+                Err(_) => return,
+            };
+
+            let request = match info._type {
+                InfoTypes::ChannelAssign => serde_json::from_value::<ChannelAssign>(info.data.clone())
+                    .ok()
+                    .map(|x| PendingRequest::ChannelAssign(x.channel_id)),
+                InfoTypes::VstDone => serde_json::from_value::<VstDone>(info.data.clone())
+                    .ok()
+                    .map(|x| PendingRequest::VstDone(x.user_id)),
+                InfoTypes::ChannelDestroy => {
+                    // The voice server can drop channels on its own, they'll have to be requested again
+                    if let Ok(destroyed) = serde_json::from_value::<ChannelDestroy>(info.data.clone()) {
+                        if let Ok(channel_id) = destroyed.channel_id.parse::<i64>() {
+                            channels.lock().await.remove(&channel_id);
+                        }
+                    }
+
+                    None
+                }
+                _ => None,
+            };
+
+            if let Some(request) = request {
+                if let Some(sender) = pending.lock().await.remove(&request) {
+                    let _ = sender.send(info.data);
+                }
+            }
+        }
+        _ => {
+            debug!("Got an LVSP op code that I don't have implemented!");
+        }
+    }
+}
+
+impl LvspConnection {
+    /// Connects and identifies to a voice server, then keeps the connection running in the background
+    async fn connect(conn: DatabaseConnection, hostname: String) -> Result<LvspConnection, LvspError> {
+        let scheme = if EplOptions::get().require_ssl { "wss" } else { "ws" };
+
+        let (socket, _) = timeout(LVSP_TIMEOUT, connect_async(format!("{}://{}", scheme, hostname)))
+            .await
+            .map_err(|_| LvspError::Timeout)?
+            .map_err(|error| {
+                error!("Failed to connect to voice server {hostname}: {error}");
+                LvspError::Connection
+            })?;
+
+        let (mut write, mut read) = socket.split();
+
+        let hello: Hello = expect_message(&mut read, LvspOpCodes::Hello).await?;
+        let heartbeat_interval = hello.hb_interval;
+
+        write
+            .send(to_text(&encode(
+                LvspOpCodes::Identify,
+                Identify {
+                    token: sign_nonce(&hello.nonce),
+                    nonce: hello.nonce,
+                },
+            )))
+            .await
+            .map_err(|_| LvspError::Connection)?;
+
+        let ready: Health = expect_message(&mut read, LvspOpCodes::Ready).await?;
+
+        set_health(&conn, &hostname, ready.health).await;
+
+        info!("Connected to voice server {hostname}");
+
+        let (sender, mut receiver) = mpsc::unbounded_channel::<LvspMessage>();
+
+        let connection = LvspConnection {
+            sender,
+            pending: Arc::default(),
+            channels: Arc::default(),
+        };
+
+        let pending = connection.pending.clone();
+        let channels = connection.channels.clone();
+
+        tokio::spawn(async move {
+            let mut heartbeat = tokio::time::interval(Duration::from_millis(heartbeat_interval));
+            let mut sequence = 0;
+
+            loop {
+                tokio::select! {
+                    message = receiver.recv() => {
+                        match message {
+                            Some(message) => {
+                                if write.send(to_text(&message)).await.is_err() {
+                                    break;
+                                }
+                            }
+                            None => break,
+                        }
+                    }
+                    _ = heartbeat.tick() => {
+                        sequence += 1;
+
+                        if write.send(to_text(&encode(LvspOpCodes::Heartbeat, Heartbeat { s: sequence }))).await.is_err() {
+                            break;
+                        }
+                    }
+                    message = read.next() => {
+                        match message {
+                            Some(Ok(Message::Text(text))) => {
+                                handle_lvsp_message(&conn, &hostname, &pending, &channels, text).await;
+                            }
+                            Some(Ok(_)) => {}
+                            _ => break,
+                        }
+                    }
+                }
+            }
+
+            error!("Lost connection to voice server {hostname}");
+
+            // Dropping the senders makes anything still waiting fail straight away
+            pending.lock().await.clear();
+        });
+
+        Ok(connection)
+    }
+
+    fn send_info(&self, _type: InfoTypes, data: impl Serialize) -> Result<(), LvspError> {
+        self.sender
+            .send(encode(
+                LvspOpCodes::Info,
+                Info {
+                    _type,
+                    data: serde_json::to_value(data).expect("Failed to encode LVSP message!"),
+                },
+            ))
+            .map_err(|_| LvspError::Connection)
+    }
+
+    /// Sends an INFO message and waits for the voice server to answer it
+    async fn request<T: DeserializeOwned>(
+        &self,
+        request: PendingRequest,
+        _type: InfoTypes,
+        data: impl Serialize,
+    ) -> Result<T, LvspError> {
+        let (sender, receiver) = oneshot::channel();
+
+        self.pending.lock().await.insert(request.clone(), sender);
+
+        self.send_info(_type, data)?;
+
+        let response = match timeout(LVSP_TIMEOUT, receiver).await {
+            Ok(Ok(response)) => response,
+            _ => {
+                self.pending.lock().await.remove(&request);
+                return Err(LvspError::Timeout);
+            }
+        };
+
+        serde_json::from_value(response).map_err(|_| LvspError::Connection)
+    }
+
+    /// Gets the token of a channel, asking the voice server to assign it first if needed
+    async fn get_channel_token(&self, channel_id: i64, guild_id: Option<i64>) -> Result<String, LvspError> {
+        if let Some(token) = self.channels.lock().await.get(&channel_id) {
+            return Ok(token.clone());
+        }
+
+        let assigned: ChannelAssign = self.request(
+            PendingRequest::ChannelAssign(channel_id.to_string()),
+            InfoTypes::ChannelReq,
+            ChannelReq {
+                channel_id: channel_id.to_string(),
+                guild_id: guild_id.map(|x| x.to_string()),
+            },
+        ).await?;
+
+        self.channels.lock().await.insert(channel_id, assigned.token.clone());
+
+        Ok(assigned.token)
+    }
+
+    /// Creates a voice state on the voice server, returns the channel's token and the voice session ID
+    pub async fn create_voice_state(
+        &self,
+        channel_id: i64,
+        guild_id: Option<i64>,
+        user_id: i64,
+    ) -> Result<(String, String), LvspError> {
+        let token = self.get_channel_token(channel_id, guild_id).await?;
+
+        let done: VstDone = self.request(
+            PendingRequest::VstDone(user_id.to_string()),
+            InfoTypes::VstCreate,
+            VstCreate {
+                user_id: user_id.to_string(),
+                channel_id: channel_id.to_string(),
+                guild_id: guild_id.map(|x| x.to_string()),
+            },
+        ).await?;
+
+        Ok((token, done.session_id))
+    }
+
+    pub fn update_voice_state(&self, voice_state: &voice_state::Model) -> Result<(), LvspError> {
+        self.send_info(
+            InfoTypes::VstUpdate,
+            VstUpdate {
+                session_id: voice_state.voice_session_id.clone(),
+                deaf: voice_state.deaf,
+                mute: voice_state.mute,
+                self_deaf: voice_state.self_deaf,
+                self_mute: voice_state.self_mute,
+                self_video: voice_state.self_video,
+                suppress: voice_state.suppress,
+            },
+        )
+    }
+
+    pub fn destroy_voice_state(&self, voice_session_id: String) -> Result<(), LvspError> {
+        self.send_info(
+            InfoTypes::VstDestroy,
+            VstDestroy {
+                session_id: voice_session_id,
+            },
+        )
+    }
+
+    /// Tells the voice server that nobody is left in a channel
+    pub async fn destroy_channel(&self, channel_id: i64, guild_id: Option<i64>) -> Result<(), LvspError> {
+        self.channels.lock().await.remove(&channel_id);
+
+        self.send_info(
+            InfoTypes::ChannelDestroy,
+            ChannelDestroy {
+                channel_id: channel_id.to_string(),
+                guild_id: guild_id.map(|x| x.to_string()),
+            },
+        )
+    }
+}
+
+impl LvspManager {
+    /// Gets the connection to a voice server, connecting to it if there isn't one yet
+    pub async fn get_connection(&self, conn: &DatabaseConnection, hostname: &str) -> Result<LvspConnection, LvspError> {
+        let mut connections = self.connections.lock().await;
+
+        if let Some(connection) = connections.get(hostname) {
+            if !connection.sender.is_closed() {
+                return Ok(connection.clone());
+            }
+        }
+
+        let connection = LvspConnection::connect(conn.clone(), hostname.to_string()).await?;
+
+        connections.insert(hostname.to_string(), connection.clone());
+
+        Ok(connection)
+    }
+}
+
+/// Picks the healthiest voice server in a region, or in any region if that one has none
+pub async fn pick_voice_server(
+    conn: &DatabaseConnection,
+    region: Option<String>,
+) -> Result<voice_server::Model, LvspError> {
+    if let Some(region) = region {
+        let server = VoiceServer::find()
+            .filter(voice_server::Column::RegionId.eq(region))
+            .order_by_desc(voice_server::Column::LastHealth)
+            .one(conn)
+            .await
+            .expect("Failed to access database!");
+
+        if let Some(server) = server {
+            return Ok(server);
+        }
+    }
+
+    VoiceServer::find()
+        .order_by_desc(voice_server::Column::LastHealth)
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+        .ok_or(LvspError::NoVoiceServers)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Once;
+
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::Notify;
+    use tokio_tungstenite::accept_async;
+
+    use super::*;
+
+    const NONCE: &str = "fake-nonce";
+
+    fn set_options() {
+        static OPTIONS: Once = Once::new();
+
+        OPTIONS.call_once(|| {
+            std::env::set_var("URL", "localhost");
+            std::env::set_var("GATEWAY_URL", "localhost");
+            std::env::set_var("CDN_URL", "localhost");
+            std::env::set_var("SECRET_KEY", "secret");
+            std::env::set_var("LVSP_SECRET", "lvsp-secret");
+            std::env::set_var("REQUIRE_SSL", "false");
+        });
+    }
+
+    /// Every heartbeat ACK updates the server's health, the mock only has to accept them
+    fn mock_database() -> DatabaseConnection {
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results((0..1000).map(|_| MockExecResult { last_insert_id: 0, rows_affected: 1 }))
+            .into_connection()
+    }
+
+    /// Speaks just enough LVSP to answer the gateway, everything the gateway sends is passed on to the test
+    struct FakeVoiceServer {
+        hostname: String,
+        messages: mpsc::UnboundedReceiver<LvspMessage>,
+        /// Drops whichever connection is open, like the voice server restarting
+        disconnect: Arc<Notify>,
+    }
+
+    impl FakeVoiceServer {
+        async fn start() -> FakeVoiceServer {
+            set_options();
+
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let hostname = listener.local_addr().unwrap().to_string();
+            let (sender, messages) = mpsc::unbounded_channel();
+            let disconnect = Arc::new(Notify::new());
+
+            let server_disconnect = disconnect.clone();
+
+            tokio::spawn(async move {
+                while let Ok((socket, _)) = listener.accept().await {
+                    serve(socket, sender.clone(), server_disconnect.clone()).await;
+                }
+            });
+
+            FakeVoiceServer { hostname, messages, disconnect }
+        }
+
+        async fn next_message(&mut self, op: LvspOpCodes) -> LvspMessage {
+            timeout(Duration::from_secs(5), async {
+                loop {
+                    let message = self.messages.recv().await.expect("The fake voice server stopped!");
+
+                    if message.op == op {
+                        return message;
+                    }
+                }
+            })
+                .await
+                .expect("The gateway never sent the message!")
+        }
+
+        async fn next_info(&mut self, _type: InfoTypes) -> Value {
+            loop {
+                let info: Info = serde_json::from_value(self.next_message(LvspOpCodes::Info).await.d).unwrap();
+
+                if info._type == _type {
+                    return info.data;
+                }
+            }
+        }
+    }
+
+    async fn serve(socket: TcpStream, messages: mpsc::UnboundedSender<LvspMessage>, disconnect: Arc<Notify>) {
+        let mut socket = accept_async(socket).await.unwrap();
+
+        socket.send(to_text(&encode(LvspOpCodes::Hello, Hello {
+            hb_interval: 50,
+            nonce: NONCE.to_string(),
+        }))).await.unwrap();
+
+        let identify: Identify = match expect_message(&mut socket, LvspOpCodes::Identify).await {
+            Ok(identify) => identify,
+            Err(_) => return,
+        };
+
+        let valid = identify.token == sign_nonce(NONCE);
+
+        let _ = messages.send(encode(LvspOpCodes::Identify, identify));
+
+        if !valid {
+            return;
+        }
+
+        socket.send(to_text(&encode(LvspOpCodes::Ready, Health { health: 1.0 }))).await.unwrap();
+
+        loop {
+            tokio::select! {
+                _ = disconnect.notified() => return,
+                message = socket.next() => {
+                    let text = match message {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(_)) => continue,
+                        _ => return,
+                    };
+
+                    let message: LvspMessage = serde_json::from_str(&text).unwrap();
+                    let reply = reply_to(&message);
+
+                    let _ = messages.send(message);
+
+                    if let Some(reply) = reply {
+                        socket.send(to_text(&reply)).await.unwrap();
+                    }
+                }
+            }
+        }
+    }
+
+    fn reply_to(message: &LvspMessage) -> Option<LvspMessage> {
+        match message.op {
+            LvspOpCodes::Heartbeat => Some(encode(LvspOpCodes::HeartbeatAck, Health { health: 0.5 })),
+            LvspOpCodes::Info => {
+                let info: Info = serde_json::from_value(message.d.clone()).unwrap();
+
+                match info._type {
+                    InfoTypes::ChannelReq => {
+                        let request: ChannelReq = serde_json::from_value(info.data).unwrap();
+
+                        Some(encode(LvspOpCodes::Info, Info {
+                            _type: InfoTypes::ChannelAssign,
+                            data: serde_json::to_value(ChannelAssign {
+                                token: format!("token-{}", request.channel_id),
+                                channel_id: request.channel_id,
+                                guild_id: request.guild_id,
+                            }).unwrap(),
+                        }))
+                    }
+                    InfoTypes::VstCreate => {
+                        let request: VstCreate = serde_json::from_value(info.data).unwrap();
+
+                        Some(encode(LvspOpCodes::Info, Info {
+                            _type: InfoTypes::VstDone,
+                            data: serde_json::to_value(VstDone {
+                                session_id: format!("session-{}", request.user_id),
+                                user_id: request.user_id,
+                                channel_id: request.channel_id,
+                                guild_id: request.guild_id,
+                            }).unwrap(),
+                        }))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn identifies_with_the_signed_nonce() {
+        let mut server = FakeVoiceServer::start().await;
+
+        LvspConnection::connect(mock_database(), server.hostname.clone()).await.unwrap();
+
+        let identify: Identify = serde_json::from_value(server.next_message(LvspOpCodes::Identify).await.d).unwrap();
+
+        assert_eq!(identify.nonce, NONCE);
+        assert_eq!(identify.token, sign_nonce(NONCE));
+    }
+
+    #[tokio::test]
+    async fn heartbeats_count_up() {
+        let mut server = FakeVoiceServer::start().await;
+
+        let _connection = LvspConnection::connect(mock_database(), server.hostname.clone()).await.unwrap();
+
+        for sequence in 1..=3 {
+            let heartbeat: Heartbeat = serde_json::from_value(server.next_message(LvspOpCodes::Heartbeat).await.d).unwrap();
+
+            assert_eq!(heartbeat.s, sequence);
+        }
+    }
+
+    #[tokio::test]
+    async fn reconnects_after_the_voice_server_drops() {
+        let mut server = FakeVoiceServer::start().await;
+        let conn = mock_database();
+        let manager = LvspManager::default();
+
+        let connection = manager.get_connection(&conn, &server.hostname).await.unwrap();
+        server.next_message(LvspOpCodes::Identify).await;
+
+        // The same connection is handed out while it's up
+        let same_connection = manager.get_connection(&conn, &server.hostname).await.unwrap();
+        assert!(connection.sender.same_channel(&same_connection.sender));
+
+        server.disconnect.notify_one();
+
+        timeout(Duration::from_secs(5), async {
+            while !connection.sender.is_closed() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+            .await
+            .expect("The gateway never noticed the voice server dropping!");
+
+        let new_connection = manager.get_connection(&conn, &server.hostname).await.unwrap();
+        server.next_message(LvspOpCodes::Identify).await;
+
+        assert!(!new_connection.sender.same_channel(&connection.sender));
+    }
+
+    #[tokio::test]
+    async fn voice_state_round_trip() {
+        let mut server = FakeVoiceServer::start().await;
+
+        let connection = LvspConnection::connect(mock_database(), server.hostname.clone()).await.unwrap();
+
+        let (token, voice_session_id) = connection.create_voice_state(100, None, 200).await.unwrap();
+
+        assert_eq!(token, "token-100");
+        assert_eq!(voice_session_id, "session-200");
+
+        let request: ChannelReq = serde_json::from_value(server.next_info(InfoTypes::ChannelReq).await).unwrap();
+        assert_eq!(request.channel_id, "100");
+
+        // The channel is only assigned once, everyone after that gets the same token
+        let (token, voice_session_id) = connection.create_voice_state(100, None, 300).await.unwrap();
+
+        assert_eq!(token, "token-100");
+        assert_eq!(voice_session_id, "session-300");
+
+        let created: VstCreate = serde_json::from_value(server.next_info(InfoTypes::VstCreate).await).unwrap();
+        assert_eq!(created.user_id, "200");
+        let created: VstCreate = serde_json::from_value(server.next_info(InfoTypes::VstCreate).await).unwrap();
+        assert_eq!(created.user_id, "300");
+
+        connection.update_voice_state(&voice_state::Model {
+            user_id: 300,
+            channel_id: 100,
+            guild_id: None,
+            session_id: "gateway-session".to_string(),
+            hostname: server.hostname.clone(),
+            voice_session_id: voice_session_id.clone(),
+            deaf: false,
+            mute: false,
+            self_deaf: false,
+            self_mute: true,
+            self_stream: false,
+            self_video: false,
+            suppress: false,
+        }).unwrap();
+
+        let updated: VstUpdate = serde_json::from_value(server.next_info(InfoTypes::VstUpdate).await).unwrap();
+        assert_eq!(updated.session_id, "session-300");
+        assert!(updated.self_mute);
+
+        connection.destroy_voice_state(voice_session_id).unwrap();
+
+        let destroyed: VstDestroy = serde_json::from_value(server.next_info(InfoTypes::VstDestroy).await).unwrap();
+        assert_eq!(destroyed.session_id, "session-300");
+
+        connection.destroy_channel(100, None).await.unwrap();
+
+        let destroyed: ChannelDestroy = serde_json::from_value(server.next_info(InfoTypes::ChannelDestroy).await).unwrap();
+        assert_eq!(destroyed.channel_id, "100");
+
+        // A destroyed channel has to be assigned again
+        connection.create_voice_state(100, None, 200).await.unwrap();
+        server.next_info(InfoTypes::ChannelReq).await;
+    }
+}
@@ -0,0 +1,116 @@
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+#[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug, Clone, Copy)]
+#[repr(u8)]
+pub enum LvspOpCodes {
+    Hello = 0,
+    Identify = 1,
+    Resume = 2,
+    Ready = 3,
+    Heartbeat = 4,
+    HeartbeatAck = 5,
+    Info = 6,
+}
+
+#[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug, Clone, Copy)]
+#[repr(u8)]
+pub enum InfoTypes {
+    ChannelReq = 0,
+    ChannelAssign = 1,
+    ChannelUpdate = 2,
+    ChannelDestroy = 3,
+    VstCreate = 4,
+    VstDone = 5,
+    VstUpdate = 6,
+    VstDestroy = 7,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LvspMessage {
+    pub op: LvspOpCodes,
+    pub d: Value,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Hello {
+    /// Heartbeat interval in milliseconds
+    pub hb_interval: u64,
+    pub nonce: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Identify {
+    /// HMAC-SHA256 of the nonce, keyed with the LVSP secret
+    pub token: String,
+    pub nonce: String,
+}
+
+/// Sent with READY and HEARTBEAT_ACK
+#[derive(Serialize, Deserialize)]
+pub struct Health {
+    pub health: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Heartbeat {
+    pub s: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Info {
+    #[serde(rename = "type")]
+    pub _type: InfoTypes,
+    pub data: Value,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ChannelReq {
+    pub channel_id: String,
+    pub guild_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ChannelAssign {
+    pub channel_id: String,
+    pub guild_id: Option<String>,
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ChannelDestroy {
+    pub channel_id: String,
+    pub guild_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VstCreate {
+    pub user_id: String,
+    pub channel_id: String,
+    pub guild_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VstDone {
+    pub user_id: String,
+    pub channel_id: String,
+    pub guild_id: Option<String>,
+    pub session_id: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VstUpdate {
+    pub session_id: String,
+    pub deaf: bool,
+    pub mute: bool,
+    pub self_deaf: bool,
+    pub self_mute: bool,
+    pub self_video: bool,
+    pub suppress: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VstDestroy {
+    pub session_id: String,
+}
@@ -10,6 +10,7 @@ use tower_http::cors::{Any, CorsLayer};
 use tracing::{debug, info, log};
 
 use crate::gateway::gateway;
+use crate::lvsp::LvspManager;
 use epl_common::options::{EplOptions, Options};
 use epl_common::rustflake;
 
@@ -17,6 +18,7 @@ use migration::{Migrator, MigratorTrait};
 
 mod fragmented_write;
 mod gateway;
+mod lvsp;
 mod state;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -71,7 +73,10 @@ async fn main() {
         .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::OPTIONS])
         .allow_headers(Any);
 
-    let app_state = AppState { conn };
+    let app_state = AppState {
+        conn,
+        lvsp: LvspManager::default(),
+    };
 
     let app = Router::new()
         .route("/", get(gateway))
@@ -95,4 +100,5 @@ async fn main() {
 #[derive(Clone)]
 pub struct AppState {
     conn: DatabaseConnection,
+    lvsp: LvspManager,
 }
@@ -1,23 +1,65 @@
 use crate::AppState;
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
 use axum::middleware::Next;
 use axum::response::Response;
 use axum::Extension;
 use axum::extract::Request;
-use epl_common::database::auth::{get_session_by_token, get_user_from_session_by_token};
-use epl_common::database::entities::{session, user};
+use chrono::Utc;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use epl_common::database::auth::{get_session_by_token, get_user_from_session_by_token, touch_session};
+use epl_common::database::entities::prelude::{Oauth2Token, User};
+use epl_common::database::entities::{oauth2_token, session, user};
+use epl_common::oauth2::{get_scopes, OAuth2Scopes};
 
 #[derive(Clone)]
 pub struct SessionContext {
     pub user: user::Model,
-    pub session: session::Model,
+    /// None when authenticated with an OAuth2 bearer token
+    pub session: Option<session::Model>,
+    /// What an OAuth2 bearer token was granted, None for regular sessions which can do anything
+    pub scopes: Option<Vec<OAuth2Scopes>>,
+}
+
+impl SessionContext {
+    pub fn has_scope(&self, scope: OAuth2Scopes) -> bool {
+        match &self.scopes {
+            None => true,
+            Some(scopes) => scopes.contains(&scope),
+        }
+    }
+}
+
+/// Gets the valid OAuth2 token sent as a bearer token in the Authorization header
+pub(crate) async fn get_bearer_token(conn: &DatabaseConnection, headers: &HeaderMap) -> Option<oauth2_token::Model> {
+    let auth = headers.get("Authorization")?.to_str().ok()?;
+    let access_token = auth.strip_prefix("Bearer ")?;
+
+    Oauth2Token::find()
+        .filter(oauth2_token::Column::AccessToken.eq(access_token))
+        .filter(oauth2_token::Column::Expires.gt(Utc::now().naive_utc()))
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
 }
 
 pub async fn get_session_context(
     Extension(state): Extension<AppState>,
-    mut request: Request,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    authenticate(state, request, next, false).await
+}
+
+/// Like [`get_session_context`], but OAuth2 bearer tokens are let through too, the routes check the scopes themselves
+pub async fn get_oauth2_session_context(
+    Extension(state): Extension<AppState>,
+    request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
+    authenticate(state, request, next, true).await
+}
+
+async fn authenticate(state: AppState, mut request: Request, next: Next, allow_bearer: bool) -> Result<Response, StatusCode> {
     let auth = request
         .headers()
         .get("Authorization")
@@ -25,9 +67,36 @@ pub async fn get_session_context(
         .to_str()
         .unwrap();
 
+    if auth.starts_with("Bearer ") {
+        if !allow_bearer {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+
+        let token = get_bearer_token(&state.conn, request.headers()).await.ok_or(StatusCode::UNAUTHORIZED)?;
+
+        let user = User::find_by_id(token.user_id)
+            .one(&state.conn)
+            .await
+            .expect("Failed to access database!")
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+
+        request.extensions_mut().insert(SessionContext {
+            user,
+            session: None,
+            scopes: Some(get_scopes(&token.scopes)),
+        });
+
+        return Ok(next.run(request).await);
+    }
+
+    // Bots send their token with a prefix, the token itself is a regular session token
+    let auth = auth.strip_prefix("Bot ").unwrap_or(auth);
+
     return if let Ok(session) = get_session_by_token(&state.conn, &String::from(auth)).await {
         if let Ok(user) = get_user_from_session_by_token(&state.conn, &String::from(auth)).await {
-            let context = SessionContext { user, session };
+            // Using a session keeps it alive
+            let session = touch_session(&state.conn, session).await;
+            let context = SessionContext { user, session: Some(session), scopes: None };
 
             request.extensions_mut().insert(context);
 
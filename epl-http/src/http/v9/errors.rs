@@ -45,6 +45,8 @@ pub enum APIErrorField {
     Email { _errors: Vec<APIErrorMessage> },
     #[serde(alias = "date_of_birth")]
     DateOfBirth { _errors: Vec<APIErrorMessage> },
+    #[serde(alias = "invite")]
+    Invite { _errors: Vec<APIErrorMessage> },
 }
 
 impl From<APIErrorField> for String {
@@ -55,6 +57,7 @@ impl From<APIErrorField> for String {
             APIErrorField::Login { .. } => "login",
             APIErrorField::Email { .. } => "email",
             APIErrorField::DateOfBirth { .. } => "date_of_birth",
+            APIErrorField::Invite { .. } => "invite",
         }
         .parse()
         .unwrap()
@@ -77,7 +80,27 @@ pub enum APIErrorCode {
     InvalidFormBody,
     FriendRequestBlocked,
     CannotSendFriendRequestToSelf,
-    UnknownEmoji
+    UnknownEmoji,
+    UnknownApplication,
+    UnknownChannel,
+    UnknownGuild,
+    MissingAccess,
+    UnknownWebhook,
+    UnknownInteraction,
+    UnknownApplicationCommand,
+    InteractionAlreadyAcknowledged,
+    UnknownMessage,
+    MaximumNumberOfEmojis,
+    MissingPermissions,
+    TwoFactorRequired,
+    InvalidTwoFactorCode,
+    AccountVerificationRequired,
+    AccountScheduledForDeletion,
+    HarvestRequestedRecently,
+    InvalidDataPackage,
+    DiscordAccountAlreadyImported,
+    CannotSendMessagesToUser,
+    IncomingFriendRequestsDisabled
 }
 
 impl From<APIErrorCode> for u32 {
@@ -91,7 +114,27 @@ impl From<APIErrorCode> for u32 {
             APIErrorCode::InvalidFormBody => 50035,
             APIErrorCode::CannotSendFriendRequestToSelf => 80003,
             APIErrorCode::FriendRequestBlocked => 80001,
-            APIErrorCode::UnknownEmoji => 10014
+            APIErrorCode::UnknownEmoji => 10014,
+            APIErrorCode::UnknownApplication => 10002,
+            APIErrorCode::UnknownChannel => 10003,
+            APIErrorCode::UnknownGuild => 10004,
+            APIErrorCode::MissingAccess => 50001,
+            APIErrorCode::UnknownWebhook => 10015,
+            APIErrorCode::UnknownInteraction => 10062,
+            APIErrorCode::UnknownApplicationCommand => 10063,
+            APIErrorCode::InteractionAlreadyAcknowledged => 40060,
+            APIErrorCode::UnknownMessage => 10008,
+            APIErrorCode::MaximumNumberOfEmojis => 30008,
+            APIErrorCode::MissingPermissions => 50013,
+            APIErrorCode::TwoFactorRequired => 60003,
+            APIErrorCode::InvalidTwoFactorCode => 60008,
+            APIErrorCode::AccountVerificationRequired => 40002,
+            APIErrorCode::AccountScheduledForDeletion => 20011,
+            APIErrorCode::HarvestRequestedRecently => 20029,
+            APIErrorCode::InvalidDataPackage => 50110,
+            APIErrorCode::DiscordAccountAlreadyImported => 50111,
+            APIErrorCode::CannotSendMessagesToUser => 50007,
+            APIErrorCode::IncomingFriendRequestsDisabled => 80000
         }
     }
 }
@@ -109,7 +152,27 @@ impl From<APIErrorCode> for String {
                 "Cannot send friend request to self".to_string()
             }
             APIErrorCode::FriendRequestBlocked => "Friend request blocked".to_string(),
-            APIErrorCode::UnknownEmoji => "Unknown Emoji".to_string()
+            APIErrorCode::UnknownEmoji => "Unknown Emoji".to_string(),
+            APIErrorCode::UnknownApplication => "Unknown Application".to_string(),
+            APIErrorCode::UnknownChannel => "Unknown Channel".to_string(),
+            APIErrorCode::UnknownGuild => "Unknown Guild".to_string(),
+            APIErrorCode::MissingAccess => "Missing Access".to_string(),
+            APIErrorCode::UnknownWebhook => "Unknown Webhook".to_string(),
+            APIErrorCode::UnknownInteraction => "Unknown interaction".to_string(),
+            APIErrorCode::UnknownApplicationCommand => "Unknown application command".to_string(),
+            APIErrorCode::InteractionAlreadyAcknowledged => "Interaction has already been acknowledged.".to_string(),
+            APIErrorCode::UnknownMessage => "Unknown Message".to_string(),
+            APIErrorCode::MaximumNumberOfEmojis => "Maximum number of emojis reached".to_string(),
+            APIErrorCode::MissingPermissions => "Missing Permissions".to_string(),
+            APIErrorCode::TwoFactorRequired => "Two factor is required for this operation".to_string(),
+            APIErrorCode::InvalidTwoFactorCode => "Invalid two-factor code".to_string(),
+            APIErrorCode::AccountVerificationRequired => "You need to verify your account in order to perform this action.".to_string(),
+            APIErrorCode::AccountScheduledForDeletion => "This account is scheduled for deletion.".to_string(),
+            APIErrorCode::HarvestRequestedRecently => "You can only request a copy of your data once every 30 days.".to_string(),
+            APIErrorCode::InvalidDataPackage => "The provided file is invalid.".to_string(),
+            APIErrorCode::DiscordAccountAlreadyImported => "This Discord account was already imported into another account.".to_string(),
+            APIErrorCode::CannotSendMessagesToUser => "Cannot send messages to this user".to_string(),
+            APIErrorCode::IncomingFriendRequestsDisabled => "Incoming friend requests disabled.".to_string()
         }
     }
 }
@@ -125,7 +188,27 @@ impl From<APIErrorCode> for StatusCode {
             APIErrorCode::InvalidFormBody => StatusCode::BAD_REQUEST,
             APIErrorCode::CannotSendFriendRequestToSelf => StatusCode::BAD_REQUEST,
             APIErrorCode::FriendRequestBlocked => StatusCode::BAD_REQUEST,
-            APIErrorCode::UnknownEmoji => StatusCode::BAD_REQUEST
+            APIErrorCode::UnknownEmoji => StatusCode::BAD_REQUEST,
+            APIErrorCode::UnknownApplication => StatusCode::NOT_FOUND,
+            APIErrorCode::UnknownChannel => StatusCode::NOT_FOUND,
+            APIErrorCode::UnknownGuild => StatusCode::NOT_FOUND,
+            APIErrorCode::MissingAccess => StatusCode::FORBIDDEN,
+            APIErrorCode::UnknownWebhook => StatusCode::NOT_FOUND,
+            APIErrorCode::UnknownInteraction => StatusCode::NOT_FOUND,
+            APIErrorCode::UnknownApplicationCommand => StatusCode::NOT_FOUND,
+            APIErrorCode::InteractionAlreadyAcknowledged => StatusCode::BAD_REQUEST,
+            APIErrorCode::UnknownMessage => StatusCode::NOT_FOUND,
+            APIErrorCode::MaximumNumberOfEmojis => StatusCode::BAD_REQUEST,
+            APIErrorCode::MissingPermissions => StatusCode::FORBIDDEN,
+            APIErrorCode::TwoFactorRequired => StatusCode::UNAUTHORIZED,
+            APIErrorCode::InvalidTwoFactorCode => StatusCode::BAD_REQUEST,
+            APIErrorCode::AccountVerificationRequired => StatusCode::FORBIDDEN,
+            APIErrorCode::AccountScheduledForDeletion => StatusCode::BAD_REQUEST,
+            APIErrorCode::HarvestRequestedRecently => StatusCode::TOO_MANY_REQUESTS,
+            APIErrorCode::InvalidDataPackage => StatusCode::BAD_REQUEST,
+            APIErrorCode::DiscordAccountAlreadyImported => StatusCode::BAD_REQUEST,
+            APIErrorCode::CannotSendMessagesToUser => StatusCode::FORBIDDEN,
+            APIErrorCode::IncomingFriendRequestsDisabled => StatusCode::BAD_REQUEST
         }
     }
 }
@@ -0,0 +1,590 @@
+use std::collections::HashSet;
+use axum::{Extension, Json};
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use num_traits::FromPrimitive;
+use sea_orm::*;
+use sea_orm::ActiveValue::Set;
+use serde_derive::Deserialize;
+use epl_common::database::entities::{application, application_command, user};
+use epl_common::database::entities::prelude::{Application, ApplicationCommand};
+use epl_common::interactions::{ApplicationCommandOptionType, ApplicationCommandType};
+use epl_common::rustflake::Snowflake;
+use epl_common::schema::v9::application_command::{generate_application_command_struct, ApplicationCommandOption};
+use crate::AppState;
+use crate::authorization_extractor::SessionContext;
+use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+
+#[derive(Deserialize)]
+pub struct ApplicationCommandReq {
+    #[serde(rename = "type")]
+    _type: Option<i32>,
+    name: String,
+    description: Option<String>,
+    options: Option<Vec<ApplicationCommandOption>>,
+    default_member_permissions: Option<String>,
+    dm_permission: Option<bool>,
+    nsfw: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct EditApplicationCommandReq {
+    name: Option<String>,
+    description: Option<String>,
+    options: Option<Vec<ApplicationCommandOption>>,
+    default_member_permissions: Option<String>,
+    dm_permission: Option<bool>,
+    nsfw: Option<bool>,
+}
+
+/// Gets an application if the user is either its bot or its owner
+async fn get_managed_application(
+    conn: &DatabaseConnection,
+    application_id: i64,
+    user: &user::Model,
+) -> Option<application::Model> {
+    Application::find_by_id(application_id)
+        .filter(
+            Condition::any()
+                .add(application::Column::OwnerId.eq(user.id))
+                .add(application::Column::BotUserId.eq(user.id))
+        )
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+fn get_command_limit(command_type: ApplicationCommandType) -> u64 {
+    match command_type {
+        ApplicationCommandType::ChatInput => 100,
+        ApplicationCommandType::User => 5,
+        ApplicationCommandType::Message => 5,
+    }
+}
+
+fn is_valid_command_name(name: &str) -> bool {
+    let length = name.chars().count();
+
+    (1..=32).contains(&length)
+        && name.to_lowercase() == name
+        && name.chars().all(|x| x == '-' || x == '_' || x.is_alphanumeric())
+}
+
+fn is_valid_description(description: &str) -> bool {
+    (1..=100).contains(&description.chars().count())
+}
+
+fn validate_options(options: &[ApplicationCommandOption], depth: u8) -> bool {
+    if options.len() > 25 {
+        return false;
+    }
+
+    let mut names = HashSet::new();
+    let mut seen_optional = false;
+
+    // Sub commands can't be mixed with regular options
+    let sub_commands = options.iter().filter(|x| x._type <= ApplicationCommandOptionType::SubCommandGroup as i32).count();
+
+    if sub_commands != 0 && sub_commands != options.len() {
+        return false;
+    }
+
+    for i in options {
+        let option_type = match ApplicationCommandOptionType::from_i32(i._type) {
+            None => return false,
+            Some(option_type) => option_type,
+        };
+
+        if !is_valid_command_name(&i.name)
+            || !is_valid_description(&i.description)
+            || !names.insert(i.name.clone())
+            || i.choices.as_ref().is_some_and(|x| x.len() > 25) {
+            return false;
+        }
+
+        match option_type {
+            ApplicationCommandOptionType::SubCommandGroup => {
+                let sub_options = i.options.clone().unwrap_or_default();
+
+                if depth > 0
+                    || sub_options.is_empty()
+                    || sub_options.iter().any(|x| x._type != ApplicationCommandOptionType::SubCommand as i32)
+                    || !validate_options(&sub_options, depth + 1) {
+                    return false;
+                }
+            }
+            ApplicationCommandOptionType::SubCommand => {
+                let sub_options = i.options.clone().unwrap_or_default();
+
+                if depth > 1
+                    || sub_options.iter().any(|x| x._type <= ApplicationCommandOptionType::SubCommandGroup as i32)
+                    || !validate_options(&sub_options, depth + 1) {
+                    return false;
+                }
+            }
+            _ => {
+                if i.options.is_some() {
+                    return false;
+                }
+
+                // Required options have to come before optional ones
+                if i.required.unwrap_or(false) {
+                    if seen_optional {
+                        return false;
+                    }
+                } else {
+                    seen_optional = true;
+                }
+            }
+        }
+    }
+
+    true
+}
+
+fn validate_command(
+    command_type: ApplicationCommandType,
+    name: &str,
+    description: &str,
+    options: &Option<Vec<ApplicationCommandOption>>,
+) -> bool {
+    match command_type {
+        ApplicationCommandType::ChatInput => {
+            is_valid_command_name(name)
+                && is_valid_description(description)
+                && options.as_ref().map(|x| validate_options(x, 0)).unwrap_or(true)
+        }
+        // Context menu commands can have spaces and capitals, but no description or options
+        ApplicationCommandType::User | ApplicationCommandType::Message => {
+            (1..=32).contains(&name.chars().count())
+                && description.is_empty()
+                && options.as_ref().map(|x| x.is_empty()).unwrap_or(true)
+        }
+    }
+}
+
+async fn get_commands(
+    conn: &DatabaseConnection,
+    application_id: i64,
+    guild_id: Option<i64>,
+) -> Vec<application_command::Model> {
+    ApplicationCommand::find()
+        .filter(application_command::Column::ApplicationId.eq(application_id))
+        .filter(match guild_id {
+            None => application_command::Column::GuildId.is_null(),
+            Some(guild_id) => application_command::Column::GuildId.eq(guild_id),
+        })
+        .order_by_asc(application_command::Column::Id)
+        .all(conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+async fn get_command(
+    conn: &DatabaseConnection,
+    application_id: i64,
+    guild_id: Option<i64>,
+    command_id: i64,
+) -> Option<application_command::Model> {
+    ApplicationCommand::find_by_id(command_id)
+        .filter(application_command::Column::ApplicationId.eq(application_id))
+        .filter(match guild_id {
+            None => application_command::Column::GuildId.is_null(),
+            Some(guild_id) => application_command::Column::GuildId.eq(guild_id),
+        })
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+/// Creates a command, or overwrites the existing one with the same name and type.
+/// Returns the command and if it was newly created
+async fn upsert_command(
+    conn: &DatabaseConnection,
+    application_id: i64,
+    guild_id: Option<i64>,
+    data: ApplicationCommandReq,
+) -> Result<(application_command::Model, bool), StatusCode> {
+    let command_type = match ApplicationCommandType::from_i32(data._type.unwrap_or(ApplicationCommandType::ChatInput as i32)) {
+        None => return Err(StatusCode::BAD_REQUEST),
+        Some(command_type) => command_type,
+    };
+
+    let description = data.description.unwrap_or_default();
+
+    if !validate_command(command_type, &data.name, &description, &data.options) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let existing_commands = get_commands(conn, application_id, guild_id).await;
+
+    let existing_command = existing_commands
+        .iter()
+        .find(|x| x.name == data.name && x.r#type == command_type as i32)
+        .cloned();
+
+    let options = data.options.map(|x| serde_json::to_value(x).expect("Failed to serialize command options!"));
+
+    match existing_command {
+        Some(existing_command) => {
+            let mut command = existing_command.into_active_model();
+
+            command.description = Set(description);
+            command.options = Set(options);
+            command.default_member_permissions = Set(data.default_member_permissions);
+            command.dm_permission = Set(data.dm_permission.unwrap_or(true));
+            command.nsfw = Set(data.nsfw.unwrap_or(false));
+            command.version = Set(Snowflake::default().generate());
+
+            Ok((command.update(conn).await.expect("Failed to access database!"), false))
+        }
+        None => {
+            let same_type = existing_commands.iter().filter(|x| x.r#type == command_type as i32).count() as u64;
+
+            if same_type >= get_command_limit(command_type) {
+                return Err(StatusCode::BAD_REQUEST);
+            }
+
+            let id = Snowflake::default().generate();
+
+            let command = application_command::ActiveModel {
+                id: Set(id),
+                application_id: Set(application_id),
+                guild_id: Set(guild_id),
+                r#type: Set(command_type as i32),
+                name: Set(data.name),
+                description: Set(description),
+                options: Set(options),
+                default_member_permissions: Set(data.default_member_permissions),
+                dm_permission: Set(data.dm_permission.unwrap_or(true)),
+                nsfw: Set(data.nsfw.unwrap_or(false)),
+                version: Set(id),
+            }
+                .insert(conn)
+                .await
+                .expect("Failed to access database!");
+
+            Ok((command, true))
+        }
+    }
+}
+
+async fn list_commands_response(
+    state: &AppState,
+    session_context: &SessionContext,
+    application_id: i64,
+    guild_id: Option<i64>,
+) -> axum::response::Response {
+    if get_managed_application(&state.conn, application_id, &session_context.user).await.is_none() {
+        return (
+            StatusCode::from(APIErrorCode::UnknownApplication),
+            throw_http_error(APIErrorCode::UnknownApplication, vec![]).await
+        ).into_response();
+    }
+
+    let commands = get_commands(&state.conn, application_id, guild_id).await;
+
+    Json(commands.into_iter().map(generate_application_command_struct).collect::<Vec<_>>()).into_response()
+}
+
+async fn new_command_response(
+    state: &AppState,
+    session_context: &SessionContext,
+    application_id: i64,
+    guild_id: Option<i64>,
+    data: ApplicationCommandReq,
+) -> axum::response::Response {
+    if get_managed_application(&state.conn, application_id, &session_context.user).await.is_none() {
+        return (
+            StatusCode::from(APIErrorCode::UnknownApplication),
+            throw_http_error(APIErrorCode::UnknownApplication, vec![]).await
+        ).into_response();
+    }
+
+    match upsert_command(&state.conn, application_id, guild_id, data).await {
+        Err(status) => status.into_response(),
+        Ok((command, created)) => (
+            if created { StatusCode::CREATED } else { StatusCode::OK },
+            Json(generate_application_command_struct(command)),
+        ).into_response(),
+    }
+}
+
+async fn overwrite_commands_response(
+    state: &AppState,
+    session_context: &SessionContext,
+    application_id: i64,
+    guild_id: Option<i64>,
+    data: Vec<ApplicationCommandReq>,
+) -> axum::response::Response {
+    if get_managed_application(&state.conn, application_id, &session_context.user).await.is_none() {
+        return (
+            StatusCode::from(APIErrorCode::UnknownApplication),
+            throw_http_error(APIErrorCode::UnknownApplication, vec![]).await
+        ).into_response();
+    }
+
+    let mut seen = HashSet::new();
+
+    // Validate everything up front so a bad command doesn't leave a half overwritten list behind
+    for i in data.iter() {
+        let command_type = match ApplicationCommandType::from_i32(i._type.unwrap_or(ApplicationCommandType::ChatInput as i32)) {
+            None => return StatusCode::BAD_REQUEST.into_response(),
+            Some(command_type) => command_type,
+        };
+
+        if !seen.insert((i.name.clone(), command_type as i32))
+            || !validate_command(command_type, &i.name, &i.description.clone().unwrap_or_default(), &i.options) {
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    }
+
+    for command_type in [ApplicationCommandType::ChatInput, ApplicationCommandType::User, ApplicationCommandType::Message] {
+        if seen.iter().filter(|x| x.1 == command_type as i32).count() as u64 > get_command_limit(command_type) {
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    }
+
+    // Anything that isn't in the new list is removed
+    for i in get_commands(&state.conn, application_id, guild_id).await {
+        if !seen.contains(&(i.name.clone(), i.r#type)) {
+            i.delete(&state.conn).await.expect("Failed to access database!");
+        }
+    }
+
+    let mut output = vec![];
+
+    for i in data {
+        match upsert_command(&state.conn, application_id, guild_id, i).await {
+            Err(status) => return status.into_response(),
+            Ok((command, _)) => output.push(generate_application_command_struct(command)),
+        }
+    }
+
+    Json(output).into_response()
+}
+
+async fn get_command_response(
+    state: &AppState,
+    session_context: &SessionContext,
+    application_id: i64,
+    guild_id: Option<i64>,
+    command_id: i64,
+) -> axum::response::Response {
+    if get_managed_application(&state.conn, application_id, &session_context.user).await.is_none() {
+        return (
+            StatusCode::from(APIErrorCode::UnknownApplication),
+            throw_http_error(APIErrorCode::UnknownApplication, vec![]).await
+        ).into_response();
+    }
+
+    match get_command(&state.conn, application_id, guild_id, command_id).await {
+        None => (
+            StatusCode::from(APIErrorCode::UnknownApplicationCommand),
+            throw_http_error(APIErrorCode::UnknownApplicationCommand, vec![]).await
+        ).into_response(),
+        Some(command) => Json(generate_application_command_struct(command)).into_response(),
+    }
+}
+
+async fn edit_command_response(
+    state: &AppState,
+    session_context: &SessionContext,
+    application_id: i64,
+    guild_id: Option<i64>,
+    command_id: i64,
+    data: EditApplicationCommandReq,
+) -> axum::response::Response {
+    if get_managed_application(&state.conn, application_id, &session_context.user).await.is_none() {
+        return (
+            StatusCode::from(APIErrorCode::UnknownApplication),
+            throw_http_error(APIErrorCode::UnknownApplication, vec![]).await
+        ).into_response();
+    }
+
+    let command = match get_command(&state.conn, application_id, guild_id, command_id).await {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownApplicationCommand),
+            throw_http_error(APIErrorCode::UnknownApplicationCommand, vec![]).await
+        ).into_response(),
+        Some(command) => command,
+    };
+
+    let command_type = ApplicationCommandType::from_i32(command.r#type).expect("Invalid command type in database!");
+
+    let name = data.name.unwrap_or(command.name.clone());
+    let description = data.description.unwrap_or(command.description.clone());
+    let options = match data.options {
+        Some(options) => Some(options),
+        None => command.options.clone().and_then(|x| serde_json::from_value(x).ok()),
+    };
+
+    if !validate_command(command_type, &name, &description, &options) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    // Names have to stay unique per type
+    let conflict = get_commands(&state.conn, application_id, guild_id)
+        .await
+        .iter()
+        .any(|x| x.id != command.id && x.name == name && x.r#type == command.r#type);
+
+    if conflict {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    let mut command = command.into_active_model();
+
+    command.name = Set(name);
+    command.description = Set(description);
+    command.options = Set(options.map(|x| serde_json::to_value(x).expect("Failed to serialize command options!")));
+
+    if let Some(default_member_permissions) = data.default_member_permissions {
+        command.default_member_permissions = Set(Some(default_member_permissions));
+    }
+
+    if let Some(dm_permission) = data.dm_permission {
+        command.dm_permission = Set(dm_permission);
+    }
+
+    if let Some(nsfw) = data.nsfw {
+        command.nsfw = Set(nsfw);
+    }
+
+    command.version = Set(Snowflake::default().generate());
+
+    let command = command.update(&state.conn).await.expect("Failed to access database!");
+
+    Json(generate_application_command_struct(command)).into_response()
+}
+
+async fn delete_command_response(
+    state: &AppState,
+    session_context: &SessionContext,
+    application_id: i64,
+    guild_id: Option<i64>,
+    command_id: i64,
+) -> axum::response::Response {
+    if get_managed_application(&state.conn, application_id, &session_context.user).await.is_none() {
+        return (
+            StatusCode::from(APIErrorCode::UnknownApplication),
+            throw_http_error(APIErrorCode::UnknownApplication, vec![]).await
+        ).into_response();
+    }
+
+    match get_command(&state.conn, application_id, guild_id, command_id).await {
+        None => (
+            StatusCode::from(APIErrorCode::UnknownApplicationCommand),
+            throw_http_error(APIErrorCode::UnknownApplicationCommand, vec![]).await
+        ).into_response(),
+        Some(command) => {
+            command.delete(&state.conn).await.expect("Failed to access database!");
+
+            StatusCode::NO_CONTENT.into_response()
+        }
+    }
+}
+
+pub async fn get_global_commands(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(application_id): Path<i64>,
+) -> impl IntoResponse {
+    list_commands_response(&state, &session_context, application_id, None).await
+}
+
+pub async fn new_global_command(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(application_id): Path<i64>,
+    Json(data): Json<ApplicationCommandReq>,
+) -> impl IntoResponse {
+    new_command_response(&state, &session_context, application_id, None, data).await
+}
+
+pub async fn overwrite_global_commands(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(application_id): Path<i64>,
+    Json(data): Json<Vec<ApplicationCommandReq>>,
+) -> impl IntoResponse {
+    overwrite_commands_response(&state, &session_context, application_id, None, data).await
+}
+
+pub async fn get_global_command(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path((application_id, command_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    get_command_response(&state, &session_context, application_id, None, command_id).await
+}
+
+pub async fn edit_global_command(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path((application_id, command_id)): Path<(i64, i64)>,
+    Json(data): Json<EditApplicationCommandReq>,
+) -> impl IntoResponse {
+    edit_command_response(&state, &session_context, application_id, None, command_id, data).await
+}
+
+pub async fn delete_global_command(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path((application_id, command_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    delete_command_response(&state, &session_context, application_id, None, command_id).await
+}
+
+// TODO: Check that the bot is actually in the guild once guilds exist
+pub async fn get_guild_commands(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path((application_id, guild_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    list_commands_response(&state, &session_context, application_id, Some(guild_id)).await
+}
+
+pub async fn new_guild_command(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path((application_id, guild_id)): Path<(i64, i64)>,
+    Json(data): Json<ApplicationCommandReq>,
+) -> impl IntoResponse {
+    new_command_response(&state, &session_context, application_id, Some(guild_id), data).await
+}
+
+pub async fn overwrite_guild_commands(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path((application_id, guild_id)): Path<(i64, i64)>,
+    Json(data): Json<Vec<ApplicationCommandReq>>,
+) -> impl IntoResponse {
+    overwrite_commands_response(&state, &session_context, application_id, Some(guild_id), data).await
+}
+
+pub async fn get_guild_command(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path((application_id, guild_id, command_id)): Path<(i64, i64, i64)>,
+) -> impl IntoResponse {
+    get_command_response(&state, &session_context, application_id, Some(guild_id), command_id).await
+}
+
+pub async fn edit_guild_command(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path((application_id, guild_id, command_id)): Path<(i64, i64, i64)>,
+    Json(data): Json<EditApplicationCommandReq>,
+) -> impl IntoResponse {
+    edit_command_response(&state, &session_context, application_id, Some(guild_id), command_id, data).await
+}
+
+pub async fn delete_guild_command(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path((application_id, guild_id, command_id)): Path<(i64, i64, i64)>,
+) -> impl IntoResponse {
+    delete_command_response(&state, &session_context, application_id, Some(guild_id), command_id).await
+}
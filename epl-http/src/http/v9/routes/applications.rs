@@ -0,0 +1,379 @@
+use axum::{Extension, Json};
+use axum::extract::Path;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use rand::Rng;
+use rand::rngs::StdRng;
+use sea_orm::*;
+use sea_orm::ActiveValue::Set;
+use sea_orm::sea_query::Expr;
+use serde_derive::{Deserialize, Serialize};
+use url::Url;
+use epl_common::database::auth::{create_user, generate_bot_session};
+use epl_common::database::entities::{application, session, user};
+use epl_common::database::entities::prelude::{Application, Session, User};
+use epl_common::gen_token;
+use epl_common::interactions::{generate_interaction_keypair, validate_interactions_endpoint};
+use epl_common::nats::Messages::InvalidateGatewaySession;
+use epl_common::nats::send_nats_message;
+use epl_common::rustflake::Snowflake;
+use epl_common::schema::v9::application::{generate_private_application_struct, PrivateApplication};
+use crate::AppState;
+use crate::authorization_extractor::SessionContext;
+use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+use crate::http::v9::routes::mfa::require_mfa;
+
+/// Gets an application if it exists and is owned by the specified user
+pub async fn get_owned_application(
+    conn: &DatabaseConnection,
+    application_id: i64,
+    user_id: i64,
+) -> Option<application::Model> {
+    Application::find_by_id(application_id)
+        .filter(application::Column::OwnerId.eq(user_id))
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+async fn assemble_private_application(
+    conn: &DatabaseConnection,
+    application: application::Model,
+    owner: user::Model,
+) -> PrivateApplication {
+    let bot = match application.bot_user_id {
+        None => None,
+        Some(bot_user_id) => User::find_by_id(bot_user_id)
+            .one(conn)
+            .await
+            .expect("Failed to access database!"),
+    };
+
+    generate_private_application_struct(application, bot, owner)
+}
+
+pub async fn get_applications(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+) -> impl IntoResponse {
+    let applications: Vec<application::Model> = Application::find()
+        .filter(application::Column::OwnerId.eq(session_context.user.id))
+        .all(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    let mut output = vec![];
+
+    for i in applications {
+        output.push(assemble_private_application(&state.conn, i, session_context.user.clone()).await);
+    }
+
+    Json(output)
+}
+
+#[derive(Deserialize)]
+pub struct NewApplicationReq {
+    name: String,
+    team_id: Option<String>,
+}
+
+pub async fn new_application(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Json(data): Json<NewApplicationReq>,
+) -> impl IntoResponse {
+    // TODO: Teams
+    if data.team_id.is_some() || data.name.trim().is_empty() || data.name.len() > 32 {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    let (signing_key, verify_key) = generate_interaction_keypair();
+
+    let new_application = application::ActiveModel {
+        id: Set(Snowflake::default().generate()),
+        name: Set(data.name),
+        description: Set(String::new()),
+        icon: Set(None),
+        owner_id: Set(session_context.user.id),
+        bot_user_id: Set(None),
+        bot_public: Set(true),
+        bot_require_code_grant: Set(false),
+        secret: Set(gen_token()),
+        redirect_uris: Set(vec![]),
+        flags: Set(0),
+        interactions_endpoint_url: Set(None),
+        verify_key: Set(Some(verify_key)),
+        signing_key: Set(Some(signing_key)),
+    }
+        .insert(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    (
+        StatusCode::CREATED,
+        Json(assemble_private_application(&state.conn, new_application, session_context.user).await)
+    ).into_response()
+}
+
+pub async fn get_application(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(application_id): Path<i64>,
+) -> impl IntoResponse {
+    match get_owned_application(&state.conn, application_id, session_context.user.id).await {
+        None => (
+            StatusCode::from(APIErrorCode::UnknownApplication),
+            throw_http_error(APIErrorCode::UnknownApplication, vec![]).await
+        ).into_response(),
+        Some(application) => {
+            Json(assemble_private_application(&state.conn, application, session_context.user).await).into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ModifyApplicationReq {
+    name: Option<String>,
+    description: Option<String>,
+    redirect_uris: Option<Vec<String>>,
+    bot_public: Option<bool>,
+    bot_require_code_grant: Option<bool>,
+    interactions_endpoint_url: Option<String>,
+}
+
+pub async fn modify_application(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(application_id): Path<i64>,
+    Json(data): Json<ModifyApplicationReq>,
+) -> impl IntoResponse {
+    let application = match get_owned_application(&state.conn, application_id, session_context.user.id).await {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownApplication),
+            throw_http_error(APIErrorCode::UnknownApplication, vec![]).await
+        ).into_response(),
+        Some(application) => application,
+    };
+
+    let bot_user_id = application.bot_user_id;
+    let signing_key = application.signing_key.clone();
+    let mut application = application.into_active_model();
+
+    if let Some(name) = data.name {
+        if name.trim().is_empty() || name.len() > 32 {
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+
+        // Keep the bot's username in sync with the application
+        if let Some(bot_user_id) = bot_user_id {
+            User::update_many()
+                .col_expr(user::Column::Username, Expr::value(name.clone()))
+                .filter(user::Column::Id.eq(bot_user_id))
+                .exec(&state.conn)
+                .await
+                .expect("Failed to access database!");
+        }
+
+        application.name = Set(name);
+    }
+
+    if let Some(description) = data.description {
+        if description.len() > 400 {
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+
+        application.description = Set(description);
+    }
+
+    if let Some(redirect_uris) = data.redirect_uris {
+        // Only absolute URLs are allowed as redirects
+        if redirect_uris.len() > 10 || redirect_uris.iter().any(|x| Url::parse(x).is_err()) {
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+
+        application.redirect_uris = Set(redirect_uris);
+    }
+
+    if let Some(bot_public) = data.bot_public {
+        application.bot_public = Set(bot_public);
+    }
+
+    if let Some(bot_require_code_grant) = data.bot_require_code_grant {
+        application.bot_require_code_grant = Set(bot_require_code_grant);
+    }
+
+    if let Some(interactions_endpoint_url) = data.interactions_endpoint_url {
+        if interactions_endpoint_url.is_empty() {
+            application.interactions_endpoint_url = Set(None);
+        } else {
+            if Url::parse(&interactions_endpoint_url).is_err() {
+                return StatusCode::BAD_REQUEST.into_response();
+            }
+
+            // Applications made before interactions existed don't have a keypair yet
+            let signing_key = match signing_key {
+                Some(signing_key) => signing_key,
+                None => {
+                    let (signing_key, verify_key) = generate_interaction_keypair();
+
+                    application.signing_key = Set(Some(signing_key.clone()));
+                    application.verify_key = Set(Some(verify_key));
+
+                    signing_key
+                }
+            };
+
+            let url = interactions_endpoint_url.clone();
+
+            let valid = tokio::task::spawn_blocking(move || {
+                validate_interactions_endpoint(&url, application_id, &signing_key)
+            })
+                .await
+                .unwrap_or(false);
+
+            if !valid {
+                return StatusCode::BAD_REQUEST.into_response();
+            }
+
+            application.interactions_endpoint_url = Set(Some(interactions_endpoint_url));
+        }
+    }
+
+    let application = application.update(&state.conn).await.expect("Failed to access database!");
+
+    Json(assemble_private_application(&state.conn, application, session_context.user).await).into_response()
+}
+
+#[derive(Serialize)]
+pub struct BotTokenRes {
+    token: String,
+}
+
+pub async fn new_bot(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(application_id): Path<i64>,
+) -> impl IntoResponse {
+    let application = match get_owned_application(&state.conn, application_id, session_context.user.id).await {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownApplication),
+            throw_http_error(APIErrorCode::UnknownApplication, vec![]).await
+        ).into_response(),
+        Some(application) => application,
+    };
+
+    // Applications can only have one bot
+    if application.bot_user_id.is_some() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    let mut rng: StdRng = rand::SeedableRng::from_entropy();
+
+    // Bots can't log in, so they get no email or password
+    let bot_user = user::ActiveModel {
+        id: Set(Snowflake::default().generate()),
+        system: Set(false),
+        bot: Set(true),
+        username: Set(application.name.clone()),
+        password_hash: Set(String::new()),
+        discriminator: Set(rng.gen_range(1..9999).to_string()),
+        email: Set(String::new()),
+        mfa_enabled: Set(false),
+        acct_verified: Set(true),
+        flags: Set(0),
+        nsfw_allowed: Set(false),
+        ..Default::default()
+    };
+
+    let bot_user_id = match create_user(&state.conn, bot_user).await {
+        Ok(bot_user_id) => bot_user_id,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    let mut application = application.into_active_model();
+
+    application.bot_user_id = Set(Some(bot_user_id));
+
+    application.update(&state.conn).await.expect("Failed to access database!");
+
+    match generate_bot_session(&state.conn, bot_user_id).await {
+        Ok(token) => Json(BotTokenRes { token }).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+pub async fn reset_bot_token(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(application_id): Path<i64>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Some(res) = require_mfa(&state.conn, &headers, &session_context.user).await {
+        return res;
+    }
+
+    let application = match get_owned_application(&state.conn, application_id, session_context.user.id).await {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownApplication),
+            throw_http_error(APIErrorCode::UnknownApplication, vec![]).await
+        ).into_response(),
+        Some(application) => application,
+    };
+
+    let bot_user_id = match application.bot_user_id {
+        None => return StatusCode::BAD_REQUEST.into_response(),
+        Some(bot_user_id) => bot_user_id,
+    };
+
+    // Throw out every existing token for the bot and kick it off the gateway
+    Session::delete_many()
+        .filter(session::Column::UserId.eq(bot_user_id))
+        .exec(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    send_nats_message(
+        &state.nats_client,
+        bot_user_id.to_string(),
+        InvalidateGatewaySession { session: "all".to_string() }
+    ).await;
+
+    match generate_bot_session(&state.conn, bot_user_id).await {
+        Ok(token) => Json(BotTokenRes { token }).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[derive(Serialize)]
+pub struct SecretRes {
+    secret: String,
+}
+
+pub async fn reset_secret(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(application_id): Path<i64>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Some(res) = require_mfa(&state.conn, &headers, &session_context.user).await {
+        return res;
+    }
+
+    let application = match get_owned_application(&state.conn, application_id, session_context.user.id).await {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownApplication),
+            throw_http_error(APIErrorCode::UnknownApplication, vec![]).await
+        ).into_response(),
+        Some(application) => application,
+    };
+
+    let secret = gen_token();
+
+    let mut application = application.into_active_model();
+
+    application.secret = Set(secret.clone());
+
+    application.update(&state.conn).await.expect("Failed to access database!");
+
+    Json(SecretRes { secret }).into_response()
+}
@@ -1,8 +1,9 @@
 use argon2::{Argon2, PasswordHash, PasswordVerifier};
 use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::response::{IntoResponse, Response};
 use axum::{Extension, Json};
-use chrono::Datelike;
+use axum_client_ip::SecureClientIp;
+use chrono::{Datelike, Duration};
 use epl_common::options::{EplOptions, Options};
 use rand::rngs::StdRng;
 use rand::Rng;
@@ -14,19 +15,121 @@ use tracing::info;
 use epl_common::database::entities::{prelude::*, *};
 
 use crate::authorization_extractor::SessionContext;
-use crate::http::v9::errors::{throw_http_error, APIErrorCode, APIErrorField, APIErrorMessage};
+use crate::http::v9::errors::{throw_http_error, APIError, APIErrorCode, APIErrorField, APIErrorMessage};
 use epl_common::nats::send_nats_message;
 use crate::AppState;
 use epl_common::database::auth::{
-    create_user, generate_password_hash, generate_session, get_all_sessions, get_session_by_id,
-    NewUserEnum,
+    create_ticket_with_state, create_user, generate_password_hash, generate_session,
+    get_all_sessions, get_session_by_id, verify_ticket_with_state, NewUserEnum,
+    NewUserError, TicketType,
 };
 use epl_common::flags::{get_user_flags, UserFlags};
+use epl_common::get_country_from_ip;
+use epl_common::invites::{is_instance_invite_usable, use_instance_invite};
+use epl_common::logins::{get_login_attempts, get_login_location, is_known_location, is_locked_out, record_login_attempt, LoginLocation, LoginOutcome};
+use epl_common::mail::{instance_link, queue_mail, Mail};
+use epl_common::mfa::{start_webauthn_authentication, verify_mfa_code};
+use epl_common::moderation::get_suspension;
+use crate::http::v9::routes::mfa::verify_webauthn_assertion;
+use crate::http::v9::routes::users::rotate_sessions;
 use epl_common::nats::Messages;
+use epl_common::registration::{is_pending_approval, request_approval, RegistrationMode};
 use epl_common::rustflake;
+use epl_common::system::send_system_message;
 
-pub async fn location_metadata() -> &'static str {
-    ""
+#[derive(Serialize)]
+pub struct LocationMetadataRes {
+    consent_required: bool,
+    country_code: Option<String>,
+    promotional_email_opt_in: PromotionalEmailOptIn,
+}
+
+#[derive(Serialize)]
+pub struct PromotionalEmailOptIn {
+    required: bool,
+    pre_checked: bool,
+}
+
+pub async fn location_metadata(
+    SecureClientIp(ip): SecureClientIp,
+) -> impl IntoResponse {
+    // We never send promotional emails, so there's nothing to consent to
+    Json(LocationMetadataRes {
+        consent_required: false,
+        country_code: get_country_from_ip(ip),
+        promotional_email_opt_in: PromotionalEmailOptIn {
+            required: false,
+            pre_checked: false,
+        },
+    })
+}
+
+/// Turns away logins for an account that failed to log in too many times
+pub(crate) async fn check_lockout(state: &AppState, user_id: i64, location: &LoginLocation) -> Option<Response> {
+    if !is_locked_out(&state.conn, user_id).await {
+        return None;
+    }
+
+    record_login_attempt(&state.conn, user_id, location, LoginOutcome::LockedOut).await;
+
+    Some((
+        StatusCode::TOO_MANY_REQUESTS,
+        throw_http_error(APIErrorCode::InvalidFormBody, vec![APIErrorField::Login {
+            _errors: vec![APIErrorMessage {
+                code: "TOO_MANY_LOGIN_ATTEMPTS".to_string(),
+                message: "Too many failed login attempts, try again later.".to_string(),
+            }],
+        }]).await,
+    ).into_response())
+}
+
+/// Maps a rejected password to the error shown under the password field
+pub(crate) fn password_error(err: &NewUserError) -> Option<APIErrorField> {
+    let message = match err.kind {
+        NewUserEnum::BadPassword => "Password is invalid",
+        NewUserEnum::TooShortPassword => "Password must be over 8 characters long",
+        NewUserEnum::TooLongPassword => "Password must be under 999 characters long",
+        NewUserEnum::WeakPassword => "Password is too weak or common to use",
+        _ => return None,
+    };
+
+    Some(APIErrorField::Password {
+        _errors: vec![APIErrorMessage {
+            code: "INVALID_PASSWORD".to_string(),
+            message: message.to_string(),
+        }],
+    })
+}
+
+/// Accounts waiting in the approval queue can't get a session until staff approve them
+async fn pending_approval_error() -> (StatusCode, Json<APIError>) {
+    (
+        StatusCode::BAD_REQUEST,
+        throw_http_error(APIErrorCode::InvalidFormBody, vec![APIErrorField::Email {
+            _errors: vec![APIErrorMessage {
+                code: "ACCOUNT_PENDING_APPROVAL".to_string(),
+                message: "Your account is waiting to be approved by the staff of this instance".to_string(),
+            }],
+        }]).await,
+    )
+}
+
+/// Suspended and banned accounts are turned away until the suspension is over
+async fn suspended_error(suspension: &moderation_action::Model) -> (StatusCode, Json<APIError>) {
+    let message = match suspension.expires_at {
+        Some(expires_at) => format!("Your account has been suspended until {}: {}", expires_at.format("%Y-%m-%d %H:%M UTC"), suspension.reason),
+        None => format!("Your account has been permanently suspended: {}", suspension.reason),
+    };
+
+    (
+        StatusCode::BAD_REQUEST,
+        throw_http_error(APIErrorCode::InvalidFormBody, vec![APIErrorField::Login {
+            _errors: vec![APIErrorMessage {
+                code: "ACCOUNT_SUSPENDED".to_string(),
+                message,
+            }],
+        }]).await,
+    )
 }
 
 #[derive(Deserialize)]
@@ -49,13 +152,16 @@ pub struct RegisterResponse {
 
 pub async fn register(
     Extension(state): Extension<AppState>,
+    SecureClientIp(ip): SecureClientIp,
     data: Json<RegisterRequest>,
 ) -> impl IntoResponse {
     let options = EplOptions::get();
     let mut error = Vec::new();
 
+    let invite = data.0.invite.clone().filter(|x| !x.is_empty());
+
     // Exit early if registration is disabled
-    if !options.registration {
+    if options.registration == RegistrationMode::Closed {
         error.push(APIErrorField::Email {
             _errors: vec![APIErrorMessage {
                 code: "REGISTRATION_DISABLED".to_string(),
@@ -69,47 +175,27 @@ pub async fn register(
         ));
     }
 
+    if options.registration == RegistrationMode::InviteOnly && invite.is_none() {
+        error.push(APIErrorField::Invite {
+            _errors: vec![APIErrorMessage {
+                code: "INVITE_REQUIRED".to_string(),
+                message: "An invite is required to register on this instance".to_string(),
+            }],
+        });
+
+        return Err((
+            StatusCode::BAD_REQUEST,
+            throw_http_error(APIErrorCode::InvalidFormBody, error).await,
+        ));
+    }
+
     let password_hash = generate_password_hash(
         &data.password,
         vec![data.0.username.as_str(), data.0.email.as_str()],
     );
 
-    if password_hash.is_err() {
-        match password_hash.clone().err().unwrap().kind {
-            NewUserEnum::BadPassword => {
-                error.push(APIErrorField::Password {
-                    _errors: vec![APIErrorMessage {
-                        code: "INVALID_PASSWORD".to_string(),
-                        message: "Password is invalid".to_string(),
-                    }],
-                });
-            }
-            NewUserEnum::TooShortPassword => {
-                error.push(APIErrorField::Password {
-                    _errors: vec![APIErrorMessage {
-                        code: "INVALID_PASSWORD".to_string(),
-                        message: "Password must be over 8 characters long".to_string(),
-                    }],
-                });
-            }
-            NewUserEnum::TooLongPassword => {
-                error.push(APIErrorField::Password {
-                    _errors: vec![APIErrorMessage {
-                        code: "INVALID_PASSWORD".to_string(),
-                        message: "Password must be under 999 characters long".to_string(),
-                    }],
-                });
-            }
-            NewUserEnum::WeakPassword => {
-                error.push(APIErrorField::Password {
-                    _errors: vec![APIErrorMessage {
-                        code: "INVALID_PASSWORD".to_string(),
-                        message: "Password is too weak or common to use".to_string(),
-                    }],
-                });
-            }
-            _ => {}
-        }
+    if let Err(err) = &password_hash {
+        error.extend(password_error(err));
     }
 
     let date_of_birth = chrono::NaiveDate::parse_from_str(&data.0.date_of_birth, "%Y-%m-%d")
@@ -135,6 +221,29 @@ pub async fn register(
 
     let password_hash = password_hash.unwrap();
 
+    // Only instance invites count here, open instances don't need one so anything else is ignored.
+    // The invite is only used up once the account exists
+    let invited = match &invite {
+        Some(code) if options.registration != RegistrationMode::Open => {
+            if !is_instance_invite_usable(&state.conn, code).await {
+                error.push(APIErrorField::Invite {
+                    _errors: vec![APIErrorMessage {
+                        code: "INVALID_INVITE".to_string(),
+                        message: "This invite is invalid or has expired".to_string(),
+                    }],
+                });
+
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    throw_http_error(APIErrorCode::InvalidFormBody, error).await,
+                ));
+            }
+
+            true
+        }
+        _ => false,
+    };
+
     let mut snowflake_factory = rustflake::Snowflake::default();
     let mut rng: StdRng = rand::SeedableRng::from_entropy();
 
@@ -154,18 +263,21 @@ pub async fn register(
     // Check if NSFW channels should be allowed
     let nsfw_allowed = date_of_birth.unwrap().year() < (chrono::Local::now().year() - 18);
 
+    let username = data.0.username.clone();
+    let email = data.0.email.clone();
+
     let new_user = user::ActiveModel {
         id: ActiveValue::Set(new_user_id),
         system: Default::default(),
         bot: Default::default(),
-        username: ActiveValue::Set(data.0.username),
+        username: ActiveValue::Set(username.clone()),
         discriminator: ActiveValue::Set(new_user_discriminator.to_string()),
         bio: Default::default(),
         pronouns: Default::default(),
         avatar: Default::default(),
         avatar_decoration: Default::default(),
         banner: Default::default(),
-        email: ActiveValue::Set(data.0.email),
+        email: ActiveValue::Set(email.clone()),
         phone: Default::default(),
         mfa_enabled: Default::default(),
         acct_verified: Default::default(),
@@ -181,9 +293,14 @@ pub async fn register(
         accent_color: Default::default(),
         display_name: Set(display_name),
         legacy_name: Default::default(),
+        totp_secret: Default::default(),
+        verification_sent_at: Default::default(),
+        deletion_scheduled_at: Default::default(),
     };
 
-    let user = create_user(&state.conn, new_user).await;
+    let txn = state.conn.begin().await.expect("Failed to access database!");
+
+    let user = create_user(&txn, new_user).await;
 
     let user_id = match user {
         Ok(user) => user,
@@ -202,6 +319,38 @@ pub async fn register(
         }
     };
 
+    // Someone else may have taken the invite's last use since it was checked
+    if let Some(code) = invite.as_ref().filter(|_| invited) {
+        if !use_instance_invite(&txn, code).await {
+            error.push(APIErrorField::Invite {
+                _errors: vec![APIErrorMessage {
+                    code: "INVALID_INVITE".to_string(),
+                    message: "This invite is invalid or has expired".to_string(),
+                }],
+            });
+
+            return Err((
+                StatusCode::BAD_REQUEST,
+                throw_http_error(APIErrorCode::InvalidFormBody, error).await,
+            ));
+        }
+    }
+
+    txn.commit().await.expect("Failed to access database!");
+
+    send_verification_email(&state, user_id, &username, &email).await;
+
+    // Where the account was made from is the first known login location
+    record_login_attempt(&state.conn, user_id, &get_login_location(ip), LoginOutcome::Success).await;
+
+    if options.registration == RegistrationMode::Approval && !invited {
+        request_approval(&state.conn, user_id).await;
+
+        info!("New account waiting for approval: {}", user_id);
+
+        return Err(pending_approval_error().await);
+    }
+
     let token = generate_session(&state.conn, user_id).await.unwrap();
 
     info!("New account registered: {}", user_id);
@@ -221,8 +370,62 @@ pub struct LoginRes {
     pub token: String,
 }
 
+/// Sent instead of a token when the user has MFA on, the ticket is exchanged for a token with a code
+#[derive(Serialize)]
+pub struct MfaLoginRes {
+    pub user_id: String,
+    pub mfa: bool,
+    pub sms: bool,
+    pub backup: bool,
+    pub totp: bool,
+    /// The WebAuthn assertion options as a JSON string, if the user has any security keys
+    pub webauthn: Option<String>,
+    pub ticket: String,
+}
+
+/// Carried over from the password step to the MFA step in the login ticket
+#[derive(Serialize, Deserialize, Default)]
+struct LoginTicketState {
+    undelete: bool,
+    webauthn: Option<serde_json::Value>,
+}
+
+fn verify_login_ticket(ticket: &str) -> Option<(i64, LoginTicketState)> {
+    let (user_id, ticket_state) = verify_ticket_with_state(ticket, TicketType::Login)?;
+
+    Some((user_id, ticket_state.and_then(|x| serde_json::from_value(x).ok()).unwrap_or_default()))
+}
+
+/// Re-enables a disabled account and cancels its deletion, only done once a session is actually handed out
+async fn undelete_account(state: &AppState, user: &user::Model) {
+    let disabled = get_user_flags(user.flags).contains(&UserFlags::Disabled);
+
+    if !disabled && user.deletion_scheduled_at.is_none() {
+        return;
+    }
+
+    let mut active_user = user.clone().into_active_model();
+
+    active_user.flags = Set(user.flags & !(UserFlags::Disabled as i64));
+    active_user.deletion_scheduled_at = Set(None);
+
+    active_user.update(&state.conn).await.expect("Failed to update user!");
+
+    // Logging back in during the grace period cancels the deletion
+    if user.deletion_scheduled_at.is_some() {
+        send_system_message(
+            &state.conn,
+            &state.nats_client,
+            user.id,
+            "Welcome back! Your account is no longer scheduled for deletion.".to_string(),
+            false,
+        ).await;
+    }
+}
+
 pub async fn login(
     Extension(state): Extension<AppState>,
+    SecureClientIp(ip): SecureClientIp,
     data: Json<LoginReq>,
 ) -> impl IntoResponse {
     let mut error = Vec::new();
@@ -230,6 +433,7 @@ pub async fn login(
     // Check if user exists
     let requested_user: Option<user::Model> = User::find()
         .filter(user::Column::Email.eq(&data.login))
+        .filter(user::Column::Bot.eq(false))
         .one(&state.conn)
         .await
         .expect("Failed to access database!");
@@ -258,6 +462,12 @@ pub async fn login(
         Some(user) => user,
     };
 
+    let location = get_login_location(ip);
+
+    if let Some(res) = check_lockout(&state, requested_user.id, &location).await {
+        return Ok(res);
+    }
+
     // Verify password
     let password_hash =
         PasswordHash::new(&requested_user.password_hash).expect("Failed to parse password hash!");
@@ -266,6 +476,8 @@ pub async fn login(
         .verify_password(data.password.as_bytes(), &password_hash)
         .is_err()
     {
+        record_login_attempt(&state.conn, requested_user.id, &location, LoginOutcome::InvalidPassword).await;
+
         error.push(APIErrorField::Email {
             _errors: vec![APIErrorMessage {
                 code: "INVALID_LOGIN".to_string(),
@@ -286,20 +498,104 @@ pub async fn login(
         ));
     }
 
-    // Check if user has the disabled bitflag
-    if get_user_flags(requested_user.flags).contains(&UserFlags::Disabled) {
-        if data.undelete {
-            let mut user = requested_user.clone().into_active_model();
-            
-            user.flags = Set(requested_user.flags - UserFlags::Disabled as i64);
-            
-            user.update(&state.conn).await.expect("Failed to update user!");
-        } else {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                throw_http_error(APIErrorCode::DisabledAccount, vec![]).await,
-            )); 
-        }
+    // Check if user has the disabled bitflag, it's only cleared once the login goes through
+    if get_user_flags(requested_user.flags).contains(&UserFlags::Disabled) && !data.undelete {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            throw_http_error(APIErrorCode::DisabledAccount, vec![]).await,
+        ));
+    }
+
+    if let Some(suspension) = get_suspension(&state.conn, requested_user.id).await {
+        record_login_attempt(&state.conn, requested_user.id, &location, LoginOutcome::Suspended).await;
+
+        return Err(suspended_error(&suspension).await);
+    }
+
+    if is_pending_approval(&state.conn, requested_user.id).await {
+        return Err(pending_approval_error().await);
+    }
+
+    if requested_user.deletion_scheduled_at.is_some() && !data.undelete {
+        return Err((
+            StatusCode::from(APIErrorCode::AccountScheduledForDeletion),
+            throw_http_error(APIErrorCode::AccountScheduledForDeletion, vec![]).await,
+        ));
+    }
+
+    // Logins from somewhere new have to be approved over email first
+    if !is_known_location(&state.conn, requested_user.id, &location).await {
+        record_login_attempt(&state.conn, requested_user.id, &location, LoginOutcome::NewLocation).await;
+
+        let ticket = create_ticket_with_state(
+            requested_user.id,
+            TicketType::LoginApproval,
+            Duration::hours(1),
+            Some(serde_json::json!({
+                "ip": location.ip,
+                "country": location.country,
+                "asn": location.asn,
+                "location": location.location,
+            })),
+        );
+
+        queue_mail(
+            &state.nats_client,
+            requested_user.email.clone(),
+            Mail::NewLogin {
+                username: requested_user.username.clone(),
+                ip: location.ip.clone(),
+                location: location.location.clone(),
+                platform: "Unknown".to_string(),
+                approve_url: Some(instance_link(&format!("/authorize-ip#token={}", ticket))),
+            },
+        ).await;
+
+        error.push(APIErrorField::Login {
+            _errors: vec![APIErrorMessage {
+                code: "ACCOUNT_LOGIN_VERIFICATION_EMAIL".to_string(),
+                message: "New login location detected, please check your e-mail.".to_string(),
+            }],
+        });
+
+        return Err((
+            StatusCode::BAD_REQUEST,
+            throw_http_error(APIErrorCode::InvalidFormBody, error).await,
+        ));
+    }
+
+    if requested_user.mfa_enabled {
+        let (webauthn, webauthn_state) = match start_webauthn_authentication(&state.conn, requested_user.id).await {
+            None => (None, None),
+            Some((challenge, webauthn_state)) => (
+                Some(serde_json::to_string(&challenge).expect("Failed to encode WebAuthn challenge!")),
+                Some(serde_json::to_value(webauthn_state).expect("Failed to encode WebAuthn state!")),
+            ),
+        };
+
+        return Ok(Json(MfaLoginRes {
+            user_id: requested_user.id.to_string(),
+            mfa: true,
+            sms: false,
+            backup: true,
+            totp: requested_user.totp_secret.is_some(),
+            webauthn,
+            ticket: create_ticket_with_state(
+                requested_user.id,
+                TicketType::Login,
+                Duration::minutes(5),
+                Some(serde_json::to_value(LoginTicketState {
+                    undelete: data.undelete,
+                    webauthn: webauthn_state,
+                }).expect("Failed to encode login ticket!")),
+            ),
+        }).into_response());
+    }
+
+    record_login_attempt(&state.conn, requested_user.id, &location, LoginOutcome::Success).await;
+
+    if data.undelete {
+        undelete_account(&state, &requested_user).await;
     }
 
     // Generate session
@@ -307,7 +603,328 @@ pub async fn login(
         .await
         .unwrap();
 
-    Ok(Json(LoginRes { token }))
+    Ok(Json(LoginRes { token }).into_response())
+}
+
+#[derive(Deserialize)]
+pub struct MfaLoginReq {
+    pub code: String,
+    pub ticket: String,
+}
+
+pub async fn totp_login(
+    Extension(state): Extension<AppState>,
+    SecureClientIp(ip): SecureClientIp,
+    data: Json<MfaLoginReq>,
+) -> impl IntoResponse {
+    let (user_id, ticket_state) = match verify_login_ticket(&data.ticket) {
+        None => return (
+            StatusCode::from(APIErrorCode::InvalidFormBody),
+            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await,
+        ).into_response(),
+        Some(ticket) => ticket,
+    };
+
+    let requested_user = match User::find_by_id(user_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!") {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownUser),
+            throw_http_error(APIErrorCode::UnknownUser, vec![]).await,
+        ).into_response(),
+        Some(user) => user,
+    };
+
+    let location = get_login_location(ip);
+
+    if let Some(res) = check_lockout(&state, user_id, &location).await {
+        return res;
+    }
+
+    if !verify_mfa_code(&state.conn, &requested_user, &data.code).await {
+        record_login_attempt(&state.conn, user_id, &location, LoginOutcome::InvalidTwoFactorCode).await;
+
+        return (
+            StatusCode::from(APIErrorCode::InvalidTwoFactorCode),
+            throw_http_error(APIErrorCode::InvalidTwoFactorCode, vec![]).await,
+        ).into_response();
+    }
+
+    record_login_attempt(&state.conn, user_id, &location, LoginOutcome::Success).await;
+
+    if ticket_state.undelete {
+        undelete_account(&state, &requested_user).await;
+    }
+
+    let token = generate_session(&state.conn, requested_user.id)
+        .await
+        .unwrap();
+
+    Json(LoginRes { token }).into_response()
+}
+
+pub async fn webauthn_login(
+    Extension(state): Extension<AppState>,
+    SecureClientIp(ip): SecureClientIp,
+    data: Json<MfaLoginReq>,
+) -> impl IntoResponse {
+    let (user_id, ticket_state) = match verify_login_ticket(&data.ticket) {
+        None => return (
+            StatusCode::from(APIErrorCode::InvalidFormBody),
+            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await,
+        ).into_response(),
+        Some(ticket) => ticket,
+    };
+
+    let requested_user = match User::find_by_id(user_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!") {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownUser),
+            throw_http_error(APIErrorCode::UnknownUser, vec![]).await,
+        ).into_response(),
+        Some(user) => user,
+    };
+
+    let location = get_login_location(ip);
+
+    if let Some(res) = check_lockout(&state, user_id, &location).await {
+        return res;
+    }
+
+    // The code is the assertion from the security key
+    if !verify_webauthn_assertion(&state.conn, user_id, &data.code, ticket_state.webauthn).await {
+        record_login_attempt(&state.conn, user_id, &location, LoginOutcome::InvalidTwoFactorCode).await;
+
+        return (
+            StatusCode::from(APIErrorCode::InvalidTwoFactorCode),
+            throw_http_error(APIErrorCode::InvalidTwoFactorCode, vec![]).await,
+        ).into_response();
+    }
+
+    record_login_attempt(&state.conn, user_id, &location, LoginOutcome::Success).await;
+
+    if ticket_state.undelete {
+        undelete_account(&state, &requested_user).await;
+    }
+
+    let token = generate_session(&state.conn, user_id)
+        .await
+        .unwrap();
+
+    Json(LoginRes { token }).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ForgotReq {
+    pub login: String,
+}
+
+/// Reset links carry a digest of the current password hash, so they stop working once the password changes
+fn password_reset_state(user: &user::Model) -> serde_json::Value {
+    serde_json::Value::String(sha256::digest(user.password_hash.as_str()))
+}
+
+pub async fn forgot_password(
+    Extension(state): Extension<AppState>,
+    data: Json<ForgotReq>,
+) -> impl IntoResponse {
+    let requested_user = User::find()
+        .filter(user::Column::Email.eq(&data.login))
+        .filter(user::Column::Bot.eq(false))
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    // Don't let this be used to find out which emails have accounts
+    let requested_user = match requested_user {
+        None => return StatusCode::NO_CONTENT,
+        Some(user) => user,
+    };
+
+    let ticket = create_ticket_with_state(
+        requested_user.id,
+        TicketType::PasswordReset,
+        Duration::hours(1),
+        Some(password_reset_state(&requested_user)),
+    );
+
+    queue_mail(
+        &state.nats_client,
+        requested_user.email.clone(),
+        Mail::PasswordReset {
+            username: requested_user.username.clone(),
+            url: instance_link(&format!("/reset#token={}", ticket)),
+        },
+    ).await;
+
+    StatusCode::NO_CONTENT
+}
+
+#[derive(Deserialize)]
+pub struct ResetReq {
+    pub token: String,
+    pub password: String,
+    /// A TOTP or backup code, required if the user has MFA on
+    pub code: Option<String>,
+}
+
+pub async fn reset_password(
+    Extension(state): Extension<AppState>,
+    SecureClientIp(ip): SecureClientIp,
+    data: Json<ResetReq>,
+) -> impl IntoResponse {
+    let (user_id, reset_state) = match verify_ticket_with_state(&data.token, TicketType::PasswordReset) {
+        Some((user_id, Some(reset_state))) => (user_id, reset_state),
+        _ => return (
+            StatusCode::from(APIErrorCode::InvalidFormBody),
+            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await,
+        ).into_response(),
+    };
+
+    let requested_user = User::find_by_id(user_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    let requested_user = match requested_user {
+        Some(user) if password_reset_state(&user) == reset_state => user,
+        _ => return (
+            StatusCode::from(APIErrorCode::InvalidFormBody),
+            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await,
+        ).into_response(),
+    };
+
+    if requested_user.mfa_enabled {
+        match &data.code {
+            None => return (
+                StatusCode::from(APIErrorCode::TwoFactorRequired),
+                throw_http_error(APIErrorCode::TwoFactorRequired, vec![]).await,
+            ).into_response(),
+            Some(code) => {
+                let location = get_login_location(ip);
+
+                // Reset tickets last an hour, which is plenty of time to guess a code without the lockout
+                if let Some(res) = check_lockout(&state, user_id, &location).await {
+                    return res;
+                }
+
+                if !verify_mfa_code(&state.conn, &requested_user, code).await {
+                    record_login_attempt(&state.conn, user_id, &location, LoginOutcome::InvalidTwoFactorCode).await;
+
+                    return (
+                        StatusCode::from(APIErrorCode::InvalidTwoFactorCode),
+                        throw_http_error(APIErrorCode::InvalidTwoFactorCode, vec![]).await,
+                    ).into_response();
+                }
+            }
+        }
+    }
+
+    let password_hash = match generate_password_hash(
+        &data.password,
+        vec![requested_user.username.as_str(), requested_user.email.as_str()],
+    ) {
+        Ok(password_hash) => password_hash,
+        Err(err) => return (
+            StatusCode::BAD_REQUEST,
+            throw_http_error(APIErrorCode::InvalidFormBody, password_error(&err).into_iter().collect()).await,
+        ).into_response(),
+    };
+
+    let mut user = requested_user.into_active_model();
+
+    user.password_hash = Set(password_hash);
+
+    user.update(&state.conn).await.expect("Failed to access database!");
+
+    if let Some(suspension) = get_suspension(&state.conn, user_id).await {
+        return suspended_error(&suspension).await.into_response();
+    }
+
+    if is_pending_approval(&state.conn, user_id).await {
+        return pending_approval_error().await.into_response();
+    }
+
+    let token = rotate_sessions(&state, user_id).await;
+
+    Json(LoginRes { token }).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct AuthorizeIpReq {
+    pub token: String,
+}
+
+#[derive(Deserialize)]
+struct LoginApprovalState {
+    ip: String,
+    country: Option<String>,
+    asn: Option<i64>,
+    location: String,
+}
+
+pub async fn authorize_ip(
+    Extension(state): Extension<AppState>,
+    data: Json<AuthorizeIpReq>,
+) -> impl IntoResponse {
+    let approval = verify_ticket_with_state(&data.token, TicketType::LoginApproval)
+        .and_then(|(user_id, approval)| {
+            Some((user_id, serde_json::from_value::<LoginApprovalState>(approval?).ok()?))
+        });
+
+    let (user_id, approval) = match approval {
+        None => return (
+            StatusCode::from(APIErrorCode::InvalidFormBody),
+            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await,
+        ).into_response(),
+        Some(approval) => approval,
+    };
+
+    // The next login from there will go through
+    record_login_attempt(
+        &state.conn,
+        user_id,
+        &LoginLocation {
+            ip: approval.ip,
+            country: approval.country,
+            asn: approval.asn,
+            location: approval.location,
+        },
+        LoginOutcome::LocationApproved,
+    ).await;
+
+    StatusCode::NO_CONTENT.into_response()
+}
+
+#[derive(Serialize)]
+pub struct LoginAttemptRes {
+    id: String,
+    ip: String,
+    location: String,
+    outcome: i32,
+    timestamp: String,
+}
+
+pub async fn login_attempts(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+) -> impl IntoResponse {
+    Json(
+        get_login_attempts(&state.conn, session_context.user.id, 50)
+            .await
+            .into_iter()
+            .map(|attempt| LoginAttemptRes {
+                id: attempt.id.to_string(),
+                ip: attempt.ip,
+                location: attempt.location,
+                outcome: attempt.outcome,
+                timestamp: attempt.created_at.and_utc().format("%Y-%m-%dT%H:%M:%S%z").to_string(),
+            })
+            .collect::<Vec<LoginAttemptRes>>()
+    )
 }
 
 // TODO: Research more into what these mean
@@ -322,32 +939,128 @@ pub async fn logout(
     Extension(session_context): Extension<SessionContext>,
     _data: Json<LogoutReq>,
 ) -> impl IntoResponse {
-    match session_context.session.delete(&state.conn).await {
+    let Some(session) = session_context.session else {
+        return StatusCode::UNAUTHORIZED;
+    };
+
+    match session.delete(&state.conn).await {
         Ok(_) => StatusCode::OK,
         Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
     }
 }
 
+/// Emails a link for verifying the user's current email address
+pub(crate) async fn send_verification_email(state: &AppState, user_id: i64, username: &str, email: &str) {
+    // The ticket is tied to the address, so changing it again makes old links useless
+    let ticket = create_ticket_with_state(
+        user_id,
+        TicketType::EmailVerification,
+        Duration::days(1),
+        Some(serde_json::Value::String(email.to_string())),
+    );
+
+    queue_mail(
+        &state.nats_client,
+        email.to_string(),
+        Mail::Verification {
+            username: username.to_string(),
+            url: instance_link(&format!("/verify#token={}", ticket)),
+        },
+    ).await;
+
+    user::ActiveModel {
+        id: Set(user_id),
+        verification_sent_at: Set(Some(chrono::Utc::now().naive_utc())),
+        ..Default::default()
+    }
+        .update(&state.conn)
+        .await
+        .expect("Failed to access database!");
+}
+
+/// Stops unverified users from doing things like adding friends when the instance requires verification
+pub(crate) async fn require_verification(user: &user::Model) -> Option<Response> {
+    if !EplOptions::get().require_verification || user.acct_verified || user.bot {
+        return None;
+    }
+
+    Some((
+        StatusCode::from(APIErrorCode::AccountVerificationRequired),
+        throw_http_error(APIErrorCode::AccountVerificationRequired, vec![]).await,
+    ).into_response())
+}
+
+#[derive(Deserialize)]
+pub struct VerifyEmailReq {
+    pub token: String,
+}
+
+/// No token is handed out, following the link only proves the inbox is read so the client still has to log in
+#[derive(Serialize)]
+pub struct VerifyEmailRes {
+    pub user_id: String,
+}
+
 pub async fn verify_email(
+    Extension(state): Extension<AppState>,
+    data: Json<VerifyEmailReq>,
+) -> impl IntoResponse {
+    let (user_id, email) = match verify_ticket_with_state(&data.token, TicketType::EmailVerification) {
+        Some((user_id, Some(serde_json::Value::String(email)))) => (user_id, email),
+        _ => return (
+            StatusCode::from(APIErrorCode::InvalidFormBody),
+            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await,
+        ).into_response(),
+    };
+
+    let requested_user = User::find_by_id(user_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    let requested_user = match requested_user {
+        Some(user) if user.email == email => user,
+        _ => return (
+            StatusCode::from(APIErrorCode::InvalidFormBody),
+            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await,
+        ).into_response(),
+    };
+
+    if !requested_user.acct_verified {
+        let flags = requested_user.flags | UserFlags::VerifiedEmail as i64;
+        let mut updated_user = requested_user.into_active_model();
+
+        updated_user.acct_verified = Set(true);
+        updated_user.flags = Set(flags);
+
+        updated_user.update(&state.conn).await.expect("Failed to access database!");
+
+        send_nats_message(&state.nats_client, user_id.to_string(), Messages::UserUpdate { user_id }).await;
+    }
+
+    Json(VerifyEmailRes { user_id: user_id.to_string() }).into_response()
+}
+
+pub async fn resend_verification_email(
     Extension(state): Extension<AppState>,
     Extension(session_context): Extension<SessionContext>,
 ) -> impl IntoResponse {
-    // Stub this and automatically verify the user
-    // TODO: Once we have SMTP, queue a verification email to be sent
+    let user = session_context.user;
 
-    if get_user_flags(session_context.user.flags).contains(&UserFlags::VerifiedEmail) {
+    if user.acct_verified || get_user_flags(user.flags).contains(&UserFlags::VerifiedEmail) {
         return StatusCode::BAD_REQUEST;
     }
 
-    let mut updated_user: user::ActiveModel = session_context.user.into_active_model();
+    // Only allow one email every few minutes so this can't be used to spam someone
+    if let Some(verification_sent_at) = user.verification_sent_at {
+        if chrono::Utc::now().naive_utc() - verification_sent_at < Duration::minutes(5) {
+            return StatusCode::TOO_MANY_REQUESTS;
+        }
+    }
 
-    updated_user.acct_verified = Set(true);
-    updated_user.flags = Set(updated_user.flags.unwrap() + UserFlags::VerifiedEmail as i64);
+    send_verification_email(&state, user.id, &user.username, &user.email).await;
 
-    match updated_user.update(&state.conn).await {
-        Ok(_) => StatusCode::OK,
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
-    }
+    StatusCode::NO_CONTENT
 }
 
 #[derive(Serialize)]
@@ -0,0 +1,149 @@
+use axum::{Extension, Json};
+use axum::extract::{Path, Query};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use sea_orm::*;
+use serde_derive::{Deserialize, Serialize};
+use epl_common::database::entities::{application, application_command, channel, channel_member};
+use epl_common::database::entities::prelude::{Application, ApplicationCommand, Channel, ChannelMember, User};
+use epl_common::permissions::{internal_permission_calculator, InternalChannelPermissions};
+use epl_common::schema::v9::application::{generate_application_struct, Application as ApplicationStruct};
+use epl_common::schema::v9::application_command::{generate_application_command_struct, ApplicationCommand as ApplicationCommandStruct};
+use crate::AppState;
+use crate::authorization_extractor::SessionContext;
+use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+
+/// Gets the applications that have their bot in the channel
+pub async fn get_channel_applications(
+    conn: &DatabaseConnection,
+    channel: &channel::Model,
+) -> Vec<application::Model> {
+    // TODO: Guild members once guilds exist
+    let members: Vec<i64> = ChannelMember::find()
+        .filter(channel_member::Column::Channel.eq(channel.id))
+        .all(conn)
+        .await
+        .expect("Failed to access database!")
+        .iter()
+        .map(|x| x.user)
+        .collect();
+
+    Application::find()
+        .filter(application::Column::BotUserId.is_in(members))
+        .all(conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+/// Checks if a command can be used in the channel
+pub fn is_command_available(channel: &channel::Model, command: &application_command::Model) -> bool {
+    match command.guild_id {
+        None => channel.guild_id.is_some() || command.dm_permission,
+        Some(guild_id) => channel.guild_id == Some(guild_id),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SearchApplicationCommandsQuery {
+    #[serde(rename = "type")]
+    _type: Option<i32>,
+    query: Option<String>,
+    limit: Option<u64>,
+    include_applications: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct SearchApplicationCommandsRes {
+    application_commands: Vec<ApplicationCommandStruct>,
+    applications: Vec<ApplicationStruct>,
+    cursor: SearchCursor,
+}
+
+#[derive(Serialize)]
+pub struct SearchCursor {
+    previous: Option<String>,
+    next: Option<String>,
+    repaired: Option<String>,
+}
+
+pub async fn search_application_commands(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(channel_id): Path<i64>,
+    Query(query): Query<SearchApplicationCommandsQuery>,
+) -> impl IntoResponse {
+    let requested_channel = match Channel::find_by_id(channel_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!") {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownChannel),
+            throw_http_error(APIErrorCode::UnknownChannel, vec![]).await
+        ).into_response(),
+        Some(requested_channel) => requested_channel,
+    };
+
+    let calculated_permissions = internal_permission_calculator(
+        &requested_channel,
+        &session_context.user,
+        None,
+        &state.conn
+    ).await;
+
+    if !calculated_permissions.contains(&InternalChannelPermissions::UseApplicationCommands) {
+        return (
+            StatusCode::from(APIErrorCode::MissingAccess),
+            throw_http_error(APIErrorCode::MissingAccess, vec![]).await
+        ).into_response();
+    }
+
+    let applications = get_channel_applications(&state.conn, &requested_channel).await;
+
+    let mut commands = ApplicationCommand::find()
+        .filter(application_command::Column::ApplicationId.is_in(applications.iter().map(|x| x.id)))
+        .order_by_asc(application_command::Column::Name);
+
+    if let Some(command_type) = query._type {
+        commands = commands.filter(application_command::Column::Type.eq(command_type));
+    }
+
+    let search = query.query.unwrap_or_default().to_lowercase();
+
+    let commands: Vec<ApplicationCommandStruct> = commands
+        .all(&state.conn)
+        .await
+        .expect("Failed to access database!")
+        .into_iter()
+        .filter(|x| is_command_available(&requested_channel, x) && x.name.to_lowercase().starts_with(&search))
+        .take(query.limit.unwrap_or(25).min(100) as usize)
+        .map(generate_application_command_struct)
+        .collect();
+
+    let mut output_applications = vec![];
+
+    if query.include_applications.unwrap_or(true) {
+        for i in applications {
+            // Only include applications that actually have something to show
+            if !commands.iter().any(|x| x.application_id == i.id.to_string()) {
+                continue;
+            }
+
+            let bot = User::find_by_id(i.bot_user_id.unwrap_or(0))
+                .one(&state.conn)
+                .await
+                .expect("Failed to access database!");
+
+            output_applications.push(generate_application_struct(i, bot));
+        }
+    }
+
+    Json(SearchApplicationCommandsRes {
+        application_commands: commands,
+        applications: output_applications,
+        cursor: SearchCursor {
+            previous: None,
+            next: None,
+            repaired: None,
+        },
+    }).into_response()
+}
@@ -0,0 +1,132 @@
+use axum::{Extension, Json};
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_derive::Deserialize;
+use epl_common::calls::{get_call, get_ringing, set_ringing};
+use epl_common::database::entities::{channel_member, voice_state};
+use epl_common::database::entities::prelude::{Channel, ChannelMember, VoiceState};
+use epl_common::permissions::{internal_permission_calculator, InternalChannelPermissions};
+use crate::AppState;
+use crate::authorization_extractor::SessionContext;
+use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+
+#[derive(Deserialize)]
+pub struct RingReq {
+    recipients: Option<Vec<String>>,
+}
+
+pub async fn ring_call(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(channel_id): Path<i64>,
+    Json(data): Json<RingReq>,
+) -> impl IntoResponse {
+    let requested_channel = match Channel::find_by_id(channel_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!") {
+        None => return (StatusCode::from(APIErrorCode::UnknownChannel), throw_http_error(APIErrorCode::UnknownChannel, vec![]).await).into_response(),
+        Some(requested_channel) => requested_channel,
+    };
+
+    let calculated_permissions = internal_permission_calculator(
+        &requested_channel,
+        &session_context.user,
+        None,
+        &state.conn
+    ).await;
+
+    if !calculated_permissions.contains(&InternalChannelPermissions::StartCall) {
+        return (StatusCode::from(APIErrorCode::MissingPermissions), throw_http_error(APIErrorCode::MissingPermissions, vec![]).await).into_response();
+    }
+
+    // Ringing only works while a call is going on
+    let call = match get_call(&state.conn, requested_channel.id).await {
+        None => return StatusCode::BAD_REQUEST.into_response(),
+        Some(call) => call,
+    };
+
+    let members: Vec<i64> = ChannelMember::find()
+        .filter(channel_member::Column::Channel.eq(requested_channel.id))
+        .all(&state.conn)
+        .await
+        .expect("Failed to access database!")
+        .into_iter()
+        .map(|x| x.user)
+        .collect();
+
+    let in_call: Vec<i64> = VoiceState::find()
+        .filter(voice_state::Column::ChannelId.eq(requested_channel.id))
+        .all(&state.conn)
+        .await
+        .expect("Failed to access database!")
+        .into_iter()
+        .map(|x| x.user_id)
+        .collect();
+
+    // Nobody asked for means everyone not already in the call
+    let recipients: Vec<i64> = match data.recipients {
+        None => members.iter().copied().filter(|x| *x != session_context.user.id).collect(),
+        Some(recipients) => recipients.iter().filter_map(|x| x.parse().ok()).collect(),
+    };
+
+    let mut ringing = get_ringing(&call);
+
+    for recipient in recipients {
+        if members.contains(&recipient) && !in_call.contains(&recipient) && !ringing.contains(&recipient) {
+            ringing.push(recipient);
+        }
+    }
+
+    set_ringing(&state.conn, &state.nats_client, call, ringing).await;
+
+    StatusCode::NO_CONTENT.into_response()
+}
+
+pub async fn stop_ringing_call(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(channel_id): Path<i64>,
+    Json(data): Json<RingReq>,
+) -> impl IntoResponse {
+    let requested_channel = match Channel::find_by_id(channel_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!") {
+        None => return (StatusCode::from(APIErrorCode::UnknownChannel), throw_http_error(APIErrorCode::UnknownChannel, vec![]).await).into_response(),
+        Some(requested_channel) => requested_channel,
+    };
+
+    let calculated_permissions = internal_permission_calculator(
+        &requested_channel,
+        &session_context.user,
+        None,
+        &state.conn
+    ).await;
+
+    if !calculated_permissions.contains(&InternalChannelPermissions::ViewChannel) {
+        return (StatusCode::from(APIErrorCode::MissingAccess), throw_http_error(APIErrorCode::MissingAccess, vec![]).await).into_response();
+    }
+
+    let call = match get_call(&state.conn, requested_channel.id).await {
+        None => return StatusCode::BAD_REQUEST.into_response(),
+        Some(call) => call,
+    };
+
+    // Declining a call stops ringing yourself
+    let recipients: Vec<i64> = match data.recipients {
+        None => vec![session_context.user.id],
+        Some(recipients) => recipients.iter().filter_map(|x| x.parse().ok()).collect(),
+    };
+
+    let ringing = get_ringing(&call)
+        .into_iter()
+        .filter(|x| !recipients.contains(x))
+        .collect();
+
+    set_ringing(&state.conn, &state.nats_client, call, ringing).await;
+
+    StatusCode::NO_CONTENT.into_response()
+}
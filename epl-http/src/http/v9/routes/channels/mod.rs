@@ -1,6 +1,8 @@
 pub mod pins;
 pub mod attachments;
 pub mod reactions;
+pub mod application_commands;
+pub mod calls;
 
 use std::io;
 use aws_sdk_s3::primitives::ByteStream;
@@ -20,22 +22,29 @@ use serde_derive::{Deserialize, Serialize};
 
 use epl_common::nats::send_nats_message;
 use epl_common::database::entities::{channel_member, embed, file, mention, message, message_attachment, pin, reaction, user};
-use epl_common::messages::MessageTypes;
+use epl_common::messages::{MessageFlags, MessageTypes};
 use epl_common::nats::Messages::{ChannelCreate, ChannelDelete, ChannelRecipientAdd, ChannelRecipientRemove, MessageCreate, MessageDelete, MessageUpdate, ProcessEmbed, TypingStarted};
 use epl_common::rustflake::Snowflake;
 use sea_orm::ActiveValue::Set;
 use sea_orm::*;
 use url::Url;
-use epl_common::channels::ChannelTypes;
+use epl_common::channels::{ChannelTypes, VideoQualityModes};
 use epl_common::permissions::{internal_permission_calculator, InternalChannelPermissions};
 use epl_common::relationship::get_relationship;
 use epl_common::{RelationshipType, URL_REGEX, USER_MENTION_REGEX};
-use epl_common::flags::{generate_public_flags, get_user_flags};
+use epl_common::flags::{generate_public_flags, get_user_flags, UserFlags};
 use epl_common::nats::Messages;
 use epl_common::options::{EplOptions, Options};
 use epl_common::schema::v9;
+use epl_common::emojis::downgrade_unknown_emojis;
+use epl_common::interactions::validate_message_components;
+use epl_common::schema::v9::component::Component;
 use epl_common::schema::v9::message::{generate_message_struct, generate_reactions, generate_refed_message};
 use crate::http::v9::routes::users::channels::{ResChannel, ResChannelMember};
+use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+use epl_common::system::{is_system_dm, set_unread_urgent_messages};
+use epl_common::voice::get_voice_region;
+use num_traits::FromPrimitive;
 
 
 #[derive(Serialize)]
@@ -142,6 +151,11 @@ pub async fn get_messages(
             };
 
             for i in messages {
+                // Ephemeral messages only live as long as the client that received them
+                if i.flags.is_some_and(|x| x & MessageFlags::Ephemeral as i32 != 0) {
+                    continue;
+                }
+
                 let author = User::find_by_id(i.author.unwrap_or(0))
                     .one(&state.conn)
                     .await
@@ -196,12 +210,13 @@ pub async fn get_messages(
 pub struct SendMessageReq {
     content: String,
     flags: Option<i32>,
-    nonce: String,
+    nonce: Option<String>,
     tts: Option<bool>,
     message_reference: Option<v9::message::MessageReference>,
     allowed_mentions: Option<AllowedMentions>,
     mobile_network_type: Option<String>,
-    attachments: Option<Vec<NewAttachment>>
+    attachments: Option<Vec<NewAttachment>>,
+    components: Option<Vec<Component>>,
 }
 
 #[derive(Deserialize)]
@@ -243,9 +258,31 @@ pub async fn send_message(
 
             // Check if the user has permission to send messages
             if !calculated_permissions.contains(&InternalChannelPermissions::SendMessage) {
+                // In a DM it's down to the other user blocking them or their privacy settings
+                if requested_channel.r#type == ChannelTypes::DM as i32
+                    && calculated_permissions.contains(&InternalChannelPermissions::ViewChannel) {
+                    return (
+                        StatusCode::from(APIErrorCode::CannotSendMessagesToUser),
+                        throw_http_error(APIErrorCode::CannotSendMessagesToUser, vec![]).await,
+                    )
+                        .into_response();
+                }
+
                 return StatusCode::BAD_REQUEST.into_response();
             }
 
+            // Only bots can attach components to their messages
+            let components = match message.components {
+                Some(components) if session_context.user.bot => {
+                    if !validate_message_components(&components) {
+                        return StatusCode::BAD_REQUEST.into_response();
+                    }
+
+                    serde_json::to_value(components).ok()
+                }
+                _ => None,
+            };
+
             let mut refed_message: Option<(message::Model, Option<user::Model>)> = None;
 
             if message.message_reference.is_some() {
@@ -261,9 +298,12 @@ pub async fn send_message(
                     .await;
             }
 
+            // Custom emojis that don't exist are shown as plain text
+            let content = downgrade_unknown_emojis(&state.conn, &message.content).await;
+
             let mut mention_results = vec![];
 
-            for i in USER_MENTION_REGEX.captures_iter(&message.content) {
+            for i in USER_MENTION_REGEX.captures_iter(&content) {
                 let user_id = i.get(1).unwrap().as_str().parse::<i64>();
 
                 if user_id.is_err() {
@@ -289,12 +329,12 @@ pub async fn send_message(
                 id: snowflake,
                 channel_id: requested_channel.id,
                 author: Some(session_context.user.id),
-                content: message.content.clone(),
+                content: content.clone(),
                 timestamp: chrono::Utc::now().naive_utc(),
                 edited_timestamp: None,
                 tts: message.tts.unwrap_or(false),
-                mention_everyone: calculated_permissions.contains(&InternalChannelPermissions::MentionEveryone) && message.content.contains("@everyone"),
-                nonce: Some(message.nonce),
+                mention_everyone: calculated_permissions.contains(&InternalChannelPermissions::MentionEveryone) && content.contains("@everyone"),
+                nonce: message.nonce,
                 r#type: {
                     if refed_message.is_some() {
                         MessageTypes::Reply as i32
@@ -316,6 +356,9 @@ pub async fn send_message(
                 pinned: false,
                 webhook_id: None,
                 application_id: None,
+                interaction: None,
+                components,
+                call: None,
             };
 
             Message::insert(new_message.clone().into_active_model())
@@ -398,7 +441,8 @@ pub async fn send_message(
 pub struct EditMessageReq {
     content: Option<String>,
     attachments: Option<Vec<v9::message::Attachment>>,
-    flags: Option<i32>
+    flags: Option<i32>,
+    components: Option<Vec<Component>>,
 }
 
 pub async fn edit_message(
@@ -473,7 +517,15 @@ pub async fn edit_message(
             let mut requested_message = requested_message.into_active_model();
 
             if message.content.is_some() {
-                requested_message.content = Set(message.content.unwrap());
+                requested_message.content = Set(downgrade_unknown_emojis(&state.conn, &message.content.unwrap()).await);
+            }
+
+            if let Some(components) = message.components.filter(|_| session_context.user.bot) {
+                if !validate_message_components(&components) {
+                    return StatusCode::BAD_REQUEST.into_response();
+                }
+
+                requested_message.components = Set(serde_json::to_value(components).ok());
             }
 
             requested_message.edited_timestamp = Set(Some(chrono::Utc::now().naive_utc()));
@@ -700,6 +752,43 @@ pub async fn typing(
     }
 }
 
+#[derive(Serialize)]
+pub struct AckRes {
+    token: Option<String>,
+}
+
+/// Read states aren't stored yet, but reading the System DM clears the urgent messages flag
+pub async fn ack_message(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path((channel_id, _message_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    let requested_channel = Channel::find_by_id(channel_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    match requested_channel {
+        None => {
+            StatusCode::BAD_REQUEST.into_response()
+        }
+        Some(requested_channel) => {
+            if !internal_permission_calculator(&requested_channel, &session_context.user, None, &state.conn)
+                .await
+                .contains(&InternalChannelPermissions::ViewChannel) {
+                return StatusCode::BAD_REQUEST.into_response();
+            }
+
+            if get_user_flags(session_context.user.flags).contains(&UserFlags::HasUnreadUrgentMessages)
+                && is_system_dm(&state.conn, &requested_channel).await {
+                set_unread_urgent_messages(&state.conn, &state.nats_client, session_context.user.id, false).await;
+            }
+
+            Json(AckRes { token: None }).into_response()
+        }
+    }
+}
+
 pub async fn add_user_to_channel(
     Extension(state): Extension<AppState>,
     Extension(session_context): Extension<SessionContext>,
@@ -813,6 +902,9 @@ pub async fn add_user_to_channel(
                             pinned: false,
                             webhook_id: None,
                             application_id: None,
+                            interaction: None,
+                            components: None,
+                            call: None,
                         };
 
                         Message::insert(new_message.clone().into_active_model())
@@ -931,6 +1023,9 @@ pub async fn remove_user_from_channel(
                             pinned: false,
                             webhook_id: None,
                             application_id: None,
+                            interaction: None,
+                            components: None,
+                            call: None,
                         };
 
                         Message::insert(new_message.clone().into_active_model())
@@ -966,7 +1061,11 @@ pub async fn remove_user_from_channel(
 pub struct ModifyChannelReq {
     pub name: Option<String>,
     pub icon: Option<String>,
-    pub owner: Option<String>
+    pub owner: Option<String>,
+    /// Null goes back to picking the region automatically
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub rtc_region: Option<Option<String>>,
+    pub video_quality_mode: Option<i32>,
 }
 
 pub async fn modify_channel(
@@ -1076,6 +1175,38 @@ pub async fn modify_channel(
                 }
             }
 
+            if let Some(rtc_region) = data.rtc_region {
+                if !calculated_permissions.contains(&InternalChannelPermissions::EditVoiceSettings) {
+                    return StatusCode::BAD_REQUEST.into_response();
+                }
+
+                if let Some(rtc_region) = &rtc_region {
+                    if get_voice_region(&state.conn, rtc_region).await.is_none() {
+                        return (
+                            StatusCode::from(APIErrorCode::InvalidFormBody),
+                            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await
+                        ).into_response();
+                    }
+                }
+
+                active_channel.rtc_region = Set(rtc_region);
+            }
+
+            if let Some(video_quality_mode) = data.video_quality_mode {
+                if !calculated_permissions.contains(&InternalChannelPermissions::EditVoiceSettings) {
+                    return StatusCode::BAD_REQUEST.into_response();
+                }
+
+                if VideoQualityModes::from_i32(video_quality_mode).is_none() {
+                    return (
+                        StatusCode::from(APIErrorCode::InvalidFormBody),
+                        throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await
+                    ).into_response();
+                }
+
+                active_channel.video_quality_mode = Set(Some(video_quality_mode));
+            }
+
             let mut users: Vec<ResChannelMember> = vec![];
 
             for (_chnlmem, usr) in ChannelMember::find()
@@ -1128,6 +1259,8 @@ pub async fn modify_channel(
                         name: channel.name,
                         owner_id: channel.owner_id.map(|x| x.to_string()),
                         recipients: Some(users),
+                        rtc_region: channel.rtc_region,
+                        video_quality_mode: channel.video_quality_mode,
                         _type: channel.r#type,
                     }).into_response()
                 }
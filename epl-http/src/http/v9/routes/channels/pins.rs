@@ -82,6 +82,9 @@ pub async fn new_pin(
                                 pinned: false,
                                 webhook_id: None,
                                 application_id: None,
+                                interaction: None,
+                                components: None,
+                                call: None,
                             };
 
                             Message::insert(new_pin_created_message.clone().into_active_model())
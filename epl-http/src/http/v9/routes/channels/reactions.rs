@@ -2,25 +2,38 @@ use axum::{Extension, Json};
 use axum::extract::{Path, Query};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel, ModelTrait, QueryFilter, QuerySelect};
+use num_traits::FromPrimitive;
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, ModelTrait, QueryFilter, QuerySelect};
 use sea_orm::ActiveValue::Set;
 use serde_derive::Deserialize;
-use unic::emoji::char::is_emoji;
 use epl_common::database::entities::prelude::{Channel, Message, Reaction, User};
-use epl_common::database::entities::reaction;
-use epl_common::nats::Messages::{MessageReactionAdd, MessageReactionRemove};
+use epl_common::database::entities::{message, reaction};
+use epl_common::emojis::{ReactionEmoji, ReactionType};
+use epl_common::nats::Messages::{MessageReactionAdd, MessageReactionRemove, MessageReactionRemoveAll, MessageReactionRemoveEmoji};
 use epl_common::nats::send_nats_message;
 use epl_common::permissions::{internal_permission_calculator, InternalChannelPermissions};
 use epl_common::schema::v9;
 use epl_common::schema::v9::user::generate_user_struct;
 use crate::AppState;
 use crate::authorization_extractor::SessionContext;
+use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+
+/// Gets the key a reaction is stored under, custom emojis are looked up since clients only send `name:id`
+async fn get_reaction_key(conn: &DatabaseConnection, emoji: &str) -> Option<String> {
+    let parsed = ReactionEmoji::parse(emoji)?;
+
+    // Reactions stay around after their emoji is deleted
+    match parsed.clone().resolve(conn).await {
+        Some(resolved) => Some(resolved.key()),
+        None => Some(parsed.key()),
+    }
+}
 
 #[derive(Deserialize)]
 pub struct GetReactionsQuery {
     limit: u64,
     #[serde(rename = "type")]
-    burst: i32
+    reaction_type: i32
 }
 
 pub async fn get_reactions(
@@ -29,7 +42,15 @@ pub async fn get_reactions(
     Path((_channel_id, message_id, emoji)): Path<(i64, i64, String)>,
     Query(params): Query<GetReactionsQuery>,
 ) -> impl IntoResponse {
-    let burst = params.burst == 1;
+    let reaction_type = match ReactionType::from_i32(params.reaction_type) {
+        None => return StatusCode::BAD_REQUEST.into_response(),
+        Some(reaction_type) => reaction_type,
+    };
+
+    let emoji = match get_reaction_key(&state.conn, &emoji).await {
+        None => return StatusCode::BAD_REQUEST.into_response(),
+        Some(emoji) => emoji,
+    };
 
     let requested_message = Message::find_by_id(message_id)
         .one(&state.conn)
@@ -61,7 +82,7 @@ pub async fn get_reactions(
             let all_reactors_of_emoji = Reaction::find()
                 .filter(reaction::Column::Message.eq(requested_message.id))
                 .filter(reaction::Column::Emoji.eq(emoji))
-                .filter(reaction::Column::Burst.eq(burst))
+                .filter(reaction::Column::Type.eq(reaction_type as i32))
                 .limit(params.limit)
                 .all(&state.conn)
                 .await
@@ -89,7 +110,7 @@ pub struct AddReactionQuery {
     #[serde(rename = "location")]
     _location: String,
     #[serde(rename = "type")]
-    burst: i32
+    reaction_type: i32
 }
 
 pub async fn add_reaction(
@@ -125,18 +146,27 @@ pub async fn add_reaction(
                 return StatusCode::BAD_REQUEST.into_response();
             }
 
-            let emoji = emoji.chars().next().unwrap();
+            let reaction_type = match ReactionType::from_i32(params.reaction_type) {
+                None => return StatusCode::BAD_REQUEST.into_response(),
+                Some(reaction_type) => reaction_type as i32,
+            };
 
-            // TODO: implement guild emojis
-            if !is_emoji(emoji) {
-                return StatusCode::BAD_REQUEST.into_response();
-            }
+            // Custom emojis have to exist to be reacted with
+            let emoji = match ReactionEmoji::parse(&emoji) {
+                None => None,
+                Some(emoji) => emoji.resolve(&state.conn).await,
+            };
+
+            let emoji = match emoji {
+                None => return StatusCode::BAD_REQUEST.into_response(),
+                Some(emoji) => emoji.key(),
+            };
 
             let new_reaction = reaction::ActiveModel {
                 user: Set(session_context.user.id),
                 message: Set(requested_message.id),
-                emoji: Set(emoji.to_string()),
-                burst: Set(params.burst == 1),
+                emoji: Set(emoji.clone()),
+                r#type: Set(reaction_type),
             };
 
             new_reaction.insert(&state.conn).await.expect("Failed to access database!");
@@ -147,7 +177,8 @@ pub async fn add_reaction(
                 MessageReactionAdd {
                     message_id: requested_message.id,
                     user_id: session_context.user.id,
-                    emoji: emoji.to_string(),
+                    emoji,
+                    reaction_type,
                 }
             ).await;
 
@@ -167,10 +198,15 @@ pub struct RemoveReactionQuery {
 pub async fn remove_reaction(
     Extension(state): Extension<AppState>,
     Extension(session_context): Extension<SessionContext>,
-    Path((_channel_id, message_id, emoji, _burst)): Path<(i64, i64, String, i32)>,
+    Path((_channel_id, message_id, emoji, reaction_type)): Path<(i64, i64, String, i32)>,
     Query(_params): Query<RemoveReactionQuery>,
 ) -> impl IntoResponse {
-    let requested_reaction = Reaction::find_by_id((session_context.user.id, message_id, emoji))
+    let emoji = match get_reaction_key(&state.conn, &emoji).await {
+        None => return StatusCode::BAD_REQUEST.into_response(),
+        Some(emoji) => emoji,
+    };
+
+    let requested_reaction = Reaction::find_by_id((session_context.user.id, message_id, emoji, reaction_type))
         .one(&state.conn)
         .await
         .expect("Failed to access database!");
@@ -210,6 +246,7 @@ pub async fn remove_reaction(
                     message_id: requested_message.id,
                     user_id: session_context.user.id,
                     emoji: requested_reaction.emoji.clone(),
+                    reaction_type: requested_reaction.r#type,
                 }
             ).await;
 
@@ -223,10 +260,15 @@ pub async fn remove_reaction(
 pub async fn delete_specific_user_reaction(
     Extension(state): Extension<AppState>,
     Extension(session_context): Extension<SessionContext>,
-    Path((_channel_id, message_id, emoji, _burst, user_id)): Path<(i64, i64, String, i32, i64)>,
+    Path((_channel_id, message_id, emoji, reaction_type, user_id)): Path<(i64, i64, String, i32, i64)>,
     Query(_params): Query<RemoveReactionQuery>,
 ) -> impl IntoResponse {
-    let requested_reaction = Reaction::find_by_id((user_id, message_id, emoji))
+    let emoji = match get_reaction_key(&state.conn, &emoji).await {
+        None => return StatusCode::BAD_REQUEST.into_response(),
+        Some(emoji) => emoji,
+    };
+
+    let requested_reaction = Reaction::find_by_id((user_id, message_id, emoji, reaction_type))
         .one(&state.conn)
         .await
         .expect("Failed to access database!");
@@ -255,7 +297,7 @@ pub async fn delete_specific_user_reaction(
                 &state.conn
             ).await;
 
-            if !calculated_permissions.contains(&InternalChannelPermissions::EditMessage) && session_context.user.id != requested_reaction.user {
+            if !calculated_permissions.contains(&InternalChannelPermissions::ManageMessages) && session_context.user.id != requested_reaction.user {
                 return StatusCode::BAD_REQUEST.into_response();
             }
 
@@ -266,6 +308,7 @@ pub async fn delete_specific_user_reaction(
                     message_id: requested_message.id,
                     user_id: requested_reaction.user,
                     emoji: requested_reaction.emoji.clone(),
+                    reaction_type: requested_reaction.r#type,
                 }
             ).await;
 
@@ -274,4 +317,105 @@ pub async fn delete_specific_user_reaction(
             StatusCode::NO_CONTENT.into_response()
         }
     }
-}
\ No newline at end of file
+}
+
+/// Finds a message and its channel, checking that the user can manage the message's reactions
+async fn get_managed_message(
+    state: &AppState,
+    session_context: &SessionContext,
+    message_id: i64,
+) -> Result<message::Model, axum::response::Response> {
+    let requested_message = match Message::find_by_id(message_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!") {
+        None => return Err((
+            StatusCode::from(APIErrorCode::UnknownMessage),
+            throw_http_error(APIErrorCode::UnknownMessage, vec![]).await
+        ).into_response()),
+        Some(requested_message) => requested_message,
+    };
+
+    let requested_channel = Channel::find_by_id(requested_message.channel_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!")
+        .expect("Message references non-existent channel!");
+
+    let calculated_permissions = internal_permission_calculator(
+        &requested_channel,
+        &session_context.user,
+        Some(&requested_message),
+        &state.conn
+    ).await;
+
+    if !calculated_permissions.contains(&InternalChannelPermissions::ManageMessages) {
+        return Err((
+            StatusCode::from(APIErrorCode::MissingPermissions),
+            throw_http_error(APIErrorCode::MissingPermissions, vec![]).await
+        ).into_response());
+    }
+
+    Ok(requested_message)
+}
+
+pub async fn remove_all_reactions(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path((_channel_id, message_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    let requested_message = match get_managed_message(&state, &session_context, message_id).await {
+        Err(response) => return response,
+        Ok(requested_message) => requested_message,
+    };
+
+    Reaction::delete_many()
+        .filter(reaction::Column::Message.eq(requested_message.id))
+        .exec(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    send_nats_message(
+        &state.nats_client,
+        requested_message.channel_id.to_string(),
+        MessageReactionRemoveAll {
+            message_id: requested_message.id,
+        }
+    ).await;
+
+    StatusCode::NO_CONTENT.into_response()
+}
+
+pub async fn remove_emoji_reactions(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path((_channel_id, message_id, emoji)): Path<(i64, i64, String)>,
+) -> impl IntoResponse {
+    let emoji = match get_reaction_key(&state.conn, &emoji).await {
+        None => return StatusCode::BAD_REQUEST.into_response(),
+        Some(emoji) => emoji,
+    };
+
+    let requested_message = match get_managed_message(&state, &session_context, message_id).await {
+        Err(response) => return response,
+        Ok(requested_message) => requested_message,
+    };
+
+    Reaction::delete_many()
+        .filter(reaction::Column::Message.eq(requested_message.id))
+        .filter(reaction::Column::Emoji.eq(emoji.clone()))
+        .exec(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    send_nats_message(
+        &state.nats_client,
+        requested_message.channel_id.to_string(),
+        MessageReactionRemoveEmoji {
+            message_id: requested_message.id,
+            emoji,
+        }
+    ).await;
+
+    StatusCode::NO_CONTENT.into_response()
+}
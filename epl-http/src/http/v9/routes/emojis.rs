@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+use std::io;
+use aws_sdk_s3::primitives::ByteStream;
+use axum::{Extension, Json};
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use ril::{Image, Rgba};
+use ril::ImageFormat::WebP;
+use sea_orm::*;
+use sea_orm::ActiveValue::Set;
+use serde_derive::Deserialize;
+use serde_json::Value;
+use epl_common::database::entities::{emoji, user};
+use epl_common::database::entities::prelude::{Emoji, User};
+use epl_common::emojis::{is_valid_emoji_name, MAX_EMOJI_SIZE, MAX_GUILD_EMOJIS};
+use epl_common::flags::{get_user_flags, UserFlags};
+use epl_common::nats::Messages::GuildEmojisUpdate;
+use epl_common::nats::send_nats_message;
+use epl_common::options::{EplOptions, Options};
+use epl_common::rustflake::Snowflake;
+use epl_common::schema::v9::emoji::{generate_emoji_struct, GuildEmoji};
+use crate::AppState;
+use crate::authorization_extractor::SessionContext;
+use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+
+/// Checks if a user can create, edit and delete a guild's emojis
+fn can_manage_emojis(user: &user::Model) -> bool {
+    // TODO: Use the guild's permissions once guilds exist
+    get_user_flags(user.flags).contains(&UserFlags::Staff)
+}
+
+async fn emoji_response(conn: &DatabaseConnection, emoji: emoji::Model, show_creator: bool) -> GuildEmoji {
+    let creator = if show_creator {
+        User::find_by_id(emoji.creator_id.unwrap_or(0))
+            .one(conn)
+            .await
+            .expect("Failed to access database!")
+    } else {
+        None
+    };
+
+    generate_emoji_struct(emoji, creator)
+}
+
+async fn get_emoji_or_error(conn: &DatabaseConnection, guild_id: i64, emoji_id: i64) -> Result<emoji::Model, axum::response::Response> {
+    match Emoji::find_by_id(emoji_id)
+        .filter(emoji::Column::GuildId.eq(guild_id))
+        .one(conn)
+        .await
+        .expect("Failed to access database!") {
+        None => Err((
+            StatusCode::from(APIErrorCode::UnknownEmoji),
+            throw_http_error(APIErrorCode::UnknownEmoji, vec![]).await
+        ).into_response()),
+        Some(emoji) => Ok(emoji),
+    }
+}
+
+/// Picks the dominant colours of an emoji for burst reactions, as `#rrggbb` strings
+fn extract_burst_colors(image: &Image<Rgba>) -> Vec<String> {
+    // Colours are bucketed so slight shading differences count as the same colour
+    let mut buckets: HashMap<(u8, u8, u8), (u64, u64, u64, u64)> = HashMap::new();
+
+    for i in image.data.iter().filter(|x| x.a >= 128) {
+        let bucket = buckets.entry((i.r >> 4, i.g >> 4, i.b >> 4)).or_default();
+
+        bucket.0 += 1;
+        bucket.1 += i.r as u64;
+        bucket.2 += i.g as u64;
+        bucket.3 += i.b as u64;
+    }
+
+    let mut buckets: Vec<(u64, u64, u64, u64)> = buckets.into_values().collect();
+    buckets.sort_by(|a, b| b.0.cmp(&a.0));
+
+    buckets.iter()
+        .take(2)
+        .map(|(count, r, g, b)| format!("#{:02x}{:02x}{:02x}", r / count, g / count, b / count))
+        .collect()
+}
+
+fn parse_roles(roles: &Option<Vec<String>>) -> Option<Option<Value>> {
+    roles.as_ref().map(|x| {
+        if x.is_empty() {
+            None
+        } else {
+            serde_json::to_value(x).ok()
+        }
+    })
+}
+
+pub async fn get_guild_emojis(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(guild_id): Path<i64>,
+) -> impl IntoResponse {
+    let show_creator = can_manage_emojis(&session_context.user);
+
+    let emojis = Emoji::find()
+        .filter(emoji::Column::GuildId.eq(guild_id))
+        .order_by_asc(emoji::Column::Id)
+        .all(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    let mut output = vec![];
+
+    for i in emojis {
+        output.push(emoji_response(&state.conn, i, show_creator).await);
+    }
+
+    Json(output).into_response()
+}
+
+pub async fn get_guild_emoji(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path((guild_id, emoji_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    match get_emoji_or_error(&state.conn, guild_id, emoji_id).await {
+        Err(response) => response,
+        Ok(requested_emoji) => Json(
+            emoji_response(&state.conn, requested_emoji, can_manage_emojis(&session_context.user)).await
+        ).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct NewEmojiReq {
+    name: String,
+    /// Data URI of the image, GIFs become animated emojis
+    image: String,
+    roles: Option<Vec<String>>,
+}
+
+pub async fn new_guild_emoji(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(guild_id): Path<i64>,
+    Json(data): Json<NewEmojiReq>,
+) -> impl IntoResponse {
+    if !can_manage_emojis(&session_context.user) {
+        return (
+            StatusCode::from(APIErrorCode::MissingPermissions),
+            throw_http_error(APIErrorCode::MissingPermissions, vec![]).await
+        ).into_response();
+    }
+
+    let (header, image) = match data.image.split_once("base64,") {
+        None => return (
+            StatusCode::from(APIErrorCode::InvalidFormBody),
+            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await
+        ).into_response(),
+        Some(image) => image,
+    };
+
+    let image = match BASE64_STANDARD.decode(image.as_bytes()) {
+        Ok(image) if image.len() <= MAX_EMOJI_SIZE => image,
+        _ => return (
+            StatusCode::from(APIErrorCode::InvalidFormBody),
+            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await
+        ).into_response(),
+    };
+
+    if !is_valid_emoji_name(&data.name) {
+        return (
+            StatusCode::from(APIErrorCode::InvalidFormBody),
+            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await
+        ).into_response();
+    }
+
+    let animated = header.contains("image/gif");
+
+    let existing_emojis = Emoji::find()
+        .filter(emoji::Column::GuildId.eq(guild_id))
+        .filter(emoji::Column::Animated.eq(animated))
+        .count(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    if existing_emojis >= MAX_GUILD_EMOJIS {
+        return (
+            StatusCode::from(APIErrorCode::MaximumNumberOfEmojis),
+            throw_http_error(APIErrorCode::MaximumNumberOfEmojis, vec![]).await
+        ).into_response();
+    }
+
+    // Decoding is CPU heavy, so it's kept off the async runtime
+    let processed = tokio::task::spawn_blocking(move || {
+        // Animated emojis only have their first frame decoded here
+        let decoded_image: Image<Rgba> = Image::from_reader_inferred(&mut io::Cursor::new(&image)).ok()?;
+
+        let burst_colors = extract_burst_colors(&decoded_image);
+
+        // Static emojis are stored as WebP like avatars, animated ones are kept as they were uploaded
+        let image_buffer = if animated {
+            image
+        } else {
+            let mut image_buffer: Vec<u8> = Vec::new();
+
+            decoded_image.encode(WebP, &mut image_buffer).expect("Failed to encode image!");
+
+            image_buffer
+        };
+
+        Some((image_buffer, burst_colors))
+    })
+        .await
+        .ok()
+        .flatten();
+
+    let Some((image_buffer, burst_colors)) = processed else {
+        return (
+            StatusCode::from(APIErrorCode::InvalidFormBody),
+            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await
+        ).into_response();
+    };
+
+    let emoji_id = Snowflake::default().generate();
+
+    let s3_res = state.aws.put_object()
+        .bucket(EplOptions::get().s3_bucket)
+        .key(format!("emojis/{}", emoji_id))
+        .body(ByteStream::from(image_buffer))
+        .send()
+        .await;
+
+    if s3_res.is_err() {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+
+    let new_emoji = emoji::ActiveModel {
+        id: Set(emoji_id),
+        guild_id: Set(guild_id),
+        name: Set(data.name),
+        animated: Set(animated),
+        creator_id: Set(Some(session_context.user.id)),
+        roles: Set(parse_roles(&data.roles).flatten()),
+        available: Set(true),
+        burst_colors: Set(serde_json::to_value(burst_colors).ok()),
+    }
+        .insert(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    send_nats_message(
+        &state.nats_client,
+        guild_id.to_string(),
+        GuildEmojisUpdate { guild_id },
+    ).await;
+
+    (
+        StatusCode::CREATED,
+        Json(generate_emoji_struct(new_emoji, Some(session_context.user))),
+    ).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ModifyEmojiReq {
+    name: Option<String>,
+    roles: Option<Vec<String>>,
+}
+
+pub async fn modify_guild_emoji(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path((guild_id, emoji_id)): Path<(i64, i64)>,
+    Json(data): Json<ModifyEmojiReq>,
+) -> impl IntoResponse {
+    if !can_manage_emojis(&session_context.user) {
+        return (
+            StatusCode::from(APIErrorCode::MissingPermissions),
+            throw_http_error(APIErrorCode::MissingPermissions, vec![]).await
+        ).into_response();
+    }
+
+    let requested_emoji = match get_emoji_or_error(&state.conn, guild_id, emoji_id).await {
+        Err(response) => return response,
+        Ok(requested_emoji) => requested_emoji,
+    };
+
+    let mut active_emoji = requested_emoji.into_active_model();
+
+    if let Some(name) = data.name {
+        if !is_valid_emoji_name(&name) {
+            return (
+                StatusCode::from(APIErrorCode::InvalidFormBody),
+                throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await
+            ).into_response();
+        }
+
+        active_emoji.name = Set(name);
+    }
+
+    if let Some(roles) = parse_roles(&data.roles) {
+        active_emoji.roles = Set(roles);
+    }
+
+    let updated_emoji = active_emoji.update(&state.conn).await.expect("Failed to access database!");
+
+    send_nats_message(
+        &state.nats_client,
+        guild_id.to_string(),
+        GuildEmojisUpdate { guild_id },
+    ).await;
+
+    Json(emoji_response(&state.conn, updated_emoji, true).await).into_response()
+}
+
+pub async fn delete_guild_emoji(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path((guild_id, emoji_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    if !can_manage_emojis(&session_context.user) {
+        return (
+            StatusCode::from(APIErrorCode::MissingPermissions),
+            throw_http_error(APIErrorCode::MissingPermissions, vec![]).await
+        ).into_response();
+    }
+
+    let requested_emoji = match get_emoji_or_error(&state.conn, guild_id, emoji_id).await {
+        Err(response) => return response,
+        Ok(requested_emoji) => requested_emoji,
+    };
+
+    // Reactions using the emoji are left alone, they just won't render anymore
+    requested_emoji.delete(&state.conn).await.expect("Failed to access database!");
+
+    let _ = state.aws.delete_object()
+        .bucket(EplOptions::get().s3_bucket)
+        .key(format!("emojis/{}", emoji_id))
+        .send()
+        .await;
+
+    send_nats_message(
+        &state.nats_client,
+        guild_id.to_string(),
+        GuildEmojisUpdate { guild_id },
+    ).await;
+
+    StatusCode::NO_CONTENT.into_response()
+}
@@ -0,0 +1,526 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use axum::{Extension, Json};
+use axum::extract::{Multipart, Path};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use sea_orm::*;
+use sea_orm::ActiveValue::Set;
+use serde_derive::Deserialize;
+use serde_json::{json, Value};
+use epl_common::database::entities::{application, application_command, channel, interaction, message, user};
+use epl_common::database::entities::prelude::{ApplicationCommand, Channel, Interaction, Message, User};
+use epl_common::gen_token;
+use epl_common::interactions::{handle_interaction_callback, ApplicationCommandOptionType, ButtonStyle, ComponentType, InteractionCallback, InteractionCallbackError, InteractionType, INTERACTION_RESPONSE_TIMEOUT, INTERACTION_TOKEN_LIFETIME};
+use epl_common::messages::MessageFlags;
+use epl_common::nats::Messages::{DeliverInteraction, InteractionCreate, InteractionFailure};
+use epl_common::nats::send_nats_message;
+use epl_common::permissions::{internal_permission_calculator, InternalChannelPermissions};
+use epl_common::rustflake::Snowflake;
+use epl_common::schema::v9::component::{find_component, Component, Modal};
+use epl_common::schema::v9::user::generate_user_struct;
+use crate::AppState;
+use crate::authorization_extractor::SessionContext;
+use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+use crate::http::v9::routes::channels::application_commands::{get_channel_applications, is_command_available};
+
+#[derive(Deserialize)]
+pub struct NewInteractionReq {
+    #[serde(rename = "type")]
+    _type: i32,
+    application_id: String,
+    channel_id: String,
+    /// The message the component is on, for component interactions
+    message_id: Option<String>,
+    data: Value,
+    nonce: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CommandInteractionData {
+    id: String,
+    #[serde(rename = "type")]
+    _type: Option<i32>,
+    options: Option<Vec<Value>>,
+    target_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ComponentInteractionData {
+    component_type: i32,
+    custom_id: String,
+    values: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+pub struct ModalSubmitInteractionData {
+    /// ID of the interaction the modal was opened by
+    id: String,
+    custom_id: String,
+    components: Vec<Value>,
+}
+
+/// Collects the users referenced in command options so the application doesn't have to fetch them
+async fn resolve_option_users(conn: &DatabaseConnection, options: &[Value], users: &mut HashMap<String, Value>) {
+    for i in options {
+        let option_type = i.get("type").and_then(|x| x.as_i64()).unwrap_or(0);
+
+        if option_type == ApplicationCommandOptionType::User as i64 || option_type == ApplicationCommandOptionType::Mentionable as i64 {
+            if let Some(user_id) = i.get("value").and_then(|x| x.as_str()) {
+                resolve_user(conn, user_id, users).await;
+            }
+        }
+
+        if let Some(sub_options) = i.get("options").and_then(|x| x.as_array()) {
+            Box::pin(resolve_option_users(conn, sub_options, users)).await;
+        }
+    }
+}
+
+async fn resolve_user(conn: &DatabaseConnection, user_id: &str, users: &mut HashMap<String, Value>) {
+    let user = match user_id.parse::<i64>() {
+        Ok(user_id) => User::find_by_id(user_id)
+            .one(conn)
+            .await
+            .expect("Failed to access database!"),
+        Err(_) => None,
+    };
+
+    if let Some(user) = user {
+        users.insert(user_id.to_string(), serde_json::to_value(generate_user_struct(user)).expect("Failed to serialize user!"));
+    }
+}
+
+/// Generates the data of a command interaction, only keeping what the application needs
+async fn generate_command_data(
+    conn: &DatabaseConnection,
+    channel: &channel::Model,
+    application: &application::Model,
+    data: Value,
+) -> Result<(Value, Option<i64>), Response> {
+    let data: CommandInteractionData = serde_json::from_value(data).map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
+    let command_id = data.id.parse::<i64>().map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
+
+    let command = ApplicationCommand::find_by_id(command_id)
+        .filter(application_command::Column::ApplicationId.eq(application.id))
+        .one(conn)
+        .await
+        .expect("Failed to access database!");
+
+    let command = match command {
+        Some(command) if is_command_available(channel, &command)
+            && data._type.unwrap_or(command.r#type) == command.r#type => command,
+        _ => return Err((
+            StatusCode::from(APIErrorCode::UnknownApplicationCommand),
+            throw_http_error(APIErrorCode::UnknownApplicationCommand, vec![]).await
+        ).into_response()),
+    };
+
+    let mut resolved_users = HashMap::new();
+
+    if let Some(options) = &data.options {
+        resolve_option_users(conn, options, &mut resolved_users).await;
+    }
+
+    // TODO: Resolve target messages for message commands
+    if let Some(target_id) = &data.target_id {
+        resolve_user(conn, target_id, &mut resolved_users).await;
+    }
+
+    Ok((
+        json!({
+            "id": command.id.to_string(),
+            "name": command.name,
+            "type": command.r#type,
+            "options": data.options,
+            "target_id": data.target_id,
+            "guild_id": command.guild_id.map(|x| x.to_string()),
+            "resolved": {
+                "users": resolved_users,
+            },
+        }),
+        None,
+    ))
+}
+
+/// Generates the data of a component interaction, checking that the component can actually be used
+async fn generate_component_data(
+    conn: &DatabaseConnection,
+    channel: &channel::Model,
+    application: &application::Model,
+    user: &user::Model,
+    message_id: Option<String>,
+    data: Value,
+) -> Result<(Value, Option<i64>), Response> {
+    let data: ComponentInteractionData = serde_json::from_value(data).map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
+
+    let requested_message = match message_id.and_then(|x| x.parse::<i64>().ok()) {
+        None => None,
+        Some(message_id) => Message::find_by_id(message_id)
+            .filter(message::Column::ChannelId.eq(channel.id))
+            .one(conn)
+            .await
+            .expect("Failed to access database!"),
+    };
+
+    // Ephemeral messages can only be used by the user they were sent to
+    let requested_message = requested_message.filter(|x| {
+        (x.application_id == Some(application.id) || x.author == application.bot_user_id)
+            && (x.flags.unwrap_or(0) & MessageFlags::Ephemeral as i32 == 0
+                || x.interaction
+                    .as_ref()
+                    .and_then(|x| x.pointer("/user/id"))
+                    .and_then(|x| x.as_str())
+                    .is_some_and(|x| x == user.id.to_string()))
+    });
+
+    let requested_message = match requested_message {
+        None => return Err((
+            StatusCode::from(APIErrorCode::UnknownMessage),
+            throw_http_error(APIErrorCode::UnknownMessage, vec![]).await
+        ).into_response()),
+        Some(requested_message) => requested_message,
+    };
+
+    let components: Vec<Component> = requested_message
+        .components
+        .clone()
+        .and_then(|x| serde_json::from_value(x).ok())
+        .unwrap_or_default();
+
+    let component = match find_component(&components, &data.custom_id) {
+        Some(component) if component.component_type() as i32 == data.component_type => component,
+        _ => return Err(StatusCode::BAD_REQUEST.into_response()),
+    };
+
+    let mut resolved_users = HashMap::new();
+
+    match component {
+        Component::Button(button) if !button.disabled && button.style != ButtonStyle::Link => {}
+        Component::SelectMenu(select) if !select.disabled => {
+            let values = data.values.as_deref().unwrap_or_default();
+
+            if values.len() < select.min_values as usize || values.len() > select.max_values as usize {
+                return Err(StatusCode::BAD_REQUEST.into_response());
+            }
+
+            if select._type == ComponentType::StringSelect && !values.iter().all(|x| select.options.iter().any(|y| &y.value == x)) {
+                return Err(StatusCode::BAD_REQUEST.into_response());
+            }
+
+            // TODO: Roles and channels once guilds exist
+            if select._type == ComponentType::UserSelect || select._type == ComponentType::MentionableSelect {
+                for i in values {
+                    resolve_user(conn, i, &mut resolved_users).await;
+                }
+            }
+        }
+        _ => return Err(StatusCode::BAD_REQUEST.into_response()),
+    }
+
+    Ok((
+        json!({
+            "custom_id": data.custom_id,
+            "component_type": data.component_type,
+            "values": data.values,
+            "resolved": {
+                "users": resolved_users,
+            },
+        }),
+        Some(requested_message.id),
+    ))
+}
+
+/// Generates the data of a modal submit, checking the submitted values against the modal the application sent
+async fn generate_modal_submit_data(
+    conn: &DatabaseConnection,
+    channel: &channel::Model,
+    application: &application::Model,
+    user: &user::Model,
+    data: Value,
+) -> Result<(Value, Option<i64>), Response> {
+    let data: ModalSubmitInteractionData = serde_json::from_value(data).map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
+
+    let modal_interaction = match data.id.parse::<i64>() {
+        Err(_) => None,
+        Ok(modal_interaction_id) => Interaction::find_by_id(modal_interaction_id)
+            .filter(interaction::Column::ApplicationId.eq(application.id))
+            .filter(interaction::Column::UserId.eq(user.id))
+            .filter(interaction::Column::ChannelId.eq(channel.id))
+            .one(conn)
+            .await
+            .expect("Failed to access database!")
+            .filter(|x| (Utc::now().naive_utc() - x.created).num_seconds() <= INTERACTION_TOKEN_LIFETIME),
+    };
+
+    let (modal_interaction, modal) = match modal_interaction
+        .and_then(|x| x.modal.clone().and_then(|y| serde_json::from_value::<Modal>(y).ok()).map(|y| (x, y))) {
+        None => return Err((
+            StatusCode::from(APIErrorCode::UnknownInteraction),
+            throw_http_error(APIErrorCode::UnknownInteraction, vec![]).await
+        ).into_response()),
+        Some(modal_interaction) => modal_interaction,
+    };
+
+    if modal.custom_id != data.custom_id {
+        return Err(StatusCode::BAD_REQUEST.into_response());
+    }
+
+    let mut submitted_values: HashMap<String, String> = HashMap::new();
+
+    for i in data.components.iter().filter_map(|x| x.get("components").and_then(|x| x.as_array())).flatten() {
+        if let (Some(custom_id), Some(value)) = (i.get("custom_id").and_then(|x| x.as_str()), i.get("value").and_then(|x| x.as_str())) {
+            submitted_values.insert(custom_id.to_string(), value.to_string());
+        }
+    }
+
+    let mut rows = vec![];
+
+    for i in &modal.components {
+        if let Component::ActionRow(row) = i {
+            for i in &row.components {
+                if let Component::TextInput(text_input) = i {
+                    let value = submitted_values.remove(&text_input.custom_id).unwrap_or_default();
+                    let value_len = value.chars().count() as i32;
+
+                    // Optional inputs can be left empty, but otherwise have to respect the length limits
+                    let invalid = if value_len == 0 {
+                        text_input.required
+                    } else {
+                        value_len < text_input.min_length.unwrap_or(0) || value_len > text_input.max_length.unwrap_or(4000)
+                    };
+
+                    if invalid {
+                        return Err(StatusCode::BAD_REQUEST.into_response());
+                    }
+
+                    rows.push(json!({
+                        "type": ComponentType::ActionRow as i32,
+                        "components": [{
+                            "type": ComponentType::TextInput as i32,
+                            "custom_id": text_input.custom_id,
+                            "value": value,
+                        }],
+                    }));
+                }
+            }
+        }
+    }
+
+    // A modal can only be submitted once
+    let mut submitted_interaction = modal_interaction.clone().into_active_model();
+    submitted_interaction.modal = Set(None);
+    submitted_interaction.update(conn).await.expect("Failed to access database!");
+
+    // Modals opened from a component can still update the message the component is on
+    let message_id = if modal_interaction.r#type == InteractionType::MessageComponent as i32 {
+        modal_interaction.message_id
+    } else {
+        None
+    };
+
+    Ok((
+        json!({
+            "custom_id": modal.custom_id,
+            "components": rows,
+        }),
+        message_id,
+    ))
+}
+
+pub async fn new_interaction(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    // The client sends the interaction as multipart so that attachment options can be uploaded alongside it
+    let mut payload: Option<NewInteractionReq> = None;
+
+    while let Ok(Some(field)) = multipart.next_field().await {
+        if field.name() == Some("payload_json") {
+            payload = field.text().await.ok().and_then(|x| serde_json::from_str(&x).ok());
+        }
+    }
+
+    let payload = match payload {
+        None => return StatusCode::BAD_REQUEST.into_response(),
+        Some(payload) => payload,
+    };
+
+    let interaction_type = match InteractionType::from_i32(payload._type) {
+        Some(interaction_type @ (InteractionType::ApplicationCommand | InteractionType::MessageComponent | InteractionType::ModalSubmit)) => interaction_type,
+        // TODO: Autocomplete
+        _ => return StatusCode::BAD_REQUEST.into_response(),
+    };
+
+    let (application_id, channel_id) = match (
+        payload.application_id.parse::<i64>(),
+        payload.channel_id.parse::<i64>(),
+    ) {
+        (Ok(application_id), Ok(channel_id)) => (application_id, channel_id),
+        _ => return StatusCode::BAD_REQUEST.into_response(),
+    };
+
+    let requested_channel = match Channel::find_by_id(channel_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!") {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownChannel),
+            throw_http_error(APIErrorCode::UnknownChannel, vec![]).await
+        ).into_response(),
+        Some(requested_channel) => requested_channel,
+    };
+
+    let calculated_permissions = internal_permission_calculator(
+        &requested_channel,
+        &session_context.user,
+        None,
+        &state.conn
+    ).await;
+
+    // Anyone who can see a message can use its components
+    let required_permission = if interaction_type == InteractionType::ApplicationCommand {
+        InternalChannelPermissions::UseApplicationCommands
+    } else {
+        InternalChannelPermissions::ViewChannel
+    };
+
+    if !calculated_permissions.contains(&required_permission) {
+        return (
+            StatusCode::from(APIErrorCode::MissingAccess),
+            throw_http_error(APIErrorCode::MissingAccess, vec![]).await
+        ).into_response();
+    }
+
+    let application = match get_channel_applications(&state.conn, &requested_channel)
+        .await
+        .into_iter()
+        .find(|x| x.id == application_id) {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownApplication),
+            throw_http_error(APIErrorCode::UnknownApplication, vec![]).await
+        ).into_response(),
+        Some(application) => application,
+    };
+
+    // Only what the application needs is passed along, the rest of the client's data is dropped
+    let generated_data = match interaction_type {
+        InteractionType::MessageComponent => generate_component_data(
+            &state.conn,
+            &requested_channel,
+            &application,
+            &session_context.user,
+            payload.message_id,
+            payload.data,
+        ).await,
+        InteractionType::ModalSubmit => generate_modal_submit_data(
+            &state.conn,
+            &requested_channel,
+            &application,
+            &session_context.user,
+            payload.data,
+        ).await,
+        _ => generate_command_data(&state.conn, &requested_channel, &application, payload.data).await,
+    };
+
+    let (data, message_id) = match generated_data {
+        Err(response) => return response,
+        Ok(generated_data) => generated_data,
+    };
+
+    let new_interaction = interaction::ActiveModel {
+        id: Set(Snowflake::default().generate()),
+        application_id: Set(application.id),
+        r#type: Set(interaction_type as i32),
+        token: Set(gen_token()),
+        user_id: Set(session_context.user.id),
+        channel_id: Set(requested_channel.id),
+        data: Set(Some(data)),
+        message_id: Set(message_id),
+        nonce: Set(payload.nonce),
+        responded: Set(false),
+        created: Set(Utc::now().naive_utc()),
+        modal: Set(None),
+    }
+        .insert(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    send_nats_message(
+        &state.nats_client,
+        session_context.user.id.to_string(),
+        InteractionCreate { id: new_interaction.id },
+    ).await;
+
+    if application.interactions_endpoint_url.is_some() {
+        // The worker will report the failure itself if the endpoint doesn't respond properly
+        send_nats_message(
+            &state.nats_client,
+            "worker_queue".to_string(),
+            DeliverInteraction { id: new_interaction.id },
+        ).await;
+    } else {
+        send_nats_message(
+            &state.nats_client,
+            application.bot_user_id.expect("Application in channel has no bot!").to_string(),
+            InteractionCreate { id: new_interaction.id },
+        ).await;
+
+        let conn = state.conn.clone();
+        let nats_client = state.nats_client.clone();
+        let interaction_id = new_interaction.id;
+        let user_id = session_context.user.id;
+
+        // Let the user know if the bot never got back to us
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(INTERACTION_RESPONSE_TIMEOUT as u64)).await;
+
+            let responded = Interaction::find_by_id(interaction_id)
+                .one(&conn)
+                .await
+                .expect("Failed to access database!")
+                .is_some_and(|x| x.responded);
+
+            if !responded {
+                send_nats_message(&nats_client, user_id.to_string(), InteractionFailure { id: interaction_id }).await;
+            }
+        });
+    }
+
+    StatusCode::NO_CONTENT.into_response()
+}
+
+pub async fn interaction_callback(
+    Extension(state): Extension<AppState>,
+    Path((interaction_id, token)): Path<(i64, String)>,
+    Json(callback): Json<InteractionCallback>,
+) -> impl IntoResponse {
+    let requested_interaction = Interaction::find_by_id(interaction_id)
+        .filter(interaction::Column::Token.eq(token))
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    let requested_interaction = match requested_interaction {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownInteraction),
+            throw_http_error(APIErrorCode::UnknownInteraction, vec![]).await
+        ).into_response(),
+        Some(requested_interaction) => requested_interaction,
+    };
+
+    match handle_interaction_callback(&state.conn, &state.nats_client, requested_interaction, callback).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(InteractionCallbackError::AlreadyAcknowledged) => (
+            StatusCode::from(APIErrorCode::InteractionAlreadyAcknowledged),
+            throw_http_error(APIErrorCode::InteractionAlreadyAcknowledged, vec![]).await
+        ).into_response(),
+        Err(InteractionCallbackError::Expired) => (
+            StatusCode::from(APIErrorCode::UnknownInteraction),
+            throw_http_error(APIErrorCode::UnknownInteraction, vec![]).await
+        ).into_response(),
+        Err(InteractionCallbackError::InvalidCallback) => StatusCode::BAD_REQUEST.into_response(),
+    }
+}
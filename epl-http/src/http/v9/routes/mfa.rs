@@ -0,0 +1,175 @@
+use axum::{Extension, Json};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum_client_ip::SecureClientIp;
+use chrono::Duration;
+use sea_orm::{DatabaseConnection, EntityTrait};
+use serde_derive::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use epl_common::database::auth::{create_ticket, create_ticket_with_state, TicketType, verify_ticket, verify_ticket_with_state};
+use epl_common::database::entities::prelude::User;
+use epl_common::database::entities::user;
+use epl_common::logins::{get_login_location, record_login_attempt, LoginOutcome};
+use epl_common::mfa::{finish_webauthn_authentication, start_webauthn_authentication, verify_mfa_code};
+use crate::AppState;
+use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+use crate::http::v9::routes::auth::check_lockout;
+
+#[skip_serializing_none]
+#[derive(Serialize)]
+pub struct MfaMethod {
+    #[serde(rename = "type")]
+    _type: String,
+    backup_codes_allowed: Option<bool>,
+    /// Only for WebAuthn, the assertion options as a JSON string
+    challenge: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct MfaChallenge {
+    ticket: String,
+    methods: Vec<MfaMethod>,
+}
+
+#[derive(Serialize)]
+pub struct MfaRequiredRes {
+    code: u32,
+    message: String,
+    mfa: MfaChallenge,
+}
+
+/// Sensitive actions need a recently finished MFA challenge if the user has MFA on, the client retries the request
+/// with the token from `/mfa/finish` in the `X-Discord-MFA-Authorization` header
+pub(crate) async fn require_mfa(conn: &DatabaseConnection, headers: &HeaderMap, user: &user::Model) -> Option<Response> {
+    if !user.mfa_enabled {
+        return None;
+    }
+
+    let verified = headers.get("X-Discord-MFA-Authorization")
+        .and_then(|x| x.to_str().ok())
+        .and_then(|x| verify_ticket(x, TicketType::MfaVerified))
+        .is_some_and(|x| x == user.id);
+
+    if verified {
+        return None;
+    }
+
+    let mut methods = vec![];
+
+    if user.totp_secret.is_some() {
+        methods.push(MfaMethod {
+            _type: String::from("totp"),
+            backup_codes_allowed: Some(true),
+            challenge: None,
+        });
+    }
+
+    // The WebAuthn state has to stick around until the challenge is answered, so it goes in the ticket
+    let webauthn_state = match start_webauthn_authentication(conn, user.id).await {
+        None => None,
+        Some((challenge, state)) => {
+            methods.push(MfaMethod {
+                _type: String::from("webauthn"),
+                backup_codes_allowed: None,
+                challenge: Some(serde_json::to_string(&challenge).expect("Failed to encode WebAuthn challenge!")),
+            });
+
+            Some(serde_json::to_value(state).expect("Failed to encode WebAuthn state!"))
+        }
+    };
+
+    Some((
+        StatusCode::from(APIErrorCode::TwoFactorRequired),
+        Json(MfaRequiredRes {
+            code: u32::from(APIErrorCode::TwoFactorRequired),
+            message: String::from(APIErrorCode::TwoFactorRequired),
+            mfa: MfaChallenge {
+                ticket: create_ticket_with_state(user.id, TicketType::MfaChallenge, Duration::minutes(5), webauthn_state),
+                methods,
+            },
+        })
+    ).into_response())
+}
+
+/// Checks a WebAuthn assertion sent as a JSON string against the state saved in a ticket
+pub(crate) async fn verify_webauthn_assertion(
+    conn: &DatabaseConnection,
+    user_id: i64,
+    assertion: &str,
+    webauthn_state: Option<serde_json::Value>,
+) -> bool {
+    let webauthn_state = match webauthn_state.and_then(|x| serde_json::from_value(x).ok()) {
+        None => return false,
+        Some(webauthn_state) => webauthn_state,
+    };
+
+    let assertion = match serde_json::from_str(assertion) {
+        Err(_) => return false,
+        Ok(assertion) => assertion,
+    };
+
+    finish_webauthn_authentication(conn, user_id, &assertion, &webauthn_state).await
+}
+
+#[derive(Deserialize)]
+pub struct MfaFinishReq {
+    ticket: String,
+    mfa_type: String,
+    data: String,
+}
+
+#[derive(Serialize)]
+pub struct MfaFinishRes {
+    token: String,
+}
+
+pub async fn mfa_finish(
+    Extension(state): Extension<AppState>,
+    SecureClientIp(ip): SecureClientIp,
+    Json(data): Json<MfaFinishReq>,
+) -> impl IntoResponse {
+    let (user_id, webauthn_state) = match verify_ticket_with_state(&data.ticket, TicketType::MfaChallenge) {
+        None => return (
+            StatusCode::from(APIErrorCode::InvalidFormBody),
+            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await
+        ).into_response(),
+        Some(ticket) => ticket,
+    };
+
+    let user = match User::find_by_id(user_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!") {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownUser),
+            throw_http_error(APIErrorCode::UnknownUser, vec![]).await
+        ).into_response(),
+        Some(user) => user,
+    };
+
+    // Codes here can be guessed the same as at login, so they share the lockout
+    let location = get_login_location(ip);
+
+    if let Some(res) = check_lockout(&state, user.id, &location).await {
+        return res;
+    }
+
+    let verified = match data.mfa_type.as_str() {
+        "totp" | "backup" => verify_mfa_code(&state.conn, &user, &data.data).await,
+        "webauthn" => verify_webauthn_assertion(&state.conn, user.id, &data.data, webauthn_state).await,
+        _ => false,
+    };
+
+    if !verified {
+        record_login_attempt(&state.conn, user.id, &location, LoginOutcome::InvalidTwoFactorCode).await;
+
+        return (
+            StatusCode::from(APIErrorCode::InvalidTwoFactorCode),
+            throw_http_error(APIErrorCode::InvalidTwoFactorCode, vec![]).await
+        ).into_response();
+    }
+
+    Json(MfaFinishRes {
+        token: create_ticket(user.id, TicketType::MfaVerified, Duration::minutes(5)),
+    }).into_response()
+}
@@ -6,32 +6,56 @@ mod users;
 mod aprilfools2024;
 mod gifs;
 mod proto_settings;
+mod applications;
+mod oauth2;
+mod application_commands;
+mod interactions;
+mod webhooks;
+mod emojis;
+mod voice;
+mod mfa;
+mod reports;
+mod safety_hub;
 
-use crate::authorization_extractor::get_session_context;
+use crate::authorization_extractor::{get_oauth2_session_context, get_session_context};
 use crate::http::v9::routes::auth::{
-    location_metadata, login, logout, logout_session, register, sessions, verify_email,
+    authorize_ip, forgot_password, location_metadata, login, login_attempts, logout, logout_session, register,
+    resend_verification_email, reset_password, sessions, totp_login, verify_email, webauthn_login,
 };
-use crate::http::v9::routes::channels::{add_user_to_channel, delete_message, edit_message, get_messages, modify_channel, remove_user_from_channel, send_message, typing};
+use crate::http::v9::routes::channels::{ack_message, add_user_to_channel, delete_message, edit_message, get_messages, modify_channel, remove_user_from_channel, send_message, typing};
 use crate::http::v9::routes::hypesquad::{join_hypesquad, leave_hypesquad};
 use crate::http::v9::routes::users::channels::new_dm_channel;
-use crate::http::v9::routes::users::{disable_account, pomelo, profile, update_profile, update_user};
+use crate::http::v9::routes::users::{delete_account, disable_account, get_current_user, get_current_user_guilds, pomelo, profile, update_profile, update_user};
 use crate::http::v9::routes::users::relationships::{
     delete_relationship, get_all_relationships, modify_relationship, new_relationship,
 };
+use axum::extract::DefaultBodyLimit;
 use axum::routing::{delete, get, patch, post, put};
-use axum::{Json, middleware, Router};
-use axum::response::IntoResponse;
-use serde_derive::Serialize;
-use epl_common::Stub;
+use axum::{middleware, Router};
 use crate::debug::debug_body;
 use crate::http::v9::routes::aprilfools2024::{count_lootboxes, get_lootboxes, open_lootbox, redeem_prize};
 use crate::http::v9::routes::channels::attachments::{delete_attachment_upload, prepare_s3_attachment_upload};
 use crate::http::v9::routes::channels::pins::{delete_pin, get_pins, new_pin};
-use crate::http::v9::routes::channels::reactions::{add_reaction, delete_specific_user_reaction, get_reactions, remove_reaction};
+use crate::http::v9::routes::channels::reactions::{add_reaction, delete_specific_user_reaction, get_reactions, remove_all_reactions, remove_emoji_reactions, remove_reaction};
 use crate::http::v9::routes::gifs::{actually_get_trending_gifs, get_trending_gifs, gif_search_suggestions, search_gifs};
 use crate::http::v9::routes::proto_settings::{edit_settings_proto, get_settings_proto};
 use crate::http::v9::routes::tracking::science;
 use crate::http::v9::routes::users::notes::{get_notes, put_notes};
+use crate::http::v9::routes::users::harvest::{get_harvest, request_harvest};
+use crate::http::v9::routes::users::import::import_discord_package;
+use crate::http::v9::routes::applications::{get_application, get_applications, modify_application, new_application, new_bot, reset_bot_token, reset_secret};
+use crate::http::v9::routes::application_commands::{delete_global_command, delete_guild_command, edit_global_command, edit_guild_command, get_global_command, get_global_commands, get_guild_command, get_guild_commands, new_global_command, new_guild_command, overwrite_global_commands, overwrite_guild_commands};
+use crate::http::v9::routes::channels::application_commands::search_application_commands;
+use crate::http::v9::routes::channels::calls::{ring_call, stop_ringing_call};
+use crate::http::v9::routes::interactions::{interaction_callback, new_interaction};
+use crate::http::v9::routes::webhooks::{delete_webhook_message, edit_webhook_message, get_webhook_message, new_followup_message};
+use crate::http::v9::routes::emojis::{delete_guild_emoji, get_guild_emoji, get_guild_emojis, modify_guild_emoji, new_guild_emoji};
+use crate::http::v9::routes::voice::get_voice_regions;
+use crate::http::v9::routes::mfa::mfa_finish;
+use crate::http::v9::routes::reports::{get_report_menu, new_report, submit_report};
+use crate::http::v9::routes::safety_hub::account_standing;
+use crate::http::v9::routes::users::mfa::{backup_codes, delete_webauthn_credential, disable_totp, enable_totp, list_webauthn_credentials, modify_webauthn_credential, new_webauthn_credential};
+use crate::http::v9::routes::oauth2::{current_authorization, delete_authorized_application, get_authorize, get_authorized_applications, post_authorize, revoke_token, token};
 
 pub fn assemble_routes() -> Router {
     let sessions = Router::new()
@@ -40,14 +64,20 @@ pub fn assemble_routes() -> Router {
 
     let authenticated_auth = Router::new()
         .route("/logout", post(logout))
-        .route("/verify", post(verify_email))
-        .route("/verify/resend", post(verify_email))
+        .route("/verify/resend", post(resend_verification_email))
+        .route("/login-attempts", get(login_attempts))
         .nest("/sessions", sessions)
         .route_layer(middleware::from_fn(get_session_context));
 
     let auth = Router::new()
         .route("/location-metadata", get(location_metadata))
         .route("/login", post(login))
+        .route("/mfa/totp", post(totp_login))
+        .route("/mfa/webauthn", post(webauthn_login))
+        .route("/forgot", post(forgot_password))
+        .route("/reset", post(reset_password))
+        .route("/verify", post(verify_email))
+        .route("/authorize-ip", post(authorize_ip))
         .route("/register", post(register))
         .merge(authenticated_auth);
 
@@ -58,24 +88,43 @@ pub fn assemble_routes() -> Router {
         .route("/relationships/:user_id", put(modify_relationship))
         .route("/channels", post(new_dm_channel))
         .route("/disable", post(disable_account))
+        .route("/delete", post(delete_account))
+        .route("/harvest", get(get_harvest))
+        .route("/harvest", post(request_harvest))
+        // Data packages are much bigger than the usual request
+        .route("/import", post(import_discord_package).layer(DefaultBodyLimit::max(104857600)))
         .route("/profile", patch(update_profile))
         .route("/notes/:user_id", get(get_notes))
         .route("/notes/:user_id", put(put_notes))
         .route("/pomelo", post(pomelo))
+        .route("/mfa/totp/enable", post(enable_totp))
+        .route("/mfa/totp/disable", post(disable_totp))
+        .route("/mfa/codes", post(backup_codes))
+        .route("/mfa/webauthn/credentials", get(list_webauthn_credentials))
+        .route("/mfa/webauthn/credentials", post(new_webauthn_credential))
+        .route("/mfa/webauthn/credentials/:credential_id", patch(modify_webauthn_credential))
+        .route("/mfa/webauthn/credentials/:credential_id", delete(delete_webauthn_credential))
         .route("/devices", post(science))
         .route("/lootboxes/open", post(open_lootbox))
         .route("/lootboxes/redeem-prize", post(redeem_prize))
         .route("/lootboxes", get(get_lootboxes))
         .route("/settings-proto/:proto", get(get_settings_proto))
         .route("/settings-proto/:proto", patch(edit_settings_proto))
-        .route("/", patch(update_user));
+        .route("/", patch(update_user))
+        .route_layer(middleware::from_fn(get_session_context));
+
+    // OAuth2 applications can use these with a bearer token, as far as its scopes allow
+    let oauth2_atme = Router::new()
+        .route("/", get(get_current_user))
+        .route("/guilds", get(get_current_user_guilds))
+        .route_layer(middleware::from_fn(get_oauth2_session_context));
 
     let users = Router::new()
-        .nest("/@me", atme)
         .route("/:user_id/profile", get(profile))
         // Workaround
         .route("/%40me/profile", patch(update_profile))
-        .route_layer(middleware::from_fn(get_session_context));
+        .route_layer(middleware::from_fn(get_session_context))
+        .nest("/@me", atme.merge(oauth2_atme));
 
     let hypesquad = Router::new()
         .route("/online", post(join_hypesquad))
@@ -85,7 +134,10 @@ pub fn assemble_routes() -> Router {
     let channels = Router::new()
         .route("/:channel_id/messages/:message_id", patch(edit_message))
         .route("/:channel_id/messages/:message_id", delete(delete_message))
+        .route("/:channel_id/messages/:message_id/ack", post(ack_message))
+        .route("/:channel_id/messages/:message_id/reactions", delete(remove_all_reactions))
         .route("/:channel_id/messages/:message_id/reactions/:emoji", get(get_reactions))
+        .route("/:channel_id/messages/:message_id/reactions/:emoji", delete(remove_emoji_reactions))
         .route("/:channel_id/messages/:message_id/reactions/:emoji/%40me", put(add_reaction))
         .route("/:channel_id/messages/:message_id/reactions/:emoji/:type/%40me", delete(remove_reaction))
         .route("/:channel_id/messages/:message_id/reactions/:emoji/:type/:user_id", delete(delete_specific_user_reaction))
@@ -98,6 +150,9 @@ pub fn assemble_routes() -> Router {
         .route("/:channel_id/pins/:message_id", put(new_pin))
         .route("/:channel_id/pins/:message_id", delete(delete_pin))
         .route("/:channel_id/attachments", post(prepare_s3_attachment_upload))
+        .route("/:channel_id/application-commands/search", get(search_application_commands))
+        .route("/:channel_id/call/ring", post(ring_call))
+        .route("/:channel_id/call/stop-ringing", post(stop_ringing_call))
         .route("/:channel_id", patch(modify_channel))
         .route_layer(middleware::from_fn(get_session_context));
 
@@ -112,6 +167,15 @@ pub fn assemble_routes() -> Router {
         .route("/@me", get(account_standing))
         .route_layer(middleware::from_fn(get_session_context));
 
+    let reports = Router::new()
+        .route("/", post(new_report))
+        .route_layer(middleware::from_fn(get_session_context));
+
+    let reporting = Router::new()
+        .route("/menu/:name", get(get_report_menu))
+        .route("/:name", post(submit_report))
+        .route_layer(middleware::from_fn(get_session_context));
+
     let attachments = Router::new()
         .route("/:attachment_id", delete(delete_attachment_upload))
         .route_layer(middleware::from_fn(get_session_context));
@@ -120,6 +184,67 @@ pub fn assemble_routes() -> Router {
         .route("/count", get(count_lootboxes))
         .route_layer(middleware::from_fn(get_session_context));
 
+    let authenticated_oauth2 = Router::new()
+        .route("/authorize", get(get_authorize))
+        .route("/authorize", post(post_authorize))
+        .route("/tokens", get(get_authorized_applications))
+        .route("/tokens/:token_id", delete(delete_authorized_application))
+        .route_layer(middleware::from_fn(get_session_context));
+
+    let oauth2 = Router::new()
+        .route("/token", post(token))
+        .route("/token/revoke", post(revoke_token))
+        .route("/@me", get(current_authorization))
+        .merge(authenticated_oauth2);
+
+    let applications = Router::new()
+        .route("/", get(get_applications))
+        .route("/", post(new_application))
+        .route("/:application_id", get(get_application))
+        .route("/:application_id", patch(modify_application))
+        .route("/:application_id/bot", post(new_bot))
+        .route("/:application_id/bot/reset", post(reset_bot_token))
+        .route("/:application_id/reset", post(reset_secret))
+        .route("/:application_id/commands", get(get_global_commands))
+        .route("/:application_id/commands", post(new_global_command))
+        .route("/:application_id/commands", put(overwrite_global_commands))
+        .route("/:application_id/commands/:command_id", get(get_global_command))
+        .route("/:application_id/commands/:command_id", patch(edit_global_command))
+        .route("/:application_id/commands/:command_id", delete(delete_global_command))
+        .route("/:application_id/guilds/:guild_id/commands", get(get_guild_commands))
+        .route("/:application_id/guilds/:guild_id/commands", post(new_guild_command))
+        .route("/:application_id/guilds/:guild_id/commands", put(overwrite_guild_commands))
+        .route("/:application_id/guilds/:guild_id/commands/:command_id", get(get_guild_command))
+        .route("/:application_id/guilds/:guild_id/commands/:command_id", patch(edit_guild_command))
+        .route("/:application_id/guilds/:guild_id/commands/:command_id", delete(delete_guild_command))
+        .route_layer(middleware::from_fn(get_session_context));
+
+    let authenticated_interactions = Router::new()
+        .route("/", post(new_interaction))
+        .route_layer(middleware::from_fn(get_session_context));
+
+    let interactions = Router::new()
+        .route("/:interaction_id/:token/callback", post(interaction_callback))
+        .merge(authenticated_interactions);
+
+    let webhooks = Router::new()
+        .route("/:application_id/:token", post(new_followup_message))
+        .route("/:application_id/:token/messages/:message_id", get(get_webhook_message))
+        .route("/:application_id/:token/messages/:message_id", patch(edit_webhook_message))
+        .route("/:application_id/:token/messages/:message_id", delete(delete_webhook_message));
+
+    let guilds = Router::new()
+        .route("/:guild_id/emojis", get(get_guild_emojis))
+        .route("/:guild_id/emojis", post(new_guild_emoji))
+        .route("/:guild_id/emojis/:emoji_id", get(get_guild_emoji))
+        .route("/:guild_id/emojis/:emoji_id", patch(modify_guild_emoji))
+        .route("/:guild_id/emojis/:emoji_id", delete(delete_guild_emoji))
+        .route_layer(middleware::from_fn(get_session_context));
+
+    let voice = Router::new()
+        .route("/regions", get(get_voice_regions))
+        .route_layer(middleware::from_fn(get_session_context));
+
     Router::new()
         .nest("/auth", auth)
         .nest("/users", users)
@@ -128,35 +253,19 @@ pub fn assemble_routes() -> Router {
         .nest("/gifs", gifs)
         .nest("/lootboxes", aprilfools2024)
         .nest("/safety-hub", safetyhub)
+        .nest("/reports", reports)
+        .nest("/reporting", reporting)
         .nest("/attachments", attachments)
+        .nest("/oauth2", oauth2)
+        .nest("/applications", applications)
+        .nest("/interactions", interactions)
+        .nest("/webhooks", webhooks)
+        .nest("/guilds", guilds)
+        .nest("/voice", voice)
+        .route("/mfa/finish", post(mfa_finish))
         .route("/experiments", get(tracking::experiments))
         .route("/science", post(tracking::science))
         .route("/track", post(tracking::science))
         .route("/metrics", post(tracking::science))
         .route_layer(middleware::from_fn(debug_body))
 }
-
-#[derive(Serialize)]
-struct AccountStandingRes {
-    account_standing: AccountStanding,
-    classifications: Vec<Stub>,
-    guild_classifications: Vec<Stub>
-}
-
-#[derive(Serialize)]
-struct AccountStanding {
-    /// 100 = All Good  
-    /// 200 = Limited  
-    /// 300 = Very Limited  
-    /// 400 = At Risk  
-    /// 500 = Suspended  
-    state: i32
-}
-
-pub async fn account_standing() -> impl IntoResponse {
-    Json(AccountStandingRes {
-        account_standing: AccountStanding { state: 100 },
-        classifications: vec![],
-        guild_classifications: vec![],
-    })
-}
\ No newline at end of file
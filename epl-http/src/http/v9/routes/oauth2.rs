@@ -0,0 +1,724 @@
+use axum::{Extension, Form, Json};
+use axum::extract::{Path, Query};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use chrono::{Duration, Utc};
+use sea_orm::*;
+use sea_orm::ActiveValue::Set;
+use serde_derive::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use url::Url;
+use epl_common::channels::ChannelTypes;
+use epl_common::database::entities::{application, channel_member, message, mention, oauth2_code, oauth2_token, user};
+use epl_common::database::entities::prelude::{Application, Channel, ChannelMember, Mention, Message, Oauth2Code, Oauth2Token, User};
+use epl_common::messages::MessageTypes;
+use epl_common::nats::Messages::{ChannelCreate, ChannelRecipientAdd, MessageCreate};
+use epl_common::nats::send_nats_message;
+use epl_common::oauth2::{get_scopes, parse_scopes, OAuth2Scopes};
+use epl_common::options::{EplOptions, Options};
+use epl_common::permissions::{internal_permission_calculator, InternalChannelPermissions};
+use epl_common::rustflake::Snowflake;
+use epl_common::schema::v9::application::{generate_application_struct, Application as ApplicationStruct};
+use epl_common::schema::v9::user::{generate_user_struct, User as UserStruct};
+use epl_common::{gen_token, Stub};
+use crate::AppState;
+use crate::authorization_extractor::{get_bearer_token, SessionContext};
+use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+
+/// How long an access token is valid for in seconds
+const ACCESS_TOKEN_LIFETIME: i64 = 604800;
+
+/// How long a refresh token can be used for in seconds, each refresh hands out a new one
+const REFRESH_TOKEN_LIFETIME: i64 = 2592000;
+
+/// How long an authorization code is valid for in seconds
+const AUTHORIZATION_CODE_LIFETIME: i64 = 600;
+
+#[derive(Serialize)]
+pub struct OAuth2Error {
+    error: String,
+    error_description: String,
+}
+
+/// Errors from the token endpoints follow RFC 6749 instead of the usual API error format
+fn oauth2_error(status: StatusCode, error: &str, description: &str) -> Response {
+    (
+        status,
+        Json(OAuth2Error {
+            error: error.to_string(),
+            error_description: description.to_string(),
+        })
+    ).into_response()
+}
+
+fn instance_url(path: &str) -> String {
+    let options = EplOptions::get();
+
+    format!(
+        "{}://{}{}",
+        if options.require_ssl { "https" } else { "http" },
+        options.url,
+        path
+    )
+}
+
+/// Authenticates an application using either HTTP basic auth or the client_id and client_secret form fields
+async fn get_client(
+    conn: &DatabaseConnection,
+    headers: &HeaderMap,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+) -> Option<application::Model> {
+    let (client_id, client_secret) = match headers.get("Authorization").and_then(|x| x.to_str().ok()) {
+        Some(auth) if auth.starts_with("Basic ") => {
+            let decoded = BASE64_STANDARD.decode(auth.trim_start_matches("Basic ")).ok()?;
+            let decoded = String::from_utf8(decoded).ok()?;
+            let (client_id, client_secret) = decoded.split_once(':')?;
+
+            (client_id.to_string(), client_secret.to_string())
+        }
+        _ => (client_id?, client_secret?),
+    };
+
+    Application::find_by_id(client_id.parse::<i64>().ok()?)
+        .filter(application::Column::Secret.eq(client_secret))
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+#[derive(Deserialize)]
+pub struct AuthorizeQuery {
+    client_id: String,
+    scope: String,
+    response_type: Option<String>,
+    redirect_uri: Option<String>,
+    state: Option<String>,
+}
+
+struct ValidatedAuthorization {
+    application: application::Model,
+    scopes: Vec<OAuth2Scopes>,
+    redirect_uri: Option<String>,
+    bot: Option<user::Model>,
+}
+
+impl ValidatedAuthorization {
+    /// Anything other than just adding a bot needs an authorization code
+    fn needs_code(&self) -> bool {
+        self.scopes.iter().any(|x| *x != OAuth2Scopes::Bot) || self.application.bot_require_code_grant
+    }
+}
+
+async fn validate_authorization(
+    conn: &DatabaseConnection,
+    query: &AuthorizeQuery,
+    user: &user::Model,
+) -> Result<ValidatedAuthorization, Response> {
+    let application = match client_id_to_application(conn, &query.client_id).await {
+        None => return Err((
+            StatusCode::from(APIErrorCode::UnknownApplication),
+            throw_http_error(APIErrorCode::UnknownApplication, vec![]).await
+        ).into_response()),
+        Some(application) => application,
+    };
+
+    let scopes = match parse_scopes(&query.scope) {
+        Some(scopes) if !scopes.is_empty() => scopes,
+        _ => return Err(StatusCode::BAD_REQUEST.into_response()),
+    };
+
+    let mut bot = None;
+
+    if scopes.contains(&OAuth2Scopes::Bot) {
+        let bot_user_id = match application.bot_user_id {
+            None => return Err(StatusCode::BAD_REQUEST.into_response()),
+            Some(bot_user_id) => bot_user_id,
+        };
+
+        // Private bots can only be added by their owner
+        if !application.bot_public && application.owner_id != user.id {
+            return Err((
+                StatusCode::from(APIErrorCode::MissingAccess),
+                throw_http_error(APIErrorCode::MissingAccess, vec![]).await
+            ).into_response());
+        }
+
+        bot = User::find_by_id(bot_user_id)
+            .one(conn)
+            .await
+            .expect("Failed to access database!");
+    }
+
+    let mut validated = ValidatedAuthorization {
+        application,
+        scopes,
+        redirect_uri: None,
+        bot,
+    };
+
+    if validated.needs_code() {
+        if query.response_type.as_deref() != Some("code") {
+            return Err(StatusCode::BAD_REQUEST.into_response());
+        }
+
+        // The redirect has to be registered, if there's only one registered it can be left out
+        validated.redirect_uri = match &query.redirect_uri {
+            Some(redirect_uri) if validated.application.redirect_uris.contains(redirect_uri) => Some(redirect_uri.clone()),
+            None if validated.application.redirect_uris.len() == 1 => validated.application.redirect_uris.first().cloned(),
+            _ => return Err(StatusCode::BAD_REQUEST.into_response()),
+        };
+    }
+
+    Ok(validated)
+}
+
+async fn client_id_to_application(conn: &DatabaseConnection, client_id: &str) -> Option<application::Model> {
+    Application::find_by_id(client_id.parse::<i64>().ok()?)
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+#[skip_serializing_none]
+#[derive(Serialize)]
+pub struct AuthorizeRes {
+    application: ApplicationStruct,
+    bot: Option<UserStruct>,
+    user: UserStruct,
+    authorized: bool,
+    redirect_uri: Option<String>,
+    guilds: Vec<Stub>,
+}
+
+pub async fn get_authorize(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Query(query): Query<AuthorizeQuery>,
+) -> impl IntoResponse {
+    let validated = match validate_authorization(&state.conn, &query, &session_context.user).await {
+        Ok(validated) => validated,
+        Err(response) => return response,
+    };
+
+    // Check if the user has already granted everything that is being asked for
+    let existing_tokens: Vec<oauth2_token::Model> = Oauth2Token::find()
+        .filter(oauth2_token::Column::ApplicationId.eq(validated.application.id))
+        .filter(oauth2_token::Column::UserId.eq(session_context.user.id))
+        .all(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    let authorized = !validated.scopes.contains(&OAuth2Scopes::Bot) && existing_tokens.iter().any(|token| {
+        let granted = get_scopes(&token.scopes);
+
+        validated.scopes.iter().all(|x| granted.contains(x))
+    });
+
+    Json(AuthorizeRes {
+        application: generate_application_struct(validated.application, None),
+        bot: validated.bot.map(generate_user_struct),
+        user: generate_user_struct(session_context.user),
+        authorized,
+        redirect_uri: validated.redirect_uri,
+        // TODO: List guilds the user can add the bot to once guilds exist
+        guilds: vec![],
+    }).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct AuthorizeReq {
+    authorize: bool,
+    permissions: Option<String>,
+    guild_id: Option<String>,
+    channel_id: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct AuthorizeLocationRes {
+    location: String,
+}
+
+pub async fn post_authorize(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Query(query): Query<AuthorizeQuery>,
+    Json(data): Json<AuthorizeReq>,
+) -> impl IntoResponse {
+    let validated = match validate_authorization(&state.conn, &query, &session_context.user).await {
+        Ok(validated) => validated,
+        Err(response) => return response,
+    };
+
+    if !data.authorize {
+        let location = match &validated.redirect_uri {
+            None => instance_url("/oauth2/error?error=access_denied"),
+            Some(redirect_uri) => {
+                let mut location = Url::parse(redirect_uri).expect("Registered redirect URI is invalid!");
+
+                location.query_pairs_mut().append_pair("error", "access_denied");
+
+                if let Some(oauth_state) = &query.state {
+                    location.query_pairs_mut().append_pair("state", oauth_state);
+                }
+
+                location.to_string()
+            }
+        };
+
+        return Json(AuthorizeLocationRes { location }).into_response();
+    }
+
+    if let Some(bot) = &validated.bot {
+        // TODO: Apply the requested permissions once guilds and roles exist
+        if data.guild_id.is_some() {
+            return (
+                StatusCode::from(APIErrorCode::UnknownGuild),
+                throw_http_error(APIErrorCode::UnknownGuild, vec![]).await
+            ).into_response();
+        }
+
+        let channel_id = match data.channel_id.as_ref().and_then(|x| x.parse::<i64>().ok()) {
+            None => return StatusCode::BAD_REQUEST.into_response(),
+            Some(channel_id) => channel_id,
+        };
+
+        if let Err(response) = add_bot_to_group_dm(&state, &session_context.user, bot, channel_id).await {
+            return response;
+        }
+    }
+
+    let location = if validated.needs_code() {
+        let code = gen_token();
+
+        oauth2_code::ActiveModel {
+            code: Set(code.clone()),
+            application_id: Set(validated.application.id),
+            user_id: Set(session_context.user.id),
+            scopes: Set(validated.scopes.iter().map(|x| String::from(*x)).collect()),
+            redirect_uri: Set(query.redirect_uri.clone()),
+            expires: Set(Utc::now().naive_utc() + Duration::seconds(AUTHORIZATION_CODE_LIFETIME)),
+        }
+            .insert(&state.conn)
+            .await
+            .expect("Failed to access database!");
+
+        let mut location = Url::parse(&validated.redirect_uri.unwrap()).expect("Registered redirect URI is invalid!");
+
+        location.query_pairs_mut().append_pair("code", &code);
+
+        if let Some(oauth_state) = &query.state {
+            location.query_pairs_mut().append_pair("state", oauth_state);
+        }
+
+        location.to_string()
+    } else {
+        instance_url("/oauth2/authorized")
+    };
+
+    Json(AuthorizeLocationRes { location }).into_response()
+}
+
+/// Adds an authorized bot to a group DM, guilds aren't implemented so this is the only place a bot can go
+async fn add_bot_to_group_dm(
+    state: &AppState,
+    user: &user::Model,
+    bot: &user::Model,
+    channel_id: i64,
+) -> Result<(), Response> {
+    let requested_channel = match Channel::find_by_id(channel_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!") {
+        Some(requested_channel) if requested_channel.r#type == ChannelTypes::GroupDM as i32 => requested_channel,
+        _ => return Err((
+            StatusCode::from(APIErrorCode::UnknownChannel),
+            throw_http_error(APIErrorCode::UnknownChannel, vec![]).await
+        ).into_response()),
+    };
+
+    let calculated_permissions = internal_permission_calculator(
+        &requested_channel,
+        user,
+        None,
+        &state.conn
+    ).await;
+
+    if !calculated_permissions.contains(&InternalChannelPermissions::AddMembers) {
+        return Err((
+            StatusCode::from(APIErrorCode::MissingAccess),
+            throw_http_error(APIErrorCode::MissingAccess, vec![]).await
+        ).into_response());
+    }
+
+    if ChannelMember::find_by_id((requested_channel.id, bot.id))
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!")
+        .is_some() {
+        // Already added, nothing to do
+        return Ok(());
+    }
+
+    let channel_members = ChannelMember::find()
+        .filter(channel_member::Column::Channel.eq(requested_channel.id))
+        .count(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    if channel_members >= 10 {
+        return Err(StatusCode::BAD_REQUEST.into_response());
+    }
+
+    ChannelMember::insert(
+        channel_member::ActiveModel {
+            channel: Set(requested_channel.id),
+            user: Set(bot.id),
+        }
+    )
+        .exec(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    send_nats_message(
+        &state.nats_client,
+        bot.id.to_string(),
+        ChannelCreate { id: requested_channel.id }
+    ).await;
+
+    send_nats_message(
+        &state.nats_client,
+        requested_channel.id.to_string(),
+        ChannelRecipientAdd {
+            channel_id: requested_channel.id,
+            user_id: bot.id,
+        }
+    ).await;
+
+    // Create the arrival message
+    let snowflake = Snowflake::default().generate();
+
+    Message::insert(message::ActiveModel {
+        id: Set(snowflake),
+        channel_id: Set(requested_channel.id),
+        author: Set(Some(user.id)),
+        content: Set(String::new()),
+        timestamp: Set(Utc::now().naive_utc()),
+        r#type: Set(MessageTypes::RecipientAdd as i32),
+        tts: Set(false),
+        mention_everyone: Set(false),
+        pinned: Set(false),
+        ..Default::default()
+    })
+        .exec(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    Mention::insert(mention::ActiveModel {
+        message: Set(snowflake),
+        user: Set(bot.id),
+    })
+        .exec(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    send_nats_message(
+        &state.nats_client,
+        requested_channel.id.to_string(),
+        MessageCreate { id: snowflake },
+    ).await;
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub struct TokenReq {
+    grant_type: String,
+    code: Option<String>,
+    redirect_uri: Option<String>,
+    refresh_token: Option<String>,
+    scope: Option<String>,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize)]
+pub struct TokenRes {
+    access_token: String,
+    token_type: String,
+    expires_in: i64,
+    refresh_token: Option<String>,
+    scope: String,
+}
+
+async fn issue_token(
+    conn: &DatabaseConnection,
+    application_id: i64,
+    user_id: i64,
+    scopes: Vec<String>,
+    refreshable: bool,
+) -> TokenRes {
+    let current_time = Utc::now().naive_utc();
+
+    let token = oauth2_token::ActiveModel {
+        id: Set(Snowflake::default().generate()),
+        access_token: Set(gen_token()),
+        refresh_token: Set(if refreshable { Some(gen_token()) } else { None }),
+        application_id: Set(application_id),
+        user_id: Set(user_id),
+        scopes: Set(scopes),
+        created: Set(current_time),
+        expires: Set(current_time + Duration::seconds(ACCESS_TOKEN_LIFETIME)),
+    }
+        .insert(conn)
+        .await
+        .expect("Failed to access database!");
+
+    TokenRes {
+        access_token: token.access_token,
+        token_type: "Bearer".to_string(),
+        expires_in: ACCESS_TOKEN_LIFETIME,
+        refresh_token: token.refresh_token,
+        scope: token.scopes.join(" "),
+    }
+}
+
+pub async fn token(
+    Extension(state): Extension<AppState>,
+    headers: HeaderMap,
+    Form(data): Form<TokenReq>,
+) -> impl IntoResponse {
+    let application = match get_client(&state.conn, &headers, data.client_id, data.client_secret).await {
+        None => return oauth2_error(StatusCode::UNAUTHORIZED, "invalid_client", "Invalid client"),
+        Some(application) => application,
+    };
+
+    match data.grant_type.as_str() {
+        "authorization_code" => {
+            let code = match data.code {
+                None => return oauth2_error(StatusCode::BAD_REQUEST, "invalid_request", "Missing code"),
+                Some(code) => code,
+            };
+
+            let code = match Oauth2Code::find_by_id(code)
+                .filter(oauth2_code::Column::ApplicationId.eq(application.id))
+                .one(&state.conn)
+                .await
+                .expect("Failed to access database!") {
+                None => return oauth2_error(StatusCode::BAD_REQUEST, "invalid_grant", "Invalid \"code\" in request."),
+                Some(code) => code,
+            };
+
+            // Codes are single use, even if the exchange fails
+            code.clone().delete(&state.conn).await.expect("Failed to access database!");
+
+            if code.expires < Utc::now().naive_utc() {
+                return oauth2_error(StatusCode::BAD_REQUEST, "invalid_grant", "Invalid \"code\" in request.");
+            }
+
+            if code.redirect_uri.is_some() && code.redirect_uri != data.redirect_uri {
+                return oauth2_error(StatusCode::BAD_REQUEST, "invalid_grant", "Invalid \"redirect_uri\" in request.");
+            }
+
+            // The bot scope is used up by the authorization itself
+            let scopes = code.scopes
+                .into_iter()
+                .filter(|x| x != &String::from(OAuth2Scopes::Bot))
+                .collect();
+
+            Json(issue_token(&state.conn, application.id, code.user_id, scopes, true).await).into_response()
+        }
+        "refresh_token" => {
+            let refresh_token = match data.refresh_token {
+                None => return oauth2_error(StatusCode::BAD_REQUEST, "invalid_request", "Missing refresh_token"),
+                Some(refresh_token) => refresh_token,
+            };
+
+            let old_token = match Oauth2Token::find()
+                .filter(oauth2_token::Column::RefreshToken.eq(refresh_token))
+                .filter(oauth2_token::Column::ApplicationId.eq(application.id))
+                .one(&state.conn)
+                .await
+                .expect("Failed to access database!") {
+                None => return oauth2_error(StatusCode::BAD_REQUEST, "invalid_grant", "Invalid \"refresh_token\" in request."),
+                Some(old_token) => old_token,
+            };
+
+            // Revoked grants are deleted, so this is also what makes sure two refreshes can't both succeed
+            let deleted = Oauth2Token::delete_by_id(old_token.id)
+                .exec(&state.conn)
+                .await
+                .expect("Failed to access database!");
+
+            if deleted.rows_affected != 1 {
+                return oauth2_error(StatusCode::BAD_REQUEST, "invalid_grant", "Invalid \"refresh_token\" in request.");
+            }
+
+            if old_token.created + Duration::seconds(REFRESH_TOKEN_LIFETIME) < Utc::now().naive_utc() {
+                return oauth2_error(StatusCode::BAD_REQUEST, "invalid_grant", "Invalid \"refresh_token\" in request.");
+            }
+
+            Json(issue_token(&state.conn, application.id, old_token.user_id, old_token.scopes, true).await).into_response()
+        }
+        "client_credentials" => {
+            let scopes = match parse_scopes(data.scope.as_deref().unwrap_or("identify")) {
+                None => return oauth2_error(StatusCode::BAD_REQUEST, "invalid_scope", "The requested scope is invalid, unknown, or malformed."),
+                Some(scopes) => scopes,
+            };
+
+            // Client credentials act as the owner of the application, so keep what they can do limited
+            if scopes.iter().any(|x| ![
+                OAuth2Scopes::Identify,
+                OAuth2Scopes::Email,
+                OAuth2Scopes::Connections,
+                OAuth2Scopes::Guilds,
+                OAuth2Scopes::ApplicationsCommandsUpdate
+            ].contains(x)) {
+                return oauth2_error(StatusCode::BAD_REQUEST, "invalid_scope", "The requested scope is invalid, unknown, or malformed.");
+            }
+
+            Json(issue_token(
+                &state.conn,
+                application.id,
+                application.owner_id,
+                scopes.into_iter().map(String::from).collect(),
+                false
+            ).await).into_response()
+        }
+        _ => oauth2_error(StatusCode::BAD_REQUEST, "unsupported_grant_type", "Unsupported grant type"),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RevokeReq {
+    token: String,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+}
+
+pub async fn revoke_token(
+    Extension(state): Extension<AppState>,
+    headers: HeaderMap,
+    Form(data): Form<RevokeReq>,
+) -> impl IntoResponse {
+    let application = match get_client(&state.conn, &headers, data.client_id, data.client_secret).await {
+        None => return oauth2_error(StatusCode::UNAUTHORIZED, "invalid_client", "Invalid client"),
+        Some(application) => application,
+    };
+
+    // Either half of the token pair revokes the whole grant
+    Oauth2Token::delete_many()
+        .filter(oauth2_token::Column::ApplicationId.eq(application.id))
+        .filter(
+            Condition::any()
+                .add(oauth2_token::Column::AccessToken.eq(data.token.clone()))
+                .add(oauth2_token::Column::RefreshToken.eq(data.token))
+        )
+        .exec(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    // RFC 7009 says invalid tokens aren't an error
+    StatusCode::OK.into_response()
+}
+
+#[skip_serializing_none]
+#[derive(Serialize)]
+pub struct CurrentAuthorizationRes {
+    application: ApplicationStruct,
+    scopes: Vec<String>,
+    expires: String,
+    user: Option<UserStruct>,
+}
+
+pub async fn current_authorization(
+    Extension(state): Extension<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let token = match get_bearer_token(&state.conn, &headers).await {
+        None => return (
+            StatusCode::from(APIErrorCode::Unauthorized),
+            throw_http_error(APIErrorCode::Unauthorized, vec![]).await
+        ).into_response(),
+        Some(token) => token,
+    };
+
+    let application = Application::find_by_id(token.application_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!")
+        .expect("Token references non-existent application!");
+
+    let user = if get_scopes(&token.scopes).contains(&OAuth2Scopes::Identify) {
+        User::find_by_id(token.user_id)
+            .one(&state.conn)
+            .await
+            .expect("Failed to access database!")
+            .map(generate_user_struct)
+    } else {
+        None
+    };
+
+    Json(CurrentAuthorizationRes {
+        application: generate_application_struct(application, None),
+        scopes: token.scopes,
+        expires: token.expires.and_utc().format("%Y-%m-%dT%H:%M:%S%z").to_string(),
+        user,
+    }).into_response()
+}
+
+#[derive(Serialize)]
+pub struct AuthorizedApplication {
+    id: String,
+    scopes: Vec<String>,
+    application: ApplicationStruct,
+}
+
+pub async fn get_authorized_applications(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+) -> impl IntoResponse {
+    let tokens: Vec<(oauth2_token::Model, Option<application::Model>)> = Oauth2Token::find()
+        .filter(oauth2_token::Column::UserId.eq(session_context.user.id))
+        .find_also_related(Application)
+        .all(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    let output: Vec<AuthorizedApplication> = tokens
+        .into_iter()
+        .filter_map(|(token, application)| {
+            Some(AuthorizedApplication {
+                id: token.id.to_string(),
+                scopes: token.scopes,
+                application: generate_application_struct(application?, None),
+            })
+        })
+        .collect();
+
+    Json(output)
+}
+
+pub async fn delete_authorized_application(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(token_id): Path<i64>,
+) -> impl IntoResponse {
+    let token = Oauth2Token::find_by_id(token_id)
+        .filter(oauth2_token::Column::UserId.eq(session_context.user.id))
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    match token {
+        None => StatusCode::NOT_FOUND,
+        Some(token) => {
+            token.delete(&state.conn).await.expect("Failed to access database!");
+
+            StatusCode::NO_CONTENT
+        }
+    }
+}
@@ -210,6 +210,10 @@ pub async fn edit_settings_proto(
                                 user_settings_active.privacy_activity_restricted_guild_ids = Set(Some(privacy.activity_restricted_guild_ids.iter().map(|x| *x as i64).collect()));
                                 user_settings_active.privacy_guild_activity_status_restriction_default = Set(privacy.default_guilds_activity_restricted);
                                 user_settings_active.privacy_activity_joining_restricted_guild_ids = Set(Some(privacy.activity_joining_restricted_guild_ids.iter().map(|x| *x as i64).collect()));
+
+                                if let Some(allow_dms_from_everyone) = privacy.allow_dms_from_everyone {
+                                    user_settings_active.privacy_allow_dms_from_everyone = Set(allow_dms_from_everyone.value);
+                                }
                             }
 
                             if let Some(debug) = settings_message.debug {
@@ -0,0 +1,216 @@
+use axum::{Extension, Json};
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use sea_orm::sea_query::SimpleExpr;
+use serde_derive::{Deserialize, Serialize};
+use epl_common::channels::ChannelTypes;
+use epl_common::database::entities::report;
+use epl_common::database::entities::prelude::{Channel, Message, Report, User};
+use epl_common::options::{EplOptions, Options};
+use epl_common::permissions::{internal_permission_calculator, InternalChannelPermissions};
+use epl_common::reports::{create_report, NewReport, reason_from_breadcrumbs, report_menu, ReportReason, ReportStatus, ReportType, snapshot_group_dm, snapshot_message, snapshot_user};
+use epl_common::rustflake::Snowflake;
+use num_traits::FromPrimitive;
+use crate::AppState;
+use crate::authorization_extractor::SessionContext;
+use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+
+#[derive(Deserialize)]
+pub struct NewReportReq {
+    /// Which menu the report came from, `message`, `user` or `group_dm`
+    name: Option<String>,
+    /// Nodes picked in the report menu
+    #[serde(default)]
+    breadcrumbs: Vec<i32>,
+    /// A reason code, for clients that don't use the menus
+    reason: Option<i32>,
+    channel_id: Option<String>,
+    message_id: Option<String>,
+    user_id: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct NewReportRes {
+    report_id: String,
+}
+
+async fn api_error_response(code: APIErrorCode) -> Response {
+    (StatusCode::from(code), throw_http_error(code, vec![]).await).into_response()
+}
+
+/// Reporting the same thing again while it's still open doesn't add another report to the queue
+async fn find_open_report(state: &AppState, reporter_id: i64, target: SimpleExpr) -> Option<report::Model> {
+    Report::find()
+        .filter(report::Column::ReporterId.eq(reporter_id))
+        .filter(report::Column::Status.eq(ReportStatus::Open as i32))
+        .filter(target)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+fn report_response(report: &report::Model) -> Response {
+    Json(NewReportRes { report_id: report.id.to_string() }).into_response()
+}
+
+fn parse_id(id: &Option<String>) -> Option<i64> {
+    id.as_ref().and_then(|x| x.parse::<i64>().ok())
+}
+
+pub async fn get_report_menu(
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    match ReportType::from_name(&name) {
+        None => StatusCode::NOT_FOUND.into_response(),
+        Some(report_type) => Json(report_menu(report_type)).into_response(),
+    }
+}
+
+/// The client's reporting API, where the menu is part of the path
+pub async fn submit_report(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(name): Path<String>,
+    Json(data): Json<NewReportReq>,
+) -> impl IntoResponse {
+    handle_report(&state, &session_context, &name, &data).await
+}
+
+pub async fn new_report(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Json(data): Json<NewReportReq>,
+) -> impl IntoResponse {
+    let name = data.name.clone().unwrap_or_default();
+
+    handle_report(&state, &session_context, &name, &data).await
+}
+
+async fn handle_report(state: &AppState, session_context: &SessionContext, name: &str, data: &NewReportReq) -> Response {
+    let reason = reason_from_breadcrumbs(&data.breadcrumbs)
+        .or_else(|| data.reason.and_then(ReportReason::from_i32));
+
+    let (Some(report_type), Some(reason)) = (ReportType::from_name(name), reason) else {
+        return api_error_response(APIErrorCode::InvalidFormBody).await;
+    };
+
+    let reporter = &session_context.user;
+    let report_id = Snowflake::default().generate();
+
+    let new_report = match report_type {
+        ReportType::Message => {
+            let (Some(channel_id), Some(message_id)) = (parse_id(&data.channel_id), parse_id(&data.message_id)) else {
+                return api_error_response(APIErrorCode::InvalidFormBody).await;
+            };
+
+            let Some(channel) = Channel::find_by_id(channel_id)
+                .one(&state.conn)
+                .await
+                .expect("Failed to access database!") else {
+                return api_error_response(APIErrorCode::UnknownChannel).await;
+            };
+
+            let Some(message) = Message::find_by_id(message_id)
+                .one(&state.conn)
+                .await
+                .expect("Failed to access database!")
+                .filter(|x| x.channel_id == channel.id) else {
+                return api_error_response(APIErrorCode::UnknownMessage).await;
+            };
+
+            // You can only report what you can see
+            if !internal_permission_calculator(&channel, reporter, None, &state.conn)
+                .await
+                .contains(&InternalChannelPermissions::ViewChannel) {
+                return api_error_response(APIErrorCode::UnknownChannel).await;
+            }
+
+            if let Some(existing_report) = find_open_report(state, reporter.id, report::Column::MessageId.eq(message.id)).await {
+                return report_response(&existing_report);
+            }
+
+            let options = EplOptions::get();
+
+            NewReport {
+                reporter_id: reporter.id,
+                report_type,
+                reason,
+                target_user_id: message.author,
+                channel_id: Some(channel.id),
+                message_id: Some(message.id),
+                snapshot: snapshot_message(&state.conn, &state.aws, &options.s3_bucket, report_id, &message).await,
+            }
+        }
+        ReportType::User => {
+            let Some(user_id) = parse_id(&data.user_id) else {
+                return api_error_response(APIErrorCode::InvalidFormBody).await;
+            };
+
+            let Some(user) = User::find_by_id(user_id)
+                .one(&state.conn)
+                .await
+                .expect("Failed to access database!") else {
+                return api_error_response(APIErrorCode::UnknownUser).await;
+            };
+
+            let target = report::Column::TargetUserId.eq(user.id)
+                .and(report::Column::ReportType.eq(ReportType::User as i32));
+
+            if let Some(existing_report) = find_open_report(state, reporter.id, target).await {
+                return report_response(&existing_report);
+            }
+
+            NewReport {
+                reporter_id: reporter.id,
+                report_type,
+                reason,
+                target_user_id: Some(user.id),
+                channel_id: None,
+                message_id: None,
+                snapshot: snapshot_user(&user),
+            }
+        }
+        ReportType::GroupDM => {
+            let Some(channel_id) = parse_id(&data.channel_id) else {
+                return api_error_response(APIErrorCode::InvalidFormBody).await;
+            };
+
+            let Some(channel) = Channel::find_by_id(channel_id)
+                .one(&state.conn)
+                .await
+                .expect("Failed to access database!")
+                .filter(|x| x.r#type == ChannelTypes::GroupDM as i32) else {
+                return api_error_response(APIErrorCode::UnknownChannel).await;
+            };
+
+            if !internal_permission_calculator(&channel, reporter, None, &state.conn)
+                .await
+                .contains(&InternalChannelPermissions::ViewChannel) {
+                return api_error_response(APIErrorCode::UnknownChannel).await;
+            }
+
+            let target = report::Column::ChannelId.eq(channel.id)
+                .and(report::Column::ReportType.eq(ReportType::GroupDM as i32));
+
+            if let Some(existing_report) = find_open_report(state, reporter.id, target).await {
+                return report_response(&existing_report);
+            }
+
+            NewReport {
+                reporter_id: reporter.id,
+                report_type,
+                reason,
+                target_user_id: channel.owner_id,
+                channel_id: Some(channel.id),
+                message_id: None,
+                snapshot: snapshot_group_dm(&state.conn, &channel).await,
+            }
+        }
+    };
+
+    let report = create_report(&state.conn, report_id, new_report).await;
+
+    report_response(&report)
+}
@@ -0,0 +1,73 @@
+use axum::{Extension, Json};
+use axum::response::IntoResponse;
+use serde_derive::Serialize;
+use epl_common::moderation::{AccountStandingState, get_account_standing, get_action_type, get_active_actions, ModerationActionType};
+use epl_common::Stub;
+use crate::AppState;
+use crate::authorization_extractor::SessionContext;
+
+#[derive(Serialize)]
+struct AccountStandingRes {
+    account_standing: AccountStanding,
+    classifications: Vec<Classification>,
+    guild_classifications: Vec<Stub>,
+    username: String,
+    discriminator: String,
+}
+
+#[derive(Serialize)]
+struct AccountStanding {
+    state: AccountStandingState,
+}
+
+/// An active moderation action, shaped like the violations Discord shows in the Safety Hub
+#[derive(Serialize)]
+struct Classification {
+    id: String,
+    classification_type: &'static str,
+    description: String,
+    explainer_link: Option<String>,
+    actions: Vec<ClassificationAction>,
+    max_expiration_time: Option<String>,
+    flagged_content: Vec<Stub>,
+    is_coppa: bool,
+    is_spam: bool,
+}
+
+#[derive(Serialize)]
+struct ClassificationAction {
+    id: String,
+    action_type: ModerationActionType,
+    descriptions: Vec<&'static str>,
+}
+
+pub async fn account_standing(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+) -> impl IntoResponse {
+    let active_actions = get_active_actions(&state.conn, session_context.user.id).await;
+
+    let classifications = active_actions.iter().map(|x| Classification {
+        id: x.id.to_string(),
+        classification_type: "instance_moderation",
+        description: x.reason.clone(),
+        explainer_link: None,
+        actions: vec![ClassificationAction {
+            id: x.id.to_string(),
+            action_type: get_action_type(x),
+            descriptions: vec![get_action_type(x).description()],
+        }],
+        max_expiration_time: x.expires_at.map(|x| x.and_utc().format("%Y-%m-%dT%H:%M:%S%z").to_string()),
+        flagged_content: vec![],
+        is_coppa: false,
+        is_spam: false,
+    }).collect();
+
+    Json(AccountStandingRes {
+        account_standing: AccountStanding { state: get_account_standing(&active_actions) },
+        classifications,
+        guild_classifications: vec![],
+        username: session_context.user.username,
+        discriminator: session_context.user.discriminator,
+    })
+}
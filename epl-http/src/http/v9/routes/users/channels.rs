@@ -18,6 +18,9 @@ use epl_common::RelationshipType;
 use sea_orm::prelude::*;
 use serde_with::skip_serializing_none;
 use epl_common::relationship::get_relationship;
+use epl_common::privacy::can_dm;
+use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+use crate::http::v9::routes::auth::require_verification;
 
 #[derive(Deserialize)]
 pub struct NewDMChannelReq {
@@ -50,6 +53,8 @@ pub struct ResChannel {
     pub name: Option<String>,
     pub owner_id: Option<String>,
     pub recipients: Option<Vec<ResChannelMember>>,
+    pub rtc_region: Option<String>,
+    pub video_quality_mode: Option<i32>,
     #[serde(rename = "type")]
     pub _type: i32,
 }
@@ -59,11 +64,17 @@ pub async fn new_dm_channel(
     Extension(session_context): Extension<SessionContext>,
     Json(new_channel_dm_req): Json<NewDMChannelReq>,
 ) -> impl IntoResponse {
+    if let Some(res) = require_verification(&session_context.user).await {
+        return res;
+    }
+
     let snowflake = Snowflake::default().generate();
 
     let mut users: Vec<ResChannelMember> = vec![];
     let mut channel_members: Vec<channel_member::ActiveModel> = vec![];
 
+    let is_dm = new_channel_dm_req.recipients.len() == 1;
+
     // First we ensure the users both exist and are friends with the creator
     for i in &new_channel_dm_req.recipients {
         let user: Option<user::Model> =
@@ -72,52 +83,55 @@ pub async fn new_dm_channel(
                 .await
                 .expect("Failed to access database!");
 
-        match user {
-            None => return StatusCode::BAD_REQUEST.into_response(),
-            Some(user) => {
-                // User exists, now we check if they're actually friends
-                match get_relationship(session_context.user.id, user.id, &state.conn).await {
-                    None => {
-                        // They're not, bail
-                        return StatusCode::BAD_REQUEST.into_response();
-                    }
-                    Some(relationship) => {
-                        if relationship.relationship_type != RelationshipType::Friend as i32 {
-                            // They're either pending or blocked, bail
-                            return StatusCode::BAD_REQUEST.into_response();
-                        } else {
-                            // They're friends, keep going
-
-                            // We'll also generate the channel member entries now
-                            channel_members.push(
-                                channel_member::Model {
-                                    channel: snowflake,
-                                    user: user.id,
-                                }
-                                .into_active_model(),
-                            );
-
-                            // And the required ResChannelMember for the HTTP response
-                            users.push(ResChannelMember {
-                                accent_color: user.accent_color.map(|e| {
-                                    e.parse().expect("Failed to parse user's accent_color")
-                                }),
-                                avatar: user.avatar,
-                                avatar_decoration: user.avatar_decoration,
-                                banner: user.banner,
-                                banner_color: user.banner_colour,
-                                discriminator: Some(user.discriminator),
-                                flags: generate_public_flags(get_user_flags(user.flags)),
-                                global_name: user.display_name,
-                                id: user.id.to_string(),
-                                public_flags: generate_public_flags(get_user_flags(user.flags)),
-                                username: user.username,
-                            });
-                        }
-                    }
-                }
+        let Some(user) = user else {
+            return StatusCode::BAD_REQUEST.into_response();
+        };
+
+        // User exists, now we check if they're actually friends or accept DMs anyway
+        let allowed = match get_relationship(session_context.user.id, user.id, &state.conn).await {
+            // Group DMs are only between friends, but privacy settings can let strangers open a DM
+            None => is_dm && can_dm(&state.conn, session_context.user.id, user.id).await,
+            // Pending and blocked relationships don't count
+            Some(relationship) => relationship.relationship_type == RelationshipType::Friend as i32,
+        };
+
+        if !allowed {
+            if is_dm {
+                return (
+                    StatusCode::from(APIErrorCode::CannotSendMessagesToUser),
+                    throw_http_error(APIErrorCode::CannotSendMessagesToUser, vec![]).await,
+                )
+                    .into_response();
             }
+
+            return StatusCode::BAD_REQUEST.into_response();
         }
+
+        // We'll also generate the channel member entries now
+        channel_members.push(
+            channel_member::Model {
+                channel: snowflake,
+                user: user.id,
+            }
+            .into_active_model(),
+        );
+
+        // And the required ResChannelMember for the HTTP response
+        users.push(ResChannelMember {
+            accent_color: user.accent_color.map(|e| {
+                e.parse().expect("Failed to parse user's accent_color")
+            }),
+            avatar: user.avatar,
+            avatar_decoration: user.avatar_decoration,
+            banner: user.banner,
+            banner_color: user.banner_colour,
+            discriminator: Some(user.discriminator),
+            flags: generate_public_flags(get_user_flags(user.flags)),
+            global_name: user.display_name,
+            id: user.id.to_string(),
+            public_flags: generate_public_flags(get_user_flags(user.flags)),
+            username: user.username,
+        });
     }
 
     // Now we do the same as above but for the creator
@@ -133,7 +147,7 @@ pub async fn new_dm_channel(
     let mut channel_type = ChannelTypes::DM;
 
     // If there's more than one user or no users specified, it's a group DM
-    if new_channel_dm_req.recipients.len().ne(&1) {
+    if !is_dm {
         channel_type = ChannelTypes::GroupDM;
     }
 
@@ -184,6 +198,8 @@ pub async fn new_dm_channel(
                 }
             },
             recipients: Some(users),
+            rtc_region: None,
+            video_quality_mode: None,
             _type: channel_type as i32,
         }),
     )
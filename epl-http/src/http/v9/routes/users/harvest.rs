@@ -0,0 +1,93 @@
+use axum::{Extension, Json};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use chrono::{Duration, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use sea_orm::ActiveValue::Set;
+use serde_derive::Serialize;
+use epl_common::database::entities::harvest;
+use epl_common::database::entities::prelude::Harvest;
+use epl_common::harvest::{HARVEST_COOLDOWN_DAYS, HarvestStatus};
+use epl_common::nats::{Messages, send_nats_message};
+use epl_common::rustflake::Snowflake;
+use crate::AppState;
+use crate::authorization_extractor::SessionContext;
+use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+
+#[derive(Serialize)]
+pub struct HarvestRes {
+    harvest_id: String,
+    user_id: String,
+    email: String,
+    status: i32,
+    created_at: String,
+    completed_at: Option<String>,
+}
+
+fn harvest_res(harvest: harvest::Model, email: String) -> HarvestRes {
+    HarvestRes {
+        harvest_id: harvest.id.to_string(),
+        user_id: harvest.user_id.to_string(),
+        email,
+        status: harvest.status,
+        created_at: harvest.created_at.and_utc().format("%Y-%m-%dT%H:%M:%S%z").to_string(),
+        completed_at: harvest.completed_at.map(|x| x.and_utc().format("%Y-%m-%dT%H:%M:%S%z").to_string()),
+    }
+}
+
+async fn get_latest_harvest(state: &AppState, user_id: i64) -> Option<harvest::Model> {
+    Harvest::find()
+        .filter(harvest::Column::UserId.eq(user_id))
+        .order_by_desc(harvest::Column::CreatedAt)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!")
+}
+
+pub async fn get_harvest(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+) -> impl IntoResponse {
+    match get_latest_harvest(&state, session_context.user.id).await {
+        Some(harvest) => Json(harvest_res(harvest, session_context.user.email)).into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+/// Queues a package with a copy of the user's data, the worker emails a link once it's built
+pub async fn request_harvest(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+) -> impl IntoResponse {
+    let now = Utc::now().naive_utc();
+
+    // Failed packages don't count, otherwise you'd have to wait a month to try again
+    if let Some(latest_harvest) = get_latest_harvest(&state, session_context.user.id).await {
+        if latest_harvest.status != HarvestStatus::Failed as i32
+            && latest_harvest.created_at > now - Duration::days(HARVEST_COOLDOWN_DAYS) {
+            return (
+                StatusCode::from(APIErrorCode::HarvestRequestedRecently),
+                throw_http_error(APIErrorCode::HarvestRequestedRecently, vec![]).await,
+            ).into_response();
+        }
+    }
+
+    let harvest = harvest::ActiveModel {
+        id: Set(Snowflake::default().generate()),
+        user_id: Set(session_context.user.id),
+        status: Set(HarvestStatus::Queued as i32),
+        created_at: Set(now),
+        completed_at: Set(None),
+    }
+        .insert(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    send_nats_message(
+        &state.nats_client,
+        "worker_queue".to_string(),
+        Messages::ProcessHarvest { harvest_id: harvest.id },
+    ).await;
+
+    Json(harvest_res(harvest, session_context.user.email)).into_response()
+}
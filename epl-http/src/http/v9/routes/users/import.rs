@@ -0,0 +1,43 @@
+use std::io::Cursor;
+use axum::body::Bytes;
+use axum::{Extension, Json};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use tracing::error;
+use epl_common::import::{import_package, ImportErrorEnum, ImportTarget};
+use epl_common::options::{EplOptions, Options};
+use crate::AppState;
+use crate::authorization_extractor::SessionContext;
+use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+
+/// Imports a Discord data package into the current account, the body is the package's zip
+pub async fn import_discord_package(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    package: Bytes,
+) -> impl IntoResponse {
+    let report = import_package(
+        &state.conn,
+        &state.aws,
+        &EplOptions::get().s3_bucket,
+        Some(&state.nats_client),
+        Cursor::new(package),
+        ImportTarget::Existing(session_context.user),
+    ).await;
+
+    match report {
+        Ok(report) => Json(report).into_response(),
+        Err(err) => {
+            let code = match err.kind {
+                ImportErrorEnum::InvalidPackage => APIErrorCode::InvalidDataPackage,
+                ImportErrorEnum::AlreadyMapped => APIErrorCode::DiscordAccountAlreadyImported,
+                _ => {
+                    error!("Failed to import data package: {}", err.message);
+                    return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+                }
+            };
+
+            (StatusCode::from(code), throw_http_error(code, vec![]).await).into_response()
+        }
+    }
+}
@@ -0,0 +1,356 @@
+use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use axum::{Extension, Json};
+use axum::extract::Path;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use chrono::Duration;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel, ModelTrait, QueryFilter};
+use sea_orm::ActiveValue::Set;
+use serde_derive::{Deserialize, Serialize};
+use epl_common::database::auth::{create_ticket_with_state, TicketType, verify_ticket_with_state};
+use epl_common::database::entities::{backup_code, user, webauthn_credential};
+use epl_common::database::entities::prelude::{BackupCode, WebauthnCredential};
+use epl_common::mfa::{finish_webauthn_registration, generate_backup_codes, get_backup_codes, get_webauthn_credentials, parse_totp_secret, ResBackupCode, start_webauthn_registration, verify_mfa_code, verify_totp};
+use epl_common::rustflake::Snowflake;
+use crate::http::v9::routes::mfa::require_mfa;
+use crate::http::v9::routes::users::rotate_sessions;
+use crate::AppState;
+use crate::authorization_extractor::SessionContext;
+use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+
+fn verify_password(user: &user::Model, password: &str) -> bool {
+    let password_hash = PasswordHash::new(&user.password_hash).expect("Failed to parse password hash!");
+
+    Argon2::default()
+        .verify_password(password.as_bytes(), &password_hash)
+        .is_ok()
+}
+
+#[derive(Deserialize)]
+pub struct EnableTotpReq {
+    password: String,
+    secret: String,
+    code: String,
+}
+
+#[derive(Serialize)]
+pub struct EnableTotpRes {
+    token: String,
+    backup_codes: Vec<ResBackupCode>,
+}
+
+pub async fn enable_totp(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Json(data): Json<EnableTotpReq>,
+) -> impl IntoResponse {
+    if session_context.user.mfa_enabled {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    if !verify_password(&session_context.user, &data.password) {
+        return (
+            StatusCode::from(APIErrorCode::PasswordDoesNotMatch),
+            throw_http_error(APIErrorCode::PasswordDoesNotMatch, vec![]).await
+        ).into_response();
+    }
+
+    let secret = match parse_totp_secret(&data.secret) {
+        None => return (
+            StatusCode::from(APIErrorCode::InvalidFormBody),
+            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await
+        ).into_response(),
+        Some(secret) => secret,
+    };
+
+    // Make sure the user actually set up their authenticator
+    if !verify_totp(&secret, &data.code) {
+        return (
+            StatusCode::from(APIErrorCode::InvalidTwoFactorCode),
+            throw_http_error(APIErrorCode::InvalidTwoFactorCode, vec![]).await
+        ).into_response();
+    }
+
+    let user_id = session_context.user.id;
+    let mut active_user = session_context.user.into_active_model();
+
+    active_user.mfa_enabled = Set(true);
+    active_user.totp_secret = Set(Some(secret));
+
+    active_user.update(&state.conn).await.expect("Failed to access database!");
+
+    let backup_codes = generate_backup_codes(&state.conn, user_id).await;
+    let token = rotate_sessions(&state, user_id).await;
+
+    Json(EnableTotpRes {
+        token,
+        backup_codes: backup_codes.into_iter().map(ResBackupCode::from).collect(),
+    }).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct DisableTotpReq {
+    code: String,
+}
+
+#[derive(Serialize)]
+pub struct DisableTotpRes {
+    token: String,
+}
+
+pub async fn disable_totp(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Json(data): Json<DisableTotpReq>,
+) -> impl IntoResponse {
+    if !session_context.user.mfa_enabled {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    if !verify_mfa_code(&state.conn, &session_context.user, &data.code).await {
+        return (
+            StatusCode::from(APIErrorCode::InvalidTwoFactorCode),
+            throw_http_error(APIErrorCode::InvalidTwoFactorCode, vec![]).await
+        ).into_response();
+    }
+
+    let user_id = session_context.user.id;
+    let mut active_user = session_context.user.into_active_model();
+
+    active_user.mfa_enabled = Set(false);
+    active_user.totp_secret = Set(None);
+
+    active_user.update(&state.conn).await.expect("Failed to access database!");
+
+    BackupCode::delete_many()
+        .filter(backup_code::Column::UserId.eq(user_id))
+        .exec(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    // Security keys are only a second factor on top of TOTP
+    WebauthnCredential::delete_many()
+        .filter(webauthn_credential::Column::UserId.eq(user_id))
+        .exec(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    let token = rotate_sessions(&state, user_id).await;
+
+    Json(DisableTotpRes { token }).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct BackupCodesReq {
+    password: String,
+    #[serde(default)]
+    regenerate: bool,
+}
+
+#[derive(Serialize)]
+pub struct BackupCodesRes {
+    backup_codes: Vec<ResBackupCode>,
+}
+
+pub async fn backup_codes(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Json(data): Json<BackupCodesReq>,
+) -> impl IntoResponse {
+    if !session_context.user.mfa_enabled {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    if !verify_password(&session_context.user, &data.password) {
+        return (
+            StatusCode::from(APIErrorCode::PasswordDoesNotMatch),
+            throw_http_error(APIErrorCode::PasswordDoesNotMatch, vec![]).await
+        ).into_response();
+    }
+
+    let backup_codes = if data.regenerate {
+        generate_backup_codes(&state.conn, session_context.user.id).await
+    } else {
+        get_backup_codes(&state.conn, session_context.user.id).await
+    };
+
+    Json(BackupCodesRes {
+        backup_codes: backup_codes.into_iter().map(ResBackupCode::from).collect(),
+    }).into_response()
+}
+
+#[derive(Serialize)]
+pub struct ResWebauthnCredential {
+    id: String,
+    /// Always 1 for WebAuthn
+    #[serde(rename = "type")]
+    _type: i32,
+    name: String,
+}
+
+impl From<webauthn_credential::Model> for ResWebauthnCredential {
+    fn from(credential: webauthn_credential::Model) -> Self {
+        ResWebauthnCredential {
+            id: credential.id.to_string(),
+            _type: 1,
+            name: credential.name,
+        }
+    }
+}
+
+async fn get_owned_webauthn_credential(state: &AppState, user_id: i64, credential_id: i64) -> Option<webauthn_credential::Model> {
+    WebauthnCredential::find_by_id(credential_id)
+        .one(&state.conn)
+        .await
+        .expect("Failed to access database!")
+        .filter(|x| x.user_id == user_id)
+}
+
+pub async fn list_webauthn_credentials(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+) -> impl IntoResponse {
+    Json(
+        get_webauthn_credentials(&state.conn, session_context.user.id)
+            .await
+            .into_iter()
+            .map(ResWebauthnCredential::from)
+            .collect::<Vec<ResWebauthnCredential>>()
+    )
+}
+
+/// Without a ticket this starts registering a key, with one it finishes
+#[derive(Deserialize)]
+pub struct NewWebauthnCredentialReq {
+    name: Option<String>,
+    ticket: Option<String>,
+    /// The attestation from the security key as a JSON string
+    credential: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct WebauthnChallengeRes {
+    ticket: String,
+    /// The creation options as a JSON string
+    challenge: String,
+}
+
+pub async fn new_webauthn_credential(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    headers: HeaderMap,
+    Json(data): Json<NewWebauthnCredentialReq>,
+) -> impl IntoResponse {
+    if !session_context.user.mfa_enabled {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    let (ticket, credential, name) = match (data.ticket, data.credential, data.name) {
+        (Some(ticket), Some(credential), Some(name)) => (ticket, credential, name),
+        (None, _, _) => {
+            if let Some(res) = require_mfa(&state.conn, &headers, &session_context.user).await {
+                return res;
+            }
+
+            let (challenge, registration_state) = match start_webauthn_registration(&state.conn, &session_context.user).await {
+                None => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+                Some(registration) => registration,
+            };
+
+            return Json(WebauthnChallengeRes {
+                ticket: create_ticket_with_state(
+                    session_context.user.id,
+                    TicketType::WebauthnRegistration,
+                    Duration::minutes(5),
+                    Some(serde_json::to_value(registration_state).expect("Failed to encode WebAuthn state!")),
+                ),
+                challenge: serde_json::to_string(&challenge).expect("Failed to encode WebAuthn challenge!"),
+            }).into_response();
+        }
+        _ => return (
+            StatusCode::from(APIErrorCode::InvalidFormBody),
+            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await
+        ).into_response(),
+    };
+
+    let registration_state = match verify_ticket_with_state(&ticket, TicketType::WebauthnRegistration) {
+        Some((user_id, Some(registration_state))) if user_id == session_context.user.id => registration_state,
+        _ => return (
+            StatusCode::from(APIErrorCode::InvalidFormBody),
+            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await
+        ).into_response(),
+    };
+
+    let security_key = match (
+        serde_json::from_value(registration_state),
+        serde_json::from_str(&credential),
+    ) {
+        (Ok(registration_state), Ok(credential)) => finish_webauthn_registration(&credential, &registration_state),
+        _ => None,
+    };
+
+    let security_key = match security_key {
+        None => return (
+            StatusCode::from(APIErrorCode::InvalidFormBody),
+            throw_http_error(APIErrorCode::InvalidFormBody, vec![]).await
+        ).into_response(),
+        Some(security_key) => security_key,
+    };
+
+    let credential = webauthn_credential::ActiveModel {
+        id: Set(Snowflake::default().generate()),
+        user_id: Set(session_context.user.id),
+        name: Set(name),
+        credential: Set(serde_json::to_value(security_key).expect("Failed to encode WebAuthn credential!")),
+    }
+        .insert(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    Json(ResWebauthnCredential::from(credential)).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ModifyWebauthnCredentialReq {
+    name: String,
+}
+
+pub async fn modify_webauthn_credential(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(credential_id): Path<i64>,
+    Json(data): Json<ModifyWebauthnCredentialReq>,
+) -> impl IntoResponse {
+    let credential = match get_owned_webauthn_credential(&state, session_context.user.id, credential_id).await {
+        None => return StatusCode::NOT_FOUND.into_response(),
+        Some(credential) => credential,
+    };
+
+    let mut credential = credential.into_active_model();
+
+    credential.name = Set(data.name);
+
+    let credential = credential.update(&state.conn).await.expect("Failed to access database!");
+
+    Json(ResWebauthnCredential::from(credential)).into_response()
+}
+
+pub async fn delete_webauthn_credential(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    Path(credential_id): Path<i64>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Some(res) = require_mfa(&state.conn, &headers, &session_context.user).await {
+        return res;
+    }
+
+    let credential = match get_owned_webauthn_credential(&state, session_context.user.id, credential_id).await {
+        None => return StatusCode::NOT_FOUND.into_response(),
+        Some(credential) => credential,
+    };
+
+    credential.delete(&state.conn).await.expect("Failed to access database!");
+
+    StatusCode::NO_CONTENT.into_response()
+}
@@ -1,6 +1,9 @@
 pub mod channels;
 pub mod relationships;
 pub mod notes;
+pub mod mfa;
+pub mod harvest;
+pub mod import;
 
 use std::io;
 use argon2::{Argon2, PasswordHash, PasswordVerifier};
@@ -8,7 +11,7 @@ use aws_sdk_s3::primitives::ByteStream;
 use crate::authorization_extractor::SessionContext;
 use crate::AppState;
 use axum::extract::{Path, Query};
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::IntoResponse;
 use axum::{Extension, Json};
 use epl_common::database::entities::{session, user};
@@ -23,6 +26,16 @@ use base64::prelude::*;
 use ril::ImageFormat::WebP;
 use ril::prelude::*;
 use epl_common::options::{EplOptions, Options};
+use epl_common::database::auth::{generate_password_hash, generate_session};
+use epl_common::database::entities::prelude::Session;
+use epl_common::schema::v9::user::generate_private_user_struct;
+use epl_common::mail::{Mail, queue_mail};
+use epl_common::oauth2::OAuth2Scopes;
+use epl_common::system::send_system_message;
+use chrono::{Duration, Utc};
+use crate::http::v9::errors::{APIErrorCode, APIErrorField, APIErrorMessage, throw_http_error};
+use crate::http::v9::routes::auth::{password_error, send_verification_email};
+use crate::http::v9::routes::mfa::require_mfa;
 
 #[derive(Serialize)]
 pub struct ProfileRes {
@@ -215,8 +228,13 @@ pub struct DisableReq {
 pub async fn disable_account(
     Extension(state): Extension<AppState>,
     Extension(session_context): Extension<SessionContext>,
+    headers: HeaderMap,
     data: Json<DisableReq>,
 ) -> impl IntoResponse {
+    if let Some(res) = require_mfa(&state.conn, &headers, &session_context.user).await {
+        return res;
+    }
+
     // Verify password
     let password_hash =
         PasswordHash::new(&session_context.user.password_hash).expect("Failed to parse password hash!");
@@ -226,7 +244,7 @@ pub async fn disable_account(
         Ok(_) => {
             let mut flags = session_context.user.flags;
 
-            flags += UserFlags::Disabled as i64;
+            flags |= UserFlags::Disabled as i64;
 
             let mut active_user = session_context.user.into_active_model();
 
@@ -243,30 +261,155 @@ pub async fn disable_account(
 
                     match session_delete_res {
                         Ok(_) => {
-                            StatusCode::NO_CONTENT
+                            StatusCode::NO_CONTENT.into_response()
                         }
                         Err(_) => {
-                            StatusCode::INTERNAL_SERVER_ERROR
+                            StatusCode::INTERNAL_SERVER_ERROR.into_response()
                         }
                     }
                 },
-                Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
             }
         }
         Err(_) => {
-            StatusCode::BAD_REQUEST
+            StatusCode::BAD_REQUEST.into_response()
         }
     }
 }
 
-pub async fn delete_account() -> impl IntoResponse {
-    StatusCode::NOT_IMPLEMENTED
+/// Schedules the account to be deleted once the grace period is over, logging back in cancels it
+pub async fn delete_account(
+    Extension(state): Extension<AppState>,
+    Extension(session_context): Extension<SessionContext>,
+    headers: HeaderMap,
+    data: Json<DisableReq>,
+) -> impl IntoResponse {
+    if let Some(res) = require_mfa(&state.conn, &headers, &session_context.user).await {
+        return res;
+    }
+
+    let password_hash =
+        PasswordHash::new(&session_context.user.password_hash).expect("Failed to parse password hash!");
+
+    if Argon2::default().verify_password(data.password.as_bytes(), &password_hash).is_err() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    let options = EplOptions::get();
+    let deletion_date = Utc::now().naive_utc() + Duration::days(options.deletion_grace_period);
+
+    let user_id = session_context.user.id;
+    let username = session_context.user.username.clone();
+    let email = session_context.user.email.clone();
+
+    let mut active_user = session_context.user.into_active_model();
+
+    active_user.deletion_scheduled_at = Set(Some(deletion_date));
+
+    active_user.update(&state.conn).await.expect("Failed to access database!");
+
+    Session::delete_many()
+        .filter(session::Column::UserId.eq(user_id))
+        .exec(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    send_nats_message(
+        &state.nats_client,
+        user_id.to_string(),
+        Messages::InvalidateGatewaySession { session: "all".to_string() }
+    ).await;
+
+    send_system_message(
+        &state.conn,
+        &state.nats_client,
+        user_id,
+        format!(
+            "Your account will be deleted on {}. Logging back in before then cancels the deletion.",
+            deletion_date.format("%Y-%m-%d"),
+        ),
+        false,
+    ).await;
+
+    queue_mail(
+        &state.nats_client,
+        email,
+        Mail::AccountDeletion {
+            username,
+            deletion_date: deletion_date.format("%Y-%m-%d").to_string(),
+        },
+    ).await;
+
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// Logs the user out everywhere, returns the token for the new session
+pub(crate) async fn rotate_sessions(state: &AppState, user_id: i64) -> String {
+    Session::delete_many()
+        .filter(session::Column::UserId.eq(user_id))
+        .exec(&state.conn)
+        .await
+        .expect("Failed to access database!");
+
+    send_nats_message(
+        &state.nats_client,
+        user_id.to_string(),
+        Messages::InvalidateGatewaySession { session: "all".to_string() }
+    ).await;
+
+    generate_session(&state.conn, user_id).await.expect("Failed to create session!")
+}
+
+/// OAuth2 applications need `identify`, and only see the email with `email`
+pub async fn get_current_user(
+    Extension(session_context): Extension<SessionContext>,
+) -> impl IntoResponse {
+    if !session_context.has_scope(OAuth2Scopes::Identify) {
+        return (
+            StatusCode::from(APIErrorCode::MissingAccess),
+            throw_http_error(APIErrorCode::MissingAccess, vec![]).await
+        ).into_response();
+    }
+
+    let has_email = session_context.has_scope(OAuth2Scopes::Email);
+    let is_oauth2 = session_context.scopes.is_some();
+
+    let user_struct = generate_private_user_struct(session_context.user, session_context.session.and_then(|x| x.platform));
+    let mut user_struct = serde_json::to_value(user_struct).expect("Failed to serialize user!");
+
+    if let Some(user_struct) = user_struct.as_object_mut() {
+        if !has_email {
+            user_struct.remove("email");
+            user_struct.remove("verified");
+        }
+
+        if is_oauth2 {
+            user_struct.remove("phone");
+        }
+    }
+
+    Json(user_struct).into_response()
+}
+
+/// Epl doesn't have guilds yet, but OAuth2 applications still need the `guilds` scope to ask
+pub async fn get_current_user_guilds(
+    Extension(session_context): Extension<SessionContext>,
+) -> impl IntoResponse {
+    if !session_context.has_scope(OAuth2Scopes::Guilds) {
+        return (
+            StatusCode::from(APIErrorCode::MissingAccess),
+            throw_http_error(APIErrorCode::MissingAccess, vec![]).await
+        ).into_response();
+    }
+
+    Json(Vec::<Stub>::new()).into_response()
 }
 
 #[derive(Deserialize, Clone)]
 pub struct UpdateUserReq {
     pub avatar: Option<String>,
     pub global_name: Option<String>,
+    pub email: Option<String>,
     pub new_password: Option<String>,
     pub password: Option<String>
 }
@@ -276,6 +419,8 @@ pub async fn update_user(
     Extension(session_context): Extension<SessionContext>,
     data: Json<UpdateUserReq>,
 ) -> impl IntoResponse {
+    let user_id = session_context.user.id;
+    let other_fields = [session_context.user.username.clone(), session_context.user.email.clone()];
     let mut active_user = session_context.user.into_active_model();
     let options = EplOptions::get();
 
@@ -315,64 +460,89 @@ pub async fn update_user(
         }
     }
 
-    if let Some(new_password) = &data.new_password {
-        let data = data.clone();
+    let email_changed = data.email.as_ref().is_some_and(|email| *email != other_fields[1]);
 
-        if data.password.is_none() {
-            return StatusCode::BAD_REQUEST.into_response();
-        }
+    // Changing the password or email needs the current password
+    if data.new_password.is_some() || email_changed {
+        let current_password = match &data.password {
+            Some(current_password) if !current_password.is_empty() => current_password.clone(),
+            _ => return StatusCode::BAD_REQUEST.into_response(),
+        };
 
-        let current_password = data.password.clone().unwrap();
         let current_password_hash = active_user.password_hash.clone().unwrap();
 
-        if current_password.is_empty() {
-            return StatusCode::BAD_REQUEST.into_response();
-        }
-
         // Verify password
         let password_hash =
             PasswordHash::new(&current_password_hash).expect("Failed to parse password hash!");
 
-        match Argon2::default()
-            .verify_password(current_password.as_bytes(), &password_hash) {
-            Ok(_) => {
-                // TODO: Implement :)
-                return StatusCode::NOT_IMPLEMENTED.into_response();
+        if Argon2::default()
+            .verify_password(current_password.as_bytes(), &password_hash)
+            .is_err() {
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    }
+
+    if let Some(new_password) = &data.new_password {
+        match generate_password_hash(new_password, other_fields.iter().map(String::as_str).collect()) {
+            Ok(new_password_hash) => {
+                active_user.password_hash = Set(new_password_hash);
             }
-            Err(_) => {
-                return StatusCode::BAD_REQUEST.into_response();
+            Err(err) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    throw_http_error(APIErrorCode::InvalidFormBody, password_error(&err).into_iter().collect()).await
+                ).into_response();
             }
         }
     }
 
+    if email_changed {
+        let email = data.email.clone().unwrap();
+
+        let existing_user = user::Entity::find()
+            .filter(user::Column::Email.eq(&email))
+            .one(&state.conn)
+            .await
+            .expect("Failed to access database!");
+
+        if existing_user.is_some() {
+            return (
+                StatusCode::BAD_REQUEST,
+                throw_http_error(APIErrorCode::InvalidFormBody, vec![APIErrorField::Email {
+                    _errors: vec![APIErrorMessage {
+                        code: "EMAIL_ALREADY_REGISTERED".to_string(),
+                        message: "Email is already registered.".to_string(),
+                    }],
+                }]).await
+            ).into_response();
+        }
+
+        // The new address has to be verified again
+        active_user.email = Set(email);
+        active_user.acct_verified = Set(false);
+        active_user.flags = Set(active_user.flags.clone().unwrap() & !(UserFlags::VerifiedEmail as i64));
+    }
+
     match active_user.update(&state.conn).await {
         Ok(user) => {
-            Json(
-                epl_common::User {
-                    verified: user.acct_verified,
-                    username: user.username,
-                    purchased_flags: user.purchased_flags.unwrap_or(0),
-                    premium_type: user.premium_type.unwrap_or(0),
-                    premium: (user.premium_type.unwrap_or(0) != 0),
-                    phone: user.phone,
-                    nsfw_allowed: user.nsfw_allowed,
-                    mobile: matches!(session_context.session.platform.clone().unwrap_or_default().as_str(), "Discord Android" | "Discord iOS"),
-                    mfa_enabled: user.mfa_enabled,
-                    id: user.id.to_string(),
-                    global_name: user.display_name.clone(),
-                    flags: user.flags,
-                    email: user.email,
-                    display_name: user.display_name,
-                    discriminator: user.discriminator,
-                    desktop: matches!(session_context.session.platform.unwrap_or_default().as_str(), "Discord Desktop"),
-                    bio: user.bio.unwrap_or(String::new()),
-                    banner_color: user.banner_colour,
-                    banner: user.banner,
-                    avatar_decoration: user.avatar_decoration,
-                    avatar: user.avatar,
-                    accent_color: user.accent_color,
-                }
-            ).into_response()
+            // Changing the password logs out every other session and hands back a new token
+            let token = if data.new_password.is_some() {
+                Some(rotate_sessions(&state, user_id).await)
+            } else {
+                None
+            };
+
+            if email_changed {
+                send_verification_email(&state, user.id, &user.username, &user.email).await;
+            }
+
+            send_nats_message(&state.nats_client, user.id.to_string(), Messages::UserUpdate { user_id: user.id }).await;
+
+            let mut user_struct = generate_private_user_struct(user, session_context.session.and_then(|x| x.platform));
+
+            user_struct.token = token;
+
+            Json(user_struct).into_response()
         }
         Err(_) => {
             StatusCode::INTERNAL_SERVER_ERROR.into_response()
@@ -12,8 +12,10 @@ use sea_orm::IntoActiveModel;
 use serde_derive::{Deserialize, Serialize};
 
 use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+use crate::http::v9::routes::auth::require_verification;
 use epl_common::RelationshipType;
 use sea_orm::prelude::*;
+use epl_common::privacy::can_send_friend_request;
 use epl_common::relationship::get_relationship;
 use epl_common::schema::v9;
 use epl_common::schema::v9::user::generate_user_struct;
@@ -102,6 +104,10 @@ pub async fn new_relationship(
     Extension(session_context): Extension<SessionContext>,
     Json(requested_user): Json<SendFriendRequestReq>,
 ) -> impl IntoResponse {
+    if let Some(res) = require_verification(&session_context.user).await {
+        return res;
+    }
+
     let normalized_discriminator: String = {
         if let Some(discriminator) = requested_user.discriminator {
             let mut output = discriminator.to_string();
@@ -134,6 +140,15 @@ pub async fn new_relationship(
                     .into_response();
             }
 
+            // Nobody can befriend the System user
+            if user.system {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    throw_http_error(APIErrorCode::FriendRequestBlocked, vec![]).await,
+                )
+                    .into_response();
+            }
+
             // Check if a relationship already exists
             let relationship_model =
                 get_relationship(session_context.user.id, user.id, &state.conn).await;
@@ -146,6 +161,15 @@ pub async fn new_relationship(
                     .into_response();
             }
 
+            // The other user's privacy settings decide who can send them requests
+            if !can_send_friend_request(&state.conn, session_context.user.id, user.id).await {
+                return (
+                    StatusCode::from(APIErrorCode::IncomingFriendRequestsDisabled),
+                    throw_http_error(APIErrorCode::IncomingFriendRequestsDisabled, vec![]).await,
+                )
+                    .into_response();
+            }
+
             let new_relationship = relationship::ActiveModel {
                 creator: Set(session_context.user.id),
                 peer: Set(user.id),
@@ -283,6 +307,31 @@ pub async fn modify_relationship(
                             .into_response();
                     };
 
+                    let requested_user = User::find_by_id(requested_user_id)
+                        .one(&state.conn)
+                        .await
+                        .expect("Failed to access database!");
+
+                    match requested_user {
+                        None => return StatusCode::NOT_FOUND.into_response(),
+                        Some(requested_user) if requested_user.system => {
+                            return (
+                                StatusCode::BAD_REQUEST,
+                                throw_http_error(APIErrorCode::FriendRequestBlocked, vec![]).await,
+                            )
+                                .into_response();
+                        }
+                        Some(_) => {}
+                    }
+
+                    if !can_send_friend_request(&state.conn, session_context.user.id, requested_user_id).await {
+                        return (
+                            StatusCode::from(APIErrorCode::IncomingFriendRequestsDisabled),
+                            throw_http_error(APIErrorCode::IncomingFriendRequestsDisabled, vec![]).await,
+                        )
+                            .into_response();
+                    }
+
                     let new_relationship = relationship::ActiveModel {
                         creator: Set(session_context.user.id),
                         peer: Set(requested_user_id),
@@ -309,6 +358,16 @@ pub async fn modify_relationship(
                     }
                 }
                 Some(relationship) => {
+                    // Only whoever the request was sent to can accept it, otherwise privacy settings could be skipped
+                    if relationship.relationship_type != RelationshipType::Outgoing as i32
+                        || relationship.creator == session_context.user.id {
+                        return (
+                            StatusCode::BAD_REQUEST,
+                            throw_http_error(APIErrorCode::FriendRequestBlocked, vec![]).await,
+                        )
+                            .into_response();
+                    }
+
                     let cached_relationship = (relationship.creator, relationship.peer);
 
                     let mut new_relationship = relationship.into_active_model();
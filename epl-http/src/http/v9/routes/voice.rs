@@ -0,0 +1,28 @@
+use axum::Json;
+use axum::Extension;
+use axum::response::IntoResponse;
+use axum_client_ip::SecureClientIp;
+use epl_common::schema::v9::voice_region::generate_voice_region_struct;
+use epl_common::voice::get_geo_ordered_regions;
+use crate::AppState;
+
+pub async fn get_voice_regions(
+    Extension(state): Extension<AppState>,
+    SecureClientIp(ip): SecureClientIp,
+) -> impl IntoResponse {
+    let regions = get_geo_ordered_regions(&state.conn, ip).await;
+
+    // The closest region that's still in use is the best one
+    let optimal = regions.iter().find(|x| !x.deprecated).map(|x| x.id.clone());
+
+    Json(
+        regions
+            .into_iter()
+            .map(|x| {
+                let is_optimal = optimal.as_ref().is_some_and(|optimal| optimal.eq(&x.id));
+
+                generate_voice_region_struct(x, is_optimal)
+            })
+            .collect::<Vec<_>>()
+    )
+}
@@ -0,0 +1,152 @@
+use axum::{Extension, Json};
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use chrono::Utc;
+use sea_orm::*;
+use epl_common::database::entities::{interaction, message};
+use epl_common::database::entities::prelude::{Interaction, Message};
+use epl_common::interactions::{create_interaction_message, delete_interaction_message, edit_interaction_message, InteractionCallbackData, INTERACTION_TOKEN_LIFETIME};
+use epl_common::schema::v9::message::generate_full_message_struct;
+use crate::AppState;
+use crate::http::v9::errors::{APIErrorCode, throw_http_error};
+
+/// Gets the interaction an application's webhook token belongs to, as long as it hasn't expired
+async fn get_interaction_by_token(
+    conn: &DatabaseConnection,
+    application_id: i64,
+    token: String,
+) -> Option<interaction::Model> {
+    Interaction::find()
+        .filter(interaction::Column::ApplicationId.eq(application_id))
+        .filter(interaction::Column::Token.eq(token))
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+        .filter(|x| (Utc::now().naive_utc() - x.created).num_seconds() <= INTERACTION_TOKEN_LIFETIME)
+}
+
+/// Gets a message sent in response to an interaction, `@original` being the initial response
+///
+/// For component interactions `@original` is the message the component is on, until it's responded to with a new message
+async fn get_interaction_message(
+    conn: &DatabaseConnection,
+    interaction: &interaction::Model,
+    message_id: String,
+) -> Option<message::Model> {
+    let message_id = match message_id.as_str() {
+        "@original" | "%40original" => interaction.message_id?,
+        _ => message_id.parse::<i64>().ok()?,
+    };
+
+    Message::find_by_id(message_id)
+        .one(conn)
+        .await
+        .expect("Failed to access database!")
+        .filter(|x| {
+            Some(x.id) == interaction.message_id || x.interaction
+                .as_ref()
+                .and_then(|x| x.get("id"))
+                .and_then(|x| x.as_str())
+                .is_some_and(|x| x == interaction.id.to_string())
+        })
+}
+
+pub async fn new_followup_message(
+    Extension(state): Extension<AppState>,
+    Path((application_id, token)): Path<(i64, String)>,
+    Json(data): Json<InteractionCallbackData>,
+) -> impl IntoResponse {
+    let requested_interaction = match get_interaction_by_token(&state.conn, application_id, token).await {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownWebhook),
+            throw_http_error(APIErrorCode::UnknownWebhook, vec![]).await
+        ).into_response(),
+        Some(requested_interaction) => requested_interaction,
+    };
+
+    // Followups only make sense once the interaction has been responded to
+    if !requested_interaction.responded || !data.is_valid() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    let new_message = create_interaction_message(&state.conn, &state.nats_client, &requested_interaction, data, 0).await;
+
+    Json(generate_full_message_struct(&state.conn, new_message, &requested_interaction.user_id).await).into_response()
+}
+
+pub async fn get_webhook_message(
+    Extension(state): Extension<AppState>,
+    Path((application_id, token, message_id)): Path<(i64, String, String)>,
+) -> impl IntoResponse {
+    let requested_interaction = match get_interaction_by_token(&state.conn, application_id, token).await {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownWebhook),
+            throw_http_error(APIErrorCode::UnknownWebhook, vec![]).await
+        ).into_response(),
+        Some(requested_interaction) => requested_interaction,
+    };
+
+    match get_interaction_message(&state.conn, &requested_interaction, message_id).await {
+        None => (
+            StatusCode::from(APIErrorCode::UnknownMessage),
+            throw_http_error(APIErrorCode::UnknownMessage, vec![]).await
+        ).into_response(),
+        Some(requested_message) => Json(generate_full_message_struct(&state.conn, requested_message, &requested_interaction.user_id).await).into_response(),
+    }
+}
+
+pub async fn edit_webhook_message(
+    Extension(state): Extension<AppState>,
+    Path((application_id, token, message_id)): Path<(i64, String, String)>,
+    Json(data): Json<InteractionCallbackData>,
+) -> impl IntoResponse {
+    let requested_interaction = match get_interaction_by_token(&state.conn, application_id, token).await {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownWebhook),
+            throw_http_error(APIErrorCode::UnknownWebhook, vec![]).await
+        ).into_response(),
+        Some(requested_interaction) => requested_interaction,
+    };
+
+    let requested_message = match get_interaction_message(&state.conn, &requested_interaction, message_id).await {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownMessage),
+            throw_http_error(APIErrorCode::UnknownMessage, vec![]).await
+        ).into_response(),
+        Some(requested_message) => requested_message,
+    };
+
+    if !data.is_valid_edit() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    let edited_message = edit_interaction_message(&state.conn, &state.nats_client, &requested_interaction, requested_message, data).await;
+
+    Json(generate_full_message_struct(&state.conn, edited_message, &requested_interaction.user_id).await).into_response()
+}
+
+pub async fn delete_webhook_message(
+    Extension(state): Extension<AppState>,
+    Path((application_id, token, message_id)): Path<(i64, String, String)>,
+) -> impl IntoResponse {
+    let requested_interaction = match get_interaction_by_token(&state.conn, application_id, token).await {
+        None => return (
+            StatusCode::from(APIErrorCode::UnknownWebhook),
+            throw_http_error(APIErrorCode::UnknownWebhook, vec![]).await
+        ).into_response(),
+        Some(requested_interaction) => requested_interaction,
+    };
+
+    match get_interaction_message(&state.conn, &requested_interaction, message_id).await {
+        None => (
+            StatusCode::from(APIErrorCode::UnknownMessage),
+            throw_http_error(APIErrorCode::UnknownMessage, vec![]).await
+        ).into_response(),
+        Some(requested_message) => {
+            delete_interaction_message(&state.conn, &state.nats_client, &requested_interaction, requested_message).await;
+
+            StatusCode::NO_CONTENT.into_response()
+        }
+    }
+}
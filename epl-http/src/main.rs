@@ -7,6 +7,7 @@ use axum::http::Method;
 use axum::response::IntoResponse;
 use axum::routing::get;
 use axum::{Extension, Json, Router};
+use axum_client_ip::SecureClientIpSource;
 use epl_common::database::entities::prelude::{Channel, Message, User};
 use epl_common::nodeinfo::{LitecordMetadata, NodeInfo, Services, Software, Usage, UsageUsers};
 use sea_orm::{ConnectOptions, Database, DatabaseConnection, EntityTrait, PaginatorTrait};
@@ -17,6 +18,7 @@ use tracing::{debug, info, log};
 
 use crate::http::api;
 use epl_common::options::{EplOptions, Options};
+use epl_common::system::get_system_user;
 use epl_common::{rustflake, Stub};
 
 use migration::{Migrator, MigratorTrait};
@@ -72,6 +74,10 @@ async fn main() {
 
     info!("Connected to database");
 
+    let system_user = get_system_user(&conn).await;
+
+    info!("System user: {}", system_user.id);
+
     info!("Connecting to NATS server");
 
     let client = async_nats::connect(EplOptions::get().nats_addr)
@@ -116,7 +122,8 @@ async fn main() {
         .route("/nodeinfo/2.1.json", get(nodeinfo))
         .route("/.well-known/nodeinfo", get(well_known_nodeinfo))
         .layer(cors)
-        .layer(Extension(app_state));
+        .layer(Extension(app_state))
+        .layer(SecureClientIpSource::RightmostXForwardedFor.into_extension());
 
     let addr: SocketAddr = options
         .listen_addr
@@ -231,7 +238,7 @@ async fn nodeinfo(Extension(state): Extension<AppState>) -> impl IntoResponse {
             inbound: vec![],
             outbound: vec![],
         },
-        open_registrations: options.registration,
+        open_registrations: options.registration.is_open(),
         usage: Usage {
             users: UsageUsers {
                 total: user_count,
@@ -0,0 +1,54 @@
+use sea_orm::EntityTrait;
+use tracing::error;
+use epl_common::database::entities::prelude::{Application, Interaction};
+use epl_common::interactions::{handle_interaction_callback, send_interaction_request, InteractionCallback};
+use epl_common::nats::{Messages, send_nats_message};
+use epl_common::schema::v9::interaction::generate_full_interaction_struct;
+use crate::AppState;
+
+pub async fn deliver_interaction(state: &AppState, interaction_id: i64) {
+    let interaction = Interaction::find_by_id(interaction_id)
+        .one(&state.db)
+        .await
+        .expect("Failed to access database!")
+        .expect("Failed to get interaction requested by NATS!");
+
+    let application = Application::find_by_id(interaction.application_id)
+        .one(&state.db)
+        .await
+        .expect("Failed to access database!")
+        .expect("Interaction references non-existent application!");
+
+    let (url, signing_key) = match (application.interactions_endpoint_url, application.signing_key) {
+        (Some(url), Some(signing_key)) => (url, signing_key),
+        _ => {
+            error!("Wanted to deliver an interaction but the application has no interactions endpoint!");
+            return;
+        }
+    };
+
+    let body = serde_json::to_string(&generate_full_interaction_struct(&state.db, interaction.clone()).await)
+        .expect("Failed to serialize interaction!");
+
+    // Whatever the endpoint answers with is the initial response to the interaction
+    let callback = tokio::task::spawn_blocking(move || send_interaction_request(&url, &signing_key, &body))
+        .await
+        .ok()
+        .and_then(|x| x.ok())
+        .and_then(|x| serde_json::from_value::<InteractionCallback>(x).ok());
+
+    let result = match callback {
+        None => false,
+        Some(callback) => handle_interaction_callback(&state.db, &state.nats, interaction.clone(), callback)
+            .await
+            .is_ok(),
+    };
+
+    if !result {
+        send_nats_message(
+            &state.nats,
+            interaction.user_id.to_string(),
+            Messages::InteractionFailure { id: interaction.id },
+        ).await;
+    }
+}
@@ -1,15 +1,30 @@
+mod deliver_interaction;
 mod process_embed;
+mod process_harvest;
+mod send_mail;
 
 use tracing::log::error;
 use epl_common::nats::Messages;
 use crate::AppState;
+use crate::handle::deliver_interaction::deliver_interaction;
 use crate::handle::process_embed::process_embed;
+use crate::handle::process_harvest::process_harvest;
+use crate::handle::send_mail::send_mail;
 
 pub async fn handle_nats_message(state: &AppState, message: Messages) {
     match message {
         Messages::ProcessEmbed { message_id } => {
             process_embed(state, message_id).await;
         }
+        Messages::DeliverInteraction { id } => {
+            deliver_interaction(state, id).await;
+        }
+        Messages::SendMail { to, mail, attempt } => {
+            send_mail(state, to, mail, attempt).await;
+        }
+        Messages::ProcessHarvest { harvest_id } => {
+            process_harvest(state, harvest_id).await;
+        }
         _ => {
             error!("Unsupported message received!");
         }
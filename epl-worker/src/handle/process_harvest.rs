@@ -0,0 +1,348 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use aws_sdk_s3::primitives::ByteStream;
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder};
+use sea_orm::ActiveValue::Set;
+use sea_orm::sea_query::Expr;
+use serde_json::json;
+use tracing::{error, info};
+use zip::{CompressionMethod, ZipWriter};
+use zip::write::FileOptions;
+use epl_common::database::entities::prelude::{Channel, ChannelMember, File, Frecency, Harvest, LoginAttempt, Message, MessageAttachment, Note, Relationship, Session, User, UserSetting};
+use epl_common::database::entities::{channel_member, frecency, harvest, login_attempt, message, message_attachment, note, relationship, session, user, user_setting};
+use epl_common::harvest::{harvest_download_link, harvest_expiry, harvest_key, HarvestStatus};
+use epl_common::mail::{Mail, queue_mail};
+use epl_common::protobufs::{generate_user_proto, ProtoType};
+use epl_common::system::send_system_message;
+use crate::AppState;
+
+const README: &str = "This is a copy of the data this instance keeps about your account.
+
+account/    Your profile, sessions, login history, relationships, notes and settings
+messages/   Every message you sent, one folder per channel, along with the files you attached
+";
+
+fn format_timestamp(timestamp: chrono::NaiveDateTime) -> String {
+    timestamp.and_utc().format("%Y-%m-%dT%H:%M:%S%z").to_string()
+}
+
+async fn set_status(state: &AppState, harvest: harvest::Model, status: HarvestStatus) -> harvest::Model {
+    let mut active_harvest = harvest.into_active_model();
+
+    active_harvest.status = Set(status as i32);
+
+    if status == HarvestStatus::Completed {
+        active_harvest.completed_at = Set(Some(Utc::now().naive_utc()));
+    }
+
+    active_harvest.update(&state.db).await.expect("Failed to access database!")
+}
+
+fn write_json(zip: &mut ZipWriter<fs::File>, name: &str, value: &serde_json::Value) -> Result<(), String> {
+    write_file(zip, name, &serde_json::to_vec_pretty(value).map_err(|err| err.to_string())?)
+}
+
+fn write_file(zip: &mut ZipWriter<fs::File>, name: &str, contents: &[u8]) -> Result<(), String> {
+    zip.start_file(name, FileOptions::default().compression_method(CompressionMethod::Deflated))
+        .map_err(|err| err.to_string())?;
+
+    zip.write_all(contents).map_err(|err| err.to_string())
+}
+
+async fn get_object(state: &AppState, key: String) -> Option<Vec<u8>> {
+    let object = state.aws.get_object()
+        .bucket(&state.options.s3_bucket)
+        .key(key)
+        .send()
+        .await
+        .ok()?;
+
+    Some(object.body.collect().await.ok()?.to_vec())
+}
+
+async fn write_account(state: &AppState, zip: &mut ZipWriter<fs::File>, user: &user::Model) -> Result<(), String> {
+    write_json(zip, "account/user.json", &json!({
+        "id": user.id.to_string(),
+        "username": user.username,
+        "discriminator": user.discriminator,
+        "global_name": user.display_name,
+        "legacy_username": user.legacy_name,
+        "email": user.email,
+        "verified": user.acct_verified,
+        "phone": user.phone,
+        "bio": user.bio,
+        "pronouns": user.pronouns,
+        "avatar_hash": user.avatar,
+        "banner_color": user.banner_colour,
+        "accent_color": user.accent_color,
+        "date_of_birth": user.date_of_birth.map(|x| x.to_string()),
+        "mfa_enabled": user.mfa_enabled,
+        "flags": user.flags,
+    }))?;
+
+    if let Some(avatar) = &user.avatar {
+        if let Some(avatar_bytes) = get_object(state, format!("avatars/{}/{}.webp", user.id, avatar)).await {
+            write_file(zip, "account/avatar.webp", &avatar_bytes)?;
+        }
+    }
+
+    let sessions = Session::find()
+        .filter(session::Column::UserId.eq(user.id))
+        .all(&state.db)
+        .await
+        .expect("Failed to access database!");
+
+    // Tokens are left out, the package could end up anywhere
+    write_json(zip, "account/sessions.json", &sessions.iter().map(|x| json!({
+        "id": x.session_id,
+        "os": x.os,
+        "platform": x.platform,
+        "location": x.location,
+        "created_at": format_timestamp(x.iat),
+        "last_used": format_timestamp(x.last_used),
+        "expires_at": format_timestamp(x.exp),
+    })).collect())?;
+
+    let login_attempts = LoginAttempt::find()
+        .filter(login_attempt::Column::UserId.eq(user.id))
+        .order_by_desc(login_attempt::Column::CreatedAt)
+        .all(&state.db)
+        .await
+        .expect("Failed to access database!");
+
+    write_json(zip, "account/login_attempts.json", &login_attempts.iter().map(|x| json!({
+        "ip": x.ip,
+        "location": x.location,
+        "outcome": x.outcome,
+        "timestamp": format_timestamp(x.created_at),
+    })).collect())?;
+
+    let relationships = Relationship::find()
+        .filter(relationship::Column::Creator.eq(user.id))
+        .all(&state.db)
+        .await
+        .expect("Failed to access database!");
+
+    write_json(zip, "account/relationships.json", &relationships.iter().map(|x| json!({
+        "id": x.peer.to_string(),
+        "type": x.relationship_type,
+        "since": format_timestamp(x.timestamp),
+    })).collect())?;
+
+    let notes = Note::find()
+        .filter(note::Column::Creator.eq(user.id))
+        .all(&state.db)
+        .await
+        .expect("Failed to access database!");
+
+    write_json(zip, "account/notes.json", &json!(notes.iter()
+        .map(|x| (x.subject.to_string(), x.text.clone()))
+        .collect::<BTreeMap<String, String>>()))?;
+
+    let user_settings = UserSetting::find()
+        .filter(user_setting::Column::User.eq(user.id))
+        .one(&state.db)
+        .await
+        .expect("Failed to access database!");
+
+    let user_frecency = Frecency::find()
+        .filter(frecency::Column::User.eq(user.id))
+        .one(&state.db)
+        .await
+        .expect("Failed to access database!");
+
+    // Same base64 protobufs the client gets from /users/@me/settings-proto
+    write_json(zip, "account/settings.json", &json!({
+        "preloaded_user_settings": user_settings.map(|x| generate_user_proto(ProtoType::PreloadedUserSettings(x))),
+        "frecency_user_settings": user_frecency.map(|x| generate_user_proto(ProtoType::FrecencyUserSettings(x))),
+    }))
+}
+
+async fn write_messages(state: &AppState, zip: &mut ZipWriter<fs::File>, user_id: i64) -> Result<(), String> {
+    let messages = Message::find()
+        .filter(message::Column::Author.eq(user_id))
+        .order_by_asc(message::Column::Id)
+        .all(&state.db)
+        .await
+        .expect("Failed to access database!");
+
+    let mut channels: BTreeMap<i64, Vec<message::Model>> = BTreeMap::new();
+
+    for message in messages {
+        channels.entry(message.channel_id).or_default().push(message);
+    }
+
+    // Channels the user is in but never talked in are listed too
+    let memberships = ChannelMember::find()
+        .filter(channel_member::Column::User.eq(user_id))
+        .all(&state.db)
+        .await
+        .expect("Failed to access database!");
+
+    for membership in memberships {
+        channels.entry(membership.channel).or_default();
+    }
+
+    let mut index = BTreeMap::new();
+
+    for (channel_id, messages) in channels {
+        let channel = Channel::find_by_id(channel_id)
+            .one(&state.db)
+            .await
+            .expect("Failed to access database!");
+
+        let recipients = ChannelMember::find()
+            .filter(channel_member::Column::Channel.eq(channel_id))
+            .all(&state.db)
+            .await
+            .expect("Failed to access database!");
+
+        index.insert(channel_id.to_string(), channel.as_ref().and_then(|x| x.name.clone()));
+
+        write_json(zip, &format!("messages/c{}/channel.json", channel_id), &json!({
+            "id": channel_id.to_string(),
+            "type": channel.as_ref().map(|x| x.r#type),
+            "name": channel.as_ref().and_then(|x| x.name.clone()),
+            "recipients": recipients.iter().map(|x| x.user.to_string()).collect::<Vec<String>>(),
+        }))?;
+
+        let mut attachments: BTreeMap<i64, Vec<String>> = BTreeMap::new();
+
+        // Keep the queries a sensible size for channels with lots of messages
+        for message_ids in messages.iter().map(|x| x.id).collect::<Vec<i64>>().chunks(1000) {
+            let files = MessageAttachment::find()
+                .filter(message_attachment::Column::Message.is_in(message_ids.to_vec()))
+                .find_also_related(File)
+                .all(&state.db)
+                .await
+                .expect("Failed to access database!");
+
+            for (message_attachment, file) in files {
+                let Some(file) = file else {
+                    continue;
+                };
+
+                let path = format!("messages/c{}/attachments/{}-{}", channel_id, file.id, file.name);
+
+                if let Some(file_bytes) = get_object(state, format!("attachments/{}", file.id)).await {
+                    write_file(zip, &path, &file_bytes)?;
+                }
+
+                attachments.entry(message_attachment.message).or_default().push(path);
+            }
+        }
+
+        write_json(zip, &format!("messages/c{}/messages.json", channel_id), &messages.iter().map(|x| json!({
+            "id": x.id.to_string(),
+            "timestamp": format_timestamp(x.timestamp),
+            "edited_timestamp": x.edited_timestamp.map(format_timestamp),
+            "contents": x.content,
+            "attachments": attachments.remove(&x.id).unwrap_or_default(),
+        })).collect())?;
+    }
+
+    write_json(zip, "messages/index.json", &json!(index))
+}
+
+/// Builds the package into a local file
+async fn build_package(state: &AppState, path: &Path, user: &user::Model) -> Result<(), String> {
+    let mut zip = ZipWriter::new(fs::File::create(path).map_err(|err| err.to_string())?);
+
+    write_file(&mut zip, "README.txt", README.as_bytes())?;
+    write_account(state, &mut zip, user).await?;
+    write_messages(state, &mut zip, user.id).await?;
+
+    zip.finish().map_err(|err| err.to_string())?;
+
+    Ok(())
+}
+
+async fn upload_package(state: &AppState, path: &Path, harvest_id: i64) -> Result<(), String> {
+    let body = ByteStream::from_path(path).await.map_err(|err| err.to_string())?;
+
+    state.aws.put_object()
+        .bucket(&state.options.s3_bucket)
+        .key(harvest_key(harvest_id))
+        .content_type("application/zip")
+        .body(body)
+        .send()
+        .await
+        .map_err(|err| err.to_string())?;
+
+    Ok(())
+}
+
+pub async fn process_harvest(state: &AppState, harvest_id: i64) {
+    // Claimed in one update so two workers can't both pick the same harvest up
+    let claimed = Harvest::update_many()
+        .col_expr(harvest::Column::Status, Expr::value(HarvestStatus::Running as i32))
+        .filter(harvest::Column::Id.eq(harvest_id))
+        .filter(harvest::Column::Status.eq(HarvestStatus::Queued as i32))
+        .exec(&state.db)
+        .await
+        .expect("Failed to access database!");
+
+    if claimed.rows_affected != 1 {
+        return;
+    }
+
+    let Some(harvest) = Harvest::find_by_id(harvest_id)
+        .one(&state.db)
+        .await
+        .expect("Failed to access database!") else {
+        return;
+    };
+
+    let Some(user) = User::find_by_id(harvest.user_id)
+        .one(&state.db)
+        .await
+        .expect("Failed to access database!") else {
+        set_status(state, harvest, HarvestStatus::Failed).await;
+
+        return;
+    };
+
+    let path = std::env::temp_dir().join(format!("epl-harvest-{}.zip", harvest.id));
+
+    let result = match build_package(state, &path, &user).await {
+        Ok(_) => upload_package(state, &path, harvest.id).await,
+        Err(err) => Err(err),
+    };
+
+    let _ = fs::remove_file(&path);
+
+    if let Err(err) = result {
+        error!("Failed to build data package {}: {}", harvest.id, err);
+        set_status(state, harvest, HarvestStatus::Failed).await;
+
+        return;
+    }
+
+    let harvest = set_status(state, harvest, HarvestStatus::Completed).await;
+
+    let url = harvest_download_link(user.id, harvest.id);
+    let expiry_date = harvest_expiry(harvest.completed_at.unwrap()).format("%Y-%m-%d").to_string();
+
+    queue_mail(
+        &state.nats,
+        user.email.clone(),
+        Mail::HarvestReady {
+            username: user.username.clone(),
+            url: url.clone(),
+            expiry_date: expiry_date.clone(),
+        },
+    ).await;
+
+    send_system_message(
+        &state.db,
+        &state.nats,
+        user.id,
+        format!("Your data package is ready! You can download it until {} from {}", expiry_date, url),
+        false,
+    ).await;
+
+    info!("Built data package {} for {}", harvest.id, user.id);
+}
@@ -0,0 +1,57 @@
+use std::time::Duration;
+use lettre::{AsyncFileTransport, AsyncTransport, Message, Tokio1Executor};
+use lettre::message::Mailbox;
+use lettre::message::header::ContentType;
+use tracing::{error, info, warn};
+use epl_common::mail::Mail;
+use epl_common::nats::{Messages, send_nats_message};
+use crate::AppState;
+
+/// How many times sending an email is tried before giving up on it
+const MAX_ATTEMPTS: u32 = 5;
+
+async fn deliver_mail(state: &AppState, to: &str, subject: String, body: String) -> Result<(), String> {
+    let message = Message::builder()
+        .from(state.options.mail_from.parse::<Mailbox>().map_err(|err| format!("Invalid MAIL_FROM: {}", err))?)
+        .to(to.parse::<Mailbox>().map_err(|err| format!("Invalid recipient: {}", err))?)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(body)
+        .map_err(|err| err.to_string())?;
+
+    if let Some(mailer) = &state.mailer {
+        mailer.send(message).await.map_err(|err| err.to_string())?;
+    } else if let Some(mail_dir) = &state.options.mail_dir {
+        AsyncFileTransport::<Tokio1Executor>::new(mail_dir)
+            .send(message)
+            .await
+            .map_err(|err| err.to_string())?;
+    } else {
+        // Nowhere to send mail to, so at least make it visible
+        info!("Not sending mail to {} as neither SMTP_URL nor MAIL_DIR is set:\n{}", to, String::from_utf8_lossy(&message.formatted()));
+    }
+
+    Ok(())
+}
+
+pub async fn send_mail(state: &AppState, to: String, mail: Mail, attempt: u32) {
+    let (subject, body) = mail.render();
+
+    if let Err(err) = deliver_mail(state, &to, subject, body).await {
+        if attempt + 1 >= MAX_ATTEMPTS {
+            error!("Giving up on sending mail to {} after {} attempts: {}", to, MAX_ATTEMPTS, err);
+            return;
+        }
+
+        warn!("Failed to send mail to {}, retrying: {}", to, err);
+
+        // Back off a bit more every time before putting it back on the queue
+        tokio::time::sleep(Duration::from_secs(30 * 2u64.pow(attempt))).await;
+
+        send_nats_message(
+            &state.nats,
+            "worker_queue".to_string(),
+            Messages::SendMail { to, mail, attempt: attempt + 1 },
+        ).await;
+    }
+}
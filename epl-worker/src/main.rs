@@ -1,7 +1,9 @@
 mod handle;
+mod tasks;
 
 use std::env;
 use futures::StreamExt;
+use lettre::{AsyncSmtpTransport, Tokio1Executor};
 use sea_orm::{ConnectOptions, Database, DatabaseConnection};
 use sea_orm_migration::MigratorTrait;
 use tracing::{debug, info, error, log};
@@ -9,6 +11,7 @@ use epl_common::options::{EplOptions, Options};
 use epl_common::rustflake;
 use migration::Migrator;
 use crate::handle::handle_nats_message;
+use crate::tasks::spawn_tasks;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -31,6 +34,10 @@ async fn main() {
         info!("\tMediaproxy URL: {}", options.mediaproxy_url.clone().unwrap());
     }
     info!("\tRequire SSL: {}", options.require_ssl);
+    info!("\tMail From: {}", options.mail_from);
+    if options.mail_dir.is_some() {
+        info!("\tMail Directory: {}", options.mail_dir.clone().unwrap());
+    }
 
     info!("\tNATS Address: {}", options.nats_addr);
 
@@ -76,6 +83,26 @@ async fn main() {
 
     info!("Loaded S3 configuration");
 
+    let mailer = options.smtp_url.as_ref().map(|smtp_url| {
+        AsyncSmtpTransport::<Tokio1Executor>::from_url(smtp_url)
+            .expect("Invalid SMTP_URL!")
+            .build()
+    });
+
+    if mailer.is_some() {
+        info!("Loaded SMTP configuration");
+    }
+
+    spawn_tasks(AppState {
+        db: conn.clone(),
+        nats: client.clone(),
+        aws: aws.clone(),
+        mailer: mailer.clone(),
+        options: options.clone()
+    });
+
+    info!("Started background tasks");
+
     let stream = client.subscribe("worker_queue").await;
     if let Err(_) = stream {
         // handle error
@@ -88,6 +115,7 @@ async fn main() {
             db: conn.clone(),
             nats: client.clone(),
             aws: aws.clone(),
+            mailer: mailer.clone(),
             options: options.clone()
         };
 
@@ -106,5 +134,6 @@ struct AppState {
     db: DatabaseConnection,
     nats: async_nats::Client,
     aws: aws_sdk_s3::Client,
+    mailer: Option<AsyncSmtpTransport<Tokio1Executor>>,
     options: EplOptions
 }
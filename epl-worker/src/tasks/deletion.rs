@@ -0,0 +1,29 @@
+use chrono::Utc;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use tracing::info;
+use epl_common::database::entities::prelude::User;
+use epl_common::database::entities::user;
+use epl_common::deletion::{delete_user, delete_user_files};
+use crate::AppState;
+
+/// Deletes accounts whose grace period is over
+pub async fn delete_scheduled_accounts(state: &AppState) {
+    let users = User::find()
+        .filter(user::Column::DeletionScheduledAt.lte(Utc::now().naive_utc()))
+        .all(&state.db)
+        .await
+        .expect("Failed to access database!");
+
+    if users.is_empty() {
+        return;
+    }
+
+    let count = users.len();
+
+    for user in users {
+        delete_user_files(&state.db, &state.aws, &state.options.s3_bucket, user.id).await;
+        delete_user(&state.db, &state.nats, user).await;
+    }
+
+    info!("Deleted {} accounts scheduled for deletion", count);
+}
@@ -0,0 +1,40 @@
+use chrono::{Duration, Utc};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use sea_orm::sea_query::Expr;
+use tracing::info;
+use epl_common::database::entities::harvest;
+use epl_common::database::entities::prelude::Harvest;
+use epl_common::harvest::{harvest_key, HARVEST_LIFETIME_DAYS, HarvestStatus};
+use crate::AppState;
+
+/// Removes data packages from the bucket once their download links have expired
+pub async fn sweep_harvests(state: &AppState) {
+    let expired_harvests = Harvest::find()
+        .filter(harvest::Column::Status.eq(HarvestStatus::Completed as i32))
+        .filter(harvest::Column::CompletedAt.lt(Utc::now().naive_utc() - Duration::days(HARVEST_LIFETIME_DAYS)))
+        .all(&state.db)
+        .await
+        .expect("Failed to access database!");
+
+    if expired_harvests.is_empty() {
+        return;
+    }
+
+    for expired_harvest in &expired_harvests {
+        let _ = state.aws.delete_object()
+            .bucket(&state.options.s3_bucket)
+            .key(harvest_key(expired_harvest.id))
+            .send()
+            .await;
+    }
+
+    // The rows stay around so the cooldown between requests still works
+    Harvest::update_many()
+        .col_expr(harvest::Column::Status, Expr::value(HarvestStatus::Expired as i32))
+        .filter(harvest::Column::Id.is_in(expired_harvests.iter().map(|x| x.id)))
+        .exec(&state.db)
+        .await
+        .expect("Failed to access database!");
+
+    info!("Removed {} expired data packages", expired_harvests.len());
+}
@@ -0,0 +1,41 @@
+mod deletion;
+mod harvests;
+mod sessions;
+
+use std::future::Future;
+use std::time::Duration;
+use crate::AppState;
+use crate::tasks::deletion::delete_scheduled_accounts;
+use crate::tasks::harvests::sweep_harvests;
+use crate::tasks::sessions::sweep_sessions;
+
+/// Runs a task over and over in the background, waiting the period between runs
+fn schedule<F, Fut>(state: AppState, period: Duration, task: F)
+where
+    F: Fn(AppState) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+            task(state.clone()).await;
+        }
+    });
+}
+
+/// Starts every background task the worker is responsible for
+pub fn spawn_tasks(state: AppState) {
+    schedule(state.clone(), Duration::from_secs(10 * 60), |state| async move {
+        sweep_sessions(&state).await;
+    });
+
+    schedule(state.clone(), Duration::from_secs(60 * 60), |state| async move {
+        delete_scheduled_accounts(&state).await;
+    });
+
+    schedule(state, Duration::from_secs(60 * 60), |state| async move {
+        sweep_harvests(&state).await;
+    });
+}
@@ -0,0 +1,36 @@
+use chrono::Utc;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use tracing::info;
+use epl_common::database::entities::prelude::Session;
+use epl_common::database::entities::session;
+use epl_common::nats::{Messages, send_nats_message};
+use crate::AppState;
+
+/// Deletes sessions that have expired and kicks them off the gateway
+pub async fn sweep_sessions(state: &AppState) {
+    let expired_sessions = Session::find()
+        .filter(session::Column::Exp.lt(Utc::now().naive_utc()))
+        .all(&state.db)
+        .await
+        .expect("Failed to access database!");
+
+    if expired_sessions.is_empty() {
+        return;
+    }
+
+    Session::delete_many()
+        .filter(session::Column::SessionId.is_in(expired_sessions.iter().map(|x| x.session_id.clone())))
+        .exec(&state.db)
+        .await
+        .expect("Failed to access database!");
+
+    for expired_session in &expired_sessions {
+        send_nats_message(
+            &state.nats,
+            expired_session.user_id.to_string(),
+            Messages::InvalidateGatewaySession { session: expired_session.session_id.clone() },
+        ).await;
+    }
+
+    info!("Swept {} expired sessions", expired_sessions.len());
+}
@@ -24,6 +24,33 @@ mod m20240413_215635_add_requested_deletion_to_files;
 mod m20240414_025415_create_reactions;
 mod m20240417_080833_create_settings;
 mod m20240418_040054_create_frecency;
+mod m20240420_013512_create_applications;
+mod m20240420_021944_create_oauth2_codes;
+mod m20240420_022310_create_oauth2_tokens;
+mod m20240421_031522_create_application_commands;
+mod m20240421_040133_create_interactions;
+mod m20240421_043807_add_interactions_endpoint_to_applications;
+mod m20240422_015730_add_components;
+mod m20240423_020811_create_emojis;
+mod m20240424_031245_add_reaction_types;
+mod m20240425_052317_create_voice;
+mod m20240426_021544_create_calls;
+mod m20240427_040512_create_voice_regions;
+mod m20240428_060233_create_mfa;
+mod m20240429_032148_create_webauthn_credentials;
+mod m20240430_021544_add_verification_sent_at;
+mod m20240501_081230_create_login_attempts;
+mod m20240502_043310_add_deletion_scheduled_at;
+mod m20240503_052817_create_harvests;
+mod m20240504_071902_create_import_mappings;
+mod m20240505_023417_create_instance_invites;
+mod m20240505_024105_create_admin_actions;
+mod m20240506_021733_add_expires_at_to_instance_invites;
+mod m20240506_022410_create_registration_requests;
+mod m20240507_031208_create_moderation_actions;
+mod m20240508_024517_create_reports;
+mod m20240509_013544_add_dm_privacy_to_user_settings;
+mod m20240510_021947_add_unique_system_user;
 
 pub struct Migrator;
 
@@ -55,6 +82,33 @@ impl MigratorTrait for Migrator {
             Box::new(m20240414_025415_create_reactions::Migration),
             Box::new(m20240417_080833_create_settings::Migration),
             Box::new(m20240418_040054_create_frecency::Migration),
+            Box::new(m20240420_013512_create_applications::Migration),
+            Box::new(m20240420_021944_create_oauth2_codes::Migration),
+            Box::new(m20240420_022310_create_oauth2_tokens::Migration),
+            Box::new(m20240421_031522_create_application_commands::Migration),
+            Box::new(m20240421_040133_create_interactions::Migration),
+            Box::new(m20240421_043807_add_interactions_endpoint_to_applications::Migration),
+            Box::new(m20240422_015730_add_components::Migration),
+            Box::new(m20240423_020811_create_emojis::Migration),
+            Box::new(m20240424_031245_add_reaction_types::Migration),
+            Box::new(m20240425_052317_create_voice::Migration),
+            Box::new(m20240426_021544_create_calls::Migration),
+            Box::new(m20240427_040512_create_voice_regions::Migration),
+            Box::new(m20240428_060233_create_mfa::Migration),
+            Box::new(m20240429_032148_create_webauthn_credentials::Migration),
+            Box::new(m20240430_021544_add_verification_sent_at::Migration),
+            Box::new(m20240501_081230_create_login_attempts::Migration),
+            Box::new(m20240502_043310_add_deletion_scheduled_at::Migration),
+            Box::new(m20240503_052817_create_harvests::Migration),
+            Box::new(m20240504_071902_create_import_mappings::Migration),
+            Box::new(m20240505_023417_create_instance_invites::Migration),
+            Box::new(m20240505_024105_create_admin_actions::Migration),
+            Box::new(m20240506_021733_add_expires_at_to_instance_invites::Migration),
+            Box::new(m20240506_022410_create_registration_requests::Migration),
+            Box::new(m20240507_031208_create_moderation_actions::Migration),
+            Box::new(m20240508_024517_create_reports::Migration),
+            Box::new(m20240509_013544_add_dm_privacy_to_user_settings::Migration),
+            Box::new(m20240510_021947_add_unique_system_user::Migration),
         ]
     }
 }
@@ -0,0 +1,65 @@
+use sea_orm_migration::prelude::*;
+use crate::ColumnType::String;
+use crate::m20220101_000001_create_user::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Application::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Application::Id).big_integer().not_null().primary_key())
+                    .col(ColumnDef::new(Application::Name).text().not_null())
+                    .col(ColumnDef::new(Application::Description).text().not_null().default(""))
+                    .col(ColumnDef::new(Application::Icon).text())
+                    .col(ColumnDef::new(Application::OwnerId).big_integer().not_null())
+                    .col(ColumnDef::new(Application::BotUserId).big_integer())
+                    .col(ColumnDef::new(Application::BotPublic).boolean().not_null().default(true))
+                    .col(ColumnDef::new(Application::BotRequireCodeGrant).boolean().not_null().default(false))
+                    .col(ColumnDef::new(Application::Secret).text().not_null())
+                    .col(ColumnDef::new(Application::RedirectUris).array(String(StringLen::None)).not_null())
+                    .col(ColumnDef::new(Application::Flags).big_integer().not_null().default(0))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_application-owner_user-id")
+                            .from(Application::Table, Application::OwnerId)
+                            .to(User::Table, User::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_application-bot_user-id")
+                            .from(Application::Table, Application::BotUserId)
+                            .to(User::Table, User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Application::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Application {
+    Table,
+    Id,
+    Name,
+    Description,
+    Icon,
+    OwnerId,
+    BotUserId,
+    BotPublic,
+    BotRequireCodeGrant,
+    Secret,
+    RedirectUris,
+    Flags,
+}
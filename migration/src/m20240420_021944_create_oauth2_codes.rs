@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+use crate::ColumnType::String;
+use crate::m20220101_000001_create_user::User;
+use crate::m20240420_013512_create_applications::Application;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Oauth2Code::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Oauth2Code::Code).text().not_null().primary_key())
+                    .col(ColumnDef::new(Oauth2Code::ApplicationId).big_integer().not_null())
+                    .col(ColumnDef::new(Oauth2Code::UserId).big_integer().not_null())
+                    .col(ColumnDef::new(Oauth2Code::Scopes).array(String(StringLen::None)).not_null())
+                    .col(ColumnDef::new(Oauth2Code::RedirectUri).text())
+                    .col(ColumnDef::new(Oauth2Code::Expires).timestamp().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_oauth2_code-application_application-id")
+                            .from(Oauth2Code::Table, Oauth2Code::ApplicationId)
+                            .to(Application::Table, Application::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_oauth2_code-user_user-id")
+                            .from(Oauth2Code::Table, Oauth2Code::UserId)
+                            .to(User::Table, User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Oauth2Code::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Oauth2Code {
+    Table,
+    Code,
+    ApplicationId,
+    UserId,
+    Scopes,
+    RedirectUri,
+    Expires,
+}
@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+use crate::ColumnType::String;
+use crate::m20220101_000001_create_user::User;
+use crate::m20240420_013512_create_applications::Application;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Oauth2Token::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Oauth2Token::Id).big_integer().not_null().primary_key())
+                    .col(ColumnDef::new(Oauth2Token::AccessToken).text().not_null().unique_key())
+                    .col(ColumnDef::new(Oauth2Token::RefreshToken).text().unique_key())
+                    .col(ColumnDef::new(Oauth2Token::ApplicationId).big_integer().not_null())
+                    .col(ColumnDef::new(Oauth2Token::UserId).big_integer().not_null())
+                    .col(ColumnDef::new(Oauth2Token::Scopes).array(String(StringLen::None)).not_null())
+                    .col(ColumnDef::new(Oauth2Token::Created).timestamp().not_null())
+                    .col(ColumnDef::new(Oauth2Token::Expires).timestamp().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_oauth2_token-application_application-id")
+                            .from(Oauth2Token::Table, Oauth2Token::ApplicationId)
+                            .to(Application::Table, Application::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_oauth2_token-user_user-id")
+                            .from(Oauth2Token::Table, Oauth2Token::UserId)
+                            .to(User::Table, User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Oauth2Token::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Oauth2Token {
+    Table,
+    Id,
+    AccessToken,
+    RefreshToken,
+    ApplicationId,
+    UserId,
+    Scopes,
+    Created,
+    Expires,
+}
@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+use crate::m20240420_013512_create_applications::Application;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ApplicationCommand::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ApplicationCommand::Id).big_integer().not_null().primary_key())
+                    .col(ColumnDef::new(ApplicationCommand::ApplicationId).big_integer().not_null())
+                    .col(ColumnDef::new(ApplicationCommand::GuildId).big_integer())
+                    .col(ColumnDef::new(ApplicationCommand::Type).integer().not_null().default(1))
+                    .col(ColumnDef::new(ApplicationCommand::Name).text().not_null())
+                    .col(ColumnDef::new(ApplicationCommand::Description).text().not_null().default(""))
+                    .col(ColumnDef::new(ApplicationCommand::Options).json())
+                    .col(ColumnDef::new(ApplicationCommand::DefaultMemberPermissions).text())
+                    .col(ColumnDef::new(ApplicationCommand::DmPermission).boolean().not_null().default(true))
+                    .col(ColumnDef::new(ApplicationCommand::Nsfw).boolean().not_null().default(false))
+                    .col(ColumnDef::new(ApplicationCommand::Version).big_integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_application_command-application_application-id")
+                            .from(ApplicationCommand::Table, ApplicationCommand::ApplicationId)
+                            .to(Application::Table, Application::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ApplicationCommand::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ApplicationCommand {
+    Table,
+    Id,
+    ApplicationId,
+    GuildId,
+    Type,
+    Name,
+    Description,
+    Options,
+    DefaultMemberPermissions,
+    DmPermission,
+    Nsfw,
+    Version,
+}
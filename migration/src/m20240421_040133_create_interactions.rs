@@ -0,0 +1,72 @@
+use sea_orm_migration::prelude::*;
+use crate::m20220101_000001_create_user::User;
+use crate::m20230604_223625_create_channel::Channel;
+use crate::m20240420_013512_create_applications::Application;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Interaction::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Interaction::Id).big_integer().not_null().primary_key())
+                    .col(ColumnDef::new(Interaction::ApplicationId).big_integer().not_null())
+                    .col(ColumnDef::new(Interaction::Type).integer().not_null())
+                    .col(ColumnDef::new(Interaction::Token).text().not_null().unique_key())
+                    .col(ColumnDef::new(Interaction::UserId).big_integer().not_null())
+                    .col(ColumnDef::new(Interaction::ChannelId).big_integer().not_null())
+                    .col(ColumnDef::new(Interaction::Data).json())
+                    .col(ColumnDef::new(Interaction::MessageId).big_integer())
+                    .col(ColumnDef::new(Interaction::Nonce).text())
+                    .col(ColumnDef::new(Interaction::Responded).boolean().not_null().default(false))
+                    .col(ColumnDef::new(Interaction::Created).timestamp().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_interaction-application_application-id")
+                            .from(Interaction::Table, Interaction::ApplicationId)
+                            .to(Application::Table, Application::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_interaction-user_user-id")
+                            .from(Interaction::Table, Interaction::UserId)
+                            .to(User::Table, User::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_interaction-channel_channel-id")
+                            .from(Interaction::Table, Interaction::ChannelId)
+                            .to(Channel::Table, Channel::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Interaction::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Interaction {
+    Table,
+    Id,
+    ApplicationId,
+    Type,
+    Token,
+    UserId,
+    ChannelId,
+    Data,
+    MessageId,
+    Nonce,
+    Responded,
+    Created,
+}
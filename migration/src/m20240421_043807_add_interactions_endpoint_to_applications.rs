@@ -0,0 +1,53 @@
+use sea_orm_migration::prelude::*;
+use crate::m20230604_231009_create_message::Message;
+use crate::m20240420_013512_create_applications::Application;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Application::Table)
+                    .add_column(ColumnDef::new(Alias::new("interactions_endpoint_url")).text())
+                    .add_column(ColumnDef::new(Alias::new("verify_key")).text())
+                    .add_column(ColumnDef::new(Alias::new("signing_key")).text())
+                    .to_owned()
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Message::Table)
+                    .add_column(ColumnDef::new(Alias::new("interaction")).json())
+                    .to_owned()
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Message::Table)
+                    .drop_column(Alias::new("interaction"))
+                    .to_owned()
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Application::Table)
+                    .drop_column(Alias::new("interactions_endpoint_url"))
+                    .drop_column(Alias::new("verify_key"))
+                    .drop_column(Alias::new("signing_key"))
+                    .to_owned()
+            )
+            .await
+    }
+}
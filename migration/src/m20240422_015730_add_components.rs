@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+use crate::m20230604_231009_create_message::Message;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Message::Table)
+                    .add_column(ColumnDef::new(Alias::new("components")).json())
+                    .to_owned()
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Alias::new("interaction"))
+                    .add_column(ColumnDef::new(Alias::new("modal")).json())
+                    .to_owned()
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Alias::new("interaction"))
+                    .drop_column(Alias::new("modal"))
+                    .to_owned()
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Message::Table)
+                    .drop_column(Alias::new("components"))
+                    .to_owned()
+            )
+            .await
+    }
+}
@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+use crate::m20220101_000001_create_user::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Emoji::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Emoji::Id).big_integer().not_null().primary_key())
+                    .col(ColumnDef::new(Emoji::GuildId).big_integer().not_null())
+                    .col(ColumnDef::new(Emoji::Name).text().not_null())
+                    .col(ColumnDef::new(Emoji::Animated).boolean().not_null().default(false))
+                    .col(ColumnDef::new(Emoji::CreatorId).big_integer())
+                    .col(ColumnDef::new(Emoji::Roles).json())
+                    .col(ColumnDef::new(Emoji::Available).boolean().not_null().default(true))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_emoji-creator_id_user-id")
+                            .from(Emoji::Table, Emoji::CreatorId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_emoji-guild_id")
+                    .table(Emoji::Table)
+                    .col(Emoji::GuildId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Emoji::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Emoji {
+    Table,
+    Id,
+    GuildId,
+    Name,
+    Animated,
+    CreatorId,
+    Roles,
+    Available,
+}
@@ -0,0 +1,50 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::ConnectionTrait;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // A user can have both a normal and a burst reaction with the same emoji, so the type has to be in the key
+        db.execute_unprepared("ALTER TABLE reaction ADD COLUMN type integer NOT NULL DEFAULT 0;").await?;
+        db.execute_unprepared("UPDATE reaction SET type = 1 WHERE burst;").await?;
+        db.execute_unprepared("ALTER TABLE reaction DROP CONSTRAINT reaction_pkey;").await?;
+        db.execute_unprepared("ALTER TABLE reaction ADD PRIMARY KEY (\"user\", message, emoji, type);").await?;
+        db.execute_unprepared("ALTER TABLE reaction DROP COLUMN burst;").await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Alias::new("emoji"))
+                    .add_column(ColumnDef::new(Alias::new("burst_colors")).json())
+                    .to_owned()
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Alias::new("emoji"))
+                    .drop_column(Alias::new("burst_colors"))
+                    .to_owned()
+            )
+            .await?;
+
+        let db = manager.get_connection();
+
+        db.execute_unprepared("DELETE FROM reaction WHERE type = 1 AND (\"user\", message, emoji) IN (SELECT \"user\", message, emoji FROM reaction WHERE type = 0);").await?;
+        db.execute_unprepared("ALTER TABLE reaction ADD COLUMN burst boolean NOT NULL DEFAULT false;").await?;
+        db.execute_unprepared("UPDATE reaction SET burst = (type = 1);").await?;
+        db.execute_unprepared("ALTER TABLE reaction DROP CONSTRAINT reaction_pkey;").await?;
+        db.execute_unprepared("ALTER TABLE reaction ADD PRIMARY KEY (\"user\", message, emoji);").await?;
+        db.execute_unprepared("ALTER TABLE reaction DROP COLUMN type;").await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use sea_orm_migration::prelude::*;
+use crate::m20220101_000001_create_user::User;
+use crate::m20230604_223625_create_channel::Channel;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(VoiceServer::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(VoiceServer::Hostname).text().not_null().primary_key())
+                    .col(ColumnDef::new(VoiceServer::RegionId).text().not_null())
+                    .col(ColumnDef::new(VoiceServer::LastHealth).double().not_null().default(1.0))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(VoiceState::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(VoiceState::UserId).big_integer().not_null().primary_key())
+                    .col(ColumnDef::new(VoiceState::ChannelId).big_integer().not_null())
+                    .col(ColumnDef::new(VoiceState::GuildId).big_integer())
+                    .col(ColumnDef::new(VoiceState::SessionId).text().not_null())
+                    .col(ColumnDef::new(VoiceState::Hostname).text().not_null())
+                    .col(ColumnDef::new(VoiceState::VoiceSessionId).text().not_null())
+                    .col(ColumnDef::new(VoiceState::Deaf).boolean().not_null().default(false))
+                    .col(ColumnDef::new(VoiceState::Mute).boolean().not_null().default(false))
+                    .col(ColumnDef::new(VoiceState::SelfDeaf).boolean().not_null().default(false))
+                    .col(ColumnDef::new(VoiceState::SelfMute).boolean().not_null().default(false))
+                    .col(ColumnDef::new(VoiceState::SelfStream).boolean().not_null().default(false))
+                    .col(ColumnDef::new(VoiceState::SelfVideo).boolean().not_null().default(false))
+                    .col(ColumnDef::new(VoiceState::Suppress).boolean().not_null().default(false))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_voice_state-user_id_user-id")
+                            .from(VoiceState::Table, VoiceState::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_voice_state-channel_id_channel-id")
+                            .from(VoiceState::Table, VoiceState::ChannelId)
+                            .to(Channel::Table, Channel::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_voice_state-hostname_voice_server-hostname")
+                            .from(VoiceState::Table, VoiceState::Hostname)
+                            .to(VoiceServer::Table, VoiceServer::Hostname)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_voice_state-channel_id")
+                    .table(VoiceState::Table)
+                    .col(VoiceState::ChannelId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(VoiceState::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(VoiceServer::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum VoiceServer {
+    Table,
+    Hostname,
+    RegionId,
+    LastHealth,
+}
+
+#[derive(DeriveIden)]
+enum VoiceState {
+    Table,
+    UserId,
+    ChannelId,
+    GuildId,
+    SessionId,
+    Hostname,
+    VoiceSessionId,
+    Deaf,
+    Mute,
+    SelfDeaf,
+    SelfMute,
+    SelfStream,
+    SelfVideo,
+    Suppress,
+}
@@ -0,0 +1,71 @@
+use sea_orm_migration::prelude::*;
+use crate::m20230604_223625_create_channel::Channel;
+use crate::m20230604_231009_create_message::Message;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Message::Table)
+                    .add_column(ColumnDef::new(Alias::new("call")).json())
+                    .to_owned()
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Call::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Call::ChannelId).big_integer().not_null().primary_key())
+                    .col(ColumnDef::new(Call::MessageId).big_integer().not_null())
+                    .col(ColumnDef::new(Call::Region).text())
+                    .col(ColumnDef::new(Call::Ringing).json().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_call-channel_id_channel-id")
+                            .from(Call::Table, Call::ChannelId)
+                            .to(Channel::Table, Channel::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_call-message_id_message-id")
+                            .from(Call::Table, Call::MessageId)
+                            .to(Message::Table, Message::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Call::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Message::Table)
+                    .drop_column(Alias::new("call"))
+                    .to_owned()
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Call {
+    Table,
+    ChannelId,
+    MessageId,
+    Region,
+    Ringing,
+}
@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+use crate::m20240425_052317_create_voice::VoiceServer;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(VoiceRegion::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(VoiceRegion::Id).text().not_null().primary_key())
+                    .col(ColumnDef::new(VoiceRegion::Name).text().not_null())
+                    .col(ColumnDef::new(VoiceRegion::Latitude).double())
+                    .col(ColumnDef::new(VoiceRegion::Longitude).double())
+                    .col(ColumnDef::new(VoiceRegion::Deprecated).boolean().not_null().default(false))
+                    .col(ColumnDef::new(VoiceRegion::Custom).boolean().not_null().default(false))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Voice servers added before regions existed need their region to exist too
+        let db = manager.get_connection();
+
+        db.execute_unprepared("INSERT INTO voice_region (id, name) SELECT DISTINCT region_id, region_id FROM voice_server;").await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_voice_server-region_id_voice_region-id")
+                    .from(VoiceServer::Table, VoiceServer::RegionId)
+                    .to(VoiceRegion::Table, VoiceRegion::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk_voice_server-region_id_voice_region-id")
+                    .table(VoiceServer::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(VoiceRegion::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum VoiceRegion {
+    Table,
+    Id,
+    Name,
+    Latitude,
+    Longitude,
+    Deprecated,
+    Custom,
+}
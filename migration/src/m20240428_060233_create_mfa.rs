@@ -0,0 +1,66 @@
+use sea_orm_migration::prelude::*;
+use crate::m20220101_000001_create_user::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(Alias::new("totp_secret")).text())
+                    .to_owned()
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(BackupCode::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(BackupCode::UserId).big_integer().not_null())
+                    .col(ColumnDef::new(BackupCode::Code).text().not_null())
+                    .col(ColumnDef::new(BackupCode::Consumed).boolean().not_null().default(false))
+                    .primary_key(
+                        Index::create()
+                            .col(BackupCode::UserId)
+                            .col(BackupCode::Code)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_backup_code-user_id_user-id")
+                            .from(BackupCode::Table, BackupCode::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BackupCode::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(Alias::new("totp_secret"))
+                    .to_owned()
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BackupCode {
+    Table,
+    UserId,
+    Code,
+    Consumed,
+}
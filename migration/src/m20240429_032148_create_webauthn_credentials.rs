@@ -0,0 +1,55 @@
+use sea_orm_migration::prelude::*;
+use crate::m20220101_000001_create_user::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebauthnCredential::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(WebauthnCredential::Id).big_integer().not_null().primary_key())
+                    .col(ColumnDef::new(WebauthnCredential::UserId).big_integer().not_null())
+                    .col(ColumnDef::new(WebauthnCredential::Name).text().not_null())
+                    .col(ColumnDef::new(WebauthnCredential::Credential).json().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webauthn_credential-user_id_user-id")
+                            .from(WebauthnCredential::Table, WebauthnCredential::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webauthn_credential-user_id")
+                    .table(WebauthnCredential::Table)
+                    .col(WebauthnCredential::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WebauthnCredential::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum WebauthnCredential {
+    Table,
+    Id,
+    UserId,
+    Name,
+    Credential,
+}
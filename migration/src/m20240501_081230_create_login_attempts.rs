@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+use crate::m20220101_000001_create_user::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LoginAttempt::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(LoginAttempt::Id).big_integer().not_null().primary_key())
+                    .col(ColumnDef::new(LoginAttempt::UserId).big_integer().not_null())
+                    .col(ColumnDef::new(LoginAttempt::Ip).text().not_null())
+                    .col(ColumnDef::new(LoginAttempt::Country).text())
+                    .col(ColumnDef::new(LoginAttempt::Asn).big_integer())
+                    .col(ColumnDef::new(LoginAttempt::Location).text().not_null())
+                    .col(ColumnDef::new(LoginAttempt::Outcome).integer().not_null())
+                    .col(ColumnDef::new(LoginAttempt::CreatedAt).timestamp().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_login_attempt-user_id_user-id")
+                            .from(LoginAttempt::Table, LoginAttempt::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_login_attempt-user_id")
+                    .table(LoginAttempt::Table)
+                    .col(LoginAttempt::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LoginAttempt::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LoginAttempt {
+    Table,
+    Id,
+    UserId,
+    Ip,
+    Country,
+    Asn,
+    Location,
+    Outcome,
+    CreatedAt,
+}
@@ -0,0 +1,57 @@
+use sea_orm_migration::prelude::*;
+use crate::m20220101_000001_create_user::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Harvest::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Harvest::Id).big_integer().not_null().primary_key())
+                    .col(ColumnDef::new(Harvest::UserId).big_integer().not_null())
+                    .col(ColumnDef::new(Harvest::Status).integer().not_null())
+                    .col(ColumnDef::new(Harvest::CreatedAt).timestamp().not_null())
+                    .col(ColumnDef::new(Harvest::CompletedAt).timestamp())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_harvest-user_id_user-id")
+                            .from(Harvest::Table, Harvest::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_harvest-user_id")
+                    .table(Harvest::Table)
+                    .col(Harvest::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Harvest::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Harvest {
+    Table,
+    Id,
+    UserId,
+    Status,
+    CreatedAt,
+    CompletedAt,
+}
@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ImportMapping::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ImportMapping::Kind).integer().not_null())
+                    .col(ColumnDef::new(ImportMapping::DiscordId).big_integer().not_null())
+                    .col(ColumnDef::new(ImportMapping::EplId).big_integer().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(ImportMapping::Kind)
+                            .col(ImportMapping::DiscordId),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ImportMapping::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ImportMapping {
+    Table,
+    Kind,
+    DiscordId,
+    EplId,
+}
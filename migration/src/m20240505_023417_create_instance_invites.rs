@@ -0,0 +1,47 @@
+use sea_orm_migration::prelude::*;
+use crate::m20220101_000001_create_user::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(InstanceInvite::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(InstanceInvite::Code).text().not_null().primary_key())
+                    .col(ColumnDef::new(InstanceInvite::CreatedBy).big_integer())
+                    .col(ColumnDef::new(InstanceInvite::CreatedAt).timestamp().not_null())
+                    .col(ColumnDef::new(InstanceInvite::Uses).integer().not_null())
+                    .col(ColumnDef::new(InstanceInvite::MaxUses).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_instance_invite-created_by_user-id")
+                            .from(InstanceInvite::Table, InstanceInvite::CreatedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(InstanceInvite::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum InstanceInvite {
+    Table,
+    Code,
+    CreatedBy,
+    CreatedAt,
+    Uses,
+    MaxUses,
+}
@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+use crate::m20220101_000001_create_user::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AdminAction::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(AdminAction::Id).big_integer().not_null().primary_key())
+                    .col(ColumnDef::new(AdminAction::ActorId).big_integer())
+                    .col(ColumnDef::new(AdminAction::Action).text().not_null())
+                    .col(ColumnDef::new(AdminAction::Target).text())
+                    .col(ColumnDef::new(AdminAction::Data).json_binary())
+                    .col(ColumnDef::new(AdminAction::CreatedAt).timestamp().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_admin_action-actor_id_user-id")
+                            .from(AdminAction::Table, AdminAction::ActorId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AdminAction::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AdminAction {
+    Table,
+    Id,
+    ActorId,
+    Action,
+    Target,
+    Data,
+    CreatedAt,
+}
@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+use crate::m20220101_000001_create_user::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RegistrationRequest::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(RegistrationRequest::UserId).big_integer().not_null().primary_key())
+                    .col(ColumnDef::new(RegistrationRequest::CreatedAt).timestamp().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_registration_request-user_id_user-id")
+                            .from(RegistrationRequest::Table, RegistrationRequest::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RegistrationRequest::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RegistrationRequest {
+    Table,
+    UserId,
+    CreatedAt,
+}
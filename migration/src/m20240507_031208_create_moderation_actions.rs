@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+use crate::m20220101_000001_create_user::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ModerationAction::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ModerationAction::Id).big_integer().not_null().primary_key())
+                    .col(ColumnDef::new(ModerationAction::UserId).big_integer().not_null())
+                    .col(ColumnDef::new(ModerationAction::ModeratorId).big_integer())
+                    .col(ColumnDef::new(ModerationAction::ActionType).integer().not_null())
+                    .col(ColumnDef::new(ModerationAction::Reason).text().not_null())
+                    .col(ColumnDef::new(ModerationAction::CreatedAt).timestamp().not_null())
+                    .col(ColumnDef::new(ModerationAction::ExpiresAt).timestamp())
+                    .col(ColumnDef::new(ModerationAction::RevokedAt).timestamp())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_moderation_action-user_id_user-id")
+                            .from(ModerationAction::Table, ModerationAction::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_moderation_action-moderator_id_user-id")
+                            .from(ModerationAction::Table, ModerationAction::ModeratorId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_moderation_action-user_id")
+                    .table(ModerationAction::Table)
+                    .col(ModerationAction::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ModerationAction::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ModerationAction {
+    Table,
+    Id,
+    UserId,
+    ModeratorId,
+    ActionType,
+    Reason,
+    CreatedAt,
+    ExpiresAt,
+    RevokedAt,
+}
@@ -0,0 +1,81 @@
+use sea_orm_migration::prelude::*;
+use crate::m20220101_000001_create_user::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Reported channels, messages and users aren't foreign keys so deleting them leaves the report alone
+        manager
+            .create_table(
+                Table::create()
+                    .table(Report::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Report::Id).big_integer().not_null().primary_key())
+                    .col(ColumnDef::new(Report::ReporterId).big_integer())
+                    .col(ColumnDef::new(Report::ReportType).integer().not_null())
+                    .col(ColumnDef::new(Report::Reason).integer().not_null())
+                    .col(ColumnDef::new(Report::TargetUserId).big_integer())
+                    .col(ColumnDef::new(Report::ChannelId).big_integer())
+                    .col(ColumnDef::new(Report::MessageId).big_integer())
+                    .col(ColumnDef::new(Report::Snapshot).json_binary().not_null())
+                    .col(ColumnDef::new(Report::Status).integer().not_null())
+                    .col(ColumnDef::new(Report::CreatedAt).timestamp().not_null())
+                    .col(ColumnDef::new(Report::ResolvedAt).timestamp())
+                    .col(ColumnDef::new(Report::ResolutionNote).text())
+                    .col(ColumnDef::new(Report::ModerationActionId).big_integer())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_report-reporter_id_user-id")
+                            .from(Report::Table, Report::ReporterId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_report-moderation_action_id_moderation_action-id")
+                            .from(Report::Table, Report::ModerationActionId)
+                            .to(Alias::new("moderation_action"), Alias::new("id"))
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_report-status")
+                    .table(Report::Table)
+                    .col(Report::Status)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Report::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Report {
+    Table,
+    Id,
+    ReporterId,
+    ReportType,
+    Reason,
+    TargetUserId,
+    ChannelId,
+    MessageId,
+    Snapshot,
+    Status,
+    CreatedAt,
+    ResolvedAt,
+    ResolutionNote,
+    ModerationActionId,
+}
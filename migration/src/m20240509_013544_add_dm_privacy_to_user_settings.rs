@@ -0,0 +1,29 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Alias::new("user_setting"))
+                    .add_column(ColumnDef::new(Alias::new("privacy_allow_dms_from_everyone")).boolean().not_null().default(false))
+                    .to_owned()
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Alias::new("user_setting"))
+                    .drop_column(Alias::new("privacy_allow_dms_from_everyone"))
+                    .to_owned()
+            )
+            .await
+    }
+}
@@ -0,0 +1,25 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::ConnectionTrait;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // There's only ever one System user, everything that needs it may try to create it at the same time
+        db.execute_unprepared("CREATE UNIQUE INDEX user_system_idx ON \"user\" (system) WHERE system;").await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared("DROP INDEX user_system_idx;").await?;
+
+        Ok(())
+    }
+}